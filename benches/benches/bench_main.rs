@@ -48,6 +48,7 @@ mod benchmarks {
     pub mod aoc_2020_19b;
     pub mod aoc_2020_1a;
     pub mod aoc_2020_1b;
+    pub mod arithmetic;
     pub mod brainfuck;
     pub mod fib;
 }
@@ -57,6 +58,7 @@ criterion::criterion_main! {
     benchmarks::aoc_2020_1b::benches,
     benchmarks::aoc_2020_11a::benches,
     benchmarks::aoc_2020_19b::benches,
+    benchmarks::arithmetic::benches,
     benchmarks::brainfuck::benches,
     benchmarks::fib::benches,
 }
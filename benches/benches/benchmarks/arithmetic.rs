@@ -0,0 +1,29 @@
+use criterion::Criterion;
+
+criterion::criterion_group!(benches, arithmetic_loop);
+
+fn arithmetic_loop(b: &mut Criterion) {
+    let mut vm = rune_vm! {
+        pub fn main(n) {
+            let sum = 0;
+            let product = 1;
+            let step = 3;
+            let i = 0;
+
+            while i < n {
+                sum = sum + i * step;
+                product = (product * step + i) % 1000003;
+                sum -= product;
+                i += 1;
+            }
+
+            sum + product
+        }
+    };
+
+    let entry = rune::Hash::type_hash(["main"]);
+
+    b.bench_function("arithmetic_loop", |b| {
+        b.iter(|| vm.call(entry, (10000,)).expect("failed call"));
+    });
+}
@@ -18,6 +18,7 @@ const SEP: u64 = 0x4bc94d6bd06053ad;
 const PARAMS: u64 = 0x19893cc8f39b1371;
 const TYPE: u64 = 0x2fac10b63a6cc57c;
 const INSTANCE_FUNCTION_HASH: u64 = 0x5ea77ffbcdf5f302;
+const TRAIT_FUNCTION_HASH: u64 = 0x6a1d0f7c38be2e49;
 const FIELD_FUNCTION_HASH: u64 = 0xab53b6a7a53c757e;
 const OBJECT_KEYS: u64 = 0x4473d7017aef7645;
 const INDEX_FUNCTION_HASH: u64 = 0x2579e52d1534901b;
@@ -96,6 +97,16 @@ impl Hash {
         Self(INSTANCE_FUNCTION_HASH ^ (type_hash.0 ^ name.0))
     }
 
+    /// Construct a hash to a function declared by a trait.
+    #[inline]
+    pub fn trait_function<N>(trait_hash: Hash, name: N) -> Self
+    where
+        N: IntoHash,
+    {
+        let name = name.into_hash();
+        Self(TRAIT_FUNCTION_HASH ^ (trait_hash.0 ^ name.0))
+    }
+
     /// Construct a hash corresponding to a field function.
     #[inline]
     pub fn field_function<N>(protocol: Protocol, type_hash: Hash, name: N) -> Self
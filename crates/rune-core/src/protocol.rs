@@ -112,6 +112,38 @@ macro_rules! define {
 }
 
 impl Protocol {
+    /// Construct a custom protocol with the given name, declared by the trait
+    /// with the given item.
+    ///
+    /// The hash of a custom protocol combines the item of its trait with its
+    /// name, so that protocols with the same name in different traits don't
+    /// conflict. Scripts implement it with an `impl` block for the trait.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rune_core::{Hash, ItemBuf, Protocol};
+    ///
+    /// let serde = ItemBuf::with_crate_item("serde", ["Serialize"]);
+    /// let protocol = Protocol::custom(&serde, "serialize");
+    /// let trait_hash = Hash::type_hash(&serde);
+    /// assert_eq!(protocol.hash, Hash::trait_function(trait_hash, "serialize"));
+    /// assert_ne!(protocol.hash, Hash::instance_fn_name("serialize"));
+    /// ```
+    pub fn custom<T>(trait_: T, name: &'static str) -> Self
+    where
+        T: ToTypeHash,
+    {
+        Self {
+            name,
+            hash: Hash::trait_function(Hash::type_hash(trait_), name),
+            #[cfg(feature = "doc")]
+            repr: None,
+            #[cfg(feature = "doc")]
+            doc: &[],
+        }
+    }
+
     define! {
         /// The function to access a field.
         pub const GET: Protocol = Protocol {
@@ -153,6 +185,45 @@ impl Protocol {
             doc: ["Allows an equality operation to work."],
         };
 
+        /// Hash a value into the provided hasher.
+        ///
+        /// Signature: `fn(self, Hasher)`.
+        ///
+        /// The hash is the same as an instance function named `hash`, so
+        /// scripts implement it by declaring such a function.
+        pub const HASH: Protocol = Protocol {
+            name: "hash",
+            hash: 0x3440d109c9dd0b5f,
+            repr: Some("let output = hash($value)"),
+            doc: ["Allows the value to be used as a key in hash maps and sets."],
+        };
+
+        /// Perform a partial comparison between two values.
+        ///
+        /// Signature: `fn(self, b) -> Option<Ordering>`.
+        ///
+        /// The hash is the same as an instance function named `partial_cmp`,
+        /// so scripts implement it by declaring such a function.
+        pub const PARTIAL_CMP: Protocol = Protocol {
+            name: "partial_cmp",
+            hash: 0x06870669a59e21cf,
+            repr: Some("let output = $value.partial_cmp(b)"),
+            doc: ["Allows for partially comparing two values."],
+        };
+
+        /// Perform a total comparison between two values.
+        ///
+        /// Signature: `fn(self, b) -> Ordering`.
+        ///
+        /// The hash is the same as an instance function named `cmp`, so
+        /// scripts implement it by declaring such a function.
+        pub const CMP: Protocol = Protocol {
+            name: "cmp",
+            hash: 0x3ab799784e414506,
+            repr: Some("let output = $value.cmp(b)"),
+            doc: ["Allows for totally comparing two values, like when sorting them."],
+        };
+
         /// The function to implement for the addition operation.
         pub const ADD: Protocol = Protocol {
             name: "add",
@@ -353,6 +424,66 @@ impl Protocol {
             ],
         };
 
+        /// The function to implement for the negation operation.
+        pub const NEG: Protocol = Protocol {
+            name: "neg",
+            hash: 0x0c52ceae608cdb21,
+            repr: Some("let output = -$value"),
+            doc: [
+                "Allows the unary `-` operator to apply to values of this type."
+            ],
+        };
+
+        /// The function to implement for the logical or bitwise not operation.
+        pub const NOT: Protocol = Protocol {
+            name: "not",
+            hash: 0x16b7529401c4e134,
+            repr: Some("let output = !$value"),
+            doc: [
+                "Allows the unary `!` operator to apply to values of this type."
+            ],
+        };
+
+        /// The function to implement for the less than operation.
+        pub const LT: Protocol = Protocol {
+            name: "lt",
+            hash: 0x81af591a85c60207,
+            repr: Some("if $value < b { }"),
+            doc: [
+                "Allows the `<` operator to apply to values of this type, where the current type is the left-hand side."
+            ],
+        };
+
+        /// The function to implement for the less than or equal operation.
+        pub const LE: Protocol = Protocol {
+            name: "le",
+            hash: 0xde9a27c98027273f,
+            repr: Some("if $value <= b { }"),
+            doc: [
+                "Allows the `<=` operator to apply to values of this type, where the current type is the left-hand side."
+            ],
+        };
+
+        /// The function to implement for the greater than operation.
+        pub const GT: Protocol = Protocol {
+            name: "gt",
+            hash: 0xc76ea7b61ffcf63a,
+            repr: Some("if $value > b { }"),
+            doc: [
+                "Allows the `>` operator to apply to values of this type, where the current type is the left-hand side."
+            ],
+        };
+
+        /// The function to implement for the greater than or equal operation.
+        pub const GE: Protocol = Protocol {
+            name: "ge",
+            hash: 0x06bd4e79dcec25ec,
+            repr: Some("if $value >= b { }"),
+            doc: [
+                "Allows the `>=` operator to apply to values of this type, where the current type is the left-hand side."
+            ],
+        };
+
         /// Protocol function used by template strings.
         pub const STRING_DISPLAY: Protocol = Protocol {
             name: "string_display",
@@ -79,7 +79,9 @@ impl Derive {
         let generics = &self.input.generics;
         let mut installers = Vec::new();
 
-        let Ok(()) = expand_install_with(&ctx, &self.input, &tokens, &attr, generics, &mut installers) else {
+        let Ok(()) =
+            expand_install_with(&ctx, &self.input, &tokens, &attr, generics, &mut installers)
+        else {
             return Err(ctx.errors.into_inner());
         };
 
@@ -152,6 +154,42 @@ pub(crate) fn expand_install_with(
         }
     }
 
+    let protocol = &tokens.protocol;
+
+    if attr.partial_eq {
+        installers.push(quote_spanned! { input.span() =>
+            module.associated_function(#protocol::EQ, |this: &Self, other: &Self| {
+                core::cmp::PartialEq::eq(this, other)
+            })?;
+        });
+    }
+
+    if attr.hash {
+        let hasher = &tokens.hasher;
+
+        installers.push(quote_spanned! { input.span() =>
+            module.associated_function(#protocol::HASH, |this: &Self, hasher: &mut #hasher| {
+                core::hash::Hash::hash(this, hasher);
+            })?;
+        });
+    }
+
+    if attr.partial_ord {
+        installers.push(quote_spanned! { input.span() =>
+            module.associated_function(#protocol::PARTIAL_CMP, |this: &Self, other: &Self| {
+                core::cmp::PartialOrd::partial_cmp(this, other)
+            })?;
+        });
+    }
+
+    if attr.ord {
+        installers.push(quote_spanned! { input.span() =>
+            module.associated_function(#protocol::CMP, |this: &Self, other: &Self| {
+                core::cmp::Ord::cmp(this, other)
+            })?;
+        });
+    }
+
     if let Some(install_with) = &attr.install_with {
         installers.push(quote_spanned! { input.span() =>
             #install_with(module)?;
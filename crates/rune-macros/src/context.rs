@@ -72,6 +72,14 @@ pub(crate) struct TypeAttr {
     pub(crate) parse: ParseKind,
     /// `#[rune(item = <path>)]`.
     pub(crate) item: Option<syn::Path>,
+    /// `#[rune(partial_eq)]` to forward the `PartialEq` implementation.
+    pub(crate) partial_eq: bool,
+    /// `#[rune(hash)]` to forward the `Hash` implementation.
+    pub(crate) hash: bool,
+    /// `#[rune(partial_ord)]` to forward the `PartialOrd` implementation.
+    pub(crate) partial_ord: bool,
+    /// `#[rune(ord)]` to forward the `Ord` implementation.
+    pub(crate) ord: bool,
     /// Parsed documentation.
     pub(crate) docs: Vec<syn::Expr>,
 }
@@ -441,6 +449,18 @@ impl Context {
                         // Parse `#[rune(install_with = <path>)]`
                         meta.input.parse::<Token![=]>()?;
                         attr.install_with = Some(parse_path_compat(meta.input)?);
+                    } else if meta.path == PARTIAL_EQ {
+                        // Parse `#[rune(partial_eq)]`
+                        attr.partial_eq = true;
+                    } else if meta.path == HASH {
+                        // Parse `#[rune(hash)]`
+                        attr.hash = true;
+                    } else if meta.path == PARTIAL_ORD {
+                        // Parse `#[rune(partial_ord)]`
+                        attr.partial_ord = true;
+                    } else if meta.path == ORD {
+                        // Parse `#[rune(ord)]`
+                        attr.ord = true;
                     } else {
                         return Err(syn::Error::new_spanned(
                             &meta.path,
@@ -667,6 +687,7 @@ impl Context {
             from_value: path(m, ["runtime", "FromValue"]),
             full_type_of: path(m, ["runtime", "FullTypeOf"]),
             hash: path(m, ["Hash"]),
+            hasher: path(m, ["runtime", "Hasher"]),
             id: path(m, ["parse", "Id"]),
             install_with: path(m, ["__private", "InstallWith"]),
             macro_context: path(m, ["macros", "MacroContext"]),
@@ -743,6 +764,7 @@ pub(crate) struct Tokens {
     pub(crate) from_value: syn::Path,
     pub(crate) full_type_of: syn::Path,
     pub(crate) hash: syn::Path,
+    pub(crate) hasher: syn::Path,
     pub(crate) id: syn::Path,
     pub(crate) install_with: syn::Path,
     pub(crate) macro_context: syn::Path,
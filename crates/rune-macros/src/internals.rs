@@ -19,6 +19,10 @@ pub const NAME: Symbol = Symbol("name");
 pub const ITEM: Symbol = Symbol("item");
 pub const MODULE: Symbol = Symbol("module");
 pub const INSTALL_WITH: Symbol = Symbol("install_with");
+pub const PARTIAL_EQ: Symbol = Symbol("partial_eq");
+pub const HASH: Symbol = Symbol("hash");
+pub const PARTIAL_ORD: Symbol = Symbol("partial_ord");
+pub const ORD: Symbol = Symbol("ord");
 
 pub const CONSTRUCTOR: Symbol = Symbol("constructor");
 pub const GET: Symbol = Symbol("get");
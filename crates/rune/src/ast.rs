@@ -137,6 +137,7 @@ mod item_fn;
 mod item_impl;
 mod item_mod;
 mod item_struct;
+mod item_trait;
 mod item_use;
 mod label;
 mod lit;
@@ -156,6 +157,7 @@ mod span;
 mod spanned;
 mod stmt;
 mod token;
+mod ty;
 pub(super) mod utils;
 mod vis;
 
@@ -168,7 +170,7 @@ pub use self::expr_await::ExprAwait;
 pub use self::expr_binary::{BinOp, ExprBinary};
 pub use self::expr_block::ExprBlock;
 pub use self::expr_break::{ExprBreak, ExprBreakValue};
-pub use self::expr_call::ExprCall;
+pub use self::expr_call::{CallArg, ExprCall};
 pub use self::expr_closure::{ExprClosure, ExprClosureArgs};
 pub use self::expr_continue::ExprContinue;
 pub use self::expr_empty::ExprEmpty;
@@ -193,7 +195,7 @@ pub use self::expr_while::ExprWhile;
 pub use self::expr_yield::ExprYield;
 pub use self::fields::Fields;
 pub use self::file::{File, Shebang};
-pub use self::fn_arg::FnArg;
+pub use self::fn_arg::{FnArg, FnArgDefault};
 pub use self::grouped::{AngleBracketed, Braced, Bracketed, Parenthesized};
 pub use self::ident::Ident;
 pub use self::item::Item;
@@ -203,6 +205,7 @@ pub use self::item_fn::ItemFn;
 pub use self::item_impl::ItemImpl;
 pub use self::item_mod::{ItemInlineBody, ItemMod, ItemModBody};
 pub use self::item_struct::{Field, ItemStruct};
+pub use self::item_trait::{ItemTrait, TraitFn};
 pub use self::item_use::{ItemUse, ItemUsePath, ItemUseSegment};
 pub use self::label::Label;
 pub use self::lit::Lit;
@@ -216,7 +219,8 @@ pub use self::local::Local;
 pub use self::macro_call::MacroCall;
 pub use self::macro_utils::{EqValue, Group};
 pub use self::pat::{
-    Pat, PatBinding, PatIgnore, PatLit, PatObject, PatPath, PatRest, PatTuple, PatVec,
+    Pat, PatAt, PatBinding, PatIgnore, PatLit, PatObject, PatOr, PatPath, PatRange, PatRest,
+    PatTuple, PatType, PatVec,
 };
 pub use self::path::{Path, PathKind, PathSegment, PathSegmentExpr};
 use self::prelude::*;
@@ -227,6 +231,7 @@ pub use self::token::{
     BuiltIn, CopySource, Delimiter, LitSource, Number, NumberBase, NumberSource, NumberText,
     StrSource, StrText, Token,
 };
+pub use self::ty::Type;
 pub use self::vis::Visibility;
 
 macro_rules! decl_tokens {
@@ -274,33 +274,6 @@ impl Expr {
         }
     }
 
-    /// Check if this expression is a literal expression.
-    ///
-    /// There are exactly two kinds of literal expressions:
-    /// * Ones that are ExprLit
-    /// * Unary expressions which are the negate operation.
-    pub(crate) fn is_lit(&self) -> bool {
-        match self {
-            Self::Lit(..) => return true,
-            Self::Unary(ast::ExprUnary {
-                op: ast::UnOp::Neg(..),
-                expr,
-                ..
-            }) => {
-                return matches!(
-                    &**expr,
-                    Self::Lit(ast::ExprLit {
-                        lit: ast::Lit::Number(..),
-                        ..
-                    })
-                );
-            }
-            _ => (),
-        }
-
-        false
-    }
-
     /// Internal function to construct a literal expression.
     pub(crate) fn from_lit(lit: ast::Lit) -> Self {
         Self::Lit(ast::ExprLit {
@@ -566,7 +539,7 @@ fn chain(p: &mut Parser<'_>, mut expr: Expr, callable: Callable) -> Result<Expr>
             }
             // Chained function call.
             K!['('] if is_callable => {
-                let args = p.parse::<ast::Parenthesized<Expr, T![,]>>()?;
+                let args = p.parse::<ast::Parenthesized<ast::CallArg, T![,]>>()?;
 
                 expr = Expr::Call(ast::ExprCall {
                     id: Default::default(),
@@ -112,7 +112,7 @@ impl Parse for ExprClosureArgs {
         let mut args = Vec::new();
 
         while !p.peek::<T![|]>()? {
-            let arg = p.parse()?;
+            let arg = ast::FnArg::parse_without_default(p)?;
 
             let comma = p.parse::<Option<T![,]>>()?;
             let is_end = comma.is_none();
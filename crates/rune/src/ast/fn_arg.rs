@@ -7,6 +7,22 @@ fn ast_parse() {
     rt::<ast::FnArg>("self");
     rt::<ast::FnArg>("_");
     rt::<ast::FnArg>("abc");
+
+    let arg = rt::<ast::FnArg>("abc: int");
+    assert!(matches!(arg, ast::FnArg::Typed(..)));
+    rt::<ast::FnArg>("(a, b): (int, int)");
+
+    let arg = rt::<ast::FnArg>("port = 80");
+    assert!(matches!(
+        arg,
+        ast::FnArg::Default(ast::FnArgDefault { ty: None, .. })
+    ));
+
+    let arg = rt::<ast::FnArg>("port: int = 80");
+    assert!(matches!(
+        arg,
+        ast::FnArg::Default(ast::FnArgDefault { ty: Some(..), .. })
+    ));
 }
 
 /// A single argument in a closure.
@@ -17,13 +33,70 @@ pub enum FnArg {
     SelfValue(T![self]),
     /// Function argument is a pattern binding.
     Pat(ast::Pat),
+    /// Function argument is a pattern binding with a type annotation.
+    Typed(ast::PatType),
+    /// Function argument with a default value.
+    Default(ast::FnArgDefault),
+}
+
+impl FnArg {
+    /// Parse an argument which is not permitted to have a default value, like
+    /// the arguments of a closure.
+    pub(crate) fn parse_without_default(p: &mut Parser<'_>) -> Result<Self> {
+        Self::parse_with(p, false)
+    }
+
+    fn parse_with(p: &mut Parser<'_>, default: bool) -> Result<Self> {
+        if let K![self] = p.nth(0)? {
+            return Ok(Self::SelfValue(p.parse()?));
+        }
+
+        let pat = ast::Pat::parse_annotated(p)?;
+
+        let ty = match p.nth(0)? {
+            K![:] => Some((p.parse()?, p.parse()?)),
+            _ => None,
+        };
+
+        if default {
+            if let Some(eq) = p.parse()? {
+                return Ok(Self::Default(ast::FnArgDefault {
+                    pat,
+                    ty,
+                    eq,
+                    value: Box::new(p.parse()?),
+                }));
+            }
+        }
+
+        Ok(match ty {
+            Some((colon, ty)) => Self::Typed(ast::PatType { pat, colon, ty }),
+            None => Self::Pat(pat),
+        })
+    }
 }
 
 impl Parse for FnArg {
     fn parse(p: &mut Parser<'_>) -> Result<Self> {
-        Ok(match p.nth(0)? {
-            K![self] => Self::SelfValue(p.parse()?),
-            _ => Self::Pat(p.parse()?),
-        })
+        Self::parse_with(p, true)
     }
 }
+
+/// A function argument with a default value.
+///
+/// * `<pat> = <expr>`.
+/// * `<pat>: <type> = <expr>`.
+#[derive(Debug, Clone, PartialEq, Eq, ToTokens, Spanned)]
+#[non_exhaustive]
+pub struct FnArgDefault {
+    /// The pattern being bound.
+    pub pat: ast::Pat,
+    /// The optional type annotation of the argument.
+    #[rune(iter)]
+    pub ty: Option<(T![:], ast::Type)>,
+    /// The `=` separator for the default value.
+    pub eq: T![=],
+    /// The default value of the argument, which must be a constant
+    /// expression.
+    pub value: Box<ast::Expr>,
+}
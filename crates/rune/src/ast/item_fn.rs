@@ -36,6 +36,13 @@ fn ast_parse() {
     assert_eq!(item.attributes.len(), 1);
     assert!(item.async_token.is_none());
     assert!(item.const_token.is_some());
+
+    let item = rt::<ast::ItemFn>("fn hello(a: int, b) -> String {}");
+    assert!(matches!(
+        item.args.first(),
+        Some((ast::FnArg::Typed(..), _))
+    ));
+    assert!(item.output.is_some());
 }
 
 /// A function item.
@@ -64,6 +71,9 @@ pub struct ItemFn {
     pub name: ast::Ident,
     /// The arguments of the function.
     pub args: ast::Parenthesized<ast::FnArg, T![,]>,
+    /// The optional return type of the function.
+    #[rune(iter)]
+    pub output: Option<(T![->], ast::Type)>,
     /// The body of the function.
     pub body: ast::Block,
 }
@@ -1,3 +1,5 @@
+use core::mem::replace;
+
 use crate::ast::prelude::*;
 
 #[test]
@@ -10,6 +12,9 @@ fn ast_parse() {
         "#[variant(enum_= \"SuperHero\", x = \"1\")] impl Foo { fn test(self) { } }",
     );
     rt::<ast::ItemImpl>("#[xyz] impl Foo { #[jit] fn test(self) { } }");
+
+    let item = rt::<ast::ItemImpl>("impl Bar for Foo { fn test(self) { } }");
+    assert!(item.trait_.is_some());
 }
 
 /// An impl item.
@@ -21,6 +26,9 @@ pub struct ItemImpl {
     pub attributes: Vec<ast::Attribute>,
     /// The `impl` keyword.
     pub impl_: T![impl],
+    /// The trait being implemented, if any.
+    #[rune(iter)]
+    pub trait_: Option<(ast::Path, T![for])>,
     /// Path of the implementation.
     pub path: ast::Path,
     /// The open brace.
@@ -38,7 +46,15 @@ impl ItemImpl {
         attributes: Vec<ast::Attribute>,
     ) -> Result<Self> {
         let impl_ = parser.parse()?;
-        let path = parser.parse()?;
+        let mut path = parser.parse()?;
+
+        let trait_ = if parser.peek::<T![for]>()? {
+            let for_ = parser.parse()?;
+            Some((replace(&mut path, parser.parse()?), for_))
+        } else {
+            None
+        };
+
         let open = parser.parse()?;
 
         let mut functions = vec![];
@@ -52,6 +68,7 @@ impl ItemImpl {
         Ok(Self {
             attributes,
             impl_,
+            trait_,
             path,
             open,
             functions,
@@ -24,6 +24,7 @@ fn ast_parse() {
 
     rt::<ast::Field>("a");
     rt::<ast::Field>("#[x] a");
+    rt::<ast::ItemStruct>("struct Foo { a: int, b: String, c }");
 }
 
 /// A struct item.
@@ -70,4 +71,7 @@ pub struct Field {
     pub visibility: ast::Visibility,
     /// Name of the field.
     pub name: ast::Ident,
+    /// The optional type annotation of the field.
+    #[rune(iter)]
+    pub ty: Option<(T![:], ast::Type)>,
 }
@@ -0,0 +1,155 @@
+use crate::ast::prelude::*;
+
+#[test]
+fn ast_parse() {
+    use crate::testing::rt;
+
+    rt::<ast::ItemTrait>("trait Foo {}");
+    rt::<ast::ItemTrait>("pub trait Foo { fn test(self); }");
+    rt::<ast::ItemTrait>("trait Foo { fn test(self); fn other(self, a) { self.test() + a } }");
+    rt::<ast::ItemTrait>("trait Foo { #[doc = \"test\"] async fn test(self); }");
+    rt::<ast::ItemTrait>("trait Foo { fn test(self, a: int) -> int; }");
+
+    let item = rt::<ast::TraitFn>("fn test(self);");
+    assert!(item.body.is_none());
+    assert!(item.is_instance());
+
+    let item = rt::<ast::TraitFn>("fn test(self) { 42 }");
+    assert!(item.body.is_some());
+}
+
+/// A trait item.
+#[derive(Debug, Clone, PartialEq, Eq, ToTokens, Spanned)]
+#[non_exhaustive]
+pub struct ItemTrait {
+    /// The attributes of the trait.
+    #[rune(iter)]
+    pub attributes: Vec<ast::Attribute>,
+    /// The visibility of the trait.
+    #[rune(optional)]
+    pub visibility: ast::Visibility,
+    /// The `trait` keyword.
+    pub trait_token: T![trait],
+    /// The name of the trait.
+    pub name: ast::Ident,
+    /// The open brace.
+    pub open: T!['{'],
+    /// The functions declared by the trait.
+    pub functions: Vec<ast::TraitFn>,
+    /// The close brace.
+    pub close: T!['}'],
+}
+
+impl ItemTrait {
+    /// Parse a `trait` item with the given meta.
+    pub(crate) fn parse_with_meta(
+        parser: &mut Parser<'_>,
+        attributes: Vec<ast::Attribute>,
+        visibility: ast::Visibility,
+    ) -> Result<Self> {
+        let trait_token = parser.parse()?;
+        let name = parser.parse()?;
+        let open = parser.parse()?;
+
+        let mut functions = vec![];
+
+        while !parser.peek::<ast::CloseBrace>()? {
+            functions.push(parser.parse()?);
+        }
+
+        let close = parser.parse()?;
+
+        Ok(Self {
+            attributes,
+            visibility,
+            trait_token,
+            name,
+            open,
+            functions,
+            close,
+        })
+    }
+
+    /// Get the descriptive span of this item, e.g. `trait Foo` instead of the
+    /// span for the whole trait declaration.
+    pub(crate) fn descriptive_span(&self) -> Span {
+        self.trait_token.span().join(self.name.span())
+    }
+}
+
+item_parse!(Trait, ItemTrait, "trait item");
+
+/// A function declared inside of a trait.
+///
+/// Functions which are terminated by a semi-colon are required to be
+/// implemented, while functions with a body provide a default implementation.
+#[derive(Debug, Clone, PartialEq, Eq, ToTokens, Spanned)]
+#[non_exhaustive]
+pub struct TraitFn {
+    /// The attributes for the fn.
+    #[rune(iter)]
+    pub attributes: Vec<ast::Attribute>,
+    /// The optional `async` keyword.
+    #[rune(iter)]
+    pub async_token: Option<T![async]>,
+    /// The `fn` token.
+    pub fn_token: T![fn],
+    /// The name of the function.
+    pub name: ast::Ident,
+    /// The arguments of the function.
+    pub args: ast::Parenthesized<ast::FnArg, T![,]>,
+    /// The optional return type of the function.
+    #[rune(iter)]
+    pub output: Option<(T![->], ast::Type)>,
+    /// The default body of the function.
+    #[rune(iter)]
+    pub body: Option<ast::Block>,
+    /// The semi-colon terminating a function without a body.
+    #[rune(iter)]
+    pub semi: Option<T![;]>,
+}
+
+impl TraitFn {
+    /// Get the descriptive span of this function, e.g. `fn foo(self)` instead
+    /// of the span for the whole function declaration, body included.
+    pub(crate) fn descriptive_span(&self) -> Span {
+        if let Some(async_token) = &self.async_token {
+            async_token.span().join(self.args.span())
+        } else {
+            self.fn_token.span().join(self.args.span())
+        }
+    }
+
+    /// Test if function is an instance fn.
+    pub(crate) fn is_instance(&self) -> bool {
+        matches!(self.args.first(), Some((ast::FnArg::SelfValue(..), _)))
+    }
+}
+
+impl Parse for TraitFn {
+    fn parse(p: &mut Parser<'_>) -> Result<Self> {
+        let attributes = p.parse()?;
+        let async_token = p.parse()?;
+        let fn_token = p.parse()?;
+        let name = p.parse()?;
+        let args = p.parse()?;
+        let output = p.parse()?;
+
+        let (body, semi) = if p.peek::<T![;]>()? {
+            (None, Some(p.parse()?))
+        } else {
+            (Some(p.parse()?), None)
+        };
+
+        Ok(Self {
+            attributes,
+            async_token,
+            fn_token,
+            name,
+            args,
+            output,
+            body,
+            semi,
+        })
+    }
+}
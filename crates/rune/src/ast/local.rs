@@ -7,11 +7,16 @@ fn ast_parse() {
     rt::<ast::Local>("let x = 1;");
     rt::<ast::Local>("#[attr] let a = f();");
     rt::<ast::Local>("let a = b{}().foo[0].await;");
+
+    let local = rt::<ast::Local>("let a: int = 1;");
+    assert!(matches!(local.pat, ast::Pat::Path(..)));
+    assert!(local.ty.is_some());
 }
 
 /// A local variable declaration.
 ///
 /// * `let <pattern> = <expr>;`
+/// * `let <pattern>: <type> = <expr>;`
 #[derive(Debug, Clone, PartialEq, Eq, ToTokens, Parse, Spanned)]
 #[non_exhaustive]
 pub struct Local {
@@ -21,7 +26,11 @@ pub struct Local {
     /// The `let` keyword.
     pub let_token: T![let],
     /// The name of the binding.
+    #[rune(parse_with = "parse_pat")]
     pub pat: ast::Pat,
+    /// The optional type annotation of the binding.
+    #[rune(iter)]
+    pub ty: Option<(T![:], ast::Type)>,
     /// The equality keyword.
     pub eq: T![=],
     /// The expression the binding is assigned to.
@@ -31,6 +40,10 @@ pub struct Local {
     pub semi: T![;],
 }
 
+fn parse_pat(p: &mut Parser<'_>) -> Result<ast::Pat> {
+    ast::Pat::parse_annotated(p)
+}
+
 fn parse_expr(p: &mut Parser<'_>) -> Result<ast::Expr> {
     ast::Expr::parse_with(
         p,
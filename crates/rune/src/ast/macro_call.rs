@@ -6,11 +6,13 @@ fn ast_parse() {
 
     rt::<ast::MacroCall>("foo!()");
     rt::<ast::MacroCall>("::bar::foo!(question to life)");
+    rt::<ast::MacroCall>("macro_rules! foo { ($a:expr) => { $a } }");
 }
 
 /// A macro call.
 ///
 /// * `<expr>!(<args>)`.
+/// * `<expr>! <name> {<args>}`, as used to define macros.
 #[derive(Debug, Clone, PartialEq, Eq, ToTokens, Spanned, Opaque)]
 #[non_exhaustive]
 pub struct MacroCall {
@@ -25,6 +27,9 @@ pub struct MacroCall {
     pub path: ast::Path,
     /// Bang operator `!`.
     pub bang: T![!],
+    /// The name of the macro being defined, like in `macro_rules! name {}`.
+    #[rune(iter)]
+    pub name: Option<ast::Ident>,
     /// Opening token.
     pub open: ast::Token,
     /// The tokens provided to the macro.
@@ -56,6 +61,7 @@ impl MacroCall {
         path: ast::Path,
     ) -> Result<Self> {
         let bang = parser.parse()?;
+        let name = parser.parse()?;
 
         let mut level = 1;
         let open = parser.next()?;
@@ -104,6 +110,7 @@ impl MacroCall {
             id: Default::default(),
             attributes,
             bang,
+            name,
             path,
             open,
             input: TokenStream::from(stream),
@@ -1,3 +1,5 @@
+use core::iter;
+
 use crate::ast::prelude::*;
 
 #[test]
@@ -16,6 +18,26 @@ fn ast_parse() {
     rt::<ast::Pat>("var");
     rt::<ast::Pat>("_");
     rt::<ast::Pat>("Foo(n)");
+    rt::<ast::Pat>("1 | 2 | 3");
+    rt::<ast::Pat>("(1 | 2, Foo(n) | Bar(n))");
+    rt::<ast::Pat>("1..=5");
+    rt::<ast::Pat>("1..5");
+    rt::<ast::Pat>("-5..");
+    rt::<ast::Pat>("..=-5");
+    rt::<ast::Pat>("'a'..='z'");
+    rt::<ast::Pat>("b'0'..=b'9'");
+    rt::<ast::Pat>("n @ 1..=5");
+    rt::<ast::Pat>("n @ Foo(..)");
+
+    let pat = rt::<ast::Pat>("1..");
+    assert!(matches!(
+        pat,
+        ast::Pat::Range(ast::PatRange { to: None, .. })
+    ));
+
+    let ty = rt::<ast::PatType>("a: int");
+    assert!(matches!(ty.pat, ast::Pat::Path(..)));
+    rt::<ast::PatType>("(a, b): (int, String)");
 }
 
 /// A pattern match.
@@ -38,24 +60,51 @@ pub enum Pat {
     Binding(PatBinding),
     /// The rest pattern `..`.
     Rest(PatRest),
+    /// An or-pattern `a | b`.
+    Or(PatOr),
+    /// A range pattern `1..=5` or `'a'..='z'`.
+    Range(PatRange),
+    /// A binding with a subpattern `n @ pattern`.
+    At(PatAt),
 }
 
 impl Parse for Pat {
     fn parse(p: &mut Parser<'_>) -> Result<Self> {
+        let first = Self::parse_single(p)?;
+
+        if !p.peek::<T![|]>()? {
+            return Ok(first);
+        }
+
+        let mut rest = Vec::new();
+
+        while let Some(pipe) = p.parse::<Option<T![|]>>()? {
+            rest.push((pipe, Self::parse_single(p)?));
+        }
+
+        Ok(Self::Or(PatOr {
+            first: Box::new(first),
+            rest,
+        }))
+    }
+}
+
+impl Pat {
+    /// Parse a single pattern, without any `|` alternatives.
+    ///
+    /// This is used where `|` has a different meaning, like in closure
+    /// arguments.
+    pub(crate) fn parse_single(p: &mut Parser<'_>) -> Result<Self> {
         let attributes = p.parse::<Vec<ast::Attribute>>()?;
 
         match p.nth(0)? {
             K![byte] => {
-                return Ok(Self::Lit(PatLit {
-                    attributes,
-                    expr: Box::new(ast::Expr::from_lit(ast::Lit::Byte(p.parse()?))),
-                }));
+                let expr = Box::new(ast::Expr::from_lit(ast::Lit::Byte(p.parse()?)));
+                return Self::parse_lit_or_range(p, attributes, expr);
             }
             K![char] => {
-                return Ok(Self::Lit(PatLit {
-                    attributes,
-                    expr: Box::new(ast::Expr::from_lit(ast::Lit::Char(p.parse()?))),
-                }));
+                let expr = Box::new(ast::Expr::from_lit(ast::Lit::Char(p.parse()?)));
+                return Self::parse_lit_or_range(p, attributes, expr);
             }
             K![bytestr] => {
                 return Ok(Self::Lit(PatLit {
@@ -84,9 +133,24 @@ impl Parse for Pat {
                 });
             }
             K![number] => {
-                return Ok(Self::Lit(PatLit {
+                let expr = Box::new(ast::Expr::from_lit(ast::Lit::Number(p.parse()?)));
+                return Self::parse_lit_or_range(p, attributes, expr);
+            }
+            K![..=] => {
+                let limits = p.parse()?;
+
+                let Some(to) = Self::parse_range_bound(p)? else {
+                    return Err(compile::Error::expected(
+                        p.tok_at(0)?,
+                        "range pattern bound",
+                    ));
+                };
+
+                return Ok(Self::Range(PatRange {
                     attributes,
-                    expr: Box::new(ast::Expr::from_lit(ast::Lit::Number(p.parse()?))),
+                    from: None,
+                    limits,
+                    to: Some(to),
                 }));
             }
             K![..] => {
@@ -120,13 +184,8 @@ impl Parse for Pat {
                 }))
             }
             K![-] => {
-                let expr: ast::Expr = p.parse()?;
-
-                if expr.is_lit() {
-                    return Ok(Self::Lit(PatLit {
-                        attributes,
-                        expr: Box::new(expr),
-                    }));
+                if let Some(expr) = Self::parse_range_bound(p)? {
+                    return Self::parse_lit_or_range(p, attributes, expr);
                 }
             }
             K![_] => {
@@ -135,6 +194,14 @@ impl Parse for Pat {
                     underscore: p.parse()?,
                 }))
             }
+            K![ident] if matches!(p.nth(1)?, K![@]) => {
+                return Ok(Self::At(PatAt {
+                    attributes,
+                    name: p.parse()?,
+                    at: p.parse()?,
+                    pat: Box::new(Self::parse_single(p)?),
+                }));
+            }
             _ if ast::Path::peek(p.peeker()) => {
                 let path = p.parse::<ast::Path>()?;
 
@@ -163,6 +230,69 @@ impl Parse for Pat {
 
         Err(compile::Error::expected(p.tok_at(0)?, "pattern"))
     }
+
+    /// Parse what follows a literal, which might turn it into a range pattern.
+    fn parse_lit_or_range(
+        p: &mut Parser<'_>,
+        attributes: Vec<ast::Attribute>,
+        expr: Box<ast::Expr>,
+    ) -> Result<Self> {
+        if !matches!(p.nth(0)?, K![..] | K![..=]) {
+            return Ok(Self::Lit(PatLit { attributes, expr }));
+        }
+
+        let limits = p.parse()?;
+        let to = Self::parse_range_bound(p)?;
+
+        if let (ast::ExprRangeLimits::Closed(..), None) = (&limits, &to) {
+            return Err(compile::Error::expected(
+                p.tok_at(0)?,
+                "range pattern bound",
+            ));
+        }
+
+        Ok(Self::Range(PatRange {
+            attributes,
+            from: Some(expr),
+            limits,
+            to,
+        }))
+    }
+
+    /// Parse the bound of a range pattern, which is a literal or a negated
+    /// number.
+    fn parse_range_bound(p: &mut Parser<'_>) -> Result<Option<Box<ast::Expr>>> {
+        let lit = match p.nth(0)? {
+            K![byte] => ast::Lit::Byte(p.parse()?),
+            K![char] => ast::Lit::Char(p.parse()?),
+            K![number] => ast::Lit::Number(p.parse()?),
+            K![-] if matches!(p.nth(1)?, K![number]) => {
+                return Ok(Some(Box::new(ast::Expr::Unary(ast::ExprUnary {
+                    attributes: Vec::new(),
+                    op: p.parse()?,
+                    expr: Box::new(ast::Expr::from_lit(ast::Lit::Number(p.parse()?))),
+                }))));
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(Box::new(ast::Expr::from_lit(lit))))
+    }
+    /// Parse a pattern which might be followed by a type annotation, like the
+    /// `a` in `let a: int = 42;`.
+    ///
+    /// An identifier followed by a colon is parsed as a path pattern instead of
+    /// an object binding.
+    pub(crate) fn parse_annotated(p: &mut Parser<'_>) -> Result<Self> {
+        if matches!((p.nth(0)?, p.nth(1)?), (K![ident], K![:])) {
+            return Ok(Self::Path(PatPath {
+                attributes: Vec::new(),
+                path: p.parse()?,
+            }));
+        }
+
+        Self::parse_single(p)
+    }
 }
 
 impl Peek for Pat {
@@ -172,7 +302,7 @@ impl Peek for Pat {
             K!['['] => true,
             K![#] => matches!(p.nth(1), K!['{']),
             K![_] => true,
-            K![..] => true,
+            K![..] | K![..=] => true,
             K![byte] | K![char] | K![number] | K![str] => true,
             K![true] | K![false] => true,
             K![-] => matches!(p.nth(1), K![number]),
@@ -256,6 +386,78 @@ pub struct PatBinding {
     pub pat: Box<ast::Pat>,
 }
 
+/// An or-pattern `a | b`, which matches if any of its alternatives match.
+#[derive(Debug, Clone, PartialEq, Eq, ToTokens, Spanned)]
+#[non_exhaustive]
+pub struct PatOr {
+    /// The first alternative.
+    pub first: Box<ast::Pat>,
+    /// The remaining alternatives, each preceded by a pipe `|`.
+    #[rune(iter)]
+    pub rest: Vec<(T![|], ast::Pat)>,
+}
+
+impl PatOr {
+    /// Iterate over all alternatives of the pattern.
+    pub fn alternatives(&self) -> impl Iterator<Item = &ast::Pat> {
+        iter::once(&*self.first).chain(self.rest.iter().map(|(_, pat)| pat))
+    }
+}
+
+/// A range pattern `1..=5`, `1..` or `'a'..='z'`.
+#[derive(Debug, Clone, PartialEq, Eq, ToTokens, Spanned)]
+#[non_exhaustive]
+pub struct PatRange {
+    /// Attributes associated with the pattern.
+    #[rune(iter)]
+    pub attributes: Vec<ast::Attribute>,
+    /// Start of the range.
+    #[rune(iter)]
+    pub from: Option<Box<ast::Expr>>,
+    /// The range limits.
+    pub limits: ast::ExprRangeLimits,
+    /// End of the range.
+    #[rune(iter)]
+    pub to: Option<Box<ast::Expr>>,
+}
+
+/// A binding with a subpattern `n @ pattern`.
+#[derive(Debug, Clone, PartialEq, Eq, ToTokens, Spanned)]
+#[non_exhaustive]
+pub struct PatAt {
+    /// Attributes associated with the pattern.
+    #[rune(iter)]
+    pub attributes: Vec<ast::Attribute>,
+    /// The name being bound.
+    pub name: ast::Ident,
+    /// The at token `@`.
+    pub at: T![@],
+    /// The pattern that has to match.
+    pub pat: Box<ast::Pat>,
+}
+
+/// A pattern with a type annotation `a: int`.
+#[derive(Debug, Clone, PartialEq, Eq, ToTokens, Spanned)]
+#[non_exhaustive]
+pub struct PatType {
+    /// The pattern being annotated.
+    pub pat: ast::Pat,
+    /// The colon separator for the type.
+    pub colon: T![:],
+    /// The type of the pattern.
+    pub ty: ast::Type,
+}
+
+impl Parse for PatType {
+    fn parse(p: &mut Parser<'_>) -> Result<Self> {
+        Ok(Self {
+            pat: Pat::parse_annotated(p)?,
+            colon: p.parse()?,
+            ty: p.parse()?,
+        })
+    }
+}
+
 /// A path pattern.
 #[derive(Debug, Clone, PartialEq, Eq, ToTokens, Spanned)]
 #[non_exhaustive]
@@ -0,0 +1,39 @@
+use crate::ast::prelude::*;
+
+#[test]
+fn ast_parse() {
+    use crate::testing::rt;
+
+    rt::<ast::Type>("int");
+    rt::<ast::Type>("std::string::String");
+    rt::<ast::Type>("()");
+    rt::<ast::Type>("(int, String)");
+    rt::<ast::Type>("!");
+}
+
+/// A type annotation.
+///
+/// * `int`.
+/// * `std::string::String`.
+/// * `()`.
+#[derive(Debug, Clone, PartialEq, Eq, ToTokens, Spanned)]
+#[non_exhaustive]
+pub enum Type {
+    /// A type referenced through a path.
+    Path(ast::Path),
+    /// The never type `!`, used by functions which never return.
+    Bang(T![!]),
+    /// A tuple type, where the empty tuple is the unit type `()`.
+    Tuple(ast::Parenthesized<Box<ast::Type>, T![,]>),
+}
+
+impl Parse for Type {
+    fn parse(p: &mut Parser<'_>) -> Result<Self> {
+        Ok(match p.nth(0)? {
+            K![!] => Self::Bang(p.parse()?),
+            K!['('] => Self::Tuple(p.parse()?),
+            _ if ast::Path::peek(p.peeker()) => Self::Path(p.parse()?),
+            _ => return Err(compile::Error::expected(p.tok_at(0)?, "type")),
+        })
+    }
+}
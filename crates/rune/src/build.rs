@@ -7,7 +7,7 @@ use crate::no_std::thiserror;
 
 use thiserror::Error;
 
-use crate::ast::{Span, Spanned};
+use crate::ast::{self, Span, Spanned};
 use crate::compile;
 use crate::compile::{CompileVisitor, FileSourceLoader, Options, Pool, SourceLoader};
 use crate::runtime::unit::{DefaultStorage, UnitEncoder};
@@ -130,6 +130,30 @@ impl<'a> compile::CompileVisitor for CompileVisitorGroup<'a> {
         }
     }
 
+    fn visit_item_name(&mut self, location: compile::Location, hash: crate::Hash) {
+        for v in self.visitors.iter_mut() {
+            v.visit_item_name(location, hash)
+        }
+    }
+
+    fn visit_field_name(&mut self, location: compile::Location, hash: crate::Hash, field: &str) {
+        for v in self.visitors.iter_mut() {
+            v.visit_field_name(location, hash, field)
+        }
+    }
+
+    fn visit_field_use(&mut self, location: compile::Location, hash: crate::Hash, field: &str) {
+        for v in self.visitors.iter_mut() {
+            v.visit_field_use(location, hash, field)
+        }
+    }
+
+    fn visit_call(&mut self, source_id: SourceId, hash: crate::Hash, ast: &ast::ExprCall) {
+        for v in self.visitors.iter_mut() {
+            v.visit_call(source_id, hash, ast)
+        }
+    }
+
     fn visit_doc_comment(
         &mut self,
         location: compile::Location,
@@ -8,6 +8,7 @@
 
 mod benches;
 mod check;
+mod dap;
 mod doc;
 mod format;
 mod languageserver;
@@ -375,12 +376,14 @@ enum Command {
     Fmt(CommandShared<format::Flags>),
     /// Run a language server.
     LanguageServer(SharedFlags),
+    /// Run a debug adapter speaking the Debug Adapter Protocol over stdio.
+    Dap(SharedFlags),
     /// Helper command to generate type hashes.
     Hash(HashFlags),
 }
 
 impl Command {
-    const ALL: [&str; 8] = [
+    const ALL: [&str; 9] = [
         "check",
         "doc",
         "test",
@@ -388,6 +391,7 @@ impl Command {
         "run",
         "fmt",
         "languageserver",
+        "dap",
         "hash",
     ];
 
@@ -400,6 +404,7 @@ impl Command {
             Command::Run(shared) => (&mut shared.shared, &mut shared.command),
             Command::Fmt(shared) => (&mut shared.shared, &mut shared.command),
             Command::LanguageServer(..) => return None,
+            Command::Dap(..) => return None,
             Command::Hash(..) => return None,
         };
 
@@ -415,6 +420,7 @@ impl Command {
             Command::Run(shared) => (&shared.shared, &shared.command),
             Command::Fmt(shared) => (&shared.shared, &shared.command),
             Command::LanguageServer(..) => return None,
+            Command::Dap(..) => return None,
             Command::Hash(..) => return None,
         };
 
@@ -572,6 +578,8 @@ struct SharedFlags {
     /// macros[=<true/false>] - Enable or disable macros (experimental).
     ///
     /// bytecode[=<true/false>] - Enable or disable bytecode caching (experimental).
+    ///
+    /// opt[=<true/false>] - Enable or disable peephole and constant-folding optimizations.
     #[arg(name = "option", short = 'O', number_of_values = 1)]
     compiler_options: Vec<String>,
 
@@ -798,6 +806,7 @@ where
         }
         Command::Test(f) => {
             let options = f.options()?;
+            let mut coverage_output = f.command.coverage_output()?;
 
             for e in entrys {
                 let capture = crate::modules::capture_io::CaptureIo::new();
@@ -820,6 +829,7 @@ where
                     load.unit,
                     &load.sources,
                     &load.functions,
+                    coverage_output.as_mut(),
                 )
                 .await?
                 {
@@ -884,6 +894,11 @@ where
             let context = shared.context(entry, c, None)?;
             languageserver::run(context).await?;
         }
+        Command::Dap(shared) => {
+            let capture = crate::modules::capture_io::CaptureIo::new();
+            let context = shared.context(entry, c, Some(&capture))?;
+            dap::run(context, &capture).await?;
+        }
         Command::Hash(args) => {
             use rand::prelude::*;
 
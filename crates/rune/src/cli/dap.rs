@@ -0,0 +1,604 @@
+//! A Debug Adapter Protocol server driving the [Debugger].
+//!
+//! The server speaks the protocol over stdio and supports launching a single
+//! program, setting line breakpoints, inspecting the call stack and locals, and
+//! stepping through execution.
+//!
+//! The program runs on a blocking task while it's being resumed, so that
+//! requests such as `pause` are served while it's running.
+
+mod protocol;
+
+#[cfg(test)]
+mod tests;
+
+use std::path::{Path, PathBuf};
+
+use crate::no_std::prelude::*;
+use crate::no_std::sync::Arc;
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use tokio::io::{AsyncBufRead, AsyncWrite, BufReader};
+use tokio::task::JoinHandle;
+
+use crate::modules::capture_io::CaptureIo;
+use crate::runtime::debugger::{DebugEvent, Debugger, PauseHandle, StepMode};
+use crate::runtime::{CancellationToken, RuntimeContext, VmSendExecution};
+use crate::termcolor::Buffer;
+use crate::{Context, Diagnostics, Options, Source, SourceId, Sources, Vm};
+
+use self::protocol::{
+    Breakpoint, InitializeArguments, Input, LaunchArguments, Output, Request, Scope,
+    ScopesArguments, SetBreakpointsArguments, StackFrame, Variable, VariablesArguments,
+};
+
+/// The only thread exposed to clients.
+const THREAD_ID: i64 = 1;
+
+pub(super) async fn run(context: Context, capture: &CaptureIo) -> Result<()> {
+    let input = BufReader::new(tokio::io::stdin());
+    serve(input, tokio::io::stdout(), &context, Some(capture)).await
+}
+
+/// Serve the debug adapter protocol over the given input and output.
+pub(super) async fn serve<R, W>(
+    input: R,
+    output: W,
+    context: &Context,
+    capture: Option<&CaptureIo>,
+) -> Result<()>
+where
+    R: Unpin + AsyncBufRead,
+    W: Unpin + AsyncWrite,
+{
+    let mut input = Input::new(input);
+
+    let mut state = State {
+        output: Output::new(output),
+        context,
+        runtime: Arc::new(context.runtime()),
+        capture,
+        lines_start_at1: true,
+        columns_start_at1: true,
+        session: None,
+        running: None,
+        cancellation: None,
+    };
+
+    loop {
+        let next = input.next();
+        tokio::pin!(next);
+
+        // Keep reading the same request while reporting back on the running
+        // program, since reading a request can't be cancelled halfway through.
+        let request = loop {
+            let Some(mut running) = state.running.take() else {
+                break next.await?;
+            };
+
+            tokio::select! {
+                request = &mut next => {
+                    state.running = Some(running);
+                    break request?;
+                }
+                result = &mut running.task => {
+                    let (session, outcome) = result?;
+                    state.finish(session, outcome).await?;
+                }
+            }
+        };
+
+        let Some(request) = request else {
+            break;
+        };
+
+        tracing::trace!(?request);
+
+        if !state.handle(&request).await? {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// A launched program.
+struct Session {
+    sources: Sources,
+    debugger: Debugger,
+    /// The execution being debugged, or `None` if it has terminated.
+    execution: Option<VmSendExecution>,
+    stop_on_entry: bool,
+}
+
+impl Session {
+    /// Resume the execution until it stops or terminates, blocking the current
+    /// thread.
+    fn resume(&mut self, mode: StepMode) -> Outcome {
+        let Some(VmSendExecution(execution)) = &mut self.execution else {
+            return Outcome::Exited(0, None);
+        };
+
+        let outcome = match self.debugger.resume(execution, mode).into_result() {
+            Ok(DebugEvent::Breakpoint { .. }) => return Outcome::Stopped("breakpoint"),
+            Ok(DebugEvent::Step { .. }) => return Outcome::Stopped("step"),
+            Ok(DebugEvent::Pause { .. }) => return Outcome::Stopped("pause"),
+            Ok(DebugEvent::Complete(..)) => Outcome::Exited(0, None),
+            Err(error) => {
+                let mut buffer = Buffer::no_color();
+                let _ = error.emit(&mut buffer, &self.sources);
+                let output = String::from_utf8_lossy(buffer.as_slice()).into_owned();
+                Outcome::Exited(1, Some(output))
+            }
+        };
+
+        self.execution = None;
+        outcome
+    }
+}
+
+/// How resuming a session ended.
+enum Outcome {
+    /// The execution stopped for the given reason.
+    Stopped(&'static str),
+    /// The execution terminated with the given exit code and error output.
+    Exited(i64, Option<String>),
+}
+
+/// A session whose execution is running on a blocking task.
+struct Running {
+    task: JoinHandle<(Session, Outcome)>,
+    pause: PauseHandle,
+}
+
+struct State<'a, W> {
+    output: Output<W>,
+    context: &'a Context,
+    runtime: Arc<RuntimeContext>,
+    capture: Option<&'a CaptureIo>,
+    lines_start_at1: bool,
+    columns_start_at1: bool,
+    /// The launched program, unless it's running.
+    session: Option<Session>,
+    running: Option<Running>,
+    /// Cancels the execution of the launched program once the server stops.
+    cancellation: Option<CancellationToken>,
+}
+
+impl<W> Drop for State<'_, W> {
+    fn drop(&mut self) {
+        if let Some(cancellation) = &self.cancellation {
+            cancellation.cancel();
+        }
+    }
+}
+
+impl<'a, W> State<'a, W>
+where
+    W: Unpin + AsyncWrite,
+{
+    /// Handle a single request, returning `false` if the server should stop.
+    async fn handle(&mut self, request: &Request) -> Result<bool> {
+        match request.command.as_str() {
+            "initialize" => {
+                let args: InitializeArguments =
+                    serde_json::from_value(request.arguments.clone()).unwrap_or_default();
+                self.lines_start_at1 = args.lines_start_at1;
+                self.columns_start_at1 = args.columns_start_at1;
+
+                let capabilities = json!({
+                    "supportsConfigurationDoneRequest": true,
+                });
+
+                self.output.response(request, capabilities).await?;
+            }
+            "launch" => {
+                let Some(args) = self.arguments::<LaunchArguments>(request).await? else {
+                    return Ok(true);
+                };
+
+                match self.launch(&args) {
+                    Ok((session, cancellation)) => {
+                        self.session = Some(session);
+                        self.cancellation = Some(cancellation);
+                        self.output.response(request, ()).await?;
+                        self.output.event("initialized", ()).await?;
+                    }
+                    Err(message) => {
+                        self.output.error(request, &message).await?;
+                    }
+                }
+            }
+            "setBreakpoints" => {
+                let Some(args) = self.arguments::<SetBreakpointsArguments>(request).await? else {
+                    return Ok(true);
+                };
+
+                if self.running.is_some() {
+                    self.output
+                        .error(request, "Breakpoints can't be set while running")
+                        .await?;
+                    return Ok(true);
+                }
+
+                let Some(session) = &mut self.session else {
+                    self.output.error(request, "No program launched").await?;
+                    return Ok(true);
+                };
+
+                let source_id = args
+                    .source
+                    .path
+                    .as_deref()
+                    .and_then(|path| find_source(&session.sources, Path::new(path)));
+
+                let mut breakpoints = Vec::new();
+
+                if let Some(source_id) = source_id {
+                    session.debugger.clear_breakpoints(source_id);
+                }
+
+                for breakpoint in args.breakpoints {
+                    let line = match source_id {
+                        Some(source_id) => {
+                            let line = from_client(breakpoint.line, self.lines_start_at1);
+                            session.debugger.set_breakpoint(source_id, line)
+                        }
+                        None => None,
+                    };
+
+                    breakpoints.push(Breakpoint {
+                        verified: line.is_some(),
+                        line: line.map(|line| to_client(line, self.lines_start_at1)),
+                    });
+                }
+
+                self.output
+                    .response(request, json!({ "breakpoints": breakpoints }))
+                    .await?;
+            }
+            "configurationDone" => {
+                self.output.response(request, ()).await?;
+
+                let stop_on_entry = match &self.session {
+                    Some(session) => session.stop_on_entry,
+                    None => return Ok(true),
+                };
+
+                if stop_on_entry {
+                    self.stopped("entry").await?;
+                } else {
+                    self.resume(StepMode::Continue);
+                }
+            }
+            "threads" => {
+                let threads = json!({
+                    "threads": [{ "id": THREAD_ID, "name": "main" }],
+                });
+
+                self.output.response(request, threads).await?;
+            }
+            "stackTrace" => {
+                let frames = self.stack_frames();
+
+                self.output
+                    .response(
+                        request,
+                        json!({ "stackFrames": frames, "totalFrames": frames.len() }),
+                    )
+                    .await?;
+            }
+            "scopes" => {
+                let Some(args) = self.arguments::<ScopesArguments>(request).await? else {
+                    return Ok(true);
+                };
+
+                let scopes = [Scope {
+                    name: "Locals",
+                    variables_reference: args.frame_id + 1,
+                    expensive: false,
+                }];
+
+                self.output
+                    .response(request, json!({ "scopes": scopes }))
+                    .await?;
+            }
+            "variables" => {
+                let Some(args) = self.arguments::<VariablesArguments>(request).await? else {
+                    return Ok(true);
+                };
+                let variables = self.variables(args.variables_reference);
+
+                self.output
+                    .response(request, json!({ "variables": variables }))
+                    .await?;
+            }
+            "continue" => {
+                self.output
+                    .response(request, json!({ "allThreadsContinued": true }))
+                    .await?;
+                self.resume(StepMode::Continue);
+            }
+            "next" => {
+                self.output.response(request, ()).await?;
+                self.resume(StepMode::Over);
+            }
+            "stepIn" => {
+                self.output.response(request, ()).await?;
+                self.resume(StepMode::Into);
+            }
+            "stepOut" => {
+                self.output.response(request, ()).await?;
+                self.resume(StepMode::Out);
+            }
+            "pause" => {
+                if let Some(running) = &self.running {
+                    running.pause.pause();
+                }
+
+                self.output.response(request, ()).await?;
+            }
+            "disconnect" | "terminate" => {
+                self.output.response(request, ()).await?;
+                return Ok(false);
+            }
+            command => {
+                self.output
+                    .error(request, &format!("Unsupported command `{command}`"))
+                    .await?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Deserialize the arguments of the given request, replying with an error
+    /// if they're malformed.
+    async fn arguments<T>(&mut self, request: &Request) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        match serde_json::from_value(request.arguments.clone()) {
+            Ok(args) => Ok(Some(args)),
+            Err(error) => {
+                self.output
+                    .error(request, &format!("Malformed arguments: {error}"))
+                    .await?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Compile the program to launch and set up its execution.
+    fn launch(&self, args: &LaunchArguments) -> Result<(Session, CancellationToken), String> {
+        let mut sources = Sources::new();
+
+        let source = Source::from_path(&args.program)
+            .map_err(|error| format!("Failed to load `{}`: {error}", args.program))?;
+
+        sources.insert(source);
+
+        let mut options = Options::default();
+        options.debug_info(true);
+
+        let mut diagnostics = Diagnostics::new();
+
+        let result = crate::prepare(&mut sources)
+            .with_context(self.context)
+            .with_diagnostics(&mut diagnostics)
+            .with_options(&options)
+            .build();
+
+        let Ok(unit) = result else {
+            let mut buffer = Buffer::no_color();
+            let _ = diagnostics.emit(&mut buffer, &sources);
+            return Err(String::from_utf8_lossy(buffer.as_slice()).into_owned());
+        };
+
+        let unit = Arc::new(unit);
+        let debugger = Debugger::new(unit.clone(), &sources);
+        let vm = Vm::new(self.runtime.clone(), unit);
+
+        let mut execution = vm
+            .send_execute(["main"], ())
+            .map_err(|error| error.to_string())?;
+
+        let cancellation = CancellationToken::new();
+        execution.set_cancellation(cancellation.clone());
+
+        let session = Session {
+            sources,
+            debugger,
+            execution: Some(execution),
+            stop_on_entry: args.stop_on_entry,
+        };
+
+        Ok((session, cancellation))
+    }
+
+    /// Resume the execution of the launched program on a blocking task.
+    ///
+    /// The outcome is reported through [State::finish] once it stops or
+    /// terminates.
+    fn resume(&mut self, mode: StepMode) {
+        let Some(mut session) = self.session.take() else {
+            return;
+        };
+
+        if session.execution.is_none() {
+            self.session = Some(session);
+            return;
+        }
+
+        let pause = session.debugger.pause_handle();
+
+        let task = tokio::task::spawn_blocking(move || {
+            let outcome = session.resume(mode);
+            (session, outcome)
+        });
+
+        self.running = Some(Running { task, pause });
+    }
+
+    /// Report the outcome of resuming the given session.
+    async fn finish(&mut self, session: Session, outcome: Outcome) -> Result<()> {
+        self.session = Some(session);
+
+        if let Some(capture) = self.capture {
+            let output = capture.drain();
+
+            if !output.is_empty() {
+                let output = String::from_utf8_lossy(&output);
+
+                self.output
+                    .event("output", json!({ "category": "stdout", "output": output }))
+                    .await?;
+            }
+        }
+
+        let (exit_code, error) = match outcome {
+            Outcome::Stopped(reason) => return self.stopped(reason).await,
+            Outcome::Exited(exit_code, error) => (exit_code, error),
+        };
+
+        if let Some(output) = error {
+            self.output
+                .event("output", json!({ "category": "stderr", "output": output }))
+                .await?;
+        }
+
+        self.output
+            .event("exited", json!({ "exitCode": exit_code }))
+            .await?;
+        self.output.event("terminated", ()).await?;
+        Ok(())
+    }
+
+    async fn stopped(&mut self, reason: &str) -> Result<()> {
+        let body = json!({
+            "reason": reason,
+            "threadId": THREAD_ID,
+            "allThreadsStopped": true,
+        });
+
+        self.output.event("stopped", body).await
+    }
+
+    fn stack_frames(&self) -> Vec<StackFrame> {
+        let Some(Session {
+            sources,
+            debugger,
+            execution: Some(execution),
+            ..
+        }) = &self.session
+        else {
+            return Vec::new();
+        };
+
+        let debug = debugger.unit().debug_info();
+        let mut frames = Vec::new();
+
+        for (id, frame) in debugger.frames(execution.0.vm()).into_iter().enumerate() {
+            let name = frame
+                .function
+                .and_then(|hash| debug?.functions.get(&hash))
+                .map(|signature| signature.to_string())
+                .unwrap_or_else(|| String::from("<unknown>"));
+
+            let (source, line, column) = match frame.location {
+                Some(location) => {
+                    let source = sources
+                        .get(location.source_id)
+                        .map(|source| protocol::Source {
+                            name: Some(source.name().into()),
+                            path: source.path().map(|path| path.display().to_string()),
+                        });
+
+                    (
+                        source,
+                        to_client(location.line, self.lines_start_at1),
+                        to_client(location.column, self.columns_start_at1),
+                    )
+                }
+                None => (None, 0, 0),
+            };
+
+            frames.push(StackFrame {
+                id,
+                name,
+                source,
+                line,
+                column,
+            });
+        }
+
+        frames
+    }
+
+    fn variables(&self, reference: usize) -> Vec<Variable> {
+        let Some(Session {
+            debugger,
+            execution: Some(execution),
+            ..
+        }) = &self.session
+        else {
+            return Vec::new();
+        };
+
+        let Some(index) = reference.checked_sub(1) else {
+            return Vec::new();
+        };
+
+        let vm = execution.0.vm();
+        let frames = debugger.frames(vm);
+
+        let Some(frame) = frames.get(index) else {
+            return Vec::new();
+        };
+
+        debugger
+            .locals(vm, frame)
+            .into_iter()
+            .map(|local| Variable {
+                name: local.name.into(),
+                value: format!("{:?}", local.value),
+                variables_reference: 0,
+            })
+            .collect()
+    }
+}
+
+/// Find the source matching the given path.
+fn find_source(sources: &Sources, path: &Path) -> Option<SourceId> {
+    let expected = canonicalize(path);
+
+    sources.source_ids().find(|&id| {
+        sources
+            .path(id)
+            .map_or(false, |candidate| canonicalize(candidate) == expected)
+    })
+}
+
+fn canonicalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_owned())
+}
+
+/// Convert a line or column from the client into a zero-based one.
+fn from_client(value: usize, start_at1: bool) -> usize {
+    if start_at1 {
+        value.saturating_sub(1)
+    } else {
+        value
+    }
+}
+
+/// Convert a zero-based line or column into one expected by the client.
+fn to_client(value: usize, start_at1: bool) -> usize {
+    if start_at1 {
+        value + 1
+    } else {
+        value
+    }
+}
@@ -0,0 +1,283 @@
+//! Wire types and framing for the Debug Adapter Protocol.
+
+use crate::no_std::prelude::*;
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt as _, AsyncReadExt as _, AsyncWrite, AsyncWriteExt as _,
+};
+
+/// An incoming request.
+#[derive(Debug, Deserialize)]
+pub(super) struct Request {
+    pub(super) seq: i64,
+    pub(super) command: String,
+    #[serde(default)]
+    pub(super) arguments: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+struct Response<'a> {
+    seq: i64,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    request_seq: i64,
+    success: bool,
+    command: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct Event<'a> {
+    seq: i64,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    event: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<serde_json::Value>,
+}
+
+/// Arguments to the `initialize` request.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct InitializeArguments {
+    #[serde(default = "default_true")]
+    pub(super) lines_start_at1: bool,
+    #[serde(default = "default_true")]
+    pub(super) columns_start_at1: bool,
+}
+
+/// Arguments to the `launch` request.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct LaunchArguments {
+    pub(super) program: String,
+    #[serde(default)]
+    pub(super) stop_on_entry: bool,
+}
+
+/// A source reference.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct Source {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) path: Option<String>,
+}
+
+/// A breakpoint requested by the client.
+#[derive(Debug, Deserialize)]
+pub(super) struct SourceBreakpoint {
+    pub(super) line: usize,
+}
+
+/// Arguments to the `setBreakpoints` request.
+#[derive(Debug, Deserialize)]
+pub(super) struct SetBreakpointsArguments {
+    pub(super) source: Source,
+    #[serde(default)]
+    pub(super) breakpoints: Vec<SourceBreakpoint>,
+}
+
+/// A breakpoint as reported back to the client.
+#[derive(Debug, Serialize)]
+pub(super) struct Breakpoint {
+    pub(super) verified: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) line: Option<usize>,
+}
+
+/// Arguments to the `scopes` request.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct ScopesArguments {
+    pub(super) frame_id: usize,
+}
+
+/// Arguments to the `variables` request.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct VariablesArguments {
+    pub(super) variables_reference: usize,
+}
+
+/// A stack frame.
+#[derive(Debug, Serialize)]
+pub(super) struct StackFrame {
+    pub(super) id: usize,
+    pub(super) name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) source: Option<Source>,
+    pub(super) line: usize,
+    pub(super) column: usize,
+}
+
+/// A scope in a stack frame.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct Scope {
+    pub(super) name: &'static str,
+    pub(super) variables_reference: usize,
+    pub(super) expensive: bool,
+}
+
+/// A variable in a scope.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct Variable {
+    pub(super) name: String,
+    pub(super) value: String,
+    pub(super) variables_reference: usize,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Input connection reading framed requests.
+pub(super) struct Input<R> {
+    buf: Vec<u8>,
+    reader: R,
+}
+
+impl<R> Input<R>
+where
+    R: Unpin + AsyncBufRead,
+{
+    pub(super) fn new(reader: R) -> Self {
+        Self {
+            buf: Vec::new(),
+            reader,
+        }
+    }
+
+    /// Read the next request, returning `None` if the input was closed.
+    pub(super) async fn next(&mut self) -> Result<Option<Request>> {
+        let mut content_length = None;
+
+        loop {
+            self.buf.clear();
+
+            if self.reader.read_until(b'\n', &mut self.buf).await? == 0 {
+                return Ok(None);
+            }
+
+            let line = std::str::from_utf8(&self.buf)?.trim();
+
+            if line.is_empty() {
+                break;
+            }
+
+            let Some((key, value)) = line.split_once(':') else {
+                bail!("bad header");
+            };
+
+            if key.trim().eq_ignore_ascii_case("content-length") {
+                let value = value.trim();
+
+                content_length = Some(
+                    value
+                        .parse::<usize>()
+                        .map_err(|e| anyhow!("bad content-length: {}: {}", value, e))?,
+                );
+            }
+        }
+
+        let Some(length) = content_length else {
+            bail!("missing content-length");
+        };
+
+        self.buf.resize(length, 0u8);
+        self.reader.read_exact(&mut self.buf[..]).await?;
+        Ok(Some(serde_json::from_slice(&self.buf)?))
+    }
+}
+
+/// Output connection writing framed responses and events.
+pub(super) struct Output<W> {
+    seq: i64,
+    writer: W,
+}
+
+impl<W> Output<W>
+where
+    W: Unpin + AsyncWrite,
+{
+    pub(super) fn new(writer: W) -> Self {
+        Self { seq: 0, writer }
+    }
+
+    /// Send a successful response to the given request.
+    pub(super) async fn response<T>(&mut self, request: &Request, body: T) -> Result<()>
+    where
+        T: Serialize,
+    {
+        let body = serde_json::to_value(body)?;
+
+        let response = Response {
+            seq: self.next_seq(),
+            kind: "response",
+            request_seq: request.seq,
+            success: true,
+            command: &request.command,
+            message: None,
+            body: (!body.is_null()).then_some(body),
+        };
+
+        self.write(&response).await
+    }
+
+    /// Send an error response to the given request.
+    pub(super) async fn error(&mut self, request: &Request, message: &str) -> Result<()> {
+        let response = Response {
+            seq: self.next_seq(),
+            kind: "response",
+            request_seq: request.seq,
+            success: false,
+            command: &request.command,
+            message: Some(message),
+            body: None,
+        };
+
+        self.write(&response).await
+    }
+
+    /// Send an event.
+    pub(super) async fn event<T>(&mut self, event: &str, body: T) -> Result<()>
+    where
+        T: Serialize,
+    {
+        let body = serde_json::to_value(body)?;
+
+        let event = Event {
+            seq: self.next_seq(),
+            kind: "event",
+            event,
+            body: (!body.is_null()).then_some(body),
+        };
+
+        self.write(&event).await
+    }
+
+    fn next_seq(&mut self) -> i64 {
+        self.seq += 1;
+        self.seq
+    }
+
+    async fn write<T>(&mut self, message: &T) -> Result<()>
+    where
+        T: Serialize,
+    {
+        let bytes = serde_json::to_vec(message)?;
+        let header = format!("Content-Length: {}\r\n\r\n", bytes.len());
+        self.writer.write_all(header.as_bytes()).await?;
+        self.writer.write_all(&bytes).await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,247 @@
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt as _, AsyncReadExt as _, AsyncWriteExt as _, BufReader};
+
+use super::*;
+
+const SCRIPT: &str = r#"
+fn add(a, b) {
+    let c = a + b;
+    c
+}
+
+pub fn main() {
+    let x = 1;
+    let y = add(x, 2);
+    println!("{}", y);
+}
+"#;
+
+/// A scripted client speaking the debug adapter protocol.
+struct Client<R, W> {
+    seq: i64,
+    reader: BufReader<R>,
+    writer: W,
+}
+
+impl<R, W> Client<R, W>
+where
+    R: Unpin + tokio::io::AsyncRead,
+    W: Unpin + AsyncWrite,
+{
+    async fn request(&mut self, command: &str, arguments: Value) -> Value {
+        self.seq += 1;
+
+        let message = json!({
+            "seq": self.seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        });
+
+        let bytes = serde_json::to_vec(&message).unwrap();
+        let header = format!("Content-Length: {}\r\n\r\n", bytes.len());
+        self.writer.write_all(header.as_bytes()).await.unwrap();
+        self.writer.write_all(&bytes).await.unwrap();
+
+        let response = self.receive().await;
+        assert_eq!(response["type"], "response");
+        assert_eq!(response["request_seq"], self.seq);
+        assert_eq!(response["command"], command);
+        response
+    }
+
+    async fn event(&mut self, event: &str) -> Value {
+        let message = self.receive().await;
+        assert_eq!(message["type"], "event");
+        assert_eq!(message["event"], event, "{message}");
+        message
+    }
+
+    async fn receive(&mut self) -> Value {
+        let mut line = String::new();
+        let mut length = None;
+
+        loop {
+            line.clear();
+            self.reader.read_line(&mut line).await.unwrap();
+
+            let line = line.trim();
+
+            if line.is_empty() {
+                break;
+            }
+
+            if let Some(value) = line.strip_prefix("Content-Length:") {
+                length = Some(value.trim().parse::<usize>().unwrap());
+            }
+        }
+
+        let mut buf = vec![0; length.expect("content length")];
+        self.reader.read_exact(&mut buf).await.unwrap();
+        serde_json::from_slice(&buf).unwrap()
+    }
+}
+
+#[tokio::test]
+async fn test_debug_session() {
+    let path = std::env::temp_dir().join(format!("rune-dap-{}.rn", std::process::id()));
+    std::fs::write(&path, SCRIPT).unwrap();
+    let program = path.display().to_string();
+
+    let capture = CaptureIo::new();
+    let mut context = Context::with_config(false).unwrap();
+    context
+        .install(crate::modules::capture_io::module(&capture).unwrap())
+        .unwrap();
+
+    let (client_io, server_io) = tokio::io::duplex(4096);
+    let (server_read, server_write) = tokio::io::split(server_io);
+    let (client_read, client_write) = tokio::io::split(client_io);
+
+    let server = serve(
+        BufReader::new(server_read),
+        server_write,
+        &context,
+        Some(&capture),
+    );
+
+    let client = async move {
+        let mut client = Client {
+            seq: 0,
+            reader: BufReader::new(client_read),
+            writer: client_write,
+        };
+
+        let response = client.request("initialize", json!({})).await;
+        assert_eq!(response["body"]["supportsConfigurationDoneRequest"], true);
+
+        let response = client
+            .request("launch", json!({ "program": program }))
+            .await;
+        assert_eq!(response["success"], true);
+        client.event("initialized").await;
+
+        let response = client
+            .request(
+                "setBreakpoints",
+                json!({
+                    "source": { "path": program },
+                    "breakpoints": [{ "line": 4 }, { "line": 100 }],
+                }),
+            )
+            .await;
+
+        let breakpoints = &response["body"]["breakpoints"];
+        assert_eq!(breakpoints[0]["verified"], true);
+        assert_eq!(breakpoints[0]["line"], 4);
+        assert_eq!(breakpoints[1]["verified"], false);
+
+        client.request("configurationDone", json!({})).await;
+        let stopped = client.event("stopped").await;
+        assert_eq!(stopped["body"]["reason"], "breakpoint");
+
+        let response = client
+            .request("stackTrace", json!({ "threadId": THREAD_ID }))
+            .await;
+        let frames = &response["body"]["stackFrames"];
+        assert_eq!(frames[0]["name"], "add(a, b)");
+        assert_eq!(frames[0]["line"], 4);
+        assert_eq!(frames[1]["name"], "main()");
+        assert_eq!(frames[1]["line"], 9);
+
+        let response = client.request("scopes", json!({ "frameId": 0 })).await;
+        let reference = response["body"]["scopes"][0]["variablesReference"].clone();
+
+        let response = client
+            .request("variables", json!({ "variablesReference": reference }))
+            .await;
+        let variables = &response["body"]["variables"];
+        assert_eq!(variables[2]["name"], "c");
+        assert_eq!(variables[2]["value"], "3");
+
+        client
+            .request("next", json!({ "threadId": THREAD_ID }))
+            .await;
+        let stopped = client.event("stopped").await;
+        assert_eq!(stopped["body"]["reason"], "step");
+
+        client
+            .request("continue", json!({ "threadId": THREAD_ID }))
+            .await;
+        let output = client.event("output").await;
+        assert_eq!(output["body"]["output"], "3\n");
+        let exited = client.event("exited").await;
+        assert_eq!(exited["body"]["exitCode"], 0);
+        client.event("terminated").await;
+
+        client.request("disconnect", json!({})).await;
+    };
+
+    let (result, ()) = tokio::join!(server, client);
+    result.unwrap();
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn test_pause_and_malformed_arguments() {
+    let path = std::env::temp_dir().join(format!("rune-dap-pause-{}.rn", std::process::id()));
+    std::fs::write(&path, "pub fn main() {\n    loop {}\n}\n").unwrap();
+    let program = path.display().to_string();
+
+    let context = Context::with_config(false).unwrap();
+
+    let (client_io, server_io) = tokio::io::duplex(4096);
+    let (server_read, server_write) = tokio::io::split(server_io);
+    let (client_read, client_write) = tokio::io::split(client_io);
+
+    let server = serve(BufReader::new(server_read), server_write, &context, None);
+
+    let client = async move {
+        let mut client = Client {
+            seq: 0,
+            reader: BufReader::new(client_read),
+            writer: client_write,
+        };
+
+        client.request("initialize", json!({})).await;
+
+        let response = client.request("launch", json!({})).await;
+        assert_eq!(response["success"], false);
+        assert!(response["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Malformed arguments"));
+
+        client
+            .request("launch", json!({ "program": program }))
+            .await;
+        client.event("initialized").await;
+
+        // The program loops forever, so it's still running when paused.
+        client.request("configurationDone", json!({})).await;
+
+        let response = client
+            .request("pause", json!({ "threadId": THREAD_ID }))
+            .await;
+        assert_eq!(response["success"], true);
+        let stopped = client.event("stopped").await;
+        assert_eq!(stopped["body"]["reason"], "pause");
+
+        let response = client
+            .request("stackTrace", json!({ "threadId": THREAD_ID }))
+            .await;
+        assert_eq!(response["body"]["stackFrames"][0]["line"], 2);
+
+        for command in ["setBreakpoints", "scopes", "variables"] {
+            let response = client.request(command, json!({ "frameId": "0" })).await;
+            assert_eq!(response["success"], false, "{command}");
+        }
+
+        client.request("continue", json!({})).await;
+        client.request("disconnect", json!({})).await;
+    };
+
+    let (result, ()) = tokio::join!(server, client);
+    result.unwrap();
+    let _ = std::fs::remove_file(&path);
+}
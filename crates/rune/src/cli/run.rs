@@ -1,4 +1,6 @@
-use std::io::Write;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -6,7 +8,7 @@ use anyhow::{anyhow, Result};
 use clap::Parser;
 
 use crate::cli::{Config, ExitCode, Io, CommandBase, AssetKind, SharedFlags};
-use crate::runtime::{VmError, VmExecution, VmResult, UnitStorage};
+use crate::runtime::{Profiler, VmError, VmExecution, VmResult, UnitStorage};
 use crate::{Context, Sources, Unit, Value, Vm};
 
 #[derive(Parser, Debug)]
@@ -53,6 +55,17 @@ pub(super) struct Flags {
     /// implies `--trace`.
     #[arg(long)]
     trace_limit: Option<usize>,
+    /// Profile the execution, writing the time spent in each call stack to a
+    /// collapsed stack file which can be used with flamegraph tools.
+    #[arg(long)]
+    profile: bool,
+    /// The file to write the profile to.
+    #[arg(long, default_value = "rune.folded")]
+    profile_output: PathBuf,
+    /// When profiling, only sample every `interval` instructions instead of
+    /// timing every instruction. This implies `--profile`.
+    #[arg(long)]
+    profile_interval: Option<usize>,
 }
 
 impl CommandBase for Flags {
@@ -79,6 +92,10 @@ impl CommandBase for Flags {
         if self.trace_limit.is_some() {
             self.trace = true;
         }
+
+        if self.profile_interval.is_some() {
+            self.profile = true;
+        }
     }
 }
 
@@ -218,6 +235,16 @@ pub(super) async fn run(
             Err(TraceError::VmError(vm)) => VmResult::Err(vm),
             Err(TraceError::Limited) => return Err(anyhow!("Trace limit reached")),
         }
+    } else if args.profile {
+        let mut profiler = Profiler::new(execution.vm().unit().clone());
+
+        if let Some(interval) = args.profile_interval {
+            profiler.set_interval(interval);
+        }
+
+        let result = profiler.async_complete(&mut execution).await;
+        write_profile(io, &profiler, &args.profile_output)?;
+        result
     } else {
         execution.async_complete().await
     };
@@ -307,6 +334,26 @@ pub(super) async fn run(
     }
 }
 
+/// Write the collected profile and a summary of the most expensive functions.
+fn write_profile(io: &mut Io<'_>, profiler: &Profiler, path: &Path) -> Result<()> {
+    let mut out = BufWriter::new(fs::File::create(path)?);
+    profiler.write_collapsed(&mut out)?;
+    out.flush()?;
+
+    writeln!(io.stderr, "# profile written to {}", path.display())?;
+    writeln!(io.stderr, "{:>12} {:>12}  function", "self", "total")?;
+
+    for function in profiler.functions().into_iter().take(10) {
+        writeln!(
+            io.stderr,
+            "{:>12?} {:>12?}  {}",
+            function.self_time, function.total_time, function.name
+        )?;
+    }
+
+    Ok(())
+}
+
 /// Perform a detailed trace of the program.
 async fn do_trace<T>(
     io: &mut Io<'_>,
@@ -1,4 +1,6 @@
-use std::io::Write;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -10,7 +12,7 @@ use clap::Parser;
 use crate::cli::{ExitCode, Io, CommandBase, AssetKind, Config, SharedFlags};
 use crate::compile::ItemBuf;
 use crate::modules::capture_io::CaptureIo;
-use crate::runtime::{Unit, Value, Vm, VmError, VmResult};
+use crate::runtime::{Coverage, Unit, Value, Vm, VmError, VmResult};
 use crate::{Context, Hash, Sources};
 
 #[derive(Parser, Debug, Clone)]
@@ -22,6 +24,32 @@ pub(super) struct Flags {
     /// Run all tests regardless of failure
     #[arg(long)]
     no_fail_fast: bool,
+
+    /// Record which lines are executed by the tests, writing an lcov report
+    /// and printing a summary for each source file.
+    #[arg(long)]
+    coverage: bool,
+
+    /// The file to write the lcov report to. This implies `--coverage`.
+    #[arg(long)]
+    coverage_output: Option<PathBuf>,
+}
+
+impl Flags {
+    /// Open the file to write the coverage report to, if coverage is enabled.
+    pub(super) fn coverage_output(&self) -> Result<Option<(PathBuf, BufWriter<fs::File>)>> {
+        if !self.coverage {
+            return Ok(None);
+        }
+
+        let path = self
+            .coverage_output
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("lcov.info"));
+
+        let out = BufWriter::new(fs::File::create(&path)?);
+        Ok(Some((path, out)))
+    }
 }
 
 impl CommandBase for Flags {
@@ -43,6 +71,10 @@ impl CommandBase for Flags {
     #[inline]
     fn propagate(&mut self, c: &mut Config, _: &mut SharedFlags) {
         c.test = true;
+
+        if self.coverage_output.is_some() {
+            self.coverage = true;
+        }
     }
 }
 
@@ -77,14 +109,16 @@ impl<'a> TestCase<'a> {
         vm: &mut Vm,
         quiet: bool,
         capture_io: Option<&CaptureIo>,
+        coverage: Option<&mut Coverage>,
     ) -> Result<bool> {
         if !quiet {
             write!(io.stdout, "Test {:30} ", self.item)?;
         }
 
-        let result = match vm.execute(self.hash, ()) {
-            Ok(mut execution) => execution.async_complete().await,
-            Err(err) => VmResult::Err(err),
+        let result = match (vm.execute(self.hash, ()), coverage) {
+            (Ok(mut execution), Some(coverage)) => coverage.async_complete(&mut execution).await,
+            (Ok(mut execution), None) => execution.async_complete().await,
+            (Err(err), _) => VmResult::Err(err),
         };
 
         if let Some(capture_io) = capture_io {
@@ -177,6 +211,7 @@ pub(super) async fn run(
     unit: Arc<Unit>,
     sources: &Sources,
     fns: &[(Hash, ItemBuf)],
+    coverage_output: Option<&mut (PathBuf, BufWriter<fs::File>)>,
 ) -> anyhow::Result<ExitCode> {
     let runtime = Arc::new(context.runtime());
 
@@ -197,10 +232,16 @@ pub(super) async fn run(
 
     let mut vm = Vm::new(runtime.clone(), unit.clone());
 
+    let mut coverage = coverage_output
+        .is_some()
+        .then(|| Coverage::new(unit.clone()));
+
     for test in &mut cases {
         executed_count += 1;
 
-        let success = test.execute(io, &mut vm, flags.quiet, capture_io).await?;
+        let success = test
+            .execute(io, &mut vm, flags.quiet, capture_io, coverage.as_mut())
+            .await?;
 
         if !success {
             failure_count += 1;
@@ -231,9 +272,47 @@ pub(super) async fn run(
         elapsed.as_secs_f64()
     )?;
 
+    if let (Some(coverage), Some((path, out))) = (&coverage, coverage_output) {
+        write_coverage(io, coverage, sources, path, out)?;
+    }
+
     if failure_count == 0 {
         Ok(ExitCode::Success)
     } else {
         Ok(ExitCode::Failure)
     }
 }
+
+/// Append the collected coverage to the lcov report and print a summary of
+/// the lines covered in each source file.
+fn write_coverage(
+    io: &mut Io<'_>,
+    coverage: &Coverage,
+    sources: &Sources,
+    path: &Path,
+    out: &mut BufWriter<fs::File>,
+) -> Result<()> {
+    coverage.write_lcov(sources, out)?;
+    out.flush()?;
+
+    writeln!(io.stdout, "==== coverage written to {}", path.display())?;
+
+    for file in coverage.files(sources) {
+        let found = file.lines_found();
+        let hit = file.lines_hit();
+
+        let percent = if found == 0 {
+            100.0
+        } else {
+            hit as f64 * 100.0 / found as f64
+        };
+
+        writeln!(
+            io.stdout,
+            "{:>6.2}% {:>5}/{:<5} {}",
+            percent, hit, found, file.name
+        )?;
+    }
+
+    Ok(())
+}
@@ -51,6 +51,8 @@ pub(crate) mod v1;
 mod options;
 pub use self::options::{Options, ParseOptionError};
 
+mod optimize;
+
 mod location;
 pub use self::location::Location;
 
@@ -81,10 +83,12 @@ use crate::no_std::prelude::*;
 use crate::ast;
 use crate::ast::{Span, Spanned};
 use crate::hir;
+use crate::indexing;
 use crate::macros::Storage;
 use crate::parse::Resolve;
 use crate::query::{Build, BuildEntry, Query};
-use crate::runtime::unit::UnitEncoder;
+use crate::runtime::unit::{default_argument_hash, UnitEncoder};
+use crate::runtime::ConstValue;
 use crate::shared::{Consts, Gen};
 use crate::worker::{LoadFileKind, Task, Worker};
 use crate::{Diagnostics, Sources};
@@ -250,6 +254,7 @@ impl CompileBuildEntry<'_> {
 
                 let span = &*f.ast;
                 let count = f.ast.args.len();
+                let defaults = argument_defaults(&self.q, item_meta.item, count);
 
                 let arena = hir::Arena::new();
                 let mut ctx = hir::lowering::Ctx::with_query(
@@ -271,6 +276,8 @@ impl CompileBuildEntry<'_> {
                         asm,
                         f.call,
                         args,
+                        defaults,
+                        self.options,
                         unit_storage,
                     )?;
                 }
@@ -285,6 +292,7 @@ impl CompileBuildEntry<'_> {
 
                 let span = &*f.ast;
                 let count = f.ast.args.len();
+                let defaults = argument_defaults(&self.q, item_meta.item, count);
 
                 let arena = hir::Arena::new();
                 let mut c = self.compiler1(location, span, &mut asm);
@@ -320,10 +328,114 @@ impl CompileBuildEntry<'_> {
                         asm,
                         f.call,
                         args,
+                        defaults,
+                        self.options,
                         unit_storage,
                     )?;
                 }
             }
+            Build::TraitImpl(t) => {
+                tracing::trace!("trait impl: {}", self.q.pool.item(item_meta.item));
+
+                let arena = hir::Arena::new();
+                let mut ctx = hir::lowering::Ctx::with_query(
+                    &arena,
+                    self.q.borrow(),
+                    item_meta.location.source_id,
+                );
+                let path = hir::lowering::path(&mut ctx, &t.path)?;
+                let named = self.q.convert_path(&path)?;
+                let meta = self.q.lookup_meta(
+                    location,
+                    named.item,
+                    self::v1::GenericsParameters::default(),
+                )?;
+
+                let meta::Kind::Trait { functions } = &meta.kind else {
+                    return Err(Error::expected_meta(
+                        location.span,
+                        meta.info(self.q.pool),
+                        "trait",
+                    ));
+                };
+
+                let impl_meta = self.q.lookup_meta(
+                    location,
+                    t.impl_item,
+                    self::v1::GenericsParameters::default(),
+                )?;
+
+                let Some(type_hash) = impl_meta.type_hash_of() else {
+                    return Err(Error::expected_meta(
+                        location.span,
+                        impl_meta.info(self.q.pool),
+                        "type",
+                    ));
+                };
+
+                let trait_item = self.q.pool.item(meta.item_meta.item).to_owned();
+
+                for (name, span) in &t.functions {
+                    if !functions.contains(name) {
+                        return Err(Error::new(
+                            span,
+                            CompileErrorKind::TraitFunctionNotMember {
+                                name: name.clone(),
+                                item: trait_item,
+                            },
+                        ));
+                    }
+                }
+
+                // NB: only traits declared in scripts have default functions.
+                let trait_ = self.q.get_trait(meta.item_meta.item);
+
+                for name in functions.iter() {
+                    if !t.functions.iter().any(|(n, _)| n == name) {
+                        let default = trait_
+                            .as_deref()
+                            .and_then(|t| t.functions.iter().find(|f| f.name == *name))
+                            .and_then(|f| f.default.as_ref());
+
+                        let Some(default) = default else {
+                            self.q.diagnostics.error(
+                                location.source_id,
+                                Error::new(
+                                    location.span,
+                                    CompileErrorKind::TraitFunctionMissing {
+                                        name: name.clone(),
+                                        item: trait_item.clone(),
+                                    },
+                                ),
+                            );
+
+                            continue;
+                        };
+
+                        // NB: the default function is built as an instance
+                        // function of the implementing type.
+                        let item = self.q.pool.item(t.impl_item).extended(&**name);
+
+                        self.q.inner.queue.push_back(BuildEntry {
+                            item_meta: ItemMeta {
+                                item: self.q.pool.alloc_item(item),
+                                ..default.item_meta
+                            },
+                            used,
+                            build: Build::InstanceFunction(indexing::InstanceFunction {
+                                ast: default.ast.clone(),
+                                call: default.call,
+                                impl_item: t.impl_item,
+                                instance_span: location.span,
+                            }),
+                        });
+                    }
+
+                    self.q
+                        .unit
+                        .new_trait_function(location, type_hash, meta.hash, name)?;
+                }
+            }
             Build::Closure(closure) => {
                 tracing::trace!("closure: {}", self.q.pool.item(item_meta.item));
 
@@ -360,6 +472,8 @@ impl CompileBuildEntry<'_> {
                         asm,
                         closure.call,
                         args,
+                        Vec::new(),
+                        self.options,
                         unit_storage,
                     )?;
                 }
@@ -395,6 +509,8 @@ impl CompileBuildEntry<'_> {
                         asm,
                         b.call,
                         Default::default(),
+                        Vec::new(),
+                        self.options,
                         unit_storage,
                     )?;
                 }
@@ -475,6 +591,18 @@ impl CompileBuildEntry<'_> {
     }
 }
 
+/// Collect the evaluated default values of the arguments of a function.
+fn argument_defaults(q: &Query<'_>, item: ItemId, count: usize) -> Vec<(usize, ConstValue)> {
+    let hash = q.pool.item_type_hash(item);
+
+    (0..count)
+        .filter_map(|index| {
+            let value = q.get_const_value(default_argument_hash(hash, index))?;
+            Some((index, value.clone()))
+        })
+        .collect()
+}
+
 fn format_fn_args<'a, I>(
     sources: &Sources,
     location: Location,
@@ -490,8 +618,8 @@ where
             ast::FnArg::SelfValue(..) => {
                 args.push("self".into());
             }
-            ast::FnArg::Pat(pat) => {
-                let span = pat.span();
+            ast::FnArg::Pat(..) | ast::FnArg::Typed(..) | ast::FnArg::Default(..) => {
+                let span = arg.span();
 
                 if let Some(s) = sources.source(location.source_id, span) {
                     args.push(s.into());
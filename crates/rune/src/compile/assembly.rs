@@ -7,6 +7,7 @@ use crate::no_std::prelude::*;
 
 use crate::ast::{Span, Spanned};
 use crate::compile::{self, Location};
+use crate::runtime::debug::DebugVariable;
 use crate::runtime::{Inst, Label};
 use crate::{Hash, SourceId};
 
@@ -33,6 +34,8 @@ pub(crate) struct Assembly {
     pub(crate) instructions: Vec<(AssemblyInst, Span)>,
     /// Comments associated with instructions.
     pub(crate) comments: HashMap<usize, Vec<Box<str>>>,
+    /// Named variables which become live at the given instruction.
+    pub(crate) variables: HashMap<usize, Vec<DebugVariable>>,
     /// The number of labels.
     pub(crate) label_count: usize,
     /// The collection of functions required by this assembly.
@@ -47,6 +50,7 @@ impl Assembly {
             labels: Default::default(),
             instructions: Default::default(),
             comments: Default::default(),
+            variables: Default::default(),
             label_count,
             required_functions: Default::default(),
         }
@@ -180,6 +184,20 @@ impl Assembly {
         self.push(raw, span);
     }
 
+    /// Record that a named variable is available at the given stack offset
+    /// starting from the next instruction.
+    pub(crate) fn variable<N>(&mut self, offset: usize, name: N)
+    where
+        N: fmt::Display,
+    {
+        let pos = self.instructions.len();
+
+        self.variables
+            .entry(pos)
+            .or_default()
+            .push(DebugVariable::new(name.to_string().into(), offset));
+    }
+
     fn inner_push(&mut self, inst: AssemblyInst, span: &dyn Spanned) {
         self.instructions.push((inst, span.span()));
     }
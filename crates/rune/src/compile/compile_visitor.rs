@@ -1,4 +1,4 @@
-use crate::ast::{Span, Spanned};
+use crate::ast::{self, Span, Spanned};
 use crate::compile::{Item, Location, MetaRef};
 use crate::hash::Hash;
 use crate::SourceId;
@@ -23,6 +23,21 @@ pub trait CompileVisitor {
     /// Visit something that is a module.
     fn visit_mod(&mut self, _source_id: SourceId, _span: Span) {}
 
+    /// Visit the name of a declared item, such as the identifier of a function
+    /// or a struct.
+    fn visit_item_name(&mut self, _location: Location, _hash: Hash) {}
+
+    /// Visit the name of a field declared in a struct / enum variant struct.
+    fn visit_field_name(&mut self, _location: Location, _hash: Hash, _field: &str) {}
+
+    /// Visit a named field in a struct literal or struct pattern, where the
+    /// struct / enum variant the field belongs to is known.
+    fn visit_field_use(&mut self, _location: Location, _hash: Hash, _field: &str) {}
+
+    /// Visit a call to the function with the given hash, where the function
+    /// being called is known at compile time.
+    fn visit_call(&mut self, _source_id: SourceId, _hash: Hash, _ast: &ast::ExprCall) {}
+
     /// Visit anterior `///`-style comments, and interior `//!`-style doc
     /// comments for an item.
     ///
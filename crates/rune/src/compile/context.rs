@@ -10,7 +10,7 @@ use crate::compile::Docs;
 use crate::compile::{ComponentRef, ContextError, IntoComponent, Item, ItemBuf, MetaInfo, Names};
 use crate::module::{
     Fields, Function, InternalEnum, Module, ModuleAssociated, ModuleAttributeMacro, ModuleConstant,
-    ModuleFunction, ModuleMacro, ModuleType, TypeSpecification, UnitType,
+    ModuleFunction, ModuleMacro, ModuleProtocol, ModuleType, TypeSpecification, UnitType,
 };
 use crate::runtime::{
     AttributeMacroHandler, ConstValue, FunctionHandler, MacroHandler, Protocol, RuntimeContext,
@@ -140,6 +140,7 @@ impl Context {
         this.install(crate::modules::fmt::module()?)?;
         this.install(crate::modules::future::module()?)?;
         this.install(crate::modules::generator::module()?)?;
+        this.install(crate::modules::hash::module()?)?;
         this.install(crate::modules::int::module()?)?;
         #[cfg(feature = "std")]
         this.install(crate::modules::io::module(stdio)?)?;
@@ -227,6 +228,10 @@ impl Context {
             self.install_constant(module, m)?;
         }
 
+        for m in &module.protocols {
+            self.install_protocol(module, m)?;
+        }
+
         if let Some(unit_type) = &module.unit_type {
             self.install_unit_type(module, unit_type)?;
         }
@@ -251,6 +256,12 @@ impl Context {
         })
     }
 
+    /// Iterate over all metadata in the [Context].
+    #[cfg(feature = "languageserver")]
+    pub(crate) fn iter_meta(&self) -> impl Iterator<Item = &ContextMeta> {
+        self.meta.iter()
+    }
+
     /// Iterate over all available types in the [Context].
     #[cfg(feature = "cli")]
     pub(crate) fn iter_types(&self) -> impl Iterator<Item = (Hash, &Item)> {
@@ -297,7 +308,6 @@ impl Context {
     }
 
     /// Lookup meta by its hash.
-    #[cfg(feature = "doc")]
     pub(crate) fn lookup_meta_by_hash(
         &self,
         hash: Hash,
@@ -433,6 +443,7 @@ impl Context {
                     fields: match fields {
                         Fields::Named(fields) => meta::Fields::Named(meta::FieldsNamed {
                             fields: fields.iter().copied().map(Box::<str>::from).collect(),
+                            types: HashMap::new(),
                         }),
                         Fields::Unnamed(args) => meta::Fields::Unnamed(*args),
                         Fields::Empty => meta::Fields::Empty,
@@ -475,6 +486,10 @@ impl Context {
                                 return_type: Some(ty.hash),
                                 #[cfg(feature = "doc")]
                                 argument_types: Box::from([]),
+                                annotated_return: None,
+                                annotated_arguments: Box::from([]),
+                                argument_names: Box::from([]),
+                                argument_defaults: Box::from([]),
                             };
 
                             self.insert_native_fn(hash, c)?;
@@ -497,6 +512,7 @@ impl Context {
                                                 .copied()
                                                 .map(Box::<str>::from)
                                                 .collect(),
+                                            types: HashMap::new(),
                                         })
                                     }
                                     Fields::Unnamed(args) => meta::Fields::Unnamed(*args),
@@ -584,6 +600,10 @@ impl Context {
                 .iter()
                 .map(|f| f.as_ref().map(|f| f.hash))
                 .collect(),
+            annotated_return: None,
+            annotated_arguments: Box::from([]),
+            argument_names: Box::from([]),
+            argument_defaults: Box::from([]),
         };
 
         self.insert_native_fn(hash, &f.handler)?;
@@ -663,6 +683,40 @@ impl Context {
         Ok(())
     }
 
+    fn install_protocol(
+        &mut self,
+        module: &Module,
+        m: &ModuleProtocol,
+    ) -> Result<(), ContextError> {
+        let item = module.item.join(&m.item);
+        let hash = Hash::type_hash(&item);
+
+        // NB: built-in protocols are global, and are implemented through
+        // instance functions with the same name.
+        for protocol in m.functions.iter() {
+            if protocol.hash != Hash::trait_function(hash, protocol.name)
+                && protocol.hash != Hash::instance_fn_name(protocol.name)
+            {
+                return Err(ContextError::ForeignProtocolFunction {
+                    item,
+                    name: protocol.name.into(),
+                });
+            }
+        }
+
+        self.install_meta(ContextMeta {
+            hash,
+            item: Some(item),
+            kind: meta::Kind::Trait {
+                functions: m.functions.iter().map(|p| p.name.into()).collect(),
+            },
+            #[cfg(feature = "doc")]
+            docs: m.docs.clone(),
+        })?;
+
+        Ok(())
+    }
+
     fn install_associated(&mut self, assoc: &ModuleAssociated) -> Result<(), ContextError> {
         let Some(info) = self.types.get(&assoc.container.hash).cloned() else {
             return Err(ContextError::MissingContainer {
@@ -691,6 +745,10 @@ impl Context {
                 .iter()
                 .map(|f| f.as_ref().map(|f| f.hash))
                 .collect(),
+            annotated_return: None,
+            annotated_arguments: Box::from([]),
+            argument_names: Box::from([]),
+            argument_defaults: Box::from([]),
         };
 
         self.insert_native_fn(hash, &assoc.handler)?;
@@ -767,6 +825,10 @@ impl Context {
             return_type: Some(hash),
             #[cfg(feature = "doc")]
             argument_types: Box::from([]),
+            annotated_return: None,
+            annotated_arguments: Box::from([]),
+            argument_names: Box::from([]),
+            argument_defaults: Box::from([]),
         };
 
         let constructor = || ();
@@ -861,6 +923,10 @@ impl Context {
                     return_type: Some(enum_hash),
                     #[cfg(feature = "doc")]
                     argument_types: Box::from([]),
+                    annotated_return: None,
+                    annotated_arguments: Box::from([]),
+                    argument_names: Box::from([]),
+                    argument_defaults: Box::from([]),
                 })
             } else {
                 None
@@ -875,6 +941,7 @@ impl Context {
                     fields: match fields {
                         Fields::Named(fields) => meta::Fields::Named(meta::FieldsNamed {
                             fields: fields.iter().copied().map(Box::<str>::from).collect(),
+                            types: HashMap::new(),
                         }),
                         Fields::Unnamed(args) => meta::Fields::Unnamed(*args),
                         Fields::Empty => meta::Fields::Empty,
@@ -25,6 +25,10 @@ pub enum ContextError {
     ConflictingMacroName { item: ItemBuf, hash: Hash },
     #[error("Constant `{item}` already exists with hash `{hash}`")]
     ConflictingConstantName { item: ItemBuf, hash: Hash },
+    #[error("Protocol `{item}` already exists with hash `{hash}`")]
+    ConflictingProtocolName { item: ItemBuf, hash: Hash },
+    #[error("Protocol function `{name}` is not declared by the protocol `{item}`")]
+    ForeignProtocolFunction { item: ItemBuf, name: Box<str> },
     #[error("Instance function `{name}` for type `{type_info}` already exists")]
     ConflictingInstanceFunction { type_info: TypeInfo, name: Box<str> },
     #[error("Protocol function `{name}` for type `{type_info}` already exists")]
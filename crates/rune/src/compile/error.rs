@@ -285,14 +285,40 @@ pub(crate) enum CompileErrorKind {
     BadFieldAccess,
     #[error("Wrong number of arguments, expected `{expected}` but got `{actual}`")]
     UnsupportedArgumentCount { expected: usize, actual: usize },
+    #[error("Default argument values are not supported here")]
+    UnsupportedArgumentDefault,
+    #[error("Arguments with a default value must come after all arguments without one")]
+    ArgumentDefaultNotTrailing,
+    #[error("Named arguments are only supported when calling a known script function")]
+    UnsupportedNamedArgument,
+    #[error("Positional arguments must come before named arguments")]
+    PositionalAfterNamedArgument,
+    #[error("Function has no argument named `{name}`")]
+    UnknownNamedArgument { name: Box<str> },
+    #[error("Argument `{name}` is provided more than once")]
+    DuplicateArgument { name: Box<str> },
+    #[error("Missing argument `{name}`")]
+    MissingArgument { name: Box<str> },
     #[error("This kind of expression is not supported as a pattern")]
     UnsupportedPatternExpr,
+    #[error("Variable `{name}` is not bound in all alternatives of the pattern")]
+    PatternMissingBinding { name: Box<str> },
+    #[error("Range pattern is empty, since its lower bound is greater than its upper bound")]
+    EmptyRangePattern,
     #[error("Not a valid binding")]
     UnsupportedBinding,
     #[error("Duplicate key in literal object")]
     DuplicateObjectKey { existing: Span, object: Span },
     #[error("Instance function declared outside of `impl` block")]
     InstanceFunctionOutsideImpl,
+    #[error("Function `{name}` is declared more than once in trait")]
+    TraitFunctionConflict { name: Box<str> },
+    #[error("Function `{name}` is not a member of trait `{item}`")]
+    TraitFunctionNotMember { name: Box<str>, item: ItemBuf },
+    #[error("Missing required function `{name}` in implementation of trait `{item}`")]
+    TraitFunctionMissing { name: Box<str>, item: ItemBuf },
+    #[error("Expected value of type `{expected}` but found `{actual}`")]
+    TypeMismatch { expected: ItemBuf, actual: ItemBuf },
     #[error("Unsupported tuple index `{number}`")]
     UnsupportedTupleIndex { number: ast::Number },
     #[error("Break outside of loop")]
@@ -390,10 +416,37 @@ pub(crate) enum CompileErrorKind {
         item: ItemBuf,
         fields: Box<[Box<str>]>,
     },
+    #[error("Non-exhaustive match for `{item}`")]
+    PatternMissingVariants {
+        item: ItemBuf,
+        variants: Box<[Box<str>]>,
+    },
     #[error("Use of label `{name}_{index}` which has no code location")]
     MissingLabelLocation { name: &'static str, index: usize },
     #[error("Reached macro recursion limit at {depth}, limit is {max}")]
     MaxMacroRecursion { depth: usize, max: usize },
+    #[error("Expected a name for the macro being defined")]
+    MissingMacroName,
+    #[error("Only `macro_rules!` definitions can be given a name")]
+    UnexpectedMacroName,
+    #[error("Macro definitions are only supported as module items")]
+    UnsupportedMacroDefinition,
+    #[error("Macro `{item}` is already defined")]
+    MacroConflict { item: ItemBuf },
+    #[error("No rules of macro `{item}` matched this input")]
+    NoMatchingMacroRule { item: ItemBuf },
+    #[error("Unsupported fragment specifier `{name}`")]
+    UnsupportedFragmentSpecifier { name: Box<str> },
+    #[error("Macro variable `${name}` is bound more than once")]
+    DuplicateMacroVariable { name: Box<str> },
+    #[error("Missing macro variable `${name}`")]
+    MissingMacroVariable { name: Box<str> },
+    #[error("Macro variable `${name}` is still repeating at this depth")]
+    MacroVariableStillRepeating { name: Box<str> },
+    #[error("Macro variables in repetition repeat a different number of times")]
+    MacroRepetitionMismatch,
+    #[error("Repetition does not contain any macro variables which repeat")]
+    MacroRepetitionWithoutVariables,
     #[error("Expression `yield` inside of constant function")]
     YieldInConst,
     #[error("Expression `.await` inside of constant context")]
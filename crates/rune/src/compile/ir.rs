@@ -30,6 +30,7 @@ use crate::indexing::index;
 use crate::macros::MacroContext;
 use crate::parse::NonZeroId;
 use crate::query::Used;
+use crate::runtime::Shared;
 
 impl ast::Expr {
     pub(crate) fn eval(&self, ctx: &mut MacroContext<'_, '_>) -> compile::Result<IrValue> {
@@ -168,6 +169,16 @@ decl_kind! {
         Object(IrObject),
         /// A call.
         Call(IrCall),
+        /// A unary operation.
+        Unary(IrUnary),
+        /// A for loop.
+        For(IrFor),
+        /// A match expression.
+        Match(IrMatch),
+        /// An index get operation.
+        Index(IrIndex),
+        /// A call to a built-in instance function.
+        CallInstance(IrCallInstance),
     }
 }
 
@@ -239,6 +250,18 @@ pub struct IrBinary {
     pub(crate) rhs: Box<Ir>,
 }
 
+/// A unary operation.
+#[derive(Debug, Clone, Spanned)]
+pub struct IrUnary {
+    /// The span of the unary op.
+    #[rune(span)]
+    pub(crate) span: Span,
+    /// The unary operation.
+    pub(crate) op: IrUnaryOp,
+    /// The operand of the unary op.
+    pub(crate) expr: Box<Ir>,
+}
+
 /// A local variable declaration.
 #[derive(Debug, Clone, Spanned)]
 pub struct IrDecl {
@@ -336,29 +359,116 @@ pub enum IrPat {
     Ignore,
     /// A named binding.
     Binding(Box<str>),
+    /// A literal value which is compared for equality.
+    Value(Box<Ir>),
+    /// An inclusive range of values `a..=b`.
+    Range(IrValue, IrValue),
+    /// A vector pattern, and if it's open or not.
+    Vec(Box<[IrPat]>, bool),
+    /// A tuple pattern, and if it's open or not.
+    Tuple(Box<[IrPat]>, bool),
+    /// An object pattern, and if it's open or not.
+    Object(Box<[(Box<str>, IrPat)]>, bool),
+    /// An or-pattern `a | b`.
+    Or(Box<[IrPat]>),
+    /// A binding with a subpattern `n @ pattern`.
+    At(Box<str>, Box<IrPat>),
 }
 
 impl IrPat {
-    fn compile_ast(hir: &hir::Pat<'_>) -> compile::Result<Self> {
+    fn compile_ast(hir: &hir::Pat<'_>, c: &mut IrCompiler<'_>) -> compile::Result<Self> {
         match hir.kind {
             hir::PatKind::Ignore => return Ok(ir::IrPat::Ignore),
             hir::PatKind::Path(&hir::PatPathKind::Ident(ident, _)) => {
                 return Ok(ir::IrPat::Binding(ident.into()));
             }
+            hir::PatKind::Lit(hir) => {
+                return Ok(ir::IrPat::Value(Box::new(compiler::expr(hir, c)?)));
+            }
+            hir::PatKind::Range(range) => {
+                let (start, end) = match *range {
+                    hir::PatRange::Integer { start, end } => {
+                        (IrValue::Integer(start), IrValue::Integer(end))
+                    }
+                    hir::PatRange::Char { start, end } => {
+                        (IrValue::Char(start), IrValue::Char(end))
+                    }
+                    hir::PatRange::Byte { start, end } => {
+                        (IrValue::Byte(start), IrValue::Byte(end))
+                    }
+                };
+
+                return Ok(ir::IrPat::Range(start, end));
+            }
+            hir::PatKind::Vec(items) => {
+                if let hir::PatItemsKind::Anonymous { .. } = items.kind {
+                    let pats = Self::compile_items(items, c)?;
+                    return Ok(ir::IrPat::Vec(pats, items.is_open));
+                }
+            }
+            hir::PatKind::Tuple(items) => {
+                if let hir::PatItemsKind::Anonymous { .. } = items.kind {
+                    let pats = Self::compile_items(items, c)?;
+                    return Ok(ir::IrPat::Tuple(pats, items.is_open));
+                }
+            }
+            hir::PatKind::Object(items) => {
+                if let hir::PatItemsKind::Anonymous { .. } = items.kind {
+                    let mut fields = Vec::with_capacity(items.bindings.len());
+
+                    for binding in items.bindings {
+                        let pat = match *binding {
+                            hir::Binding::Binding(_, _, pat) => Self::compile_ast(pat, c)?,
+                            hir::Binding::Ident(_, name, _) => ir::IrPat::Binding(name.into()),
+                        };
+
+                        fields.push((binding.key().into(), pat));
+                    }
+
+                    return Ok(ir::IrPat::Object(fields.into(), items.is_open));
+                }
+            }
+            hir::PatKind::Or(hir) => {
+                let mut alternatives = Vec::with_capacity(hir.alternatives.len());
+
+                for pat in hir.alternatives {
+                    alternatives.push(Self::compile_ast(pat, c)?);
+                }
+
+                return Ok(ir::IrPat::Or(alternatives.into()));
+            }
+            hir::PatKind::At(hir) => {
+                let pat = Self::compile_ast(hir.pat, c)?;
+                return Ok(ir::IrPat::At(hir.name.into(), Box::new(pat)));
+            }
             _ => (),
         }
 
         Err(compile::Error::msg(hir, "pattern not supported yet"))
     }
 
+    fn compile_items(
+        hir: &hir::PatItems<'_>,
+        c: &mut IrCompiler<'_>,
+    ) -> compile::Result<Box<[Self]>> {
+        let mut pats = Vec::with_capacity(hir.count);
+
+        for pat in hir.items.iter().take(hir.count) {
+            pats.push(Self::compile_ast(pat, c)?);
+        }
+
+        Ok(pats.into())
+    }
+
     fn matches<S>(
         &self,
         interp: &mut IrInterpreter<'_>,
         value: IrValue,
         spanned: S,
+        used: Used,
     ) -> Result<bool, IrEvalOutcome>
     where
-        S: Spanned,
+        S: Copy + Spanned,
     {
         match self {
             IrPat::Ignore => Ok(true),
@@ -366,7 +476,100 @@ impl IrPat {
                 interp.scopes.decl(name, value).with_span(spanned)?;
                 Ok(true)
             }
+            IrPat::Value(ir) => {
+                let expected = ir::eval_ir(ir, interp, used)?;
+                Ok(value.eq_with(&expected, spanned)?)
+            }
+            IrPat::Range(start, end) => Ok(match (start, &value, end) {
+                (IrValue::Integer(a), IrValue::Integer(n), IrValue::Integer(b)) => {
+                    (a..=b).contains(&n)
+                }
+                (IrValue::Char(a), IrValue::Char(n), IrValue::Char(b)) => (a..=b).contains(&n),
+                (IrValue::Byte(a), IrValue::Byte(n), IrValue::Byte(b)) => (a..=b).contains(&n),
+                _ => false,
+            }),
+            IrPat::Vec(pats, is_open) => {
+                let IrValue::Vec(vec) = value else {
+                    return Ok(false);
+                };
+
+                let values = vec.borrow_ref().with_span(spanned)?.clone();
+                Self::matches_items(pats, *is_open, values, interp, spanned, used)
+            }
+            IrPat::Tuple(pats, is_open) => {
+                let values = match value {
+                    IrValue::Unit => Vec::new(),
+                    IrValue::Tuple(tuple) => tuple.borrow_ref().with_span(spanned)?.to_vec(),
+                    _ => return Ok(false),
+                };
+
+                Self::matches_items(pats, *is_open, values, interp, spanned, used)
+            }
+            IrPat::Object(fields, is_open) => {
+                let IrValue::Object(object) = value else {
+                    return Ok(false);
+                };
+
+                let object = object.borrow_ref().with_span(spanned)?.clone();
+
+                if !*is_open && object.len() != fields.len() {
+                    return Ok(false);
+                }
+
+                for (key, pat) in fields.iter() {
+                    let Some(value) = object.get(key.as_ref()) else {
+                        return Ok(false);
+                    };
+
+                    if !pat.matches(interp, value.clone(), spanned, used)? {
+                        return Ok(false);
+                    }
+                }
+
+                Ok(true)
+            }
+            IrPat::Or(alternatives) => {
+                for pat in alternatives.iter() {
+                    if pat.matches(interp, value.clone(), spanned, used)? {
+                        return Ok(true);
+                    }
+                }
+
+                Ok(false)
+            }
+            IrPat::At(name, pat) => {
+                if !pat.matches(interp, value.clone(), spanned, used)? {
+                    return Ok(false);
+                }
+
+                interp.scopes.decl(name, value).with_span(spanned)?;
+                Ok(true)
+            }
+        }
+    }
+
+    fn matches_items<S>(
+        pats: &[IrPat],
+        is_open: bool,
+        values: Vec<IrValue>,
+        interp: &mut IrInterpreter<'_>,
+        spanned: S,
+        used: Used,
+    ) -> Result<bool, IrEvalOutcome>
+    where
+        S: Copy + Spanned,
+    {
+        if values.len() < pats.len() || !is_open && values.len() != pats.len() {
+            return Ok(false);
+        }
+
+        for (pat, value) in pats.iter().zip(values) {
+            if !pat.matches(interp, value, spanned, used)? {
+                return Ok(false);
+            }
         }
+
+        Ok(true)
     }
 }
 
@@ -384,6 +587,57 @@ pub struct IrLoop {
     pub(crate) body: IrScope,
 }
 
+/// A for loop.
+#[derive(Debug, Clone, Spanned)]
+pub struct IrFor {
+    /// The span of the loop.
+    #[rune(span)]
+    pub(crate) span: Span,
+    /// The label of the loop.
+    pub(crate) label: Option<Box<str>>,
+    /// The pattern each value is bound to.
+    pub(crate) binding: IrPat,
+    /// The values being iterated over.
+    pub(crate) iter: IrForIter,
+    /// The body of the loop.
+    pub(crate) body: IrScope,
+}
+
+/// The values iterated over by a for loop.
+#[derive(Debug, Clone)]
+pub enum IrForIter {
+    /// An integer range, and if the range is closed or not.
+    Range(Box<Ir>, Box<Ir>, bool),
+    /// A collection of values.
+    Ir(Box<Ir>),
+}
+
+/// A match expression.
+#[derive(Debug, Clone, Spanned)]
+pub struct IrMatch {
+    /// The span of the match.
+    #[rune(span)]
+    pub(crate) span: Span,
+    /// The expression being matched over.
+    pub(crate) expr: Box<Ir>,
+    /// The branches of the match.
+    pub(crate) branches: Vec<IrMatchBranch>,
+}
+
+/// A single match branch.
+#[derive(Debug, Clone, Spanned)]
+pub struct IrMatchBranch {
+    /// The span of the branch.
+    #[rune(span)]
+    pub(crate) span: Span,
+    /// The pattern of the branch.
+    pub(crate) pat: IrPat,
+    /// The optional condition of the branch.
+    pub(crate) condition: Option<Box<Ir>>,
+    /// The body of the branch.
+    pub(crate) body: Box<Ir>,
+}
+
 /// A break operation.
 #[derive(Debug, Clone, Spanned)]
 pub struct IrBreak {
@@ -479,6 +733,41 @@ pub struct IrCall {
     pub(crate) args: Vec<Ir>,
 }
 
+/// Index get expressions.
+#[derive(Debug, Clone, Spanned)]
+pub struct IrIndex {
+    /// Span of the index.
+    #[rune(span)]
+    pub(crate) span: Span,
+    /// The value being indexed.
+    pub(crate) target: Box<Ir>,
+    /// The index.
+    pub(crate) index: Box<Ir>,
+}
+
+/// A call to a built-in instance function.
+#[derive(Debug, Clone, Spanned)]
+pub struct IrCallInstance {
+    /// Span of the call.
+    #[rune(span)]
+    pub(crate) span: Span,
+    /// The value the function is called on.
+    pub(crate) target: Box<Ir>,
+    /// The function being called.
+    pub(crate) kind: IrInstanceFn,
+    /// Arguments to the call.
+    pub(crate) args: Vec<Ir>,
+}
+
+/// Instance functions which are supported in constant contexts.
+#[derive(Debug, Clone, Copy)]
+pub enum IrInstanceFn {
+    /// `push`.
+    Push,
+    /// `len`.
+    Len,
+}
+
 /// Vector expression.
 #[derive(Debug, Clone, Spanned)]
 pub struct IrVec {
@@ -500,22 +789,45 @@ pub enum IrBinaryOp {
     Mul,
     /// Division `/`.
     Div,
+    /// Remainder `%`.
+    Rem,
     /// `<<`.
     Shl,
     /// `>>`.
     Shr,
+    /// `&`.
+    BitAnd,
+    /// `|`.
+    BitOr,
+    /// `^`.
+    BitXor,
+    /// `&&`.
+    And,
+    /// `||`.
+    Or,
     /// `<`,
     Lt,
     /// `<=`,
     Lte,
     /// `==`,
     Eq,
+    /// `!=`,
+    Neq,
     /// `>`,
     Gt,
     /// `>=`,
     Gte,
 }
 
+/// A unary operation.
+#[derive(Debug, Clone, Copy)]
+pub enum IrUnaryOp {
+    /// Negation `-`.
+    Neg,
+    /// Not `!`.
+    Not,
+}
+
 /// An assign operation.
 #[derive(Debug, Clone, Copy)]
 pub enum IrAssignOp {
@@ -527,6 +839,8 @@ pub enum IrAssignOp {
     Mul,
     /// `/=`.
     Div,
+    /// `%=`.
+    Rem,
     /// `<<=`.
     Shl,
     /// `>>=`.
@@ -544,10 +858,21 @@ impl IrAssignOp {
     where
         S: Copy + Spanned,
     {
-        if let IrValue::Integer(target) = target {
-            if let IrValue::Integer(operand) = operand {
+        match (target, operand) {
+            (IrValue::Integer(target), IrValue::Integer(operand)) => {
                 return self.assign_int(spanned, target, operand);
             }
+            (IrValue::String(target), IrValue::String(operand)) => {
+                if let IrAssignOp::Add = self {
+                    // NB: strings might be shared with literals, so a new
+                    // string is constructed instead of modifying it in place.
+                    let mut string = target.borrow_ref().with_span(spanned)?.clone();
+                    string.push_str(&operand.borrow_ref().with_span(spanned)?);
+                    *target = Shared::new(string);
+                    return Ok(());
+                }
+            }
+            _ => (),
         }
 
         Err(compile::Error::msg(spanned, "unsupported operands"))
@@ -574,6 +899,12 @@ impl IrAssignOp {
                     .ok_or("division by zero")
                     .with_span(spanned)?;
             }
+            IrAssignOp::Rem => {
+                *target = target
+                    .checked_rem(operand)
+                    .ok_or("division by zero")
+                    .with_span(spanned)?;
+            }
             IrAssignOp::Shl => {
                 let operand = u32::try_from(operand)
                     .map_err(|_| "bad operand")
@@ -9,7 +9,7 @@ use crate::hir;
 use crate::parse::Resolve;
 use crate::query::Query;
 use crate::runtime::{Bytes, Shared};
-use crate::SourceId;
+use crate::{Hash, SourceId};
 
 use rune_macros::instrument;
 
@@ -83,9 +83,13 @@ pub(crate) fn expr(hir: &hir::Expr<'_>, c: &mut IrCompiler<'_>) -> compile::Resu
         hir::ExprKind::Group(hir) => expr(hir, c)?,
         hir::ExprKind::Binary(hir) => expr_binary(span, c, hir)?,
         hir::ExprKind::Assign(hir) => expr_assign(span, c, hir)?,
-        hir::ExprKind::Call(hir) => ir::Ir::new(span, expr_call(span, c, hir)?),
+        hir::ExprKind::Call(hir) => expr_call(span, c, hir)?,
         hir::ExprKind::If(hir) => ir::Ir::new(span, expr_if(span, c, hir)?),
         hir::ExprKind::Loop(hir) => ir::Ir::new(span, expr_loop(span, c, hir)?),
+        hir::ExprKind::For(hir) => ir::Ir::new(span, expr_for(span, c, hir)?),
+        hir::ExprKind::Match(hir) => ir::Ir::new(span, expr_match(span, c, hir)?),
+        hir::ExprKind::Unary(hir) => ir::Ir::new(span, expr_unary(span, c, hir)?),
+        hir::ExprKind::Index(hir) => ir::Ir::new(span, expr_index(span, c, hir)?),
         hir::ExprKind::Lit(hir) => lit(c, span, hir)?,
         hir::ExprKind::Block(hir) => ir::Ir::new(span, block(hir, c)?),
         hir::ExprKind::Path(hir) => path(hir, c)?,
@@ -137,15 +141,40 @@ fn expr_call(
     span: Span,
     c: &mut IrCompiler<'_>,
     hir: &hir::ExprCall<'_>,
-) -> compile::Result<ir::IrCall> {
+) -> compile::Result<ir::Ir> {
     let mut args = Vec::with_capacity(hir.args.len());
 
     for e in hir.args {
         args.push(expr(e, c)?);
     }
 
-    if let hir::Call::ConstFn { id, .. } = hir.call {
-        return Ok(ir::IrCall { span, id, args });
+    match hir.call {
+        hir::Call::ConstFn { id, .. } => {
+            return Ok(ir::Ir::new(span, ir::IrCall { span, id, args }));
+        }
+        hir::Call::Instance { target, hash } => {
+            let kind = if hash == Hash::instance_fn_name("push") {
+                ir::IrInstanceFn::Push
+            } else if hash == Hash::instance_fn_name("len") {
+                ir::IrInstanceFn::Len
+            } else {
+                return Err(compile::Error::msg(
+                    span,
+                    "Instance function not supported in constant contexts",
+                ));
+            };
+
+            return Ok(ir::Ir::new(
+                span,
+                ir::IrCallInstance {
+                    span,
+                    target: Box::new(expr(target, c)?),
+                    kind,
+                    args,
+                },
+            ));
+        }
+        _ => (),
     }
 
     Err(compile::Error::msg(
@@ -166,6 +195,7 @@ fn expr_binary(
             ast::BinOp::SubAssign(..) => ir::IrAssignOp::Sub,
             ast::BinOp::MulAssign(..) => ir::IrAssignOp::Mul,
             ast::BinOp::DivAssign(..) => ir::IrAssignOp::Div,
+            ast::BinOp::RemAssign(..) => ir::IrAssignOp::Rem,
             ast::BinOp::ShlAssign(..) => ir::IrAssignOp::Shl,
             ast::BinOp::ShrAssign(..) => ir::IrAssignOp::Shr,
             _ => return Err(compile::Error::msg(hir.op, "op not supported yet")),
@@ -192,11 +222,18 @@ fn expr_binary(
         ast::BinOp::Sub(..) => ir::IrBinaryOp::Sub,
         ast::BinOp::Mul(..) => ir::IrBinaryOp::Mul,
         ast::BinOp::Div(..) => ir::IrBinaryOp::Div,
+        ast::BinOp::Rem(..) => ir::IrBinaryOp::Rem,
         ast::BinOp::Shl(..) => ir::IrBinaryOp::Shl,
         ast::BinOp::Shr(..) => ir::IrBinaryOp::Shr,
+        ast::BinOp::BitAnd(..) => ir::IrBinaryOp::BitAnd,
+        ast::BinOp::BitOr(..) => ir::IrBinaryOp::BitOr,
+        ast::BinOp::BitXor(..) => ir::IrBinaryOp::BitXor,
+        ast::BinOp::And(..) => ir::IrBinaryOp::And,
+        ast::BinOp::Or(..) => ir::IrBinaryOp::Or,
         ast::BinOp::Lt(..) => ir::IrBinaryOp::Lt,
         ast::BinOp::Lte(..) => ir::IrBinaryOp::Lte,
         ast::BinOp::Eq(..) => ir::IrBinaryOp::Eq,
+        ast::BinOp::Neq(..) => ir::IrBinaryOp::Neq,
         ast::BinOp::Gt(..) => ir::IrBinaryOp::Gt,
         ast::BinOp::Gte(..) => ir::IrBinaryOp::Gte,
         _ => return Err(compile::Error::msg(hir.op, "op not supported yet")),
@@ -213,6 +250,38 @@ fn expr_binary(
     ))
 }
 
+#[instrument]
+fn expr_unary(
+    span: Span,
+    c: &mut IrCompiler<'_>,
+    hir: &hir::ExprUnary<'_>,
+) -> compile::Result<ir::IrUnary> {
+    let op = match hir.op {
+        ast::UnOp::Neg(..) => ir::IrUnaryOp::Neg,
+        ast::UnOp::Not(..) => ir::IrUnaryOp::Not,
+        _ => return Err(compile::Error::msg(hir.op, "op not supported yet")),
+    };
+
+    Ok(ir::IrUnary {
+        span,
+        op,
+        expr: Box::new(expr(hir.expr, c)?),
+    })
+}
+
+#[instrument]
+fn expr_index(
+    span: Span,
+    c: &mut IrCompiler<'_>,
+    hir: &hir::ExprIndex<'_>,
+) -> compile::Result<ir::IrIndex> {
+    Ok(ir::IrIndex {
+        span,
+        target: Box::new(expr(hir.target, c)?),
+        index: Box::new(expr(hir.index, c)?),
+    })
+}
+
 #[instrument(span = span)]
 fn lit(c: &mut IrCompiler<'_>, span: Span, hir: hir::Lit<'_>) -> compile::Result<ir::Ir> {
     Ok(match hir {
@@ -399,7 +468,7 @@ fn condition(hir: &hir::Condition<'_>, c: &mut IrCompiler<'_>) -> compile::Resul
     match hir {
         hir::Condition::Expr(e) => Ok(ir::IrCondition::Ir(expr(e, c)?)),
         hir::Condition::ExprLet(expr_let) => {
-            let pat = ir::IrPat::compile_ast(expr_let.pat)?;
+            let pat = ir::IrPat::compile_ast(expr_let.pat, c)?;
             let ir = expr(expr_let.expr, c)?;
 
             Ok(ir::IrCondition::Let(ir::IrLet {
@@ -458,3 +527,67 @@ fn expr_loop(
         body: block(hir.body, c)?,
     })
 }
+
+#[instrument]
+fn expr_for(
+    span: Span,
+    c: &mut IrCompiler<'_>,
+    hir: &hir::ExprFor<'_>,
+) -> compile::Result<ir::IrFor> {
+    let iter = match hir.iter.kind {
+        hir::ExprKind::Range(&hir::ExprRange {
+            from: Some(from),
+            limits,
+            to: Some(to),
+        }) => ir::IrForIter::Range(
+            Box::new(expr(from, c)?),
+            Box::new(expr(to, c)?),
+            matches!(limits, hir::ExprRangeLimits::Closed),
+        ),
+        hir::ExprKind::Range(..) => {
+            return Err(compile::Error::msg(
+                hir.iter,
+                "Only bounded ranges are supported in constant contexts",
+            ));
+        }
+        _ => ir::IrForIter::Ir(Box::new(expr(hir.iter, c)?)),
+    };
+
+    Ok(ir::IrFor {
+        span,
+        label: match hir.label {
+            Some(label) => Some(c.resolve(label)?.into()),
+            None => None,
+        },
+        binding: ir::IrPat::compile_ast(hir.binding, c)?,
+        iter,
+        body: block(hir.body, c)?,
+    })
+}
+
+#[instrument]
+fn expr_match(
+    span: Span,
+    c: &mut IrCompiler<'_>,
+    hir: &hir::ExprMatch<'_>,
+) -> compile::Result<ir::IrMatch> {
+    let mut branches = Vec::with_capacity(hir.branches.len());
+
+    for branch in hir.branches {
+        branches.push(ir::IrMatchBranch {
+            span: branch.span(),
+            pat: ir::IrPat::compile_ast(branch.pat, c)?,
+            condition: match branch.condition {
+                Some(hir) => Some(Box::new(expr(hir, c)?)),
+                None => None,
+            },
+            body: Box::new(expr(branch.body, c)?),
+        });
+    }
+
+    Ok(ir::IrMatch {
+        span,
+        expr: Box::new(expr(hir.expr, c)?),
+        branches,
+    })
+}
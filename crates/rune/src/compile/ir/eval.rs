@@ -7,9 +7,9 @@ use crate::no_std::prelude::*;
 use crate::ast::{Span, Spanned};
 use crate::compile::ir;
 use crate::compile::ir::{IrInterpreter, IrValue};
-use crate::compile::{self, WithSpan};
+use crate::compile::{self, IrErrorKind, WithSpan};
 use crate::query::Used;
-use crate::runtime::Shared;
+use crate::runtime::{Shared, Value};
 
 /// Process an ir value as a boolean.
 fn as_bool(span: Span, value: IrValue) -> compile::Result<bool> {
@@ -18,6 +18,14 @@ fn as_bool(span: Span, value: IrValue) -> compile::Result<bool> {
         .map_err(|actual| compile::Error::expected_type::<_, bool>(span, &actual))
 }
 
+/// Process an ir value as an integer.
+fn as_integer(span: Span, value: IrValue) -> compile::Result<i64> {
+    match value {
+        IrValue::Integer(n) => Ok(n),
+        actual => Err(compile::Error::expected_type::<_, i64>(span, &actual)),
+    }
+}
+
 /// The outcome of a constant evaluation.
 pub enum IrEvalOutcome {
     /// Encountered expression that is not a valid constant expression.
@@ -81,10 +89,26 @@ fn eval_ir_binary(
     interp.budget.take(span)?;
 
     let a = eval_ir(&ir.lhs, interp, used)?;
+
+    // NB: logical operators are short-circuiting.
+    match ir.op {
+        ir::IrBinaryOp::And | ir::IrBinaryOp::Or => {
+            let a = as_bool(ir.lhs.span(), a)?;
+
+            if a == matches!(ir.op, ir::IrBinaryOp::Or) {
+                return Ok(IrValue::Bool(a));
+            }
+
+            let b = eval_ir(&ir.rhs, interp, used)?;
+            return Ok(IrValue::Bool(as_bool(ir.rhs.span(), b)?));
+        }
+        _ => (),
+    }
+
     let b = eval_ir(&ir.rhs, interp, used)?;
 
-    match (a, b) {
-        (IrValue::Integer(a), IrValue::Integer(b)) => match ir.op {
+    match (&a, &b) {
+        (&IrValue::Integer(a), &IrValue::Integer(b)) => match ir.op {
             ir::IrBinaryOp::Add => {
                 return Ok(IrValue::Integer(a.add(&b)));
             }
@@ -100,6 +124,12 @@ fn eval_ir_binary(
                     .ok_or_else(|| compile::Error::msg(span, "division by zero"))?;
                 return Ok(IrValue::Integer(number));
             }
+            ir::IrBinaryOp::Rem => {
+                let number = a
+                    .checked_rem(b)
+                    .ok_or_else(|| compile::Error::msg(span, "division by zero"))?;
+                return Ok(IrValue::Integer(number));
+            }
             ir::IrBinaryOp::Shl => {
                 let b = u32::try_from(b).map_err(|_| {
                     compile::Error::msg(&ir.rhs, "cannot be converted to shift operand")
@@ -116,35 +146,47 @@ fn eval_ir_binary(
                 let n = a.shr(b);
                 return Ok(IrValue::Integer(n));
             }
+            ir::IrBinaryOp::BitAnd => return Ok(IrValue::Integer(a & b)),
+            ir::IrBinaryOp::BitOr => return Ok(IrValue::Integer(a | b)),
+            ir::IrBinaryOp::BitXor => return Ok(IrValue::Integer(a ^ b)),
             ir::IrBinaryOp::Lt => return Ok(IrValue::Bool(a < b)),
             ir::IrBinaryOp::Lte => return Ok(IrValue::Bool(a <= b)),
-            ir::IrBinaryOp::Eq => return Ok(IrValue::Bool(a == b)),
             ir::IrBinaryOp::Gt => return Ok(IrValue::Bool(a > b)),
             ir::IrBinaryOp::Gte => return Ok(IrValue::Bool(a >= b)),
+            _ => (),
+        },
+        (&IrValue::Float(a), &IrValue::Float(b)) => match ir.op {
+            ir::IrBinaryOp::Add => return Ok(IrValue::Float(a + b)),
+            ir::IrBinaryOp::Sub => return Ok(IrValue::Float(a - b)),
+            ir::IrBinaryOp::Mul => return Ok(IrValue::Float(a * b)),
+            ir::IrBinaryOp::Div => return Ok(IrValue::Float(a / b)),
+            ir::IrBinaryOp::Rem => return Ok(IrValue::Float(a % b)),
+            ir::IrBinaryOp::Lt => return Ok(IrValue::Bool(a < b)),
+            ir::IrBinaryOp::Lte => return Ok(IrValue::Bool(a <= b)),
+            ir::IrBinaryOp::Gt => return Ok(IrValue::Bool(a > b)),
+            ir::IrBinaryOp::Gte => return Ok(IrValue::Bool(a >= b)),
+            _ => (),
+        },
+        (&IrValue::Bool(a), &IrValue::Bool(b)) => match ir.op {
+            ir::IrBinaryOp::BitAnd => return Ok(IrValue::Bool(a & b)),
+            ir::IrBinaryOp::BitOr => return Ok(IrValue::Bool(a | b)),
+            ir::IrBinaryOp::BitXor => return Ok(IrValue::Bool(a ^ b)),
+            _ => (),
         },
-        (IrValue::Float(a), IrValue::Float(b)) => {
-            #[allow(clippy::float_cmp)]
-            match ir.op {
-                ir::IrBinaryOp::Add => return Ok(IrValue::Float(a + b)),
-                ir::IrBinaryOp::Sub => return Ok(IrValue::Float(a - b)),
-                ir::IrBinaryOp::Mul => return Ok(IrValue::Float(a * b)),
-                ir::IrBinaryOp::Div => return Ok(IrValue::Float(a / b)),
-                ir::IrBinaryOp::Lt => return Ok(IrValue::Bool(a < b)),
-                ir::IrBinaryOp::Lte => return Ok(IrValue::Bool(a <= b)),
-                ir::IrBinaryOp::Eq => return Ok(IrValue::Bool(a == b)),
-                ir::IrBinaryOp::Gt => return Ok(IrValue::Bool(a > b)),
-                ir::IrBinaryOp::Gte => return Ok(IrValue::Bool(a >= b)),
-                _ => (),
-            };
-        }
         (IrValue::String(a), IrValue::String(b)) => {
             if let ir::IrBinaryOp::Add = ir.op {
-                return Ok(IrValue::String(add_strings(span, &a, &b)?));
+                return Ok(IrValue::String(add_strings(span, a, b)?));
             }
         }
         _ => (),
     }
 
+    match ir.op {
+        ir::IrBinaryOp::Eq => return Ok(IrValue::Bool(a.eq_with(&b, span)?)),
+        ir::IrBinaryOp::Neq => return Ok(IrValue::Bool(!a.eq_with(&b, span)?)),
+        _ => (),
+    }
+
     return Err(IrEvalOutcome::not_const(span));
 
     fn add_strings(
@@ -167,18 +209,10 @@ fn eval_ir_branches(
 ) -> Result<IrValue, IrEvalOutcome> {
     for (ir_condition, branch) in &ir.branches {
         let guard = interp.scopes.push();
-
-        let value = eval_ir_condition(ir_condition, interp, used)?;
-
-        let output = if as_bool(ir_condition.span(), value)? {
-            Some(eval_ir_scope(branch, interp, used)?)
-        } else {
-            None
-        };
-
+        let output = eval_ir_branch(ir_condition, branch, interp, used);
         interp.scopes.pop(guard).with_span(branch)?;
 
-        if let Some(output) = output {
+        if let Some(output) = output? {
             return Ok(output);
         }
     }
@@ -190,6 +224,21 @@ fn eval_ir_branches(
     Ok(IrValue::Unit)
 }
 
+fn eval_ir_branch(
+    ir_condition: &ir::IrCondition,
+    branch: &ir::IrScope,
+    interp: &mut IrInterpreter<'_>,
+    used: Used,
+) -> Result<Option<IrValue>, IrEvalOutcome> {
+    let value = eval_ir_condition(ir_condition, interp, used)?;
+
+    if !as_bool(ir_condition.span(), value)? {
+        return Ok(None);
+    }
+
+    Ok(Some(eval_ir_scope(branch, interp, used)?))
+}
+
 fn eval_ir_call(
     ir: &ir::IrCall,
     interp: &mut IrInterpreter<'_>,
@@ -204,6 +253,59 @@ fn eval_ir_call(
     Ok(interp.call_const_fn(ir, ir.id, args, used)?)
 }
 
+fn eval_ir_call_instance(
+    ir: &ir::IrCallInstance,
+    interp: &mut IrInterpreter<'_>,
+    used: Used,
+) -> Result<IrValue, IrEvalOutcome> {
+    interp.budget.take(ir)?;
+
+    let target = eval_ir(&ir.target, interp, used)?;
+    let mut args = Vec::with_capacity(ir.args.len());
+
+    for arg in &ir.args {
+        args.push(eval_ir(arg, interp, used)?);
+    }
+
+    let expected = match ir.kind {
+        ir::IrInstanceFn::Push => 1,
+        ir::IrInstanceFn::Len => 0,
+    };
+
+    if args.len() != expected {
+        return Err(IrEvalOutcome::from(compile::Error::new(
+            ir,
+            IrErrorKind::ArgumentCountMismatch {
+                actual: args.len(),
+                expected,
+            },
+        )));
+    }
+
+    match (ir.kind, target) {
+        (ir::IrInstanceFn::Push, IrValue::Vec(vec)) => {
+            vec.borrow_mut().with_span(ir)?.extend(args);
+            Ok(IrValue::Unit)
+        }
+        (ir::IrInstanceFn::Len, value) => {
+            let len = match &value {
+                IrValue::String(s) => s.borrow_ref().with_span(ir)?.len(),
+                IrValue::Bytes(b) => b.borrow_ref().with_span(ir)?.len(),
+                IrValue::Vec(vec) => vec.borrow_ref().with_span(ir)?.len(),
+                IrValue::Tuple(tuple) => tuple.borrow_ref().with_span(ir)?.len(),
+                IrValue::Object(object) => object.borrow_ref().with_span(ir)?.len(),
+                _ => return Err(IrEvalOutcome::not_const(ir)),
+            };
+
+            Ok(IrValue::Integer(len as i64))
+        }
+        (_, actual) => Err(IrEvalOutcome::from(compile::Error::expected_type::<
+            _,
+            Vec<Value>,
+        >(&ir.target, &actual))),
+    }
+}
+
 fn eval_ir_condition(
     ir: &ir::IrCondition,
     interp: &mut IrInterpreter<'_>,
@@ -216,7 +318,7 @@ fn eval_ir_condition(
         }
         ir::IrCondition::Let(ir_let) => {
             let value = eval_ir(&ir_let.ir, interp, used)?;
-            ir_let.pat.matches(interp, value, ir)?
+            ir_let.pat.matches(interp, value, ir, used)?
         }
     }))
 }
@@ -232,6 +334,204 @@ fn eval_ir_decl(
     Ok(IrValue::Unit)
 }
 
+fn eval_ir_for(
+    ir: &ir::IrFor,
+    interp: &mut IrInterpreter<'_>,
+    used: Used,
+) -> Result<IrValue, IrEvalOutcome> {
+    let span = ir.span();
+    interp.budget.take(span)?;
+
+    let guard = interp.scopes.push();
+
+    let result = match &ir.iter {
+        ir::IrForIter::Range(from, to, closed) => {
+            eval_ir_for_range(ir, from, to, *closed, interp, used)
+        }
+        ir::IrForIter::Ir(iter) => eval_ir_for_values(ir, iter, interp, used),
+    };
+
+    interp.scopes.pop(guard).with_span(ir)?;
+    result?;
+    Ok(IrValue::Unit)
+}
+
+fn eval_ir_for_range(
+    ir: &ir::IrFor,
+    from: &ir::Ir,
+    to: &ir::Ir,
+    closed: bool,
+    interp: &mut IrInterpreter<'_>,
+    used: Used,
+) -> Result<(), IrEvalOutcome> {
+    let mut n = as_integer(from.span(), eval_ir(from, interp, used)?)?;
+    let to = as_integer(to.span(), eval_ir(to, interp, used)?)?;
+
+    while n < to || closed && n == to {
+        if !eval_ir_for_body(ir, IrValue::Integer(n), interp, used)? {
+            break;
+        }
+
+        let Some(next) = n.checked_add(1) else {
+            break;
+        };
+
+        n = next;
+    }
+
+    Ok(())
+}
+
+fn eval_ir_for_values(
+    ir: &ir::IrFor,
+    iter: &ir::Ir,
+    interp: &mut IrInterpreter<'_>,
+    used: Used,
+) -> Result<(), IrEvalOutcome> {
+    let values = match eval_ir(iter, interp, used)? {
+        IrValue::String(s) => s
+            .borrow_ref()
+            .with_span(iter)?
+            .chars()
+            .map(IrValue::Char)
+            .collect(),
+        IrValue::Bytes(b) => b
+            .borrow_ref()
+            .with_span(iter)?
+            .iter()
+            .map(|&b| IrValue::Byte(b))
+            .collect(),
+        IrValue::Vec(vec) => vec.borrow_ref().with_span(iter)?.clone(),
+        IrValue::Tuple(tuple) => tuple.borrow_ref().with_span(iter)?.to_vec(),
+        IrValue::Object(object) => {
+            let object = object.borrow_ref().with_span(iter)?;
+            let mut entries = object.iter().collect::<Vec<_>>();
+            // NB: sort entries so that evaluation is deterministic.
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+
+            entries
+                .into_iter()
+                .map(|(key, value)| {
+                    let key = IrValue::String(Shared::new(key.clone()));
+                    IrValue::Tuple(Shared::new(Box::from([key, value.clone()])))
+                })
+                .collect::<Vec<_>>()
+        }
+        actual => {
+            return Err(IrEvalOutcome::from(compile::Error::msg(
+                iter,
+                format_args!(
+                    "Cannot iterate over {} in a constant context",
+                    actual.type_info()
+                ),
+            )))
+        }
+    };
+
+    for value in values {
+        if !eval_ir_for_body(ir, value, interp, used)? {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Evaluate a single iteration of a for loop, returning `false` if the loop
+/// should be exited.
+fn eval_ir_for_body(
+    ir: &ir::IrFor,
+    value: IrValue,
+    interp: &mut IrInterpreter<'_>,
+    used: Used,
+) -> Result<bool, IrEvalOutcome> {
+    interp.budget.take(ir)?;
+    interp.scopes.clear_current().with_span(ir)?;
+
+    if !ir.binding.matches(interp, value, ir, used)? {
+        return Err(IrEvalOutcome::from(compile::Error::msg(
+            ir,
+            "value does not match the binding of the for loop",
+        )));
+    }
+
+    match eval_ir_scope(&ir.body, interp, used) {
+        Ok(..) => Ok(true),
+        Err(IrEvalOutcome::Break(span, b)) => match b {
+            IrEvalBreak::Inherent => Ok(false),
+            IrEvalBreak::Label(l) => {
+                if ir.label.as_ref() == Some(&l) {
+                    return Ok(false);
+                }
+
+                Err(IrEvalOutcome::Break(span, IrEvalBreak::Label(l)))
+            }
+            IrEvalBreak::Value(..) => Err(IrEvalOutcome::from(compile::Error::msg(
+                span,
+                "break with value is not supported in for loops",
+            ))),
+        },
+        Err(outcome) => Err(outcome),
+    }
+}
+
+fn eval_ir_index(
+    ir: &ir::IrIndex,
+    interp: &mut IrInterpreter<'_>,
+    used: Used,
+) -> Result<IrValue, IrEvalOutcome> {
+    interp.budget.take(ir)?;
+
+    let target = eval_ir(&ir.target, interp, used)?;
+    let index = eval_ir(&ir.index, interp, used)?;
+
+    match (target, index) {
+        (IrValue::Vec(vec), IrValue::Integer(index)) => {
+            let vec = vec.borrow_ref().with_span(ir)?;
+
+            if let Some(value) = usize::try_from(index).ok().and_then(|i| vec.get(i)) {
+                return Ok(value.clone());
+            }
+
+            Err(missing_index(ir, index))
+        }
+        (IrValue::Tuple(tuple), IrValue::Integer(index)) => {
+            let tuple = tuple.borrow_ref().with_span(ir)?;
+
+            if let Some(value) = usize::try_from(index).ok().and_then(|i| tuple.get(i)) {
+                return Ok(value.clone());
+            }
+
+            Err(missing_index(ir, index))
+        }
+        (IrValue::Object(object), IrValue::String(field)) => {
+            let object = object.borrow_ref().with_span(ir)?;
+            let field = field.borrow_ref().with_span(ir)?;
+
+            if let Some(value) = object.get(field.as_str()) {
+                return Ok(value.clone());
+            }
+
+            Err(IrEvalOutcome::from(compile::Error::new(
+                ir,
+                IrErrorKind::MissingField {
+                    field: field.as_str().into(),
+                },
+            )))
+        }
+        _ => Err(IrEvalOutcome::not_const(ir)),
+    }
+}
+
+fn missing_index(ir: &ir::IrIndex, index: i64) -> IrEvalOutcome {
+    match usize::try_from(index) {
+        Ok(index) => {
+            IrEvalOutcome::from(compile::Error::new(ir, IrErrorKind::MissingIndex { index }))
+        }
+        Err(..) => IrEvalOutcome::from(compile::Error::msg(&ir.index, "index out of bounds")),
+    }
+}
+
 fn eval_ir_loop(
     ir: &ir::IrLoop,
     interp: &mut IrInterpreter<'_>,
@@ -241,8 +541,19 @@ fn eval_ir_loop(
     interp.budget.take(span)?;
 
     let guard = interp.scopes.push();
+    let result = eval_ir_loop_body(ir, interp, used);
+    interp.scopes.pop(guard).with_span(ir)?;
+    result
+}
 
+fn eval_ir_loop_body(
+    ir: &ir::IrLoop,
+    interp: &mut IrInterpreter<'_>,
+    used: Used,
+) -> Result<IrValue, IrEvalOutcome> {
     loop {
+        interp.budget.take(ir)?;
+
         if let Some(condition) = &ir.condition {
             interp.scopes.clear_current().with_span(condition)?;
 
@@ -281,10 +592,55 @@ fn eval_ir_loop(
         };
     }
 
-    interp.scopes.pop(guard).with_span(ir)?;
     Ok(IrValue::Unit)
 }
 
+fn eval_ir_match(
+    ir: &ir::IrMatch,
+    interp: &mut IrInterpreter<'_>,
+    used: Used,
+) -> Result<IrValue, IrEvalOutcome> {
+    interp.budget.take(ir)?;
+
+    let value = eval_ir(&ir.expr, interp, used)?;
+
+    for branch in &ir.branches {
+        let guard = interp.scopes.push();
+        let output = eval_ir_match_branch(branch, value.clone(), interp, used);
+        interp.scopes.pop(guard).with_span(branch)?;
+
+        if let Some(output) = output? {
+            return Ok(output);
+        }
+    }
+
+    Err(IrEvalOutcome::from(compile::Error::msg(
+        ir,
+        "no branch matched the value",
+    )))
+}
+
+fn eval_ir_match_branch(
+    branch: &ir::IrMatchBranch,
+    value: IrValue,
+    interp: &mut IrInterpreter<'_>,
+    used: Used,
+) -> Result<Option<IrValue>, IrEvalOutcome> {
+    if !branch.pat.matches(interp, value, branch, used)? {
+        return Ok(None);
+    }
+
+    if let Some(condition) = &branch.condition {
+        let value = eval_ir(condition, interp, used)?;
+
+        if !as_bool(condition.span(), value)? {
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(eval_ir(&branch.body, interp, used)?))
+}
+
 fn eval_ir_object(
     ir: &ir::IrObject,
     interp: &mut IrInterpreter<'_>,
@@ -306,19 +662,25 @@ fn eval_ir_scope(
 ) -> Result<IrValue, IrEvalOutcome> {
     interp.budget.take(ir)?;
     let guard = interp.scopes.push();
+    let value = eval_ir_scope_body(ir, interp, used);
+    interp.scopes.pop(guard).with_span(ir)?;
+    value
+}
 
+fn eval_ir_scope_body(
+    ir: &ir::IrScope,
+    interp: &mut IrInterpreter<'_>,
+    used: Used,
+) -> Result<IrValue, IrEvalOutcome> {
     for ir in &ir.instructions {
         let _ = eval_ir(ir, interp, used)?;
     }
 
-    let value = if let Some(last) = &ir.last {
-        eval_ir(last, interp, used)?
-    } else {
-        IrValue::Unit
-    };
+    if let Some(last) = &ir.last {
+        return eval_ir(last, interp, used);
+    }
 
-    interp.scopes.pop(guard).with_span(ir)?;
-    Ok(value)
+    Ok(IrValue::Unit)
 }
 
 fn eval_ir_set(
@@ -389,6 +751,28 @@ fn eval_ir_tuple(
     Ok(IrValue::Tuple(Shared::new(items.into_boxed_slice())))
 }
 
+fn eval_ir_unary(
+    ir: &ir::IrUnary,
+    interp: &mut IrInterpreter<'_>,
+    used: Used,
+) -> Result<IrValue, IrEvalOutcome> {
+    let span = ir.span();
+    interp.budget.take(span)?;
+
+    match (ir.op, eval_ir(&ir.expr, interp, used)?) {
+        (ir::IrUnaryOp::Neg, IrValue::Integer(n)) => {
+            let n = n
+                .checked_neg()
+                .ok_or_else(|| compile::Error::msg(span, "integer overflow"))?;
+            Ok(IrValue::Integer(n))
+        }
+        (ir::IrUnaryOp::Neg, IrValue::Float(n)) => Ok(IrValue::Float(-n)),
+        (ir::IrUnaryOp::Not, IrValue::Bool(b)) => Ok(IrValue::Bool(!b)),
+        (ir::IrUnaryOp::Not, IrValue::Integer(n)) => Ok(IrValue::Integer(!n)),
+        _ => Err(IrEvalOutcome::not_const(span)),
+    }
+}
+
 fn eval_ir_vec(
     ir: &ir::IrVec,
     interp: &mut IrInterpreter<'_>,
@@ -428,5 +812,10 @@ pub(crate) fn eval_ir(
         ir::IrKind::Tuple(ir) => eval_ir_tuple(ir, interp, used),
         ir::IrKind::Object(ir) => eval_ir_object(ir, interp, used),
         ir::IrKind::Call(ir) => eval_ir_call(ir, interp, used),
+        ir::IrKind::Unary(ir) => eval_ir_unary(ir, interp, used),
+        ir::IrKind::For(ir) => eval_ir_for(ir, interp, used),
+        ir::IrKind::Match(ir) => eval_ir_match(ir, interp, used),
+        ir::IrKind::Index(ir) => eval_ir_index(ir, interp, used),
+        ir::IrKind::CallInstance(ir) => eval_ir_call_instance(ir, interp, used),
     }
 }
@@ -113,19 +113,21 @@ impl IrValue {
             IrValue::Integer(n) => ConstValue::Integer(n),
             IrValue::Float(f) => ConstValue::Float(f),
             IrValue::String(s) => {
-                let s = s.take().with_span(spanned)?;
+                let s = s.borrow_ref().with_span(spanned)?.clone();
                 ConstValue::String(s)
             }
             IrValue::Bytes(b) => {
-                let b = b.take().with_span(spanned)?;
+                let b = b.borrow_ref().with_span(spanned)?.clone();
                 ConstValue::Bytes(b)
             }
-            Self::Option(option) => ConstValue::Option(match option.take().with_span(spanned)? {
-                Some(value) => Some(Box::new(value.into_const(spanned)?)),
-                None => None,
-            }),
+            Self::Option(option) => {
+                ConstValue::Option(match option.borrow_ref().with_span(spanned)?.clone() {
+                    Some(value) => Some(Box::new(value.into_const(spanned)?)),
+                    None => None,
+                })
+            }
             IrValue::Vec(vec) => {
-                let vec = vec.take().with_span(spanned)?;
+                let vec = vec.borrow_ref().with_span(spanned)?.clone();
                 let mut const_vec = Vec::with_capacity(vec.len());
 
                 for value in vec {
@@ -135,7 +137,7 @@ impl IrValue {
                 ConstValue::Vec(const_vec)
             }
             IrValue::Tuple(tuple) => {
-                let tuple = tuple.take().with_span(spanned)?;
+                let tuple = tuple.borrow_ref().with_span(spanned)?.clone();
                 let mut const_tuple = Vec::with_capacity(tuple.len());
 
                 for value in Vec::from(tuple) {
@@ -145,7 +147,7 @@ impl IrValue {
                 ConstValue::Tuple(const_tuple.into_boxed_slice())
             }
             IrValue::Object(object) => {
-                let object = object.take().with_span(spanned)?;
+                let object = object.borrow_ref().with_span(spanned)?.clone();
                 let mut const_object = HashMap::with_capacity(object.len());
 
                 for (key, value) in object {
@@ -157,6 +159,87 @@ impl IrValue {
         })
     }
 
+    /// Test if the value is equal to another value. Values of different types
+    /// are never equal.
+    pub(crate) fn eq_with<S>(&self, other: &Self, spanned: S) -> compile::Result<bool>
+    where
+        S: Copy + Spanned,
+    {
+        Ok(match (self, other) {
+            (IrValue::Unit, IrValue::Unit) => true,
+            (IrValue::Byte(a), IrValue::Byte(b)) => a == b,
+            (IrValue::Char(a), IrValue::Char(b)) => a == b,
+            (IrValue::Bool(a), IrValue::Bool(b)) => a == b,
+            (IrValue::Integer(a), IrValue::Integer(b)) => a == b,
+            #[allow(clippy::float_cmp)]
+            (IrValue::Float(a), IrValue::Float(b)) => a == b,
+            (IrValue::String(a), IrValue::String(b)) => {
+                *a.borrow_ref().with_span(spanned)? == *b.borrow_ref().with_span(spanned)?
+            }
+            (IrValue::Bytes(a), IrValue::Bytes(b)) => {
+                *a.borrow_ref().with_span(spanned)? == *b.borrow_ref().with_span(spanned)?
+            }
+            (IrValue::Option(a), IrValue::Option(b)) => {
+                let a = a.borrow_ref().with_span(spanned)?;
+                let b = b.borrow_ref().with_span(spanned)?;
+
+                match (&*a, &*b) {
+                    (Some(a), Some(b)) => a.eq_with(b, spanned)?,
+                    (None, None) => true,
+                    _ => false,
+                }
+            }
+            (IrValue::Vec(a), IrValue::Vec(b)) => {
+                let a = a.borrow_ref().with_span(spanned)?;
+                let b = b.borrow_ref().with_span(spanned)?;
+                Self::eq_slice(&a, &b, spanned)?
+            }
+            (IrValue::Tuple(a), IrValue::Tuple(b)) => {
+                let a = a.borrow_ref().with_span(spanned)?;
+                let b = b.borrow_ref().with_span(spanned)?;
+                Self::eq_slice(&a, &b, spanned)?
+            }
+            (IrValue::Object(a), IrValue::Object(b)) => {
+                let a = a.borrow_ref().with_span(spanned)?;
+                let b = b.borrow_ref().with_span(spanned)?;
+
+                if a.len() != b.len() {
+                    return Ok(false);
+                }
+
+                for (key, a) in a.iter() {
+                    let Some(b) = b.get(key) else {
+                        return Ok(false);
+                    };
+
+                    if !a.eq_with(b, spanned)? {
+                        return Ok(false);
+                    }
+                }
+
+                true
+            }
+            _ => false,
+        })
+    }
+
+    fn eq_slice<S>(a: &[IrValue], b: &[IrValue], spanned: S) -> compile::Result<bool>
+    where
+        S: Copy + Spanned,
+    {
+        if a.len() != b.len() {
+            return Ok(false);
+        }
+
+        for (a, b) in a.iter().zip(b) {
+            if !a.eq_with(b, spanned)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
     /// Get the type information of the value.
     pub(crate) fn type_info(&self) -> TypeInfo {
         match self {
@@ -3,7 +3,7 @@
 use core::fmt;
 
 use crate::no_std::borrow::Cow;
-use crate::no_std::collections::HashSet;
+use crate::no_std::collections::{HashMap, HashSet};
 use crate::no_std::path::Path;
 use crate::no_std::prelude::*;
 
@@ -118,6 +118,7 @@ impl Meta {
             Kind::Const { .. } => None,
             Kind::ConstFn { .. } => None,
             Kind::Import { .. } => None,
+            Kind::Trait { .. } => None,
             Kind::Macro => None,
             Kind::AttributeMacro => None,
             Kind::Module => None,
@@ -224,6 +225,11 @@ pub enum Kind {
     },
     /// Purely an import.
     Import(Import),
+    /// A trait.
+    Trait {
+        /// The names of the functions declared by the trait.
+        functions: Box<[Box<str>]>,
+    },
     /// A module.
     Module,
 }
@@ -282,6 +288,8 @@ pub struct Import {
 pub struct FieldsNamed {
     /// Fields associated with the type.
     pub(crate) fields: HashSet<Box<str>>,
+    /// Types of the fields which have a type annotation.
+    pub(crate) types: HashMap<Box<str>, Hash>,
 }
 
 /// Item and the module that the item belongs to.
@@ -322,6 +330,16 @@ pub struct Signature {
     /// Argument types to the function.
     #[cfg(feature = "doc")]
     pub(crate) argument_types: Box<[Option<Hash>]>,
+    /// The type of the value produced by calling a script function, as
+    /// checked at compile time. This is the future for asynchronous functions.
+    pub(crate) annotated_return: Option<Hash>,
+    /// The annotated types of the arguments of a script function, as checked
+    /// at compile time.
+    pub(crate) annotated_arguments: Box<[Option<Hash>]>,
+    /// Names of the arguments to the function which can be passed by name.
+    pub(crate) argument_names: Box<[Option<Box<str>>]>,
+    /// The hashes of the constants holding the default values of arguments.
+    pub(crate) argument_defaults: Box<[Option<Hash>]>,
 }
 
 /// The kind of an associated function.
@@ -84,6 +84,9 @@ impl fmt::Display for MetaInfo {
             MetaInfoKind::Import => {
                 write!(fmt, "import {name}")?;
             }
+            MetaInfoKind::Trait => {
+                write!(fmt, "trait {name}")?;
+            }
             MetaInfoKind::Module => {
                 write!(fmt, "module {name}")?;
             }
@@ -108,6 +111,7 @@ pub(crate) enum MetaInfoKind {
     Const,
     ConstFn,
     Import,
+    Trait,
     Module,
 }
 
@@ -127,6 +131,7 @@ impl MetaInfoKind {
             meta::Kind::Const { .. } => MetaInfoKind::Const,
             meta::Kind::ConstFn { .. } => MetaInfoKind::ConstFn,
             meta::Kind::Import { .. } => MetaInfoKind::Import,
+            meta::Kind::Trait { .. } => MetaInfoKind::Trait,
             meta::Kind::Module { .. } => MetaInfoKind::Module,
         }
     }
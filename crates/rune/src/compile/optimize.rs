@@ -0,0 +1,491 @@
+//! Peephole optimizations over assembly.
+//!
+//! These are performed over the [Assembly] of a single function after it has
+//! been assembled and before it is added to the unit, if enabled through
+//! [Options::optimize][crate::compile::Options::optimize].
+//!
+//! Instructions which are removed hand their labels and variables over to the
+//! instruction following them, so that debug information for the instructions
+//! which remain is kept intact.
+
+use core::mem::take;
+
+use crate::no_std::collections::{HashMap, HashSet};
+use crate::no_std::prelude::*;
+
+use crate::ast::Span;
+use crate::compile::{Assembly, AssemblyInst};
+use crate::runtime::debug::DebugVariable;
+use crate::runtime::{Inst, InstAddress, InstOp, InstValue, Label};
+
+/// The maximum number of times optimizations are repeated while they are
+/// making progress.
+const MAX_PASSES: usize = 8;
+
+/// An instruction being optimized.
+struct Entry {
+    inst: AssemblyInst,
+    span: Span,
+    /// Labels which point to this instruction.
+    labels: Vec<Label>,
+    comments: Vec<Box<str>>,
+    /// Variables which become live at this instruction.
+    variables: Vec<DebugVariable>,
+}
+
+impl Entry {
+    fn raw(&self) -> Option<&Inst> {
+        match &self.inst {
+            AssemblyInst::Raw { raw } => Some(raw),
+            _ => None,
+        }
+    }
+}
+
+/// Labels and variables which point past the last instruction.
+#[derive(Default)]
+struct Tail {
+    labels: Vec<Label>,
+    variables: Vec<DebugVariable>,
+}
+
+/// Optimize the given assembly.
+pub(crate) fn optimize(asm: &mut Assembly) {
+    let mut labels = take(&mut asm.labels);
+    let mut comments = take(&mut asm.comments);
+    let mut variables = take(&mut asm.variables);
+
+    let mut entries = Vec::with_capacity(asm.instructions.len());
+
+    for (pos, (inst, span)) in asm.instructions.drain(..).enumerate() {
+        entries.push(Entry {
+            inst,
+            span,
+            labels: labels.remove(&pos).map(|(_, l)| l).unwrap_or_default(),
+            comments: comments.remove(&pos).unwrap_or_default(),
+            variables: variables.remove(&pos).unwrap_or_default(),
+        });
+    }
+
+    let mut tail = Tail {
+        labels: labels.into_values().flat_map(|(_, l)| l).collect(),
+        variables: variables.into_values().flatten().collect(),
+    };
+
+    for _ in 0..MAX_PASSES {
+        let mut changed = peephole(&mut entries, &mut tail);
+        changed |= thread_jumps(&mut entries, &tail);
+
+        let mut live = reachable(&entries, &tail);
+        changed |= remove_jumps_to_next(&entries, &tail, &mut live);
+
+        if live.iter().any(|live| !live) {
+            entries = retain(entries, &live, &mut tail);
+            changed = true;
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    let mut slot = 0;
+
+    for (pos, entry) in entries.into_iter().enumerate() {
+        if !entry.labels.is_empty() {
+            asm.labels.insert(pos, (slot, relabel(entry.labels, slot)));
+            slot += 1;
+        }
+
+        if !entry.comments.is_empty() {
+            asm.comments.insert(pos, entry.comments);
+        }
+
+        if !entry.variables.is_empty() {
+            asm.variables.insert(pos, entry.variables);
+        }
+
+        asm.instructions.push((entry.inst, entry.span));
+    }
+
+    let end = asm.instructions.len();
+
+    if !tail.labels.is_empty() {
+        asm.labels.insert(end, (slot, relabel(tail.labels, slot)));
+    }
+
+    if !tail.variables.is_empty() {
+        asm.variables.insert(end, tail.variables);
+    }
+}
+
+/// Point every label to the given label slot.
+fn relabel(labels: Vec<Label>, slot: usize) -> Vec<Label> {
+    for label in &labels {
+        label.set_jump(slot);
+    }
+
+    labels
+}
+
+/// Rewrite short sequences of instructions into cheaper ones.
+///
+/// Rewritten instructions are pushed onto an output stack, and the top of it is
+/// inspected after every push so that rewrites can cascade, like when folding
+/// nested constant expressions. A sequence is only rewritten if no jump lands
+/// in the middle of it.
+fn peephole(entries: &mut Vec<Entry>, tail: &mut Tail) -> bool {
+    let mut changed = false;
+    let mut out = Vec::<Entry>::with_capacity(entries.len());
+    let mut pending = Tail::default();
+
+    for mut entry in entries.drain(..) {
+        if !pending.labels.is_empty() {
+            entry.labels.splice(0..0, pending.labels.drain(..));
+        }
+
+        if !pending.variables.is_empty() {
+            entry.variables.splice(0..0, pending.variables.drain(..));
+        }
+
+        out.push(entry);
+
+        while rewrite(&mut out, &mut pending) {
+            changed = true;
+        }
+    }
+
+    tail.labels.splice(0..0, pending.labels);
+    tail.variables.splice(0..0, pending.variables);
+    *entries = out;
+    changed
+}
+
+/// Try to rewrite the instructions at the top of the output stack.
+fn rewrite(out: &mut Vec<Entry>, pending: &mut Tail) -> bool {
+    match out.as_slice() {
+        [.., a, b, c] if b.labels.is_empty() && c.labels.is_empty() => {
+            if let (
+                Some(Inst::Push { value: lhs }),
+                Some(Inst::Push { value: rhs }),
+                Some(&Inst::Op {
+                    op,
+                    a: InstAddress::Top,
+                    b: InstAddress::Top,
+                    out: output,
+                }),
+            ) = (a.raw(), b.raw(), c.raw())
+            {
+                if let Some(value) = fold(op, *lhs, *rhs) {
+                    let c = pop(out, pending);
+                    let mut b = pop(out, pending);
+                    let a = out.last_mut().expect("missing instruction");
+                    a.inst = raw(Inst::Push { value });
+                    a.span = c.span;
+
+                    if let InstAddress::Offset(offset) = output {
+                        b.inst = raw(Inst::Replace { offset });
+                        b.span = c.span;
+                        b.comments.clear();
+                        b.variables.append(&mut pending.variables);
+                        out.push(b);
+                    }
+
+                    return true;
+                }
+            }
+        }
+        _ => {}
+    }
+
+    let [.., a, b] = out.as_slice() else {
+        return false;
+    };
+
+    if !b.labels.is_empty() {
+        return false;
+    }
+
+    let inst = match (&a.inst, &b.inst) {
+        (AssemblyInst::Raw { raw: a }, AssemblyInst::Raw { raw: b }) => match (*a, *b) {
+            (Inst::Pop | Inst::PopN { .. }, Inst::Pop | Inst::PopN { .. }) => {
+                let count = pop_count(a) + pop_count(b);
+                raw(Inst::PopN { count })
+            }
+            _ => return false,
+        },
+        (
+            AssemblyInst::Raw {
+                raw:
+                    Inst::Push {
+                        value: InstValue::Bool(condition),
+                    },
+            },
+            AssemblyInst::JumpIf { label },
+        ) => {
+            if !*condition {
+                pop(out, pending);
+                let a = pop(out, pending);
+                pending.labels.splice(0..0, a.labels);
+                pending.variables.splice(0..0, a.variables);
+                return true;
+            }
+
+            AssemblyInst::Jump {
+                label: label.clone(),
+            }
+        }
+        _ => return false,
+    };
+
+    pop(out, pending);
+    let a = out.last_mut().expect("missing instruction");
+    a.inst = inst;
+    a.comments.clear();
+    true
+}
+
+/// Pop an instruction from the output stack, holding on to its variables.
+fn pop(out: &mut Vec<Entry>, pending: &mut Tail) -> Entry {
+    let mut entry = out.pop().expect("missing instruction");
+    pending.variables.splice(0..0, entry.variables.drain(..));
+    entry
+}
+
+fn raw(raw: Inst) -> AssemblyInst {
+    AssemblyInst::Raw { raw }
+}
+
+fn pop_count(inst: &Inst) -> usize {
+    match *inst {
+        Inst::PopN { count } => count,
+        _ => 1,
+    }
+}
+
+/// Fold a constant operation in the same way the virtual machine would
+/// perform it.
+///
+/// Operations which would cause an error at runtime are left alone so that the
+/// error is raised when the instruction is executed.
+fn fold(op: InstOp, lhs: InstValue, rhs: InstValue) -> Option<InstValue> {
+    let value = match (lhs, rhs) {
+        (InstValue::Integer(a), InstValue::Integer(b)) => match op {
+            InstOp::Add => InstValue::Integer(a.checked_add(b)?),
+            InstOp::Sub => InstValue::Integer(a.checked_sub(b)?),
+            InstOp::Mul => InstValue::Integer(a.checked_mul(b)?),
+            InstOp::Div => InstValue::Integer(a.checked_div(b)?),
+            InstOp::Rem => InstValue::Integer(a.checked_rem(b)?),
+            InstOp::BitAnd => InstValue::Integer(a & b),
+            InstOp::BitXor => InstValue::Integer(a ^ b),
+            InstOp::BitOr => InstValue::Integer(a | b),
+            InstOp::Shl => InstValue::Integer(a.checked_shl(u32::try_from(b).ok()?)?),
+            InstOp::Shr => InstValue::Integer(a.checked_shr(u32::try_from(b).ok()?)?),
+            InstOp::Lt => InstValue::Bool(a < b),
+            InstOp::Gt => InstValue::Bool(a > b),
+            InstOp::Lte => InstValue::Bool(a <= b),
+            InstOp::Gte => InstValue::Bool(a >= b),
+            InstOp::Eq => InstValue::Bool(a == b),
+            InstOp::Neq => InstValue::Bool(a != b),
+            _ => return None,
+        },
+        (InstValue::Float(a), InstValue::Float(b)) => match op {
+            InstOp::Add => InstValue::Float(a + b),
+            InstOp::Sub => InstValue::Float(a - b),
+            InstOp::Mul => InstValue::Float(a * b),
+            InstOp::Div => InstValue::Float(a / b),
+            InstOp::Rem => InstValue::Float(a % b),
+            InstOp::Lt => InstValue::Bool(a < b),
+            InstOp::Gt => InstValue::Bool(a > b),
+            InstOp::Lte => InstValue::Bool(a <= b),
+            InstOp::Gte => InstValue::Bool(a >= b),
+            _ => return None,
+        },
+        (InstValue::Bool(a), InstValue::Bool(b)) => match op {
+            InstOp::BitAnd => InstValue::Bool(a & b),
+            InstOp::BitXor => InstValue::Bool(a ^ b),
+            InstOp::BitOr => InstValue::Bool(a | b),
+            InstOp::Eq => InstValue::Bool(a == b),
+            InstOp::Neq => InstValue::Bool(a != b),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+/// Get the label an instruction jumps to, if any.
+fn jump_label(inst: &AssemblyInst) -> Option<&Label> {
+    match inst {
+        AssemblyInst::Jump { label }
+        | AssemblyInst::JumpIf { label }
+        | AssemblyInst::JumpIfOrPop { label }
+        | AssemblyInst::JumpIfNotOrPop { label }
+        | AssemblyInst::JumpIfBranch { label, .. }
+        | AssemblyInst::PopAndJumpIfNot { label, .. }
+        | AssemblyInst::IterNext { label, .. } => Some(label),
+        AssemblyInst::Raw { .. } => None,
+    }
+}
+
+fn jump_label_mut(inst: &mut AssemblyInst) -> Option<&mut Label> {
+    match inst {
+        AssemblyInst::Jump { label }
+        | AssemblyInst::JumpIf { label }
+        | AssemblyInst::JumpIfOrPop { label }
+        | AssemblyInst::JumpIfNotOrPop { label }
+        | AssemblyInst::JumpIfBranch { label, .. }
+        | AssemblyInst::PopAndJumpIfNot { label, .. }
+        | AssemblyInst::IterNext { label, .. } => Some(label),
+        AssemblyInst::Raw { .. } => None,
+    }
+}
+
+/// Map every label to the instruction it points to, where labels pointing
+/// past the last instruction map to the number of instructions.
+fn label_targets(entries: &[Entry], tail: &Tail) -> HashMap<usize, usize> {
+    let mut targets = HashMap::new();
+
+    for (n, entry) in entries.iter().enumerate() {
+        for label in &entry.labels {
+            targets.insert(label.index, n);
+        }
+    }
+
+    for label in &tail.labels {
+        targets.insert(label.index, entries.len());
+    }
+
+    targets
+}
+
+/// Make jumps which land on an unconditional jump go straight to its
+/// destination.
+fn thread_jumps(entries: &mut [Entry], tail: &Tail) -> bool {
+    let targets = label_targets(entries, tail);
+    let mut changed = false;
+
+    for n in 0..entries.len() {
+        let Some(label) = jump_label(&entries[n].inst) else {
+            continue;
+        };
+
+        let mut current = label;
+        let mut seen = HashSet::new();
+
+        while let Some(&target) = targets.get(&current.index) {
+            let Some(AssemblyInst::Jump { label: next }) = entries.get(target).map(|e| &e.inst)
+            else {
+                break;
+            };
+
+            if !seen.insert(next.index) {
+                break;
+            }
+
+            current = next;
+        }
+
+        if current.index != label.index {
+            let current = current.clone();
+
+            if let Some(label) = jump_label_mut(&mut entries[n].inst) {
+                *label = current;
+                changed = true;
+            }
+        }
+    }
+
+    changed
+}
+
+/// Calculate which instructions are reachable from the start of the
+/// assembly.
+fn reachable(entries: &[Entry], tail: &Tail) -> Vec<bool> {
+    let targets = label_targets(entries, tail);
+    let mut live = vec![false; entries.len()];
+    let mut queue = vec![0];
+
+    while let Some(n) = queue.pop() {
+        let Some(entry) = entries.get(n) else {
+            continue;
+        };
+
+        if live[n] {
+            continue;
+        }
+
+        live[n] = true;
+
+        if let Some(label) = jump_label(&entry.inst) {
+            if let Some(&target) = targets.get(&label.index) {
+                queue.push(target);
+            }
+        }
+
+        let falls_through = !matches!(
+            entry.inst,
+            AssemblyInst::Jump { .. }
+                | AssemblyInst::Raw {
+                    raw: Inst::Return { .. } | Inst::ReturnUnit | Inst::Panic { .. }
+                }
+        );
+
+        if falls_through {
+            queue.push(n + 1);
+        }
+    }
+
+    live
+}
+
+/// Remove unconditional jumps to the instruction immediately following them.
+fn remove_jumps_to_next(entries: &[Entry], tail: &Tail, live: &mut [bool]) -> bool {
+    let targets = label_targets(entries, tail);
+    let mut changed = false;
+
+    for (n, entry) in entries.iter().enumerate() {
+        if !live[n] {
+            continue;
+        }
+
+        let AssemblyInst::Jump { label } = &entry.inst else {
+            continue;
+        };
+
+        let Some(&target) = targets.get(&label.index) else {
+            continue;
+        };
+
+        if target > n && (n + 1..target).all(|n| !live[n]) {
+            live[n] = false;
+            changed = true;
+        }
+    }
+
+    changed
+}
+
+/// Retain live instructions, handing the labels and variables of removed
+/// instructions over to the next live instruction.
+fn retain(entries: Vec<Entry>, live: &[bool], tail: &mut Tail) -> Vec<Entry> {
+    let mut out = Vec::with_capacity(entries.len());
+    let mut pending = Tail::default();
+
+    for (mut entry, &live) in entries.into_iter().zip(live) {
+        if !live {
+            pending.labels.append(&mut entry.labels);
+            pending.variables.append(&mut entry.variables);
+            continue;
+        }
+
+        entry.labels.splice(0..0, pending.labels.drain(..));
+        entry.variables.splice(0..0, pending.variables.drain(..));
+        out.push(entry);
+    }
+
+    tail.labels.splice(0..0, pending.labels);
+    tail.variables.splice(0..0, pending.variables);
+    out
+}
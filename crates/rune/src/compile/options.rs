@@ -24,6 +24,8 @@ pub struct Options {
     pub(crate) debug_info: bool,
     /// Support (experimental) macros.
     pub(crate) macros: bool,
+    /// Optimize assembled instructions.
+    pub(crate) optimize: bool,
     /// Support (experimental) bytecode caching.
     pub bytecode: bool,
 
@@ -56,6 +58,9 @@ impl Options {
             Some("macros") => {
                 self.macros = it.next() != Some("false");
             }
+            Some("opt") => {
+                self.optimize = it.next() != Some("false");
+            }
             Some("bytecode") => {
                 self.bytecode = it.next() != Some("false");
             }
@@ -97,6 +102,15 @@ impl Options {
         self.macros = enabled;
     }
 
+    /// Set if assembled instructions should be optimized or not. Defaults to
+    /// `false`.
+    ///
+    /// This folds constant arithmetic, simplifies jumps and stack
+    /// manipulation, and removes unreachable code.
+    pub fn optimize(&mut self, enabled: bool) {
+        self.optimize = enabled;
+    }
+
     /// Set if bytecode caching is enabled or not. Defaults to `false`.
     pub fn bytecode(&mut self, enabled: bool) {
         self.bytecode = enabled;
@@ -115,6 +129,7 @@ impl Default for Options {
             memoize_instance_fn: true,
             debug_info: true,
             macros: true,
+            optimize: false,
             bytecode: false,
             cfg_test: false,
             v2: false,
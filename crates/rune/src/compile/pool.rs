@@ -102,6 +102,11 @@ impl Pool {
         self.item_storage(id).hash
     }
 
+    /// Look up an item which has already been allocated by its type hash.
+    pub(crate) fn item_by_type_hash(&self, hash: Hash) -> Option<ItemId> {
+        self.hash_to_item.get(&hash).copied()
+    }
+
     /// Lookup mod meta by the given identifier.
     pub(crate) fn module(&self, ModId(id): ModId) -> &ModMeta {
         let id = usize::try_from(id).expect("module id overflow");
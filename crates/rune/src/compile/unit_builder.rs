@@ -14,11 +14,12 @@ use thiserror::Error;
 use crate::ast::{Span, Spanned};
 use crate::compile::meta;
 use crate::compile::{
-    self, Assembly, AssemblyInst, CompileErrorKind, Item, Location, Pool, QueryErrorKind, WithSpan,
+    self, optimize, Assembly, AssemblyInst, CompileErrorKind, Item, Location, Options, Pool,
+    QueryErrorKind, WithSpan,
 };
 use crate::query::QueryInner;
 use crate::runtime::debug::{DebugArgs, DebugSignature};
-use crate::runtime::unit::UnitEncoder;
+use crate::runtime::unit::{default_argument_hash, UnitEncoder};
 use crate::runtime::{
     Call, ConstValue, DebugInfo, DebugInst, Inst, Protocol, Rtti, StaticString, Unit, UnitFn,
     VariantRtti,
@@ -523,6 +524,7 @@ impl UnitBuilder {
             meta::Kind::AsyncBlock { .. } => (),
             meta::Kind::ConstFn { .. } => (),
             meta::Kind::Import { .. } => (),
+            meta::Kind::Trait { .. } => (),
             meta::Kind::Module { .. } => (),
         }
 
@@ -543,6 +545,8 @@ impl UnitBuilder {
         assembly: Assembly,
         call: Call,
         debug_args: Box<[Box<str>]>,
+        defaults: Vec<(usize, ConstValue)>,
+        options: &Options,
         unit_encoder: &mut dyn UnitEncoder,
     ) -> compile::Result<()> {
         let offset = unit_encoder.offset();
@@ -566,9 +570,14 @@ impl UnitBuilder {
             ConstValue::String(signature.path.to_string()),
         );
 
+        for (index, value) in defaults {
+            self.constants
+                .insert(default_argument_hash(hash, index), value);
+        }
+
         self.debug_info_mut().functions.insert(hash, signature);
 
-        self.add_assembly(location, assembly, unit_encoder)?;
+        self.add_assembly(location, assembly, options, unit_encoder)?;
         Ok(())
     }
 
@@ -592,6 +601,29 @@ impl UnitBuilder {
         Ok(())
     }
 
+    /// Register the instance function which implements a function of a trait
+    /// for the given type, so that it can be called through the protocol of
+    /// the trait.
+    pub(crate) fn new_trait_function(
+        &mut self,
+        location: Location,
+        type_hash: Hash,
+        trait_hash: Hash,
+        name: &str,
+    ) -> compile::Result<()> {
+        let hash = Hash::associated_function(type_hash, Hash::trait_function(trait_hash, name));
+        let target = Hash::associated_function(type_hash, name);
+
+        if self.reexports.insert(hash, target).is_some() {
+            return Err(compile::Error::new(
+                location.span,
+                CompileErrorKind::FunctionReExportConflict { hash },
+            ));
+        }
+
+        Ok(())
+    }
+
     /// Declare a new instance function at the current instruction pointer.
     pub(crate) fn new_instance_function(
         &mut self,
@@ -603,6 +635,8 @@ impl UnitBuilder {
         assembly: Assembly,
         call: Call,
         debug_args: Box<[Box<str>]>,
+        defaults: Vec<(usize, ConstValue)>,
+        options: &Options,
         unit_storage: &mut dyn UnitEncoder,
     ) -> compile::Result<()> {
         tracing::trace!("instance fn: {}", item);
@@ -637,11 +671,18 @@ impl UnitBuilder {
             ConstValue::String(signature.path.to_string()),
         );
 
+        for (index, value) in defaults {
+            self.constants
+                .insert(default_argument_hash(instance_fn, index), value.clone());
+            self.constants
+                .insert(default_argument_hash(hash, index), value);
+        }
+
         self.debug_info_mut()
             .functions
             .insert(instance_fn, signature);
         self.functions_rev.insert(offset, hash);
-        self.add_assembly(location, assembly, unit_storage)?;
+        self.add_assembly(location, assembly, options, unit_storage)?;
         Ok(())
     }
 
@@ -668,13 +709,19 @@ impl UnitBuilder {
         self.debug.get_or_insert_with(Default::default)
     }
 
-    /// Translate the given assembly into instructions.
+    /// Translate the given assembly into instructions, optimizing it first if
+    /// enabled.
     fn add_assembly(
         &mut self,
         location: Location,
-        assembly: Assembly,
+        mut assembly: Assembly,
+        options: &Options,
         storage: &mut dyn UnitEncoder,
     ) -> compile::Result<()> {
+        if options.optimize {
+            optimize::optimize(&mut assembly);
+        }
+
         self.label_count = assembly.label_count;
 
         let base = storage.extend_offsets(assembly.labels.len());
@@ -815,12 +862,14 @@ impl UnitBuilder {
                 comment = Some(actual.into())
             }
 
-            let debug = self.debug.get_or_insert_with(Default::default);
+            let mut debug_inst = DebugInst::new(location.source_id, span, comment, labels);
 
-            debug.instructions.insert(
-                at,
-                DebugInst::new(location.source_id, span, comment, labels),
-            );
+            if let Some(variables) = assembly.variables.get(&pos) {
+                debug_inst.variables = variables.clone();
+            }
+
+            let debug = self.debug.get_or_insert_with(Default::default);
+            debug.instructions.insert(at, debug_inst);
         }
 
         Ok(())
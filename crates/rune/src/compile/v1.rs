@@ -14,6 +14,7 @@ use crate::runtime::{ConstValue, Inst};
 use crate::{Hash, SourceId};
 
 pub(crate) mod assemble;
+mod exhaustive;
 mod loops;
 mod scopes;
 
@@ -90,6 +91,19 @@ impl<'a, 'hir> Assembler<'a, 'hir> {
             .lookup_meta(Location::new(self.source_id, span), item, parameters)
     }
 
+    /// Define a named variable in the current scope, recording it in the
+    /// debug information of the assembly.
+    pub(crate) fn define(
+        &mut self,
+        variable: hir::Variable,
+        name: hir::Name<'hir>,
+        span: &'hir dyn Spanned,
+    ) -> compile::Result<usize> {
+        let offset = self.scopes.define(variable, name, span)?;
+        self.asm.variable(offset, name);
+        Ok(offset)
+    }
+
     /// Pop locals by simply popping them.
     pub(crate) fn locals_pop(&mut self, total_var_count: usize, span: &dyn Spanned) {
         match total_var_count {
@@ -3,7 +3,7 @@ use core::mem::{replace, take};
 use crate::no_std::prelude::*;
 
 use crate::ast::{self, Span, Spanned};
-use crate::compile::v1::{Assembler, Layer, Loop, Needs, Var};
+use crate::compile::v1::{exhaustive, Assembler, Layer, Loop, Needs, Var};
 use crate::compile::{self, CompileErrorKind, WithSpan};
 use crate::hir;
 use crate::parse::Resolve;
@@ -58,6 +58,15 @@ impl<'hir> Asm<'hir> {
         Ok(())
     }
 
+    /// Get the address of the assembled value without copying variables onto
+    /// the stack, for values which are immediately consumed.
+    fn address(self) -> InstAddress {
+        match self.kind {
+            AsmKind::Top => InstAddress::Top,
+            AsmKind::Var(var) => InstAddress::Offset(var.offset),
+        }
+    }
+
     /// Assemble into an instruction declaring an anonymous variable if appropriate.
     fn apply_targeted(self, c: &mut Assembler) -> compile::Result<InstAddress> {
         let address = match self.kind {
@@ -92,7 +101,7 @@ pub(crate) fn fn_from_item_fn<'hir>(
                     ));
                 }
 
-                c.scopes.define(*variable, hir::Name::SelfValue, span)?;
+                c.define(*variable, hir::Name::SelfValue, span)?;
             }
             hir::FnArg::Pat(pat) => {
                 let offset = c.scopes.alloc(pat)?;
@@ -135,7 +144,7 @@ pub(crate) fn async_block_secondary<'hir>(
     hir: &'hir hir::AsyncBlock<'hir>,
 ) -> compile::Result<()> {
     for (variable, capture) in hir.captures.iter().copied() {
-        c.scopes.define(variable, capture, &hir.block)?;
+        c.define(variable, capture, &hir.block)?;
     }
 
     return_(c, &hir.block, hir.block, block)?;
@@ -168,7 +177,7 @@ pub(crate) fn expr_closure_secondary<'hir>(
         c.asm.push(Inst::PushTuple, span);
 
         for (variable, capture) in hir.captures.iter().copied() {
-            c.scopes.define(variable, capture, span)?;
+            c.define(variable, capture, span)?;
         }
     }
 
@@ -271,7 +280,7 @@ fn pat<'hir>(
             }
             hir::PatPathKind::Ident(name, variable) => {
                 load(c, Needs::Value)?;
-                c.scopes.define(variable, name.into(), hir)?;
+                c.define(variable, name.into(), hir)?;
                 Ok(false)
             }
         },
@@ -288,6 +297,34 @@ fn pat<'hir>(
             pat_object(c, hir, span, false_label, &load)?;
             Ok(true)
         }
+        hir::PatKind::Or(hir) => pat_or(c, hir, span, false_label, &load),
+        hir::PatKind::Range(range) => {
+            let inst = match *range {
+                hir::PatRange::Integer { start, end } => Inst::RangeInteger { start, end },
+                hir::PatRange::Char { start, end } => Inst::RangeChar { start, end },
+                hir::PatRange::Byte { start, end } => Inst::RangeByte { start, end },
+            };
+
+            load(c, Needs::Value)?;
+            c.asm.push(inst, hir);
+            c.asm
+                .pop_and_jump_if_not(c.scopes.local(hir)?, false_label, hir);
+            Ok(true)
+        }
+        hir::PatKind::At(at) => {
+            load(c, Needs::Value)?;
+            let offset = c.define(at.variable, at.name.into(), hir)?;
+
+            let load = move |c: &mut Assembler<'_, 'hir>, needs: Needs| {
+                if needs.value() {
+                    c.asm.push(Inst::Copy { offset }, hir);
+                }
+
+                Ok(())
+            };
+
+            pat(c, at.pat, false_label, &load)
+        }
         _ => Err(compile::Error::new(
             hir,
             CompileErrorKind::UnsupportedPatternExpr,
@@ -472,6 +509,70 @@ fn pat_tuple<'hir>(
     Ok(())
 }
 
+/// Assemble an or-pattern.
+///
+/// Every alternative is tested in a scope of its own, so that a failed
+/// alternative only cleans up after itself before the next one is tested. The
+/// variables bound by the pattern are stored in slots reserved up front, since
+/// each alternative might bind them at different offsets.
+#[instrument(span = span)]
+fn pat_or<'hir>(
+    c: &mut Assembler<'_, 'hir>,
+    hir: &hir::PatOr<'hir>,
+    span: &'hir dyn Spanned,
+    false_label: &Label,
+    load: &dyn Fn(&mut Assembler<'_, 'hir>, Needs) -> compile::Result<()>,
+) -> compile::Result<bool> {
+    load(c, Needs::Value)?;
+    let offset = c.scopes.alloc(span)?;
+
+    let mut slots = Vec::with_capacity(hir.bindings.len());
+
+    for &(name, variable) in hir.bindings {
+        c.asm.push(Inst::unit(), span);
+        slots.push(c.define(variable, name.into(), span)?);
+    }
+
+    let ok_label = c.asm.new_label("pat_or_ok");
+    let mut refutable = false;
+
+    for alternative in hir.alternatives {
+        let alternative_false = c.asm.new_label("pat_or_false");
+        let guard = c.scopes.child(alternative)?;
+
+        let load = move |c: &mut Assembler<'_, 'hir>, needs: Needs| {
+            if needs.value() {
+                c.asm.push(Inst::Copy { offset }, alternative);
+            }
+
+            Ok(())
+        };
+
+        refutable = pat(c, alternative, &alternative_false, &load)?;
+
+        for (&(name, variable), &slot) in hir.bindings.iter().zip(&slots) {
+            let var = c.scopes.get(&mut c.q, variable, name.into(), alternative)?;
+            var.copy(c, alternative, "pattern alternative");
+            c.asm.push(Inst::Replace { offset: slot }, alternative);
+        }
+
+        let layer = c.scopes.pop(guard, alternative)?;
+        c.locals_pop(layer.local, alternative);
+        c.asm.jump(&ok_label, alternative);
+        c.asm.label(&alternative_false)?;
+    }
+
+    // NB: only the last alternative can fail the whole pattern, at which point
+    // the locals of the enclosing scope have to be cleaned up.
+    if refutable {
+        c.locals_pop(c.scopes.local(span)?, span);
+        c.asm.jump(false_label, span);
+    }
+
+    c.asm.label(&ok_label)?;
+    Ok(refutable)
+}
+
 fn to_tuple_match_instruction(kind: hir::PatItemsKind) -> Inst {
     match kind {
         hir::PatItemsKind::Type { hash } => Inst::MatchType { hash },
@@ -561,7 +662,7 @@ fn pat_object<'hir>(
             }
             hir::Binding::Ident(span, name, variable) => {
                 c.asm.push(Inst::ObjectIndexGetAt { offset, slot }, &span);
-                c.scopes.define(variable, name.into(), binding)?;
+                c.define(variable, name.into(), binding)?;
             }
         }
     }
@@ -928,13 +1029,21 @@ fn expr_assign<'hir>(
     let supported = match hir.lhs.kind {
         // <var> = <value>
         hir::ExprKind::Variable(variable, name) => {
-            expr(c, hir.rhs, Needs::Value)?.apply(c)?;
-            let var = c.scopes.get(&mut c.q, variable, name.into(), span)?;
-            c.asm.push_with_comment(
-                Inst::Replace { offset: var.offset },
-                span,
-                format_args!("var `{var}`"),
-            );
+            // NB: binary operations write their result directly to the
+            // variable.
+            if let Some(binary) = as_binary_op(hir.rhs) {
+                let var = c.scopes.get(&mut c.q, variable, name.into(), span)?;
+                binary_op(c, binary, hir.rhs, InstAddress::Offset(var.offset))?;
+            } else {
+                expr(c, hir.rhs, Needs::Value)?.apply(c)?;
+                let var = c.scopes.get(&mut c.q, variable, name.into(), span)?;
+                c.asm.push_with_comment(
+                    Inst::Replace { offset: var.offset },
+                    span,
+                    format_args!("var `{var}`"),
+                );
+            }
+
             true
         }
         // <expr>.<field> = <value>
@@ -1046,45 +1155,7 @@ fn expr_binary<'hir>(
         return Ok(Asm::top(span));
     }
 
-    let guard = c.scopes.child(span)?;
-
-    // NB: need to declare these as anonymous local variables so that they
-    // get cleaned up in case there is an early break (return, try, ...).
-    let rhs_needs = rhs_needs_of(&hir.op);
-    let a = expr(c, hir.lhs, Needs::Value)?.apply_targeted(c)?;
-    let b = expr(c, hir.rhs, rhs_needs)?.apply_targeted(c)?;
-
-    let op = match hir.op {
-        ast::BinOp::Eq(..) => InstOp::Eq,
-        ast::BinOp::Neq(..) => InstOp::Neq,
-        ast::BinOp::Lt(..) => InstOp::Lt,
-        ast::BinOp::Gt(..) => InstOp::Gt,
-        ast::BinOp::Lte(..) => InstOp::Lte,
-        ast::BinOp::Gte(..) => InstOp::Gte,
-        ast::BinOp::Is(..) => InstOp::Is,
-        ast::BinOp::IsNot(..) => InstOp::IsNot,
-        ast::BinOp::And(..) => InstOp::And,
-        ast::BinOp::Or(..) => InstOp::Or,
-        ast::BinOp::Add(..) => InstOp::Add,
-        ast::BinOp::Sub(..) => InstOp::Sub,
-        ast::BinOp::Div(..) => InstOp::Div,
-        ast::BinOp::Mul(..) => InstOp::Mul,
-        ast::BinOp::Rem(..) => InstOp::Rem,
-        ast::BinOp::BitAnd(..) => InstOp::BitAnd,
-        ast::BinOp::BitXor(..) => InstOp::BitXor,
-        ast::BinOp::BitOr(..) => InstOp::BitOr,
-        ast::BinOp::Shl(..) => InstOp::Shl,
-        ast::BinOp::Shr(..) => InstOp::Shr,
-
-        op => {
-            return Err(compile::Error::new(
-                span,
-                CompileErrorKind::UnsupportedBinaryOp { op },
-            ));
-        }
-    };
-
-    c.asm.push(Inst::Op { op, a, b }, span);
+    binary_op(c, hir, span, InstAddress::Top)?;
 
     // NB: we put it here to preserve the call in case it has side effects.
     // But if we don't need the value, then pop it from the stack.
@@ -1092,18 +1163,8 @@ fn expr_binary<'hir>(
         c.asm.push(Inst::Pop, span);
     }
 
-    c.scopes.pop(guard, span)?;
     return Ok(Asm::top(span));
 
-    /// Get the need of the right-hand side operator from the type of the
-    /// operator.
-    fn rhs_needs_of(op: &ast::BinOp) -> Needs {
-        match op {
-            ast::BinOp::Is(..) | ast::BinOp::IsNot(..) => Needs::Type,
-            _ => Needs::Value,
-        }
-    }
-
     fn compile_conditional_binop<'hir>(
         c: &mut Assembler<'_, 'hir>,
         lhs: &'hir hir::Expr<'hir>,
@@ -1150,17 +1211,19 @@ fn expr_binary<'hir>(
         span: &dyn Spanned,
         needs: Needs,
     ) -> compile::Result<()> {
+        let mut value = InstAddress::Top;
+
         let supported = match lhs.kind {
             // <var> <op> <expr>
             hir::ExprKind::Variable(variable, name) => {
-                expr(c, rhs, Needs::Value)?.apply(c)?;
+                value = expr(c, rhs, Needs::Value)?.address();
                 let var = c.scopes.get(&mut c.q, variable, name.into(), lhs)?;
                 Some(InstTarget::Offset(var.offset))
             }
             // <expr>.<field> <op> <value>
             hir::ExprKind::FieldAccess(field_access) => {
                 expr(c, field_access.expr, Needs::Value)?.apply(c)?;
-                expr(c, rhs, Needs::Value)?.apply(c)?;
+                value = expr(c, rhs, Needs::Value)?.address();
 
                 // field assignment
                 match field_access.expr_field {
@@ -1217,7 +1280,7 @@ fn expr_binary<'hir>(
             }
         };
 
-        c.asm.push(Inst::Assign { target, op }, span);
+        c.asm.push(Inst::Assign { target, op, value }, span);
 
         if needs.value() {
             c.asm.push(Inst::unit(), span);
@@ -1227,6 +1290,77 @@ fn expr_binary<'hir>(
     }
 }
 
+/// Get the given expression as a binary operation which can be assembled
+/// through [binary_op], if it is one.
+fn as_binary_op<'hir>(hir: &hir::Expr<'hir>) -> Option<&'hir hir::ExprBinary<'hir>> {
+    match hir.kind {
+        hir::ExprKind::Binary(binary) if !binary.op.is_assign() && !binary.op.is_conditional() => {
+            Some(binary)
+        }
+        _ => None,
+    }
+}
+
+/// Assemble a binary operation, writing its result to the given output.
+#[instrument(span = span)]
+fn binary_op<'hir>(
+    c: &mut Assembler<'_, 'hir>,
+    hir: &hir::ExprBinary<'hir>,
+    span: &dyn Spanned,
+    out: InstAddress,
+) -> compile::Result<()> {
+    let guard = c.scopes.child(span)?;
+
+    // NB: need to declare these as anonymous local variables so that they
+    // get cleaned up in case there is an early break (return, try, ...).
+    let rhs_needs = rhs_needs_of(&hir.op);
+    let a = expr(c, hir.lhs, Needs::Value)?.apply_targeted(c)?;
+    let b = expr(c, hir.rhs, rhs_needs)?.apply_targeted(c)?;
+
+    let op = match hir.op {
+        ast::BinOp::Eq(..) => InstOp::Eq,
+        ast::BinOp::Neq(..) => InstOp::Neq,
+        ast::BinOp::Lt(..) => InstOp::Lt,
+        ast::BinOp::Gt(..) => InstOp::Gt,
+        ast::BinOp::Lte(..) => InstOp::Lte,
+        ast::BinOp::Gte(..) => InstOp::Gte,
+        ast::BinOp::Is(..) => InstOp::Is,
+        ast::BinOp::IsNot(..) => InstOp::IsNot,
+        ast::BinOp::And(..) => InstOp::And,
+        ast::BinOp::Or(..) => InstOp::Or,
+        ast::BinOp::Add(..) => InstOp::Add,
+        ast::BinOp::Sub(..) => InstOp::Sub,
+        ast::BinOp::Div(..) => InstOp::Div,
+        ast::BinOp::Mul(..) => InstOp::Mul,
+        ast::BinOp::Rem(..) => InstOp::Rem,
+        ast::BinOp::BitAnd(..) => InstOp::BitAnd,
+        ast::BinOp::BitXor(..) => InstOp::BitXor,
+        ast::BinOp::BitOr(..) => InstOp::BitOr,
+        ast::BinOp::Shl(..) => InstOp::Shl,
+        ast::BinOp::Shr(..) => InstOp::Shr,
+
+        op => {
+            return Err(compile::Error::new(
+                span,
+                CompileErrorKind::UnsupportedBinaryOp { op },
+            ));
+        }
+    };
+
+    c.asm.push(Inst::Op { op, a, b, out }, span);
+    c.scopes.pop(guard, span)?;
+    return Ok(());
+
+    /// Get the need of the right-hand side operator from the type of the
+    /// operator.
+    fn rhs_needs_of(op: &ast::BinOp) -> Needs {
+        match op {
+            ast::BinOp::Is(..) | ast::BinOp::IsNot(..) => Needs::Type,
+            _ => Needs::Value,
+        }
+    }
+}
+
 /// Assemble a block expression.
 #[instrument(span = span)]
 fn expr_async_block<'hir>(
@@ -1382,13 +1516,41 @@ fn expr_call<'hir>(
             c.scopes.free(span, hir.args.len() + 1)?;
         }
         hir::Call::Meta { hash } => {
-            for e in hir.args {
-                expr(c, e, Needs::Value)?.apply(c)?;
-                c.scopes.alloc(span)?;
-            }
+            if let Some(order) = hir.order {
+                let mut offsets = Vec::with_capacity(args);
+
+                for e in hir.args {
+                    expr(c, e, Needs::Value)?.apply(c)?;
+                    offsets.push(c.scopes.alloc(span)?);
+                }
+
+                // NB: the arguments are evaluated in the order they appear in
+                // the call, so they are copied into the order of the
+                // parameters before calling.
+                for &index in order {
+                    c.asm.push(
+                        Inst::Copy {
+                            offset: offsets[index],
+                        },
+                        span,
+                    );
+                    c.scopes.alloc(span)?;
+                }
+
+                c.asm.push(Inst::Call { hash, args }, span);
+                c.scopes.free(span, args)?;
+
+                c.asm.push(Inst::Clean { count: args }, span);
+                c.scopes.free(span, args)?;
+            } else {
+                for e in hir.args {
+                    expr(c, e, Needs::Value)?.apply(c)?;
+                    c.scopes.alloc(span)?;
+                }
 
-            c.asm.push(Inst::Call { hash, args }, span);
-            c.scopes.free(span, args)?;
+                c.asm.push(Inst::Call { hash, args }, span);
+                c.scopes.free(span, args)?;
+            }
         }
         hir::Call::Expr { expr: e } => {
             for e in hir.args {
@@ -1875,6 +2037,8 @@ fn expr_match<'hir>(
     span: &dyn Spanned,
     needs: Needs,
 ) -> compile::Result<Asm<'hir>> {
+    exhaustive::check(c, hir, span)?;
+
     let expected_scopes = c.scopes.child(span)?;
 
     expr(c, hir.expr, Needs::Value)?.apply(c)?;
@@ -1883,6 +2047,7 @@ fn expr_match<'hir>(
 
     let end_label = c.asm.new_label("match_end");
     let mut branches = Vec::new();
+    let mut irrefutable = false;
 
     for branch in hir.branches {
         let span = branch;
@@ -1900,7 +2065,8 @@ fn expr_match<'hir>(
             Ok(())
         };
 
-        pat(c, branch.pat, &match_false, &load)?;
+        let refutable = pat(c, branch.pat, &match_false, &load)?;
+        irrefutable |= !refutable && branch.condition.is_none();
 
         let scope = if let Some(condition) = branch.condition {
             let span = condition;
@@ -1927,11 +2093,13 @@ fn expr_match<'hir>(
 
     // what to do in case nothing matches and the pattern doesn't have any
     // default match branch.
-    if needs.value() {
-        c.asm.push(Inst::unit(), span);
-    }
+    if !irrefutable {
+        if needs.value() {
+            c.asm.push(Inst::unit(), span);
+        }
 
-    c.asm.jump(&end_label, span);
+        c.asm.jump(&end_label, span);
+    }
 
     let mut it = hir.branches.iter().zip(&branches).peekable();
 
@@ -2167,7 +2335,7 @@ fn expr_select<'hir>(
 
         match branch.pat.kind {
             hir::PatKind::Path(&hir::PatPathKind::Ident(name, variable)) => {
-                c.scopes.define(variable, name.into(), branch.pat)?;
+                c.define(variable, name.into(), branch.pat)?;
             }
             hir::PatKind::Ignore => {
                 c.asm.push(Inst::Pop, span);
@@ -0,0 +1,431 @@
+use crate::no_std::collections::HashMap;
+use crate::no_std::prelude::*;
+
+use crate::ast::Spanned;
+use crate::compile::v1::Assembler;
+use crate::compile::{self, CompileErrorKind, ItemBuf};
+use crate::hir;
+use crate::runtime::TypeCheck;
+use crate::Hash;
+
+/// A constructor of a value which a pattern can test for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Ctor<'hir> {
+    /// A variant of an enum declared in a script or in a module.
+    Variant {
+        variant_hash: Hash,
+        enum_hash: Hash,
+        index: usize,
+    },
+    /// A variant of a built-in enum, like `Option`.
+    BuiltIn(TypeCheck),
+    /// A boolean literal.
+    Bool(bool),
+    /// An anonymous tuple with the given number of elements.
+    Tuple(usize),
+    /// A vector with the given number of elements.
+    Vec(usize),
+    /// A struct with the given type hash.
+    Type(Hash),
+    /// Any other literal.
+    Lit(hir::Lit<'hir>),
+    /// A pattern which is never considered to be equal to another pattern,
+    /// like a range or an open vector.
+    Opaque(usize),
+}
+
+/// A simplified pattern used for the analysis.
+#[derive(Debug, Clone)]
+enum Pat<'hir> {
+    /// A pattern which matches anything.
+    Wild,
+    /// An or-pattern.
+    Or(Vec<Pat<'hir>>),
+    /// A constructor and the patterns of its arguments.
+    Ctor(Ctor<'hir>, Vec<Pat<'hir>>),
+}
+
+type Row<'hir> = Vec<Pat<'hir>>;
+
+/// The reason why a match is not exhaustive.
+enum Missing {
+    /// The given variants of an enum are not matched.
+    Variants(ItemBuf, Box<[Box<str>]>),
+    /// Some other value is not matched, like a literal.
+    Other,
+}
+
+/// The set of constructors seen in the first column of a matrix.
+enum Signature {
+    /// Every possible constructor is present.
+    Complete,
+    /// Some constructors are missing.
+    Incomplete(Option<Missing>),
+}
+
+/// Check the branches of a match.
+///
+/// Branches which can never be reached since earlier branches already match
+/// everything they do cause a warning, and a match over an enum which doesn't
+/// cover all of its variants is an error.
+pub(crate) fn check<'hir>(
+    c: &mut Assembler<'_, 'hir>,
+    hir: &hir::ExprMatch<'hir>,
+    span: &dyn Spanned,
+) -> compile::Result<()> {
+    let mut cx = Checker {
+        c,
+        opaque: 0,
+        variants: HashMap::new(),
+    };
+
+    let mut rows = Vec::new();
+
+    for branch in hir.branches {
+        let row = vec![cx.pat(branch.pat)];
+
+        if !useful(&rows, &row) {
+            cx.c.q
+                .diagnostics
+                .unreachable_pattern(cx.c.source_id, branch.pat, cx.c.context());
+        }
+
+        // Guarded branches might not match, so they don't contribute to
+        // covering any values.
+        if branch.condition.is_none() {
+            rows.push(row);
+        }
+    }
+
+    if let Some(Missing::Variants(item, variants)) = cx.missing(rows, 1) {
+        return Err(compile::Error::new(
+            span,
+            CompileErrorKind::PatternMissingVariants { item, variants },
+        ));
+    }
+
+    Ok(())
+}
+
+struct Checker<'a, 'b, 'hir> {
+    c: &'a mut Assembler<'b, 'hir>,
+    /// Counter used to construct unique opaque constructors.
+    opaque: usize,
+    /// Cached variants of enums.
+    variants: HashMap<Hash, Option<Vec<(usize, ItemBuf)>>>,
+}
+
+impl<'hir> Checker<'_, '_, 'hir> {
+    /// Simplify a pattern.
+    fn pat(&mut self, pat: &hir::Pat<'hir>) -> Pat<'hir> {
+        match pat.kind {
+            hir::PatKind::Ignore | hir::PatKind::Rest | hir::PatKind::Binding => Pat::Wild,
+            hir::PatKind::Path(kind) => match *kind {
+                hir::PatPathKind::Kind(kind) => self.items(kind, Vec::new()),
+                hir::PatPathKind::Ident(..) => Pat::Wild,
+            },
+            hir::PatKind::Lit(expr) => match expr.kind {
+                hir::ExprKind::Lit(hir::Lit::Bool(value)) => {
+                    Pat::Ctor(Ctor::Bool(value), Vec::new())
+                }
+                hir::ExprKind::Lit(lit) => Pat::Ctor(Ctor::Lit(lit), Vec::new()),
+                _ => self.opaque(),
+            },
+            hir::PatKind::Vec(items) => {
+                if items.is_open {
+                    return self.opaque();
+                }
+
+                let args = self.args(items);
+                Pat::Ctor(Ctor::Vec(items.count), args)
+            }
+            hir::PatKind::Tuple(items) => {
+                let args = self.args(items);
+                self.items(&items.kind, args)
+            }
+            hir::PatKind::Object(items) => {
+                if let hir::PatItemsKind::Anonymous { .. } = items.kind {
+                    return self.opaque();
+                }
+
+                // Fields are represented by a single argument which only
+                // matches everything if every field does.
+                let mut arg = Pat::Wild;
+
+                for binding in items.bindings {
+                    if let hir::Binding::Binding(_, _, pat) = binding {
+                        if !matches!(self.pat(pat), Pat::Wild) {
+                            arg = self.opaque();
+                        }
+                    }
+                }
+
+                self.items(&items.kind, vec![arg])
+            }
+            hir::PatKind::Or(or) => Pat::Or(or.alternatives.iter().map(|p| self.pat(p)).collect()),
+            hir::PatKind::Range(..) => self.opaque(),
+            hir::PatKind::At(at) => self.pat(at.pat),
+        }
+    }
+
+    fn args(&mut self, items: &hir::PatItems<'hir>) -> Vec<Pat<'hir>> {
+        items
+            .items
+            .iter()
+            .take(items.count)
+            .map(|p| self.pat(p))
+            .collect()
+    }
+
+    fn items(&mut self, kind: &hir::PatItemsKind, args: Vec<Pat<'hir>>) -> Pat<'hir> {
+        let ctor = match *kind {
+            hir::PatItemsKind::Type { hash } => Ctor::Type(hash),
+            hir::PatItemsKind::BuiltInVariant { type_check } => Ctor::BuiltIn(type_check),
+            hir::PatItemsKind::Variant {
+                variant_hash,
+                enum_hash,
+                index,
+            } => Ctor::Variant {
+                variant_hash,
+                enum_hash,
+                index,
+            },
+            hir::PatItemsKind::Anonymous {
+                count,
+                is_open: false,
+            } => Ctor::Tuple(count),
+            hir::PatItemsKind::Anonymous { is_open: true, .. } => return self.opaque(),
+        };
+
+        Pat::Ctor(ctor, args)
+    }
+
+    fn opaque(&mut self) -> Pat<'hir> {
+        self.opaque += 1;
+        Pat::Ctor(Ctor::Opaque(self.opaque), Vec::new())
+    }
+
+    /// Find a reason why the given rows don't match every value, treating
+    /// every column as having the type of the constructors in it.
+    fn missing(&mut self, rows: Vec<Row<'hir>>, width: usize) -> Option<Missing> {
+        if width == 0 {
+            return rows.is_empty().then_some(Missing::Other);
+        }
+
+        let rows = expand(rows);
+        let mut ctors = Vec::new();
+
+        for row in &rows {
+            if let Pat::Ctor(ctor, _) = &row[0] {
+                if !ctors.contains(ctor) {
+                    ctors.push(*ctor);
+                }
+            }
+        }
+
+        match self.signature(&ctors) {
+            Signature::Complete => {
+                for ctor in &ctors {
+                    let arity = arity(&rows, ctor);
+                    let rows = specialize(&rows, ctor, arity);
+
+                    if let Some(missing) = self.missing(rows, width - 1 + arity) {
+                        return Some(missing);
+                    }
+                }
+
+                None
+            }
+            Signature::Incomplete(variants) => {
+                let missing = self.missing(default(&rows), width - 1)?;
+                Some(variants.unwrap_or(missing))
+            }
+        }
+    }
+
+    fn signature(&mut self, ctors: &[Ctor<'hir>]) -> Signature {
+        match ctors {
+            [Ctor::Variant {
+                variant_hash,
+                enum_hash,
+                ..
+            }, ..] => {
+                let mut present = Vec::new();
+
+                for ctor in ctors {
+                    match ctor {
+                        Ctor::Variant {
+                            enum_hash: hash,
+                            index,
+                            ..
+                        } if hash == enum_hash => present.push(*index),
+                        _ => return Signature::Incomplete(None),
+                    }
+                }
+
+                let Some(variants) = self.variants(*variant_hash, *enum_hash) else {
+                    return Signature::Incomplete(None);
+                };
+
+                let mut item = None;
+                let mut missing = Vec::new();
+
+                for (index, variant) in variants {
+                    if !present.contains(index) {
+                        item = variant.parent().map(|item| item.to_owned());
+                        missing.extend(variant.last().map(|c| c.to_string().into()));
+                    }
+                }
+
+                match item {
+                    Some(item) => {
+                        Signature::Incomplete(Some(Missing::Variants(item, missing.into())))
+                    }
+                    None => Signature::Complete,
+                }
+            }
+            [Ctor::BuiltIn(first), ..] => {
+                let mut present = [false; 2];
+
+                for ctor in ctors {
+                    let index = match (first, ctor) {
+                        (TypeCheck::Option(..), Ctor::BuiltIn(TypeCheck::Option(index)))
+                        | (TypeCheck::Result(..), Ctor::BuiltIn(TypeCheck::Result(index)))
+                        | (
+                            TypeCheck::GeneratorState(..),
+                            Ctor::BuiltIn(TypeCheck::GeneratorState(index)),
+                        ) => *index,
+                        _ => return Signature::Incomplete(None),
+                    };
+
+                    if let Some(present) = present.get_mut(index) {
+                        *present = true;
+                    }
+                }
+
+                complete(present.iter().all(|p| *p))
+            }
+            [Ctor::Bool(..), ..] => {
+                complete(ctors.contains(&Ctor::Bool(true)) && ctors.contains(&Ctor::Bool(false)))
+            }
+            [first @ (Ctor::Tuple(..) | Ctor::Type(..)), ..] => {
+                complete(ctors.iter().all(|c| c == first))
+            }
+            _ => Signature::Incomplete(None),
+        }
+    }
+
+    fn variants(&mut self, variant_hash: Hash, enum_hash: Hash) -> Option<&[(usize, ItemBuf)]> {
+        let q = &mut self.c.q;
+
+        self.variants
+            .entry(enum_hash)
+            .or_insert_with(|| q.enum_variants(variant_hash, enum_hash))
+            .as_deref()
+    }
+}
+
+fn complete(complete: bool) -> Signature {
+    if complete {
+        Signature::Complete
+    } else {
+        Signature::Incomplete(None)
+    }
+}
+
+/// Test if the given row matches any value which isn't matched by the rows
+/// before it.
+///
+/// Since values are dynamically typed, no set of constructors is considered to
+/// be complete here.
+fn useful(rows: &[Row<'_>], row: &[Pat<'_>]) -> bool {
+    let Some((head, rest)) = row.split_first() else {
+        return rows.is_empty();
+    };
+
+    let rows = expand(rows.to_vec());
+
+    match head {
+        Pat::Wild => useful(&default(&rows), rest),
+        Pat::Or(alternatives) => alternatives.iter().any(|alternative| {
+            let mut row = row.to_vec();
+            row[0] = alternative.clone();
+            useful(&rows, &row)
+        }),
+        Pat::Ctor(ctor, args) => {
+            let arity = arity(&rows, ctor).max(args.len());
+            let mut row = pad(args, arity);
+            row.extend(rest.iter().cloned());
+            useful(&specialize(&rows, ctor, arity), &row)
+        }
+    }
+}
+
+/// Expand or-patterns in the first column into one row per alternative.
+fn expand(rows: Vec<Row<'_>>) -> Vec<Row<'_>> {
+    let mut out = Vec::with_capacity(rows.len());
+    let mut queue = rows;
+    queue.reverse();
+
+    while let Some(mut row) = queue.pop() {
+        match row.first_mut() {
+            Some(Pat::Or(alternatives)) => {
+                let alternatives = core::mem::take(alternatives);
+
+                for alternative in alternatives.into_iter().rev() {
+                    let mut row = row.clone();
+                    row[0] = alternative;
+                    queue.push(row);
+                }
+            }
+            _ => out.push(row),
+        }
+    }
+
+    out
+}
+
+/// The largest number of arguments the given constructor is used with.
+fn arity(rows: &[Row<'_>], ctor: &Ctor<'_>) -> usize {
+    rows.iter()
+        .filter_map(|row| match &row[0] {
+            Pat::Ctor(c, args) if c == ctor => Some(args.len()),
+            _ => None,
+        })
+        .max()
+        .unwrap_or_default()
+}
+
+/// Pad arguments of an open pattern with wildcards.
+fn pad<'hir>(args: &[Pat<'hir>], arity: usize) -> Row<'hir> {
+    let mut row = args.to_vec();
+    row.resize(arity.max(args.len()), Pat::Wild);
+    row
+}
+
+/// The rows which match the given constructor, with the first column replaced
+/// by the arguments of the constructor.
+fn specialize<'hir>(rows: &[Row<'hir>], ctor: &Ctor<'hir>, arity: usize) -> Vec<Row<'hir>> {
+    let mut out = Vec::new();
+
+    for row in rows {
+        let mut new = match &row[0] {
+            Pat::Wild => vec![Pat::Wild; arity],
+            Pat::Ctor(c, args) if c == ctor => pad(args, arity),
+            _ => continue,
+        };
+
+        new.extend(row[1..].iter().cloned());
+        out.push(new);
+    }
+
+    out
+}
+
+/// The rows which match anything in the first column, with it removed.
+fn default<'hir>(rows: &[Row<'hir>]) -> Vec<Row<'hir>> {
+    rows.iter()
+        .filter(|row| matches!(row[0], Pat::Wild))
+        .map(|row| row[1..].to_vec())
+        .collect()
+}
@@ -221,6 +221,24 @@ impl Diagnostics {
         );
     }
 
+    /// Indicate that a match branch can never be reached.
+    ///
+    /// Like the second branch in `match value { _ => 1, 2 => 3 }`.
+    pub(crate) fn unreachable_pattern(
+        &mut self,
+        source_id: SourceId,
+        span: &dyn Spanned,
+        context: Option<Span>,
+    ) {
+        self.warning(
+            source_id,
+            WarningDiagnosticKind::UnreachablePattern {
+                span: span.span(),
+                context,
+            },
+        );
+    }
+
     /// Indicate that we encountered a template string without any expansion
     /// groups.
     ///
@@ -22,11 +22,6 @@ use crate::runtime::{Unit, VmErrorKind, VmError};
 use crate::{Source, Diagnostics, SourceId, Sources};
 use crate::ast::{Span, Spanned};
 
-struct StackFrame {
-    source_id: SourceId,
-    span: Span,
-}
-
 /// Errors that can be raised when formatting diagnostics.
 #[derive(Debug, Error)]
 pub enum EmitError {
@@ -88,49 +83,26 @@ impl VmError {
     where
         O: WriteColor,
     {
-        let mut backtrace = vec![];
         let config = codespan_reporting::term::Config::default();
 
-        for l in &self.inner.stacktrace {
-            let debug_info = match l.unit.debug_info() {
-                Some(debug_info) => debug_info,
-                None => continue,
-            };
-
-            for ip in [l.ip].into_iter().chain(l.frames.iter().rev().map(|v| v.ip)) {
-                let debug_inst = match debug_info.instruction_at(ip) {
-                    Some(debug_inst) => debug_inst,
-                    None => continue,
-                };
-
-                let source_id = debug_inst.source_id;
-                let span = debug_inst.span;
-
-                backtrace.push(StackFrame { source_id, span });
-            }
-        }
-
         let mut diagnostic = d::Diagnostic::error();
 
+        let (backtrace, locations) = self.backtrace_with_locations();
+
         for at in [&self.inner.error].into_iter().chain(&self.inner.chain) {
-            let get = || {
-                let l = self.inner.stacktrace.get(at.instruction())?;
-                let debug_info = l.unit.debug_info()?;
-                let debug_inst = debug_info.instruction_at(l.ip)?;
-                Some(debug_inst)
-            };
+            let location = locations
+                .get(at.instruction())
+                .and_then(|&n| backtrace.get(n))
+                .and_then(|frame| Some((frame.source_id?, frame.span?)));
 
-            let debug_inst = match get() {
-                Some(debug_inst) => debug_inst,
+            let (source_id, span) = match location {
+                Some(location) => location,
                 None => {
                     println!("error: {} (no debug information)", at);
                     continue;
                 }
             };
 
-            let source_id = debug_inst.source_id;
-            let span = debug_inst.span;
-
             let mut labels = Vec::new();
 
             let (reason, notes) = match at.kind() {
@@ -185,23 +157,22 @@ impl VmError {
         if !backtrace.is_empty() {
             writeln!(out, "Backtrace:")?;
 
-            for frame in &backtrace {
-                let source = match sources.get(frame.source_id) {
-                    Some(source) => source,
-                    None => continue,
-                };
+            for (n, frame) in backtrace.iter().enumerate() {
+                let mut diagnostic =
+                    d::Diagnostic::note().with_message(format!("#{} {}", n, frame));
 
-                let (line, line_count, text) = match source.line(frame.span) {
-                    Some((line, line_count, text)) => (
-                        line.saturating_add(1),
-                        line_count.saturating_add(1),
-                        text.trim_end(),
-                    ),
-                    None => continue,
-                };
+                if let (Some(source_id), Some(span)) = (frame.source_id, frame.span) {
+                    let message = if n == 0 {
+                        "in this expression"
+                    } else {
+                        "in this call"
+                    };
+
+                    let label = d::Label::secondary(source_id, span.range()).with_message(message);
+                    diagnostic = diagnostic.with_labels(vec![label]);
+                }
 
-                writeln!(out, "At {}:{}:{}:", source.name(), line, line_count)?;
-                writeln!(out, "{text}")?;
+                term::emit(out, &config, sources, &diagnostic)?;
             }
         }
 
@@ -544,6 +515,22 @@ where
 
                 notes.push("You can also make the pattern non-exhaustive by adding `..`".to_string());
             }
+            CompileErrorKind::PatternMissingVariants { variants, .. } => {
+                let pl = if variants.len() == 1 {
+                    "variant"
+                } else {
+                    "variants"
+                };
+
+                let variants = variants.join(", ");
+
+                labels.push(
+                    d::Label::secondary(this.source_id(), span.range())
+                        .with_message(format!("Missing {}: {}", pl, variants)),
+                );
+
+                notes.push("You can also add a wildcard branch like `_ => {}`".to_string());
+            }
             _ => (),
         }
 
@@ -43,7 +43,8 @@ impl WarningDiagnostic {
             WarningDiagnosticKind::LetPatternMightPanic { context, .. }
             | WarningDiagnosticKind::RemoveTupleCallParams { context, .. }
             | WarningDiagnosticKind::NotUsed { context, .. }
-            | WarningDiagnosticKind::TemplateWithoutExpansions { context, .. } => *context,
+            | WarningDiagnosticKind::TemplateWithoutExpansions { context, .. }
+            | WarningDiagnosticKind::UnreachablePattern { context, .. } => *context,
             WarningDiagnosticKind::UnnecessarySemiColon { .. } => None,
         }
     }
@@ -58,6 +59,7 @@ impl Spanned for WarningDiagnostic {
             WarningDiagnosticKind::TemplateWithoutExpansions { span, .. } => *span,
             WarningDiagnosticKind::RemoveTupleCallParams { span, .. } => *span,
             WarningDiagnosticKind::UnnecessarySemiColon { span, .. } => *span,
+            WarningDiagnosticKind::UnreachablePattern { span, .. } => *span,
         }
     }
 }
@@ -114,6 +116,15 @@ pub enum WarningDiagnosticKind {
         /// The context in which it is used.
         context: Option<Span>,
     },
+    /// A match branch which can never be reached, since the branches before
+    /// it already match everything it does.
+    #[error("Unreachable pattern")]
+    UnreachablePattern {
+        /// The span of the pattern.
+        span: Span,
+        /// The context in which it is used.
+        context: Option<Span>,
+    },
     /// An unecessary semi-colon is used.
     #[error("Unnecessary semicolon")]
     UnnecessarySemiColon {
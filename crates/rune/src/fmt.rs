@@ -11,7 +11,7 @@ mod whitespace;
 
 use crate::no_std::prelude::*;
 
-use crate::ast;
+use crate::ast::{self, Span, Spanned};
 use crate::parse::{Parse, Parser};
 use crate::{Source, SourceId};
 
@@ -33,3 +33,55 @@ pub fn layout_source(source: &Source) -> Result<Vec<u8>, FormattingError> {
     printer.visit_file(&ast)?;
     printer.commit()
 }
+
+/// Format the items in the given source which overlap with the given span.
+///
+/// Returns the span covered by the formatted items and their formatted
+/// contents, or `None` if no items overlap with the span.
+pub fn layout_source_span(
+    source: &Source,
+    span: Span,
+) -> Result<Option<(Span, Vec<u8>)>, FormattingError> {
+    let mut parser = Parser::new(source.as_str(), SourceId::new(0), true);
+    let ast = ast::File::parse(&mut parser)?;
+
+    let mut items = Vec::new();
+    let mut covered = None::<Span>;
+
+    for (item, semi) in &ast.items {
+        let item_span = match semi {
+            Some(semi) => item.span().join(semi.span()),
+            None => item.span(),
+        };
+
+        if item_span.end < span.start || item_span.start > span.end {
+            continue;
+        }
+
+        covered = Some(match covered {
+            Some(covered) => covered.join(item_span),
+            None => item_span,
+        });
+
+        items.push((item, *semi));
+    }
+
+    let Some(covered) = covered else {
+        return Ok(None);
+    };
+
+    let mut printer: Printer = Printer::with_span(source, covered)?;
+
+    for (item, semi) in items {
+        printer.visit_item(item, semi)?;
+    }
+
+    let mut output = printer.commit()?;
+
+    // The newline following the last item is not part of the covered span.
+    if output.ends_with(b"\n") {
+        output.pop();
+    }
+
+    Ok(Some((covered, output)))
+}
@@ -135,6 +135,15 @@ impl<'a> SpanInjectionWriter<'a> {
         })
     }
 
+    /// Only inject the comments and empty lines which are within the given
+    /// span.
+    pub(super) fn retain_within(&mut self, span: Span) {
+        self.queued_spans.retain(|queued| {
+            let queued = queued.span();
+            queued.start >= span.start && queued.end <= span.end
+        });
+    }
+
     pub(super) fn into_inner(mut self) -> Result<Vec<Vec<u8>>, FormattingError> {
         while !self.queued_spans.is_empty() {
             let span = self.queued_spans.remove(0);
@@ -12,10 +12,10 @@ use crate::ast::{
     ExprFor, ExprGroup, ExprIf, ExprIndex, ExprLet, ExprLit, ExprLoop, ExprMatch, ExprMatchBranch,
     ExprObject, ExprRange, ExprReturn, ExprSelect, ExprSelectBranch, ExprSelectPatBranch, ExprTry,
     ExprTuple, ExprUnary, ExprVec, ExprWhile, ExprYield, Field, FieldAssign, Fields, FnArg, Item,
-    ItemConst, ItemEnum, ItemFn, ItemImpl, ItemMod, ItemModBody, ItemStruct, ItemVariant,
-    LitSource, Local, MacroCall, ObjectKey, Pat, PatBinding, PatIgnore, PatLit, PatObject, PatPath,
-    PatRest, PatTuple, PatVec, Path, PathSegment, PathSegmentExpr, SelfType, SelfValue, SemiColon,
-    Span, Spanned, Stmt, StmtSemi,
+    ItemConst, ItemEnum, ItemFn, ItemImpl, ItemMod, ItemModBody, ItemStruct, ItemTrait,
+    ItemVariant, LitSource, Local, MacroCall, ObjectKey, Parenthesized, Pat, PatBinding, PatIgnore,
+    PatLit, PatObject, PatPath, PatRest, PatTuple, PatVec, Path, PathSegment, PathSegmentExpr,
+    SelfType, SelfValue, SemiColon, Span, Spanned, Stmt, StmtSemi, TraitFn,
 };
 use crate::Source;
 
@@ -36,6 +36,14 @@ impl<'a> Printer<'a> {
         Ok(Self { writer, source })
     }
 
+    /// Construct a printer which only injects the comments and empty lines
+    /// found within the given span of the source.
+    pub(super) fn with_span(source: &'a Source, span: Span) -> Result<Self> {
+        let mut writer = SpanInjectionWriter::new(IndentedWriter::new(), source)?;
+        writer.retain_within(span);
+        Ok(Self { writer, source })
+    }
+
     pub(super) fn commit(self) -> Result<Vec<u8>> {
         let inner = self.writer.into_inner()?;
 
@@ -136,6 +144,7 @@ impl<'a> Printer<'a> {
             ast::Item::Enum(item) => self.visit_enum(item, semi)?,
             ast::Item::Struct(item) => self.visit_struct(item, semi)?,
             ast::Item::Impl(item) => self.visit_impl(item, semi)?,
+            ast::Item::Trait(item) => self.visit_trait(item, semi)?,
             ast::Item::Mod(item) => self.visit_mod(item, semi)?,
             ast::Item::Const(item) => self.visit_const(item, semi)?,
             ast::Item::MacroCall(item) => self.visit_macro_call(item, semi)?,
@@ -227,6 +236,7 @@ impl<'a> Printer<'a> {
         let ItemImpl {
             attributes,
             impl_,
+            trait_,
             path,
             open,
             functions,
@@ -239,6 +249,13 @@ impl<'a> Printer<'a> {
         }
 
         self.writer.write_spanned_raw(impl_.span, false, true)?;
+
+        if let Some((trait_path, for_)) = trait_ {
+            self.visit_path(trait_path)?;
+            self.writer.write_unspanned(" ")?;
+            self.writer.write_spanned_raw(for_.span, false, true)?;
+        }
+
         self.visit_path(path)?;
 
         self.writer.write_unspanned(" ")?;
@@ -440,6 +457,7 @@ impl<'a> Printer<'a> {
             attributes,
             visibility,
             name,
+            ty,
         } = field;
 
         for attribute in attributes {
@@ -450,6 +468,11 @@ impl<'a> Printer<'a> {
         self.emit_visibility(visibility)?;
         self.writer.write_spanned_raw(name.span, false, false)?;
 
+        if let Some((colon, ty)) = ty {
+            self.writer.write_spanned_raw(colon.span, false, true)?;
+            self.visit_type(ty)?;
+        }
+
         Ok(())
     }
 
@@ -463,6 +486,7 @@ impl<'a> Printer<'a> {
             fn_token,
             name,
             args,
+            output,
             body,
         } = item;
 
@@ -484,7 +508,20 @@ impl<'a> Printer<'a> {
 
         self.writer.write_spanned_raw(fn_token.span, false, true)?;
         self.writer.write_spanned_raw(name.span, false, false)?;
+        self.visit_fn_args(args)?;
+        self.writer
+            .write_spanned_raw(args.close.span, false, true)?;
+        self.visit_fn_output(output)?;
+        self.visit_block(body)?;
+
+        if let Some(semi) = semi {
+            self.writer.write_spanned_raw(semi.span, false, false)?;
+        }
+
+        Ok(())
+    }
 
+    fn visit_fn_args(&mut self, args: &Parenthesized<FnArg, Comma>) -> Result<()> {
         self.writer
             .write_spanned_raw(args.open.span, false, false)?;
 
@@ -499,6 +536,8 @@ impl<'a> Printer<'a> {
             match arg {
                 FnArg::SelfValue(selfvalue) => self.visit_self_value(selfvalue)?,
                 FnArg::Pat(pattern) => self.visit_pattern(pattern)?,
+                FnArg::Typed(typed) => self.visit_pat_type(typed)?,
+                FnArg::Default(default) => self.visit_fn_arg_default(default)?,
             }
             if let Some(comma) = comma {
                 self.writer
@@ -509,9 +548,148 @@ impl<'a> Printer<'a> {
             self.writer.dedent();
             self.writer.newline()?;
         }
+
+        Ok(())
+    }
+
+    fn visit_pat_type(&mut self, typed: &ast::PatType) -> Result<()> {
+        let ast::PatType { pat, colon, ty } = typed;
+        self.visit_pattern(pat)?;
+        self.writer.write_spanned_raw(colon.span, false, true)?;
+        self.visit_type(ty)?;
+        Ok(())
+    }
+
+    fn visit_fn_arg_default(&mut self, default: &ast::FnArgDefault) -> Result<()> {
+        let ast::FnArgDefault { pat, ty, eq, value } = default;
+        self.visit_pattern(pat)?;
+
+        if let Some((colon, ty)) = ty {
+            self.writer.write_spanned_raw(colon.span, false, true)?;
+            self.visit_type(ty)?;
+        }
+
+        self.writer.write_unspanned(" ")?;
+        self.writer.write_spanned_raw(eq.span, false, true)?;
+        self.visit_expr(value)?;
+        Ok(())
+    }
+
+    fn visit_fn_output(&mut self, output: &Option<(ast::Arrow, ast::Type)>) -> Result<()> {
+        if let Some((arrow, ty)) = output {
+            self.writer.write_spanned_raw(arrow.span, false, true)?;
+            self.visit_type(ty)?;
+            self.writer.write_unspanned(" ")?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_type(&mut self, ty: &ast::Type) -> Result<()> {
+        match ty {
+            ast::Type::Path(path) => self.visit_path(path)?,
+            ast::Type::Bang(bang) => self.writer.write_spanned_raw(bang.span, false, false)?,
+            ast::Type::Tuple(tuple) => {
+                self.writer
+                    .write_spanned_raw(tuple.open.span, false, false)?;
+
+                for (ty, comma) in tuple {
+                    self.visit_type(ty)?;
+
+                    if let Some(comma) = comma {
+                        self.writer.write_spanned_raw(comma.span, false, true)?;
+                    }
+                }
+
+                self.writer
+                    .write_spanned_raw(tuple.close.span, false, false)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn visit_trait(&mut self, item: &ItemTrait, semi: Option<SemiColon>) -> Result<()> {
+        let ItemTrait {
+            attributes,
+            visibility,
+            trait_token,
+            name,
+            open,
+            functions,
+            close,
+        } = item;
+
+        for attribute in attributes {
+            self.visit_attribute(attribute)?;
+            self.writer.newline()?;
+        }
+
+        self.emit_visibility(visibility)?;
         self.writer
-            .write_spanned_raw(args.close.span, false, true)?;
-        self.visit_block(body)?;
+            .write_spanned_raw(trait_token.span, false, true)?;
+        self.writer.write_spanned_raw(name.span, false, false)?;
+
+        self.writer.write_unspanned(" ")?;
+        self.writer.write_spanned_raw(open.span, true, false)?;
+
+        self.writer.indent();
+
+        for function in functions {
+            self.visit_trait_fn(function)?;
+            self.writer.newline()?;
+        }
+
+        self.writer.dedent();
+        self.writer.write_spanned_raw(close.span, false, false)?;
+
+        if let Some(semi) = semi {
+            self.writer.write_spanned_raw(semi.span, false, false)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_trait_fn(&mut self, item: &TraitFn) -> Result<()> {
+        let TraitFn {
+            attributes,
+            async_token,
+            fn_token,
+            name,
+            args,
+            output,
+            body,
+            semi,
+        } = item;
+
+        for attribute in attributes {
+            self.visit_attribute(attribute)?;
+            self.writer.newline()?;
+        }
+
+        if let Some(async_token) = async_token {
+            self.writer
+                .write_spanned_raw(async_token.span, false, true)?;
+        }
+
+        self.writer.write_spanned_raw(fn_token.span, false, true)?;
+        self.writer.write_spanned_raw(name.span, false, false)?;
+        self.visit_fn_args(args)?;
+
+        if let Some(body) = body {
+            self.writer
+                .write_spanned_raw(args.close.span, false, true)?;
+            self.visit_fn_output(output)?;
+            self.visit_block(body)?;
+        } else if let Some((arrow, ty)) = output {
+            self.writer
+                .write_spanned_raw(args.close.span, false, true)?;
+            self.writer.write_spanned_raw(arrow.span, false, true)?;
+            self.visit_type(ty)?;
+        } else {
+            self.writer
+                .write_spanned_raw(args.close.span, false, false)?;
+        }
 
         if let Some(semi) = semi {
             self.writer.write_spanned_raw(semi.span, false, false)?;
@@ -657,6 +835,7 @@ impl<'a> Printer<'a> {
             attributes,
             path,
             bang,
+            name,
             open,
             input: _,
             close,
@@ -678,7 +857,13 @@ impl<'a> Printer<'a> {
 
         self.visit_path(path)?;
 
-        self.writer.write_spanned_raw(bang.span, false, false)?;
+        self.writer
+            .write_spanned_raw(bang.span, false, name.is_some())?;
+
+        if let Some(name) = name {
+            self.writer.write_spanned_raw(name.span, false, true)?;
+        }
+
         self.writer.write_spanned_raw(open.span, false, false)?;
         self.writer
             .write_spanned_raw(Span::new(open.span.end, close.span.start), false, false)?;
@@ -1110,7 +1295,12 @@ impl<'a> Printer<'a> {
 
         let count = args.parenthesized.len();
         for (idx, (arg, comma)) in args.parenthesized.iter().enumerate() {
-            self.visit_expr(arg)?;
+            if let Some((name, colon)) = &arg.name {
+                self.writer.write_spanned_raw(name.span, false, false)?;
+                self.writer.write_spanned_raw(colon.span, false, true)?;
+            }
+
+            self.visit_expr(&arg.value)?;
             if idx != count - 1 {
                 if let Some(comma) = comma {
                     self.writer.write_spanned_raw(comma.span, false, true)?;
@@ -1258,6 +1448,8 @@ impl<'a> Printer<'a> {
                     match arg {
                         ast::FnArg::SelfValue(self_) => self.visit_self_value(self_)?,
                         ast::FnArg::Pat(pat) => self.visit_pattern(pat)?,
+                        ast::FnArg::Typed(typed) => self.visit_pat_type(typed)?,
+                        ast::FnArg::Default(default) => self.visit_fn_arg_default(default)?,
                     }
                     if let Some(comma) = comma {
                         self.writer.write_spanned_raw(comma.span, false, true)?;
@@ -1442,11 +1634,75 @@ impl<'a> Printer<'a> {
             Pat::Object(patobject) => self.visit_pat_object(patobject)?,
             Pat::Binding(binding) => self.visit_pat_binding(binding)?,
             Pat::Rest(rest) => self.visit_pat_rest(rest)?,
+            Pat::Or(or) => self.visit_pat_or(or)?,
+            Pat::Range(range) => self.visit_pat_range(range)?,
+            Pat::At(at) => self.visit_pat_at(at)?,
         }
 
         Ok(())
     }
 
+    fn visit_pat_or(&mut self, or: &ast::PatOr) -> Result<()> {
+        let ast::PatOr { first, rest } = or;
+
+        self.visit_pattern(first)?;
+
+        for (pipe, pat) in rest {
+            self.writer.write_unspanned(" ")?;
+            self.writer.write_spanned_raw(pipe.span, false, true)?;
+            self.visit_pattern(pat)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_pat_range(&mut self, range: &ast::PatRange) -> Result<()> {
+        let ast::PatRange {
+            attributes,
+            from,
+            limits,
+            to,
+        } = range;
+
+        for attribute in attributes {
+            self.visit_attribute(attribute)?;
+        }
+
+        if let Some(from) = from {
+            self.visit_expr(from)?;
+        }
+
+        match limits {
+            ast::ExprRangeLimits::HalfOpen(_) => write!(self.writer, "..")?,
+            ast::ExprRangeLimits::Closed(_) => write!(self.writer, "..=")?,
+        }
+
+        if let Some(to) = to {
+            self.visit_expr(to)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_pat_at(&mut self, at: &ast::PatAt) -> Result<()> {
+        let ast::PatAt {
+            attributes,
+            name,
+            at,
+            pat,
+        } = at;
+
+        for attribute in attributes {
+            self.visit_attribute(attribute)?;
+        }
+
+        self.writer.write_spanned_raw(name.span, false, true)?;
+        self.writer.write_spanned_raw(at.span, false, true)?;
+        self.visit_pattern(pat)?;
+
+        Ok(())
+    }
+
     fn visit_pat_rest(&mut self, rest: &PatRest) -> Result<()> {
         let PatRest {
             attributes,
@@ -1816,6 +2072,7 @@ impl<'a> Printer<'a> {
             attributes,
             let_token,
             pat,
+            ty,
             eq,
             expr,
             semi,
@@ -1827,6 +2084,12 @@ impl<'a> Printer<'a> {
 
         self.writer.write_spanned_raw(let_token.span, false, true)?;
         self.visit_pattern(pat)?;
+
+        if let Some((colon, ty)) = ty {
+            self.writer.write_spanned_raw(colon.span, false, true)?;
+            self.visit_type(ty)?;
+        }
+
         self.writer.write_unspanned(" ")?;
         self.writer.write_spanned_raw(eq.span, false, true)?;
         self.visit_expr(expr)?;
@@ -1,6 +1,9 @@
 use crate::no_std::prelude::*;
 
-use super::layout_string;
+use crate::ast::Span;
+use crate::Source;
+
+use super::{layout_source_span, layout_string};
 
 #[test]
 fn test_layout_string() {
@@ -152,3 +155,37 @@ fn test_macrocall_whitespace3() {
     let output = layout_string(String::from_utf8(output).unwrap()).unwrap();
     assert_eq!(std::str::from_utf8(&output).unwrap(), expected);
 }
+
+#[test]
+fn test_layout_source_span() {
+    let input = r#"fn a() {   1 }
+
+// The second function.
+fn b() {   2 }
+
+fn c() {   3 }
+"#;
+
+    let source = Source::memory(input);
+    let start = input.find("fn b").unwrap();
+    let span = Span::new(start, start + 2);
+
+    let (span, output) = layout_source_span(&source, span).unwrap().unwrap();
+
+    assert_eq!(&input[span.range()], "fn b() {   2 }");
+    assert_eq!(std::str::from_utf8(&output).unwrap(), "fn b() {\n    2\n}");
+
+    let span = Span::new(0, input.find("fn c").unwrap() + 1);
+    let (span, output) = layout_source_span(&source, span).unwrap().unwrap();
+
+    assert_eq!(span.start.into_usize(), 0);
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        "fn a() {\n    1\n}\n\n// The second function.\nfn b() {\n    2\n}\n\nfn c() {\n    3\n}"
+    );
+
+    let end = input.len();
+    assert!(layout_source_span(&source, Span::new(end, end))
+        .unwrap()
+        .is_none());
+}
@@ -43,6 +43,42 @@ pub(crate) enum PatKind<'hir> {
     Object(&'hir PatItems<'hir>),
     /// A binding `a: pattern` or `"foo": pattern`.
     Binding,
+    /// An or-pattern `a | b`.
+    Or(&'hir PatOr<'hir>),
+    /// A range pattern `1..=5`.
+    Range(&'hir PatRange),
+    /// A binding with a subpattern `n @ pattern`.
+    At(&'hir PatAt<'hir>),
+}
+
+/// An or-pattern.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub(crate) struct PatOr<'hir> {
+    /// The alternatives of the pattern.
+    pub(crate) alternatives: &'hir [Pat<'hir>],
+    /// The variables bound by every alternative.
+    pub(crate) bindings: &'hir [(&'hir str, Variable)],
+}
+
+/// A range pattern, where both bounds are inclusive.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum PatRange {
+    Integer { start: i64, end: i64 },
+    Char { start: char, end: char },
+    Byte { start: u8, end: u8 },
+}
+
+/// A binding with a subpattern.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub(crate) struct PatAt<'hir> {
+    /// The name of the binding.
+    pub(crate) name: &'hir str,
+    /// The variable being bound.
+    pub(crate) variable: Variable,
+    /// The subpattern.
+    pub(crate) pat: &'hir Pat<'hir>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -117,7 +153,7 @@ pub(crate) struct Expr<'hir> {
 }
 
 /// The kind of a number.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[non_exhaustive]
 pub(crate) enum Lit<'hir> {
     Bool(bool),
@@ -352,8 +388,12 @@ pub(crate) enum Call<'hir> {
 pub(crate) struct ExprCall<'hir> {
     /// The call being performed.
     pub(crate) call: Call<'hir>,
-    /// The arguments of the function call.
+    /// The arguments of the function call, in the order they are evaluated.
     pub(crate) args: &'hir [Expr<'hir>],
+    /// The index of the argument passed to each parameter, if the arguments
+    /// are evaluated in a different order than they are passed in. Like when
+    /// named arguments are passed out of order.
+    pub(crate) order: Option<&'hir [usize]>,
 }
 
 /// A field access `<expr>.<field>`.
@@ -1,4 +1,5 @@
 use core::cell::Cell;
+use core::mem::replace;
 use core::ops::Neg;
 
 use crate::no_std::collections::{HashMap, HashSet};
@@ -17,7 +18,10 @@ use crate::hir;
 use crate::indexing;
 use crate::parse::Resolve;
 use crate::query::{self, Build, BuildEntry, Named, Query, Used};
-use crate::runtime::Type;
+use crate::runtime::{
+    Type, BOOL_TYPE, BYTES_TYPE, BYTE_TYPE, CHAR_TYPE, FLOAT_TYPE, INTEGER_TYPE, OBJECT_TYPE,
+    RANGE_TYPE, STRING_TYPE, TUPLE_TYPE, UNIT_TYPE, VEC_TYPE,
+};
 use crate::SourceId;
 
 use rune_macros::instrument;
@@ -145,6 +149,12 @@ pub(crate) struct Ctx<'hir, 'a> {
     needs: Cell<Needs>,
     scopes: hir::Scopes<'hir>,
     const_eval: bool,
+    /// Statically known types of variables, from their type annotations.
+    types: HashMap<hir::Variable, Hash>,
+    /// Statically known return types of items being called.
+    returns: HashMap<Hash, Hash>,
+    /// The annotated return type of the function being lowered.
+    return_type: Option<Hash>,
 }
 
 impl<'hir, 'a> Ctx<'hir, 'a> {
@@ -194,6 +204,9 @@ impl<'hir, 'a> Ctx<'hir, 'a> {
             needs: Cell::new(Needs::default()),
             scopes: hir::Scopes::default(),
             const_eval,
+            types: HashMap::new(),
+            returns: HashMap::new(),
+            return_type: None,
         }
     }
 
@@ -229,11 +242,26 @@ pub(crate) fn item_fn<'hir>(
 ) -> compile::Result<hir::ItemFn<'hir>> {
     alloc_with!(ctx, ast);
 
+    let return_type = match &ast.output {
+        Some((_, ast)) => ty(ctx, ast)?,
+        None => None,
+    };
+
+    let args = iter!(&ast.args, |(ast, _)| fn_arg(ctx, ast)?);
+
+    let old = replace(&mut ctx.return_type, return_type);
+    let body = alloc!(block(ctx, &ast.body)?);
+    ctx.return_type = old;
+
+    if let (Some(expected), Some(hir::Stmt::Expr(expr))) = (return_type, body.statements.last()) {
+        check_type(ctx, expected, expr)?;
+    }
+
     Ok(hir::ItemFn {
         id: ast.id,
         span: ast.span(),
-        args: iter!(&ast.args, |(ast, _)| fn_arg(ctx, ast)?),
-        body: alloc!(block(ctx, &ast.body)?),
+        args,
+        body,
     })
 }
 
@@ -350,7 +378,10 @@ fn expr_call_closure<'hir>(
                 fn_arg(ctx, arg)?;
             }
 
+            let return_type = ctx.return_type.take();
             expr(ctx, &ast.body)?;
+            ctx.return_type = return_type;
+
             let layer = ctx.scopes.pop().with_span(&ast.body)?;
 
             ctx.q.inner.queue.push_back(BuildEntry {
@@ -489,6 +520,7 @@ pub(crate) fn expr_object<'hir>(
             let named = ctx.q.convert_path(path)?;
             let parameters = generics_parameters(&named)?;
             let meta = ctx.lookup_meta(path.span(), named.item, parameters)?;
+            record_return_type(ctx, &meta);
             let item = ctx.q.pool.item(meta.item_meta.item);
 
             match &meta.kind {
@@ -504,6 +536,8 @@ pub(crate) fn expr_object<'hir>(
                     ..
                 } => {
                     check_object_fields(&st.fields, item)?;
+                    check_field_types(ctx, st, assignments)?;
+                    visit_field_uses(ctx, meta.hash, assignments);
                     hir::ExprObjectKind::Struct { hash: meta.hash }
                 }
                 meta::Kind::Variant {
@@ -511,6 +545,8 @@ pub(crate) fn expr_object<'hir>(
                     ..
                 } => {
                     check_object_fields(&st.fields, item)?;
+                    check_field_types(ctx, st, assignments)?;
+                    visit_field_uses(ctx, meta.hash, assignments);
                     hir::ExprObjectKind::StructVariant { hash: meta.hash }
                 }
                 _ => {
@@ -550,10 +586,18 @@ pub(crate) fn expr<'hir>(
                 expr_path(ctx, ast)?
             }
         }
-        ast::Expr::Assign(ast) => hir::ExprKind::Assign(alloc!(hir::ExprAssign {
-            lhs: alloc!(expr(ctx, &ast.lhs)?),
-            rhs: alloc!(expr(ctx, &ast.rhs)?),
-        })),
+        ast::Expr::Assign(ast) => {
+            let lhs = alloc!(expr(ctx, &ast.lhs)?);
+            let rhs = alloc!(expr(ctx, &ast.rhs)?);
+
+            if let hir::ExprKind::Variable(variable, _) = lhs.kind {
+                if let Some(&expected) = ctx.types.get(&variable) {
+                    check_type(ctx, expected, rhs)?;
+                }
+            }
+
+            hir::ExprKind::Assign(alloc!(hir::ExprAssign { lhs, rhs }))
+        }
         // TODO: lower all of these loop constructs to the same loop-like
         // representation. We only do different ones here right now since it's
         // easier when refactoring.
@@ -670,7 +714,21 @@ pub(crate) fn expr<'hir>(
             hir::ExprKind::Continue(option!(&ast.label, |ast| label(ctx, ast)?))
         }
         ast::Expr::Yield(ast) => hir::ExprKind::Yield(option!(&ast.expr, |ast| expr(ctx, ast)?)),
-        ast::Expr::Return(ast) => hir::ExprKind::Return(option!(&ast.expr, |ast| expr(ctx, ast)?)),
+        ast::Expr::Return(ast) => {
+            let value = option!(&ast.expr, |ast| expr(ctx, ast)?);
+
+            if let Some(expected) = ctx.return_type {
+                match value {
+                    Some(value) => check_type(ctx, expected, value)?,
+                    None if expected != UNIT_TYPE.hash => {
+                        return Err(type_mismatch(ctx, ast, expected, UNIT_TYPE.hash));
+                    }
+                    None => {}
+                }
+            }
+
+            hir::ExprKind::Return(value)
+        }
         ast::Expr::Await(ast) => hir::ExprKind::Await(alloc!(expr(ctx, &ast.expr)?)),
         ast::Expr::Try(ast) => hir::ExprKind::Try(alloc!(expr(ctx, &ast.expr)?)),
         ast::Expr::Select(ast) => hir::ExprKind::Select(alloc!(hir::ExprSelect {
@@ -947,7 +1005,9 @@ pub(crate) fn expr_block<'hir>(
                     tracing::trace!("queuing async block build entry");
 
                     ctx.scopes.push_captures();
+                    let return_type = ctx.return_type.take();
                     block(ctx, &ast.block)?;
+                    ctx.return_type = return_type;
                     let layer = ctx.scopes.pop().with_span(&ast.block)?;
 
                     ctx.q.insert_captures(meta.hash, layer.captures());
@@ -1003,6 +1063,27 @@ fn fn_arg<'hir>(ctx: &mut Ctx<'hir, '_>, ast: &ast::FnArg) -> compile::Result<hi
             hir::FnArg::SelfValue(ast.span(), variable)
         }
         ast::FnArg::Pat(ast) => hir::FnArg::Pat(alloc!(pat(ctx, ast)?)),
+        ast::FnArg::Typed(ast) => {
+            let pat = alloc!(pat(ctx, &ast.pat)?);
+
+            if let Some(hash) = ty(ctx, &ast.ty)? {
+                bind_type(ctx, pat, hash);
+            }
+
+            hir::FnArg::Pat(pat)
+        }
+        // NB: the default value is evaluated as part of the signature.
+        ast::FnArg::Default(ast) => {
+            let pat = alloc!(pat(ctx, &ast.pat)?);
+
+            if let Some((_, ty)) = &ast.ty {
+                if let Some(hash) = self::ty(ctx, ty)? {
+                    bind_type(ctx, pat, hash);
+                }
+            }
+
+            hir::FnArg::Pat(pat)
+        }
     })
 }
 
@@ -1013,6 +1094,13 @@ fn local<'hir>(ctx: &mut Ctx<'hir, '_>, ast: &ast::Local) -> compile::Result<hir
     let expr = alloc!(expr(ctx, &ast.expr)?);
     let pat = alloc!(pat(ctx, &ast.pat)?);
 
+    if let Some((_, ast)) = &ast.ty {
+        if let Some(hash) = ty(ctx, ast)? {
+            check_type(ctx, hash, expr)?;
+            bind_type(ctx, pat, hash);
+        }
+    }
+
     Ok(hir::Local {
         span: ast.span(),
         pat,
@@ -1209,6 +1297,14 @@ fn pat<'hir>(ctx: &mut Ctx<'hir, '_>, ast: &ast::Pat) -> compile::Result<hir::Pa
                             }
                         }
 
+                        for binding in bindings.iter() {
+                            ctx.q.visitor.visit_field_use(
+                                Location::new(ctx.source_id, binding.span()),
+                                meta.hash,
+                                binding.key(),
+                            );
+                        }
+
                         if !is_open && !fields.is_empty() {
                             let mut fields = fields
                                 .into_iter()
@@ -1239,10 +1335,167 @@ fn pat<'hir>(ctx: &mut Ctx<'hir, '_>, ast: &ast::Pat) -> compile::Result<hir::Pa
                 }))
             }
             ast::Pat::Binding(..) => hir::PatKind::Binding,
+            ast::Pat::Or(ast) => {
+                let alternatives =
+                    iter!(ast.alternatives(), ast.rest.len() + 1, |ast| pat(ctx, ast)?);
+
+                let mut bindings = Vec::new();
+
+                for (index, alternative) in alternatives.iter().enumerate() {
+                    let mut names = Vec::new();
+                    pat_bindings(alternative, &mut names);
+
+                    if index == 0 {
+                        bindings = names;
+                        continue;
+                    }
+
+                    let missing = bindings
+                        .iter()
+                        .find(|(name, _)| !names.iter().any(|(n, _)| n == name))
+                        .or_else(|| {
+                            names
+                                .iter()
+                                .find(|(name, _)| !bindings.iter().any(|(n, _)| n == name))
+                        });
+
+                    if let Some((name, _)) = missing {
+                        return Err(compile::Error::new(
+                            alternative,
+                            CompileErrorKind::PatternMissingBinding {
+                                name: (*name).into(),
+                            },
+                        ));
+                    }
+                }
+
+                hir::PatKind::Or(alloc!(hir::PatOr {
+                    alternatives,
+                    bindings: iter!(bindings),
+                }))
+            }
+            ast::Pat::Range(ast) => hir::PatKind::Range(alloc!(pat_range(ctx, ast)?)),
+            ast::Pat::At(ast) => {
+                let name = alloc_str!(ast.name.resolve(resolve_context!(ctx.q))?);
+                let variable = ctx.scopes.define(name).with_span(ast.name)?;
+
+                hir::PatKind::At(alloc!(hir::PatAt {
+                    name,
+                    variable,
+                    pat: alloc!(pat(ctx, &ast.pat)?),
+                }))
+            }
         },
     })
 }
 
+/// Collect the variables bound by a pattern.
+fn pat_bindings<'hir>(pat: &hir::Pat<'hir>, out: &mut Vec<(&'hir str, hir::Variable)>) {
+    match pat.kind {
+        hir::PatKind::Path(&hir::PatPathKind::Ident(name, variable)) => {
+            out.push((name, variable));
+        }
+        hir::PatKind::Vec(items) | hir::PatKind::Tuple(items) => {
+            for pat in items.items {
+                pat_bindings(pat, out);
+            }
+        }
+        hir::PatKind::Object(items) => {
+            for binding in items.bindings {
+                match *binding {
+                    hir::Binding::Binding(_, _, pat) => pat_bindings(pat, out),
+                    hir::Binding::Ident(_, name, variable) => out.push((name, variable)),
+                }
+            }
+        }
+        hir::PatKind::Or(or) => {
+            out.extend(or.bindings.iter().copied());
+        }
+        hir::PatKind::At(at) => {
+            out.push((at.name, at.variable));
+            pat_bindings(at.pat, out);
+        }
+        _ => {}
+    }
+}
+
+/// Lower a range pattern into inclusive bounds.
+fn pat_range(ctx: &mut Ctx<'_, '_>, ast: &ast::PatRange) -> compile::Result<hir::PatRange> {
+    let from = option_range_bound(ctx, ast.from.as_deref())?;
+    let to = option_range_bound(ctx, ast.to.as_deref())?;
+    let closed = matches!(ast.limits, ast::ExprRangeLimits::Closed(..));
+
+    let Some((_, kind)) = from.or(to) else {
+        return Err(compile::Error::new(
+            ast,
+            CompileErrorKind::UnsupportedPatternExpr,
+        ));
+    };
+
+    macro_rules! bounds {
+        ($variant:ident, $min:expr, $max:expr, $prev:expr) => {{
+            let unwrap = |bound: Option<(Span, hir::Lit<'_>)>, default| match bound {
+                Some((_, hir::Lit::$variant(value))) => Ok(value),
+                Some((span, _)) => Err(compile::Error::new(
+                    span,
+                    CompileErrorKind::UnsupportedPatternExpr,
+                )),
+                None => Ok(default),
+            };
+
+            let start = unwrap(from, $min)?;
+            let end = unwrap(to, $max)?;
+
+            // NB: exclusive bounds are converted into inclusive ones.
+            let end = if !closed && to.is_some() {
+                $prev(end)
+            } else {
+                Some(end)
+            };
+
+            let Some(end) = end.filter(|end| start <= *end) else {
+                return Err(compile::Error::new(ast, CompileErrorKind::EmptyRangePattern));
+            };
+
+            hir::PatRange::$variant { start, end }
+        }};
+    }
+
+    Ok(match kind {
+        hir::Lit::Integer(..) => bounds!(Integer, i64::MIN, i64::MAX, |n: i64| n.checked_sub(1)),
+        hir::Lit::Byte(..) => bounds!(Byte, u8::MIN, u8::MAX, |n: u8| n.checked_sub(1)),
+        hir::Lit::Char(..) => bounds!(Char, '\0', char::MAX, |c: char| match u32::from(c) {
+            0xe000 => Some('\u{d7ff}'),
+            n => char::from_u32(n.checked_sub(1)?),
+        }),
+        _ => {
+            return Err(compile::Error::new(
+                ast,
+                CompileErrorKind::UnsupportedPatternExpr,
+            ));
+        }
+    })
+}
+
+/// Lower the optional bound of a range pattern, which must be a literal.
+fn option_range_bound<'hir>(
+    ctx: &mut Ctx<'hir, '_>,
+    ast: Option<&ast::Expr>,
+) -> compile::Result<Option<(Span, hir::Lit<'hir>)>> {
+    let Some(ast) = ast else {
+        return Ok(None);
+    };
+
+    let hir::ExprKind::Lit(lit) = expr(ctx, ast)?.kind else {
+        return Err(compile::Error::new(
+            ast,
+            CompileErrorKind::UnsupportedPatternExpr,
+        ));
+    };
+
+    Ok(Some((ast.span(), lit)))
+}
+
 fn object_key<'hir>(
     ctx: &mut Ctx<'hir, '_>,
     ast: &ast::ObjectKey,
@@ -1355,6 +1608,8 @@ fn expr_path_meta<'hir>(
     alloc_with!(ctx, span);
 
     if let Needs::Value = ctx.needs.get() {
+        record_return_type(ctx, meta);
+
         match &meta.kind {
             meta::Kind::Struct {
                 fields: meta::Fields::Empty,
@@ -1366,6 +1621,7 @@ fn expr_path_meta<'hir>(
             } => Ok(hir::ExprKind::Call(alloc!(hir::ExprCall {
                 call: hir::Call::Meta { hash: meta.hash },
                 args: &[],
+                order: None,
             }))),
             meta::Kind::Variant {
                 fields: meta::Fields::Unnamed(0),
@@ -1377,6 +1633,7 @@ fn expr_path_meta<'hir>(
             } => Ok(hir::ExprKind::Call(alloc!(hir::ExprCall {
                 call: hir::Call::Meta { hash: meta.hash },
                 args: &[],
+                order: None,
             }))),
             meta::Kind::Struct {
                 fields: meta::Fields::Unnamed(..),
@@ -1613,6 +1870,7 @@ fn expr_call<'hir>(
     alloc_with!(ctx, ast);
 
     let expr = ctx.in_path(true, |ctx| expr(ctx, &ast.expr))?;
+    let mut signature = None;
 
     let call = 'ok: {
         match expr.kind {
@@ -1630,6 +1888,7 @@ fn expr_call<'hir>(
 
                 let meta = ctx.lookup_meta(path.span(), named.item, parameters)?;
                 debug_assert_eq!(meta.item_meta.item, named.item);
+                record_return_type(ctx, &meta);
 
                 match &meta.kind {
                     meta::Kind::Struct {
@@ -1678,7 +1937,15 @@ fn expr_call<'hir>(
                             );
                         }
                     }
-                    meta::Kind::Function { .. } | meta::Kind::AssociatedFunction { .. } => (),
+                    meta::Kind::Function { signature: s, .. }
+                    | meta::Kind::AssociatedFunction { signature: s, .. } => {
+                        // NB: only signatures in scripts are checked.
+                        if !meta.context {
+                            signature = Some(s.clone());
+                        }
+
+                        ctx.q.visitor.visit_call(ctx.source_id, meta.hash, ast);
+                    }
                     meta::Kind::ConstFn { id, .. } => {
                         let id = *id;
                         break 'ok hir::Call::ConstFn { id, ast_id: ast.id };
@@ -1717,8 +1984,290 @@ fn expr_call<'hir>(
         break 'ok hir::Call::Expr { expr: alloc!(expr) };
     };
 
-    Ok(hir::ExprCall {
-        call,
-        args: iter!(&ast.args, |(ast, _)| self::expr(ctx, ast)?),
-    })
+    let Some(signature) = signature else {
+        if let Some((name, _)) = ast.args.iter().find_map(|(arg, _)| arg.name.as_ref()) {
+            return Err(compile::Error::new(
+                name,
+                CompileErrorKind::UnsupportedNamedArgument,
+            ));
+        }
+
+        let args = iter!(&ast.args, |(arg, _)| self::expr(ctx, &arg.value)?);
+
+        return Ok(hir::ExprCall {
+            call,
+            args,
+            order: None,
+        });
+    };
+
+    let (args, order) = call_args(ctx, ast, &signature)?;
+
+    for (index, expected) in signature.annotated_arguments.iter().enumerate() {
+        let index = order.map_or(index, |order| order[index]);
+
+        if let Some(expected) = *expected {
+            check_type(ctx, expected, &args[index])?;
+        }
+    }
+
+    Ok(hir::ExprCall { call, args, order })
+}
+
+/// Lower the arguments of a call to a script function with a known signature.
+///
+/// Named arguments are matched to the parameter with the same name, and any
+/// missing arguments are filled in with their default values. Arguments are
+/// evaluated in the order that they appear in the call, so if that differs
+/// from the order of the parameters the index of the argument passed to each
+/// parameter is returned as well.
+fn call_args<'hir>(
+    ctx: &mut Ctx<'hir, '_>,
+    ast: &ast::ExprCall,
+    signature: &meta::Signature,
+) -> compile::Result<(&'hir [hir::Expr<'hir>], Option<&'hir [usize]>)> {
+    alloc_with!(ctx, ast);
+
+    let expected = signature.argument_names.len();
+    let mut slots = vec![None; expected];
+    let mut named = false;
+
+    // NB: positional arguments always come first, so their index in the call
+    // is the index of the parameter.
+    for (index, (arg, _)) in ast.args.iter().enumerate() {
+        let Some((name, _)) = &arg.name else {
+            if named {
+                return Err(compile::Error::new(
+                    arg,
+                    CompileErrorKind::PositionalAfterNamedArgument,
+                ));
+            }
+
+            let Some(slot) = slots.get_mut(index) else {
+                return Err(compile::Error::new(
+                    ast.args.span(),
+                    CompileErrorKind::UnsupportedArgumentCount {
+                        expected,
+                        actual: ast.args.len(),
+                    },
+                ));
+            };
+
+            *slot = Some(index);
+            continue;
+        };
+
+        named = true;
+        let name = name.resolve(resolve_context!(ctx.q))?;
+
+        let Some(slot) = signature
+            .argument_names
+            .iter()
+            .position(|n| n.as_deref() == Some(name))
+        else {
+            return Err(compile::Error::new(
+                arg,
+                CompileErrorKind::UnknownNamedArgument { name: name.into() },
+            ));
+        };
+
+        if slots[slot].replace(index).is_some() {
+            return Err(compile::Error::new(
+                arg,
+                CompileErrorKind::DuplicateArgument { name: name.into() },
+            ));
+        }
+    }
+
+    let span = ast.args.span();
+    let mut args = Vec::with_capacity(expected);
+
+    for (arg, _) in &ast.args {
+        args.push(self::expr(ctx, &arg.value)?);
+    }
+
+    // NB: default values are constants, so they can be evaluated last.
+    for (index, slot) in slots.iter_mut().enumerate() {
+        if slot.is_some() {
+            continue;
+        }
+
+        let Some(hash) = signature.argument_defaults[index] else {
+            let kind = match &signature.argument_names[index] {
+                Some(name) => CompileErrorKind::MissingArgument { name: name.clone() },
+                None => CompileErrorKind::UnsupportedArgumentCount {
+                    expected,
+                    actual: ast.args.len(),
+                },
+            };
+
+            return Err(compile::Error::new(span, kind));
+        };
+
+        *slot = Some(args.len());
+
+        args.push(hir::Expr {
+            span,
+            kind: hir::ExprKind::Const(hash),
+        });
+    }
+
+    let order = slots.into_iter().flatten().collect::<Vec<_>>();
+
+    let order: Option<&[usize]> = if order.iter().copied().eq(0..order.len()) {
+        None
+    } else {
+        Some(iter!(order))
+    };
+
+    Ok((iter!(args), order))
+}
+
+/// Resolve a type annotation.
+fn ty(ctx: &mut Ctx<'_, '_>, ast: &ast::Type) -> compile::Result<Option<Hash>> {
+    // NB: type annotations are not indexed in constant contexts.
+    if ctx.const_eval {
+        return Ok(None);
+    }
+
+    ctx.q.resolve_type(ctx.source_id, ast)
+}
+
+/// Associate a type with the variable bound by the given pattern.
+fn bind_type(ctx: &mut Ctx<'_, '_>, pat: &hir::Pat<'_>, hash: Hash) {
+    if let hir::PatKind::Path(&hir::PatPathKind::Ident(_, variable)) = pat.kind {
+        ctx.types.insert(variable, hash);
+    }
+}
+
+/// Record the statically known type returned when calling the given item.
+fn record_return_type(ctx: &mut Ctx<'_, '_>, meta: &meta::Meta) {
+    let return_type = match &meta.kind {
+        meta::Kind::Struct { .. } => Some(meta.hash),
+        meta::Kind::Variant { enum_hash, .. } => Some(*enum_hash),
+        meta::Kind::Function { signature, .. }
+        | meta::Kind::AssociatedFunction { signature, .. } => signature.annotated_return,
+        _ => None,
+    };
+
+    if let Some(return_type) = return_type {
+        ctx.returns.insert(meta.hash, return_type);
+    }
+}
+
+/// Get the statically known type of an expression.
+///
+/// This is only known for a handful of expressions, like literals and calls to
+/// functions with an annotated return type.
+fn type_of(ctx: &Ctx<'_, '_>, expr: &hir::Expr<'_>) -> Option<Hash> {
+    match expr.kind {
+        hir::ExprKind::Variable(variable, _) => ctx.types.get(&variable).copied(),
+        hir::ExprKind::Lit(lit) => Some(match lit {
+            hir::Lit::Bool(..) => BOOL_TYPE.hash,
+            hir::Lit::Integer(..) => INTEGER_TYPE.hash,
+            hir::Lit::Float(..) => FLOAT_TYPE.hash,
+            hir::Lit::Byte(..) => BYTE_TYPE.hash,
+            hir::Lit::Char(..) => CHAR_TYPE.hash,
+            hir::Lit::Str(..) => STRING_TYPE.hash,
+            hir::Lit::ByteStr(..) => BYTES_TYPE.hash,
+        }),
+        hir::ExprKind::Template(..) => Some(STRING_TYPE.hash),
+        hir::ExprKind::Vec(..) => Some(VEC_TYPE.hash),
+        hir::ExprKind::Tuple(seq) if seq.items.is_empty() => Some(UNIT_TYPE.hash),
+        hir::ExprKind::Tuple(..) => Some(TUPLE_TYPE.hash),
+        hir::ExprKind::Range(..) => Some(RANGE_TYPE.hash),
+        hir::ExprKind::Object(object) => match object.kind {
+            hir::ExprObjectKind::Anonymous => Some(OBJECT_TYPE.hash),
+            hir::ExprObjectKind::UnitStruct { hash } | hir::ExprObjectKind::Struct { hash } => {
+                Some(hash)
+            }
+            hir::ExprObjectKind::StructVariant { hash } => ctx.returns.get(&hash).copied(),
+        },
+        hir::ExprKind::Call(hir::ExprCall {
+            call: hir::Call::Meta { hash },
+            ..
+        }) => ctx.returns.get(hash).copied(),
+        hir::ExprKind::Binary(binary) => match binary.op {
+            ast::BinOp::Eq(..)
+            | ast::BinOp::Neq(..)
+            | ast::BinOp::Gt(..)
+            | ast::BinOp::Lt(..)
+            | ast::BinOp::Gte(..)
+            | ast::BinOp::Lte(..)
+            | ast::BinOp::Is(..)
+            | ast::BinOp::IsNot(..)
+            | ast::BinOp::And(..)
+            | ast::BinOp::Or(..) => Some(BOOL_TYPE.hash),
+            ast::BinOp::Add(..)
+            | ast::BinOp::Sub(..)
+            | ast::BinOp::Mul(..)
+            | ast::BinOp::Div(..)
+            | ast::BinOp::Rem(..) => {
+                let lhs = type_of(ctx, binary.lhs)?;
+
+                if (lhs == INTEGER_TYPE.hash || lhs == FLOAT_TYPE.hash)
+                    && type_of(ctx, binary.rhs)? == lhs
+                {
+                    Some(lhs)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        },
+        hir::ExprKind::Group(expr) => type_of(ctx, expr),
+        hir::ExprKind::Block(block) => match block.statements.last() {
+            Some(hir::Stmt::Expr(expr)) => type_of(ctx, expr),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Check that the statically known type of an expression matches the expected
+/// type.
+fn check_type(ctx: &Ctx<'_, '_>, expected: Hash, expr: &hir::Expr<'_>) -> compile::Result<()> {
+    match type_of(ctx, expr) {
+        Some(actual) if actual != expected => Err(type_mismatch(ctx, expr, expected, actual)),
+        _ => Ok(()),
+    }
+}
+
+/// Visit the fields assigned in a struct literal whose struct is known.
+fn visit_field_uses(ctx: &mut Ctx<'_, '_>, hash: Hash, assignments: &[hir::FieldAssign<'_>]) {
+    for assign in assignments {
+        ctx.q.visitor.visit_field_use(
+            Location::new(ctx.source_id, assign.key.0),
+            hash,
+            assign.key.1,
+        );
+    }
+}
+
+/// Check the assignments to fields with a type annotation.
+fn check_field_types(
+    ctx: &Ctx<'_, '_>,
+    fields: &meta::FieldsNamed,
+    assignments: &[hir::FieldAssign<'_>],
+) -> compile::Result<()> {
+    for assign in assignments {
+        if let Some(&expected) = fields.types.get(assign.key.1) {
+            check_type(ctx, expected, assign.assign)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn type_mismatch<S>(ctx: &Ctx<'_, '_>, spanned: S, expected: Hash, actual: Hash) -> compile::Error
+where
+    S: Spanned,
+{
+    compile::Error::new(
+        spanned,
+        CompileErrorKind::TypeMismatch {
+            expected: ctx.q.type_item(expected),
+            actual: ctx.q.type_item(actual),
+        },
+    )
 }
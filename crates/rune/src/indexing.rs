@@ -45,6 +45,8 @@ pub(crate) enum Indexed {
     Function(Function),
     /// An instance function.
     InstanceFunction(InstanceFunction),
+    /// A trait.
+    Trait(Trait),
     /// A constant expression.
     ConstExpr(ConstExpr),
     /// A constant block.
@@ -81,6 +83,40 @@ pub(crate) struct InstanceFunction {
     pub(crate) instance_span: Span,
 }
 
+#[derive(Debug, Clone)]
+pub(crate) struct Trait {
+    /// The functions declared by the trait.
+    pub(crate) functions: Vec<TraitFn>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TraitFn {
+    /// The name of the function.
+    pub(crate) name: Box<str>,
+    /// The default implementation of the function, if it has one.
+    pub(crate) default: Option<TraitFnDefault>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TraitFnDefault {
+    /// The item of the default implementation.
+    pub(crate) item_meta: ItemMeta,
+    /// Ast for the default implementation.
+    pub(crate) ast: Box<ast::ItemFn>,
+    /// The calling convention of the function.
+    pub(crate) call: Call,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TraitImpl {
+    /// The path of the implemented trait.
+    pub(crate) path: Box<ast::Path>,
+    /// The item the trait is implemented for.
+    pub(crate) impl_item: ItemId,
+    /// The names and spans of functions defined in the impl block.
+    pub(crate) functions: Vec<(Box<str>, Span)>,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct Import {
     /// The import entry.
@@ -10,13 +10,13 @@ use crate::ast::{self, OptionSpanned, Span, Spanned};
 use crate::compile::attrs::Attributes;
 use crate::compile::meta;
 use crate::compile::{
-    self, attrs, CompileErrorKind, Doc, ItemId, Location, ModId, ParseErrorKind, Visibility,
-    WithSpan,
+    self, attrs, CompileErrorKind, Doc, ItemId, ItemMeta, Location, ModId, ParseErrorKind,
+    Visibility, WithSpan,
 };
 use crate::indexing::locals;
 use crate::indexing::{self, Indexed};
 use crate::indexing::{Layer, Scopes};
-use crate::macros::MacroCompiler;
+use crate::macros::{MacroCompiler, MacroRules};
 use crate::parse::{Parse, Parser, Resolve};
 use crate::query::{BuiltInFile, BuiltInFormat, BuiltInLine, BuiltInMacro, BuiltInTemplate, Query};
 use crate::runtime::format;
@@ -404,6 +404,36 @@ impl<'a> Indexer<'a> {
         Ok(expanded)
     }
 
+    /// Define a declarative macro from a `macro_rules!` item.
+    fn define_macro_rules(&mut self, ast: &ast::MacroCall) -> compile::Result<()> {
+        let Some(name) = &ast.name else {
+            return Err(compile::Error::new(
+                &ast.path,
+                CompileErrorKind::MissingMacroName,
+            ));
+        };
+
+        // NB: Documentation comments are permitted, but are not recorded
+        // anywhere yet.
+        let mut attrs = Attributes::new(ast.attributes.clone());
+        Doc::collect_from(resolve_context!(self.q), &mut attrs)?;
+
+        if let Some(first) = attrs.remaining() {
+            return Err(compile::Error::msg(
+                first,
+                "macro attributes are not supported",
+            ));
+        }
+
+        let span = name.span;
+        let name = name.resolve(resolve_context!(self.q))?;
+        let item = self.q.pool.module_item(self.mod_item).extended(name);
+        let item = self.q.pool.alloc_item(item);
+
+        let macro_rules = MacroRules::parse(resolve_context!(self.q), self.source_id, ast)?;
+        self.q.insert_macro_rules(span, item, macro_rules)
+    }
+
     /// Perform an attribute macro expansion.
     fn expand_attribute_macro<T>(
         &mut self,
@@ -534,6 +564,13 @@ pub(crate) fn file(idx: &mut Indexer<'_>, ast: &mut ast::File) -> compile::Resul
 
     for (item, semi) in ast.items.drain(..) {
         match item {
+            // Macro definitions are registered up front, so that they are
+            // visible regardless of where in the module they are defined.
+            ast::Item::MacroCall(macro_call)
+                if MacroRules::is_definition(resolve_context!(idx.q), &macro_call)? =>
+            {
+                idx.define_macro_rules(&macro_call)?;
+            }
             i @ ast::Item::MacroCall(_) => {
                 queue.push_back((0, i, Vec::new(), semi));
             }
@@ -624,6 +661,11 @@ pub(crate) fn file(idx: &mut Indexer<'_>, ast: &mut ast::File) -> compile::Resul
 
             macro_call.attributes = skipped_attributes.clone();
 
+            if MacroRules::is_definition(resolve_context!(idx.q), &macro_call)? {
+                idx.define_macro_rules(&macro_call)?;
+                continue;
+            }
+
             let mut attributes = attrs::Attributes::new(skipped_attributes);
 
             if idx.try_expand_internal_macro(&mut attributes, &mut macro_call)? {
@@ -679,6 +721,11 @@ fn item_fn(idx: &mut Indexer<'_>, mut ast: ast::ItemFn) -> compile::Result<()> {
         &docs,
     )?;
 
+    idx.q.visitor.visit_item_name(
+        Location::new(idx.source_id, ast.name.span()),
+        idx.q.pool.item_type_hash(item_meta.item),
+    );
+
     idx.scopes.push();
 
     for (arg, _) in &mut ast.args {
@@ -687,9 +734,33 @@ fn item_fn(idx: &mut Indexer<'_>, mut ast: ast::ItemFn) -> compile::Result<()> {
             ast::FnArg::Pat(p) => {
                 locals::pat(idx, p)?;
             }
+            ast::FnArg::Typed(p) => {
+                locals::pat(idx, &mut p.pat)?;
+                ty(idx, &mut p.ty)?;
+            }
+            ast::FnArg::Default(p) => {
+                if ast.const_token.is_some() {
+                    return Err(compile::Error::new(
+                        p,
+                        CompileErrorKind::UnsupportedArgumentDefault,
+                    ));
+                }
+
+                locals::pat(idx, &mut p.pat)?;
+
+                if let Some((_, p)) = &mut p.ty {
+                    ty(idx, p)?;
+                }
+
+                expr(idx, &mut p.value)?;
+            }
         }
     }
 
+    if let Some((_, output)) = &mut ast.output {
+        ty(idx, output)?;
+    }
+
     // Take and restore item nesting.
     let last = idx.nested_item.replace(ast.descriptive_span());
     block(idx, &mut ast.body)?;
@@ -959,6 +1030,11 @@ fn local(idx: &mut Indexer<'_>, ast: &mut ast::Local) -> compile::Result<()> {
     // declaration and use that instead of capturing from the outside.
     expr(idx, &mut ast.expr)?;
     pat(idx, &mut ast.pat)?;
+
+    if let Some((_, ast)) = &mut ast.ty {
+        ty(idx, ast)?;
+    }
+
     Ok(())
 }
 
@@ -987,8 +1063,15 @@ fn pat(idx: &mut Indexer<'_>, ast: &mut ast::Pat) -> compile::Result<()> {
         ast::Pat::Binding(pat) => {
             pat_binding(idx, pat)?;
         }
+        ast::Pat::Or(pat) => {
+            pat_or(idx, pat)?;
+        }
+        ast::Pat::At(at) => {
+            pat(idx, &mut at.pat)?;
+        }
         ast::Pat::Ignore(..) => (),
         ast::Pat::Lit(..) => (),
+        ast::Pat::Range(..) => (),
         ast::Pat::Rest(..) => (),
     }
 
@@ -1015,6 +1098,17 @@ fn pat_binding(idx: &mut Indexer<'_>, ast: &mut ast::PatBinding) -> compile::Res
     Ok(())
 }
 
+#[instrument(span = ast)]
+fn pat_or(idx: &mut Indexer<'_>, ast: &mut ast::PatOr) -> compile::Result<()> {
+    pat(idx, &mut ast.first)?;
+
+    for (_, p) in &mut ast.rest {
+        pat(idx, p)?;
+    }
+
+    Ok(())
+}
+
 #[instrument(span = ast)]
 fn pat_object(idx: &mut Indexer<'_>, ast: &mut ast::PatObject) -> compile::Result<()> {
     match &mut ast.ident {
@@ -1257,6 +1351,11 @@ fn item_enum(idx: &mut Indexer<'_>, ast: ast::ItemEnum) -> compile::Result<()> {
         &docs,
     )?;
 
+    idx.q.visitor.visit_item_name(
+        Location::new(idx.source_id, ast.name.span()),
+        idx.q.pool.item_type_hash(enum_item.item),
+    );
+
     idx.q.index_enum(enum_item)?;
 
     for (index, (mut variant, _)) in ast.variants.into_iter().enumerate() {
@@ -1283,6 +1382,11 @@ fn item_enum(idx: &mut Indexer<'_>, ast: ast::ItemEnum) -> compile::Result<()> {
         )?;
         variant.id = item_meta.id;
 
+        idx.q.visitor.visit_item_name(
+            Location::new(idx.source_id, span),
+            idx.q.pool.item_type_hash(item_meta.item),
+        );
+
         let ctx = resolve_context!(idx.q);
 
         for (field, _) in variant.body.fields() {
@@ -1290,6 +1394,12 @@ fn item_enum(idx: &mut Indexer<'_>, ast: ast::ItemEnum) -> compile::Result<()> {
             let docs = Doc::collect_from(ctx, &mut attrs)?;
             let name = field.name.resolve(ctx)?;
 
+            idx.q.visitor.visit_field_name(
+                Location::new(idx.source_id, field.name.span()),
+                idx.q.pool.item_type_hash(item_meta.item),
+                name,
+            );
+
             for doc in docs {
                 idx.q.visitor.visit_field_doc_comment(
                     Location::new(idx.source_id, doc.span),
@@ -1308,6 +1418,8 @@ fn item_enum(idx: &mut Indexer<'_>, ast: ast::ItemEnum) -> compile::Result<()> {
             }
         }
 
+        fields(idx, &mut variant.body)?;
+
         idx.q
             .index_variant(item_meta, enum_item.id, variant, index)?;
     }
@@ -1342,6 +1454,11 @@ fn item_struct(idx: &mut Indexer<'_>, mut ast: ast::ItemStruct) -> compile::Resu
     )?;
     ast.id = item_meta.id;
 
+    idx.q.visitor.visit_item_name(
+        Location::new(idx.source_id, ast.ident.span()),
+        idx.q.pool.item_type_hash(item_meta.item),
+    );
+
     let ctx = resolve_context!(idx.q);
 
     for (field, _) in ast.body.fields() {
@@ -1349,6 +1466,12 @@ fn item_struct(idx: &mut Indexer<'_>, mut ast: ast::ItemStruct) -> compile::Resu
         let docs = Doc::collect_from(ctx, &mut attrs)?;
         let name = field.name.resolve(ctx)?;
 
+        idx.q.visitor.visit_field_name(
+            Location::new(idx.source_id, field.name.span()),
+            idx.q.pool.item_type_hash(item_meta.item),
+            name,
+        );
+
         for doc in docs {
             idx.q.visitor.visit_field_doc_comment(
                 Location::new(idx.source_id, doc.span),
@@ -1372,6 +1495,8 @@ fn item_struct(idx: &mut Indexer<'_>, mut ast: ast::ItemStruct) -> compile::Resu
         }
     }
 
+    fields(idx, &mut ast.body)?;
+
     idx.q.index_struct(item_meta, Box::new(ast))?;
     Ok(())
 }
@@ -1403,6 +1528,34 @@ fn item_impl(idx: &mut Indexer<'_>, ast: ast::ItemImpl) -> compile::Result<()> {
     }
 
     let new = idx.q.pool.alloc_item(&*idx.items.item());
+
+    if let Some((mut trait_path, _)) = ast.trait_ {
+        path(idx, &mut trait_path)?;
+
+        let mut functions = Vec::with_capacity(ast.functions.len());
+
+        for f in &ast.functions {
+            let name = f.name.resolve(resolve_context!(idx.q))?;
+            functions.push((name.into(), f.descriptive_span()));
+        }
+
+        let item_meta = ItemMeta {
+            location: Location::new(idx.source_id, trait_path.span()),
+            item: new,
+            module: idx.mod_item,
+            ..ItemMeta::default()
+        };
+
+        idx.q.index_trait_impl(
+            item_meta,
+            indexing::TraitImpl {
+                path: Box::new(trait_path),
+                impl_item: new,
+                functions,
+            },
+        );
+    }
+
     let old = replace(&mut idx.impl_item, Some(new));
 
     for i in ast.functions {
@@ -1413,6 +1566,154 @@ fn item_impl(idx: &mut Indexer<'_>, ast: ast::ItemImpl) -> compile::Result<()> {
     Ok(())
 }
 
+#[instrument(span = ast)]
+fn item_trait(idx: &mut Indexer<'_>, ast: ast::ItemTrait) -> compile::Result<()> {
+    let mut attrs = Attributes::new(ast.attributes.to_vec());
+    let docs = Doc::collect_from(resolve_context!(idx.q), &mut attrs)?;
+
+    if let Some(first) = attrs.remaining() {
+        return Err(compile::Error::msg(
+            first,
+            "trait attributes are not supported",
+        ));
+    }
+
+    let name = ast.name.resolve(resolve_context!(idx.q))?;
+    let _guard = idx.items.push_name(name.as_ref());
+
+    let item_meta = idx.q.insert_new_item(
+        &idx.items,
+        Location::new(idx.source_id, ast.descriptive_span()),
+        idx.mod_item,
+        ast_to_visibility(&ast.visibility)?,
+        &docs,
+    )?;
+
+    // NB: default functions are compiled once for every type implementing the
+    // trait, so `Self` has no single meaning inside of them.
+    let old = idx.impl_item.take();
+    let mut functions = Vec::<indexing::TraitFn>::with_capacity(ast.functions.len());
+
+    for f in ast.functions {
+        let span = f.descriptive_span();
+
+        if !f.is_instance() {
+            return Err(compile::Error::msg(
+                span,
+                "trait functions must take `self` as their first argument",
+            ));
+        }
+
+        let name = Box::<str>::from(f.name.resolve(resolve_context!(idx.q))?);
+
+        if functions.iter().any(|existing| existing.name == name) {
+            return Err(compile::Error::new(
+                span,
+                CompileErrorKind::TraitFunctionConflict { name },
+            ));
+        }
+
+        let default = match f.body {
+            Some(body) => {
+                let ast = ast::ItemFn {
+                    id: Default::default(),
+                    attributes: f.attributes,
+                    visibility: ast::Visibility::Inherited,
+                    const_token: None,
+                    async_token: f.async_token,
+                    fn_token: f.fn_token,
+                    name: f.name,
+                    args: f.args,
+                    output: f.output,
+                    body,
+                };
+
+                Some(trait_fn_default(idx, ast)?)
+            }
+            None => None,
+        };
+
+        functions.push(indexing::TraitFn { name, default });
+    }
+
+    idx.impl_item = old;
+    idx.q.index_trait(item_meta, functions)?;
+    Ok(())
+}
+
+/// Index the default implementation of a trait function.
+fn trait_fn_default(
+    idx: &mut Indexer<'_>,
+    mut ast: ast::ItemFn,
+) -> compile::Result<indexing::TraitFnDefault> {
+    let span = ast.span();
+
+    let name = ast.name.resolve(resolve_context!(idx.q))?;
+    let _guard = idx.items.push_name(name.as_ref());
+
+    let mut attributes = attrs::Attributes::new(ast.attributes.clone());
+    let docs = Doc::collect_from(resolve_context!(idx.q), &mut attributes)?;
+
+    if let Some(attrs) = attributes.remaining() {
+        return Err(compile::Error::msg(
+            attrs,
+            "unrecognized function attribute",
+        ));
+    }
+
+    let item_meta = idx.q.insert_new_item(
+        &idx.items,
+        Location::new(idx.source_id, span),
+        idx.mod_item,
+        Visibility::default(),
+        &docs,
+    )?;
+
+    idx.scopes.push();
+
+    for (arg, _) in &mut ast.args {
+        match arg {
+            ast::FnArg::SelfValue(..) => {}
+            ast::FnArg::Pat(p) => {
+                locals::pat(idx, p)?;
+            }
+            ast::FnArg::Typed(p) => {
+                locals::pat(idx, &mut p.pat)?;
+                ty(idx, &mut p.ty)?;
+            }
+            ast::FnArg::Default(p) => {
+                locals::pat(idx, &mut p.pat)?;
+
+                if let Some((_, p)) = &mut p.ty {
+                    ty(idx, p)?;
+                }
+
+                expr(idx, &mut p.value)?;
+            }
+        }
+    }
+
+    if let Some((_, output)) = &mut ast.output {
+        ty(idx, output)?;
+    }
+
+    let last = idx.nested_item.replace(ast.descriptive_span());
+    block(idx, &mut ast.body)?;
+    idx.nested_item = last;
+
+    let layer = idx.scopes.pop().with_span(span)?;
+
+    let Some(call) = validate_call(None, ast.async_token.as_ref(), &layer)? else {
+        return Err(compile::Error::new(span, CompileErrorKind::ClosureKind));
+    };
+
+    Ok(indexing::TraitFnDefault {
+        item_meta,
+        ast: Box::new(ast),
+        call,
+    })
+}
+
 #[instrument(span = ast)]
 fn item_mod(idx: &mut Indexer<'_>, mut ast: ast::ItemMod) -> compile::Result<()> {
     let mut attrs = Attributes::new(ast.attributes.clone());
@@ -1481,6 +1782,11 @@ fn item_const(idx: &mut Indexer<'_>, mut ast: ast::ItemConst) -> compile::Result
 
     ast.id = item_meta.id;
 
+    idx.q.visitor.visit_item_name(
+        Location::new(idx.source_id, ast.name.span()),
+        idx.q.pool.item_type_hash(item_meta.item),
+    );
+
     let last = idx.nested_item.replace(ast.descriptive_span());
     expr(idx, &mut ast.expr)?;
     idx.nested_item = last;
@@ -1506,6 +1812,9 @@ fn item(idx: &mut Indexer<'_>, ast: ast::Item) -> compile::Result<()> {
         ast::Item::Impl(item) => {
             item_impl(idx, item)?;
         }
+        ast::Item::Trait(item) => {
+            item_trait(idx, item)?;
+        }
         ast::Item::Mod(item) => {
             item_mod(idx, item)?;
         }
@@ -1574,6 +1883,34 @@ fn path(idx: &mut Indexer<'_>, ast: &mut ast::Path) -> compile::Result<()> {
     Ok(())
 }
 
+/// Index the type annotations of fields.
+fn fields(idx: &mut Indexer<'_>, ast: &mut ast::Fields) -> compile::Result<()> {
+    for (field, _) in ast.fields_mut() {
+        if let Some((_, ast)) = &mut field.ty {
+            ty(idx, ast)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[instrument(span = ast)]
+fn ty(idx: &mut Indexer<'_>, ast: &mut ast::Type) -> compile::Result<()> {
+    match ast {
+        ast::Type::Path(ast) => {
+            path(idx, ast)?;
+        }
+        ast::Type::Bang(..) => {}
+        ast::Type::Tuple(ast) => {
+            for (ast, _) in ast {
+                ty(idx, ast)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 #[instrument(span = ast)]
 fn path_segment(idx: &mut Indexer<'_>, ast: &mut ast::PathSegment) -> compile::Result<()> {
     if let ast::PathSegment::Generics(generics) = ast {
@@ -1631,6 +1968,16 @@ fn expr_closure(idx: &mut Indexer<'_>, ast: &mut ast::ExprClosure) -> compile::R
             ast::FnArg::Pat(p) => {
                 locals::pat(idx, p)?;
             }
+            ast::FnArg::Typed(p) => {
+                locals::pat(idx, &mut p.pat)?;
+                ty(idx, &mut p.ty)?;
+            }
+            ast::FnArg::Default(p) => {
+                return Err(compile::Error::new(
+                    p,
+                    CompileErrorKind::UnsupportedArgumentDefault,
+                ));
+            }
         }
     }
 
@@ -1773,8 +2120,8 @@ fn expr_select(idx: &mut Indexer<'_>, ast: &mut ast::ExprSelect) -> compile::Res
 fn expr_call(idx: &mut Indexer<'_>, ast: &mut ast::ExprCall) -> compile::Result<()> {
     ast.id.set(idx.items.id().with_span(ast.span())?);
 
-    for (e, _) in &mut ast.args {
-        expr(idx, e)?;
+    for (arg, _) in &mut ast.args {
+        expr(idx, &mut arg.value)?;
     }
 
     expr(idx, &mut ast.expr)?;
@@ -23,8 +23,15 @@ pub(crate) fn pat(idx: &mut Indexer<'_>, ast: &mut ast::Pat) -> compile::Result<
         ast::Pat::Binding(p) => {
             pat_binding(idx, p)?;
         }
+        ast::Pat::Or(p) => {
+            pat_or(idx, p)?;
+        }
+        ast::Pat::At(p) => {
+            pat(idx, &mut p.pat)?;
+        }
         ast::Pat::Ignore(..) => (),
         ast::Pat::Lit(..) => (),
+        ast::Pat::Range(..) => (),
         ast::Pat::Rest(..) => (),
     }
 
@@ -89,3 +96,14 @@ fn pat_binding(idx: &mut Indexer<'_>, ast: &mut ast::PatBinding) -> compile::Res
     pat(idx, &mut ast.pat)?;
     Ok(())
 }
+
+#[instrument(span = ast)]
+fn pat_or(idx: &mut Indexer<'_>, ast: &mut ast::PatOr) -> compile::Result<()> {
+    pat(idx, &mut ast.first)?;
+
+    for (_, p) in &mut ast.rest {
+        pat(idx, p)?;
+    }
+
+    Ok(())
+}
@@ -6,9 +6,17 @@ mod completion;
 mod connection;
 pub mod envelope;
 mod fs;
+mod hover;
+mod inlay;
+mod signature;
 mod state;
+mod symbols;
+#[cfg(test)]
+mod tests;
 mod url;
 
+use core::fmt;
+
 use crate::no_std::prelude::*;
 
 use lsp::notification::Notification;
@@ -16,7 +24,7 @@ use lsp::request::Request;
 use serde::Deserialize;
 use tokio::sync::Notify;
 
-use crate::languageserver::connection::stdio;
+use crate::languageserver::connection::{stdio, Input, Output};
 use crate::languageserver::envelope::Code;
 use crate::languageserver::state::State;
 use crate::workspace::MANIFEST_FILE;
@@ -28,10 +36,43 @@ enum Language {
     Other,
 }
 
+/// An error raised while handling a request, which is sent back to the client
+/// as an error response.
+#[derive(Debug)]
+struct RequestError {
+    code: Code,
+    message: String,
+}
+
+impl RequestError {
+    /// The request had valid parameters but could not be completed.
+    fn failed<E>(error: E) -> Self
+    where
+        E: fmt::Display,
+    {
+        Self {
+            code: Code::RequestFailed,
+            message: error.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.message.fmt(f)
+    }
+}
+
+impl std::error::Error for RequestError {}
+
 /// Run a language server with the given options.
 pub async fn run(context: Context, options: Options) -> Result<()> {
-    let (mut input, output) = stdio()?;
+    let (input, output) = stdio()?;
+    serve(input, output, context, options).await
+}
 
+/// Serve the language server protocol over the given connection.
+async fn serve(mut input: Input, output: Output, context: Context, options: Options) -> Result<()> {
     let rebuild_notify = Notify::new();
 
     let rebuild = rebuild_notify.notified();
@@ -76,8 +117,18 @@ pub async fn run(context: Context, options: Options) -> Result<()> {
                         match incoming.method.as_str() {
                             $(<$req_ty>::METHOD => {
                                 let params = <$req_ty as Request>::Params::deserialize(incoming.params)?;
-                                let result = $req_handle(&mut state, params).await?;
-                                state.output.response(incoming.id, result).await?;
+                                match $req_handle(&mut state, params).await {
+                                    Ok(result) => {
+                                        state.output.response(incoming.id, result).await?;
+                                    }
+                                    Err(error) => {
+                                        let error = error.downcast::<RequestError>()?;
+
+                                        state.output
+                                            .error(incoming.id, error.code, &error.message, None::<()>)
+                                            .await?;
+                                    }
+                                }
                             })*
                             $(<$notif_ty>::METHOD => {
                                 let params = <$notif_ty as Notification>::Params::deserialize(incoming.params)?;
@@ -101,6 +152,15 @@ pub async fn run(context: Context, options: Options) -> Result<()> {
                     req(lsp::request::Shutdown, shutdown),
                     req(lsp::request::GotoDefinition, goto_definition),
                     req(lsp::request::Completion, completion),
+                    req(lsp::request::HoverRequest, hover),
+                    req(lsp::request::References, references),
+                    req(lsp::request::Rename, rename),
+                    req(lsp::request::DocumentSymbolRequest, document_symbol),
+                    req(lsp::request::WorkspaceSymbolRequest, workspace_symbol),
+                    req(lsp::request::Formatting, formatting),
+                    req(lsp::request::RangeFormatting, range_formatting),
+                    req(lsp::request::SignatureHelpRequest, signature_help),
+                    req(lsp::request::InlayHintRequest, inlay_hint),
                     notif(lsp::notification::DidOpenTextDocument, did_open_text_document),
                     notif(lsp::notification::DidChangeTextDocument, did_change_text_document),
                     notif(lsp::notification::DidCloseTextDocument, did_close_text_document),
@@ -130,6 +190,21 @@ async fn initialize(
             lsp::TextDocumentSyncKind::INCREMENTAL,
         )),
         definition_provider: Some(lsp::OneOf::Left(true)),
+        hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+        references_provider: Some(lsp::OneOf::Left(true)),
+        rename_provider: Some(lsp::OneOf::Left(true)),
+        document_symbol_provider: Some(lsp::OneOf::Left(true)),
+        workspace_symbol_provider: Some(lsp::OneOf::Left(true)),
+        document_formatting_provider: Some(lsp::OneOf::Left(true)),
+        document_range_formatting_provider: Some(lsp::OneOf::Left(true)),
+        signature_help_provider: Some(lsp::SignatureHelpOptions {
+            trigger_characters: Some(vec!["(".into(), ",".into()]),
+            retrigger_characters: None,
+            work_done_progress_options: lsp::WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        }),
+        inlay_hint_provider: Some(lsp::OneOf::Left(true)),
         completion_provider: Some(lsp::CompletionOptions {
             all_commit_characters: None,
             resolve_provider: Some(false),
@@ -217,6 +292,95 @@ async fn completion(
     Ok(results)
 }
 
+/// Handle hover request.
+async fn hover(state: &mut State<'_>, params: lsp::HoverParams) -> Result<Option<lsp::Hover>> {
+    Ok(state.hover(
+        &params.text_document_position_params.text_document.uri,
+        params.text_document_position_params.position,
+    ))
+}
+
+/// Handle find references request.
+async fn references(
+    state: &mut State<'_>,
+    params: lsp::ReferenceParams,
+) -> Result<Option<Vec<lsp::Location>>> {
+    Ok(state.references(
+        &params.text_document_position.text_document.uri,
+        params.text_document_position.position,
+        params.context.include_declaration,
+    ))
+}
+
+/// Handle rename request.
+async fn rename(
+    state: &mut State<'_>,
+    params: lsp::RenameParams,
+) -> Result<Option<lsp::WorkspaceEdit>> {
+    Ok(state.rename(
+        &params.text_document_position.text_document.uri,
+        params.text_document_position.position,
+        &params.new_name,
+    ))
+}
+
+/// Handle document symbol request.
+async fn document_symbol(
+    state: &mut State<'_>,
+    params: lsp::DocumentSymbolParams,
+) -> Result<Option<lsp::DocumentSymbolResponse>> {
+    let symbols = state.document_symbols(&params.text_document.uri);
+    Ok(symbols.map(lsp::DocumentSymbolResponse::Nested))
+}
+
+/// Handle workspace symbol request.
+async fn workspace_symbol(
+    state: &mut State<'_>,
+    params: lsp::WorkspaceSymbolParams,
+) -> Result<Option<lsp::WorkspaceSymbolResponse>> {
+    let symbols = state.workspace_symbols(&params.query);
+    Ok(Some(lsp::WorkspaceSymbolResponse::Nested(symbols)))
+}
+
+/// Handle formatting request.
+async fn formatting(
+    state: &mut State<'_>,
+    params: lsp::DocumentFormattingParams,
+) -> Result<Option<Vec<lsp::TextEdit>>> {
+    state
+        .format(&params.text_document.uri)
+        .map_err(|error| RequestError::failed(format_args!("{error:#}")).into())
+}
+
+/// Handle range formatting request.
+async fn range_formatting(
+    state: &mut State<'_>,
+    params: lsp::DocumentRangeFormattingParams,
+) -> Result<Option<Vec<lsp::TextEdit>>> {
+    state
+        .format_range(&params.text_document.uri, params.range)
+        .map_err(|error| RequestError::failed(format_args!("{error:#}")).into())
+}
+
+/// Handle signature help request.
+async fn signature_help(
+    state: &mut State<'_>,
+    params: lsp::SignatureHelpParams,
+) -> Result<Option<lsp::SignatureHelp>> {
+    Ok(state.signature_help(
+        &params.text_document_position_params.text_document.uri,
+        params.text_document_position_params.position,
+    ))
+}
+
+/// Handle inlay hint request.
+async fn inlay_hint(
+    state: &mut State<'_>,
+    params: lsp::InlayHintParams,
+) -> Result<Option<Vec<lsp::InlayHint>>> {
+    Ok(state.inlay_hints(&params.text_document.uri, params.range))
+}
+
 /// Handle open text document.
 async fn did_open_text_document(
     s: &mut State<'_>,
@@ -6,7 +6,8 @@ use crate::no_std::sync::Arc;
 use anyhow::{anyhow, bail, Result};
 use tokio::io;
 use tokio::io::{
-    AsyncBufRead, AsyncBufReadExt as _, AsyncReadExt as _, AsyncWriteExt as _, BufReader,
+    AsyncBufRead, AsyncBufReadExt as _, AsyncReadExt as _, AsyncWrite, AsyncWriteExt as _,
+    BufReader,
 };
 use tokio::sync::Mutex;
 
@@ -21,13 +22,13 @@ pub(super) struct Frame<'a> {
 /// Input connection.
 pub(super) struct Input {
     buf: Vec<u8>,
-    stdin: BufReader<io::Stdin>,
+    reader: Box<dyn AsyncBufRead + Send + Unpin>,
 }
 
 impl Input {
     /// Get the next input frame.
     pub(super) async fn next(&mut self) -> Result<Option<Frame<'_>>> {
-        let headers = match Headers::read(&mut self.buf, &mut self.stdin).await? {
+        let headers = match Headers::read(&mut self.buf, &mut self.reader).await? {
             Some(headers) => headers,
             None => return Ok(None),
         };
@@ -40,7 +41,7 @@ impl Input {
         };
 
         self.buf.resize(length, 0u8);
-        self.stdin.read_exact(&mut self.buf[..]).await?;
+        self.reader.read_exact(&mut self.buf[..]).await?;
         Ok(Some(Frame { content: &self.buf }))
     }
 }
@@ -48,7 +49,7 @@ impl Input {
 /// Output connection.
 #[derive(Clone)]
 pub(super) struct Output {
-    stdout: Arc<Mutex<io::Stdout>>,
+    writer: Arc<Mutex<Box<dyn AsyncWrite + Send + Unpin>>>,
 }
 
 impl Output {
@@ -86,7 +87,7 @@ impl Output {
         &self,
         id: Option<envelope::RequestId>,
         code: envelope::Code,
-        message: &'static str,
+        message: &str,
         data: Option<D>,
     ) -> Result<()>
     where
@@ -148,28 +149,34 @@ impl Output {
         write!(m, "\r\n")?;
         m.append(bytes);
 
-        let mut stdout = self.stdout.lock().await;
-        stdout.write_all(&m).await?;
-        stdout.flush().await?;
+        let mut writer = self.writer.lock().await;
+        writer.write_all(&m).await?;
+        writer.flush().await?;
         Ok(())
     }
 }
 
 /// Setup a stdin/stdout connection.
 pub(super) fn stdio() -> Result<(Input, Output)> {
-    let stdin = io::stdin();
-    let stdout = io::stdout();
+    Ok(from_io(BufReader::new(io::stdin()), io::stdout()))
+}
 
+/// Setup a connection over the given reader and writer.
+pub(super) fn from_io<R, W>(reader: R, writer: W) -> (Input, Output)
+where
+    R: 'static + Send + Unpin + AsyncBufRead,
+    W: 'static + Send + Unpin + AsyncWrite,
+{
     let input = Input {
         buf: Vec::new(),
-        stdin: BufReader::new(stdin),
+        reader: Box::new(reader),
     };
 
     let output = Output {
-        stdout: Arc::new(Mutex::new(stdout)),
+        writer: Arc::new(Mutex::new(Box::new(writer))),
     };
 
-    Ok((input, output))
+    (input, output)
 }
 
 #[derive(Debug)]
@@ -96,6 +96,7 @@ code! {
         ServerNotInitialized = -32002,
         UnknownErrorCode = -32001,
         RequestCancelled = -32800,
+        RequestFailed = -32803,
     }
 }
 
@@ -0,0 +1,140 @@
+use core::fmt::Write;
+
+use crate::no_std::prelude::*;
+
+use lsp::MarkupContent;
+use lsp::MarkupKind;
+
+use crate::doc::context::{Context, Function, Kind, Meta, Signature};
+use crate::runtime::debug::DebugArgs;
+use crate::{Hash, Unit};
+
+use super::state::DefinitionKind;
+
+/// Construct the markdown contents used when hovering over the given meta.
+pub(super) fn contents(
+    cx: &Context<'_>,
+    meta: Meta<'_>,
+    kind: DefinitionKind,
+    unit: Option<&Unit>,
+) -> Option<MarkupContent> {
+    let name = meta.item?.to_string();
+    let name = name.trim_start_matches("::");
+
+    let mut value = String::new();
+    writeln!(value, "```rune").ok()?;
+
+    match meta.kind {
+        Kind::Function(f) => {
+            if f.is_async {
+                write!(value, "async ").ok()?;
+            }
+
+            let args = args(f, meta.hash, unit).join(", ");
+            write!(value, "fn {name}({args})").ok()?;
+
+            if let Some(return_type) = return_type(cx, f) {
+                write!(value, " -> {return_type}").ok()?;
+            }
+        }
+        Kind::Type => write!(value, "type {name}").ok()?,
+        Kind::Struct => write!(value, "struct {name}").ok()?,
+        Kind::Enum => write!(value, "enum {name}").ok()?,
+        Kind::Const(..) => write!(value, "const {name}").ok()?,
+        Kind::Macro => write!(value, "{name}!").ok()?,
+        Kind::Module => write!(value, "mod {name}").ok()?,
+        Kind::Unsupported if matches!(kind, DefinitionKind::Const) => {
+            write!(value, "const {name}").ok()?
+        }
+        Kind::Variant | Kind::Unsupported => write!(value, "{name}").ok()?,
+    }
+
+    writeln!(value).ok()?;
+    writeln!(value, "```").ok()?;
+    writeln!(value).ok()?;
+    writeln!(value, "*{}*", kind_name(kind)).ok()?;
+
+    if !meta.docs.is_empty() {
+        writeln!(value).ok()?;
+        writeln!(value, "{}", meta.docs.join("\n")).ok()?;
+    }
+
+    Some(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value,
+    })
+}
+
+/// Get the name of the type returned by a function, if it is known.
+pub(super) fn return_type(cx: &Context<'_>, f: Function<'_>) -> Option<String> {
+    let item = f
+        .return_type
+        .and_then(|hash| cx.meta_by_hash(hash).into_iter().find_map(|m| m.item))?;
+
+    Some(item.to_string().trim_start_matches("::").to_owned())
+}
+
+/// Get the names of arguments to a function, falling back to placeholder
+/// names if they are not known.
+pub(super) fn args(f: Function<'_>, hash: Hash, unit: Option<&Unit>) -> Vec<String> {
+    if let Some(names) = arg_names(f, hash, unit) {
+        return names;
+    }
+
+    let Some(count) = f.args else {
+        return vec![String::from("..")];
+    };
+
+    (0..count)
+        .map(|n| match (f.signature, n) {
+            (Signature::Instance, 0) => String::from("self"),
+            (_, 0) => String::from("value"),
+            (_, n) => format!("value{n}"),
+        })
+        .collect()
+}
+
+/// Get the names of arguments to a function, if they are known.
+///
+/// Native functions might have their arguments documented, while the names of
+/// arguments for script functions are available in the debug info of a unit.
+pub(super) fn arg_names(f: Function<'_>, hash: Hash, unit: Option<&Unit>) -> Option<Vec<String>> {
+    if let Some(arg_names) = f.arg_names {
+        return Some(arg_names.to_vec());
+    }
+
+    let debug = unit.and_then(Unit::debug_info)?;
+
+    // Instance functions are recorded under their instance function hash, so
+    // they are found through the hash of their path instead.
+    let debug = match debug.functions.get(&hash) {
+        Some(debug) => debug,
+        None => debug
+            .functions
+            .values()
+            .find(|debug| Hash::type_hash(&debug.path) == hash)?,
+    };
+
+    match &debug.args {
+        DebugArgs::Named(names) => Some(names.iter().map(|name| name.to_string()).collect()),
+        _ => None,
+    }
+}
+
+/// Human readable name of a definition kind.
+fn kind_name(kind: DefinitionKind) -> &'static str {
+    match kind {
+        DefinitionKind::UnitStruct => "unit struct",
+        DefinitionKind::TupleStruct => "tuple struct",
+        DefinitionKind::Struct => "struct",
+        DefinitionKind::UnitVariant => "unit variant",
+        DefinitionKind::TupleVariant => "tuple variant",
+        DefinitionKind::StructVariant => "struct variant",
+        DefinitionKind::Enum => "enum",
+        DefinitionKind::Function => "function",
+        DefinitionKind::AssociatedFunction => "associated function",
+        DefinitionKind::Const => "constant",
+        DefinitionKind::Local => "local variable",
+        DefinitionKind::Module => "module",
+    }
+}
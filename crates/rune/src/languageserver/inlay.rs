@@ -0,0 +1,117 @@
+use core::fmt::{self, Write};
+
+use crate::no_std::prelude::*;
+
+use lsp::{InlayHint, InlayHintKind, InlayHintLabel, Position};
+
+use crate::runtime::ConstValue;
+
+/// The longest rendered constant value to show, in characters.
+const MAX_VALUE_LEN: usize = 64;
+
+/// Construct a hint naming the parameter an argument is passed to.
+pub(super) fn parameter(position: Position, name: &str) -> InlayHint {
+    InlayHint {
+        position,
+        label: InlayHintLabel::String(format!("{name}:")),
+        kind: Some(InlayHintKind::PARAMETER),
+        text_edits: None,
+        tooltip: None,
+        padding_left: None,
+        padding_right: Some(true),
+        data: None,
+    }
+}
+
+/// Construct a hint showing the value a constant evaluated to.
+pub(super) fn constant(position: Position, value: &str) -> InlayHint {
+    InlayHint {
+        position,
+        label: InlayHintLabel::String(format!("= {value}")),
+        kind: None,
+        text_edits: None,
+        tooltip: None,
+        padding_left: Some(true),
+        padding_right: None,
+        data: None,
+    }
+}
+
+/// Render a constant value the way it would be written in a script.
+///
+/// Long values are truncated.
+pub(super) fn render(value: &ConstValue) -> Option<String> {
+    let mut out = String::new();
+    write_value(&mut out, value).ok()?;
+
+    if let Some((n, _)) = out.char_indices().nth(MAX_VALUE_LEN) {
+        out.truncate(n);
+        out.push_str("..");
+    }
+
+    Some(out)
+}
+
+fn write_value(out: &mut String, value: &ConstValue) -> fmt::Result {
+    match value {
+        ConstValue::Unit => write!(out, "()"),
+        ConstValue::Byte(b) => write!(out, "b'{}'", b.escape_ascii()),
+        ConstValue::Char(c) => write!(out, "{c:?}"),
+        ConstValue::Bool(b) => write!(out, "{b}"),
+        ConstValue::Integer(n) => write!(out, "{n}"),
+        ConstValue::Float(n) => write!(out, "{n:?}"),
+        ConstValue::String(s) => write!(out, "{s:?}"),
+        ConstValue::StaticString(s) => write!(out, "{:?}", s.as_str()),
+        ConstValue::Bytes(b) => write!(out, "b\"{}\"", b.escape_ascii()),
+        ConstValue::Vec(values) => {
+            write!(out, "[")?;
+            write_values(out, values)?;
+            write!(out, "]")
+        }
+        ConstValue::Tuple(values) => {
+            write!(out, "(")?;
+            write_values(out, values)?;
+
+            if values.len() == 1 {
+                write!(out, ",")?;
+            }
+
+            write!(out, ")")
+        }
+        ConstValue::Object(object) => {
+            let mut entries = object.iter().collect::<Vec<_>>();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+
+            write!(out, "#{{")?;
+
+            for (n, (key, value)) in entries.into_iter().enumerate() {
+                if n > 0 {
+                    write!(out, ", ")?;
+                }
+
+                write!(out, "{key}: ")?;
+                write_value(out, value)?;
+            }
+
+            write!(out, "}}")
+        }
+        ConstValue::Option(Some(value)) => {
+            write!(out, "Some(")?;
+            write_value(out, value)?;
+            write!(out, ")")
+        }
+        ConstValue::Option(None) => write!(out, "None"),
+    }
+}
+
+fn write_values(out: &mut String, values: &[ConstValue]) -> fmt::Result {
+    for (n, value) in values.iter().enumerate() {
+        if n > 0 {
+            write!(out, ", ")?;
+        }
+
+        write_value(out, value)?;
+    }
+
+    Ok(())
+}
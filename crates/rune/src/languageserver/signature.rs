@@ -0,0 +1,231 @@
+use core::fmt::Write;
+
+use crate::no_std::collections::HashSet;
+use crate::no_std::prelude::*;
+
+use lsp::{
+    Documentation, MarkupContent, MarkupKind, ParameterInformation, ParameterLabel, SignatureHelp,
+    SignatureInformation,
+};
+
+use crate::ast::{self, Span};
+use crate::compile::{meta, Item};
+use crate::doc::context::{Context, Kind, Meta};
+use crate::Unit;
+
+use super::hover;
+
+/// A call whose arguments are being typed.
+pub(super) struct Call<'a> {
+    /// The path being called, such as `foo` or `String::new`.
+    pub(super) path: &'a str,
+    /// The span of the path being called.
+    pub(super) span: Span,
+    /// Indicates if this is an instance call, such as `value.foo(..)`.
+    pub(super) instance: bool,
+    /// The index of the argument being typed.
+    pub(super) active: usize,
+}
+
+/// Find the call which the given byte offset is in the arguments of.
+///
+/// This scans backwards for the parenthesis which hasn't been closed, since
+/// the call being typed usually doesn't parse.
+pub(super) fn call_at(text: &str, offset: usize) -> Option<Call<'_>> {
+    let mut depth = 0usize;
+    let mut active = 0;
+
+    let open = 'found: {
+        for (n, c) in text.get(..offset)?.char_indices().rev() {
+            match c {
+                ')' | ']' | '}' => depth += 1,
+                '(' | '[' | '{' if depth > 0 => depth -= 1,
+                '(' => break 'found n,
+                '[' | '{' => return None,
+                ';' if depth == 0 => return None,
+                ',' if depth == 0 => active += 1,
+                _ => {}
+            }
+        }
+
+        return None;
+    };
+
+    let head = text[..open].trim_end();
+
+    let start = head
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| c.is_alphanumeric() || matches!(c, '_' | ':'))
+        .last()
+        .map(|(n, _)| n)?;
+
+    let path = &head[start..];
+    let name = path.rsplit("::").next()?;
+
+    if !matches!(name.chars().next(), Some(c) if c.is_alphabetic() || c == '_')
+        || ast::Kind::from_keyword(name).is_some()
+    {
+        return None;
+    }
+
+    Some(Call {
+        path,
+        span: Span::new(start, head.len()),
+        instance: head[..start].trim_end().ends_with('.'),
+        active,
+    })
+}
+
+/// Find the functions which could be called by the given call by their name.
+///
+/// This is used when the call hasn't been compiled, such as while it's being
+/// typed. Instance calls match any instance function with the same name.
+pub(super) fn find_by_name<'a>(
+    cx: &'a Context<'a>,
+    context: &'a crate::Context,
+    docs: Option<&'a crate::doc::Visitor>,
+    call: &Call<'_>,
+) -> Vec<Meta<'a>> {
+    let mut hashes = Vec::new();
+
+    for (meta, _) in context.iter_functions() {
+        if let Some(item) = &meta.item {
+            if matches(&meta.kind, item, call) {
+                hashes.push(meta.hash);
+            }
+        }
+    }
+
+    for data in docs.into_iter().flat_map(|docs| docs.data.values()) {
+        if let Some(kind) = &data.kind {
+            if matches(kind, &data.item, call) {
+                hashes.push(data.hash);
+            }
+        }
+    }
+
+    let mut seen = HashSet::new();
+    hashes.retain(|hash| seen.insert(*hash));
+
+    hashes
+        .into_iter()
+        .flat_map(|hash| cx.meta_by_hash(hash).into_iter().next())
+        .collect()
+}
+
+/// Construct signature help for the given functions, marking the argument
+/// being typed as active.
+pub(super) fn help<'a, I>(
+    cx: &Context<'_>,
+    metas: I,
+    unit: Option<&Unit>,
+    call: &Call<'_>,
+) -> Option<SignatureHelp>
+where
+    I: IntoIterator<Item = Meta<'a>>,
+{
+    let signatures = metas
+        .into_iter()
+        .flat_map(|meta| information(cx, meta, unit))
+        .collect::<Vec<_>>();
+
+    if signatures.is_empty() {
+        return None;
+    }
+
+    // The receiver of an instance call is passed as the first argument.
+    let active = call.active + usize::from(call.instance);
+
+    Some(SignatureHelp {
+        signatures,
+        active_signature: Some(0),
+        active_parameter: u32::try_from(active).ok(),
+    })
+}
+
+/// Construct the signature of a function, with the parameters labelled by
+/// their offsets in it.
+fn information(
+    cx: &Context<'_>,
+    meta: Meta<'_>,
+    unit: Option<&Unit>,
+) -> Option<SignatureInformation> {
+    let Kind::Function(f) = meta.kind else {
+        return None;
+    };
+
+    let name = meta.item?.to_string();
+
+    let mut label = String::new();
+
+    if f.is_async {
+        label.push_str("async ");
+    }
+
+    write!(label, "fn {}(", name.trim_start_matches("::")).ok()?;
+
+    let mut parameters = Vec::new();
+
+    for (n, arg) in hover::args(f, meta.hash, unit).into_iter().enumerate() {
+        if n > 0 {
+            label.push_str(", ");
+        }
+
+        let start = utf16_len(&label);
+        label.push_str(&arg);
+
+        parameters.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, utf16_len(&label)]),
+            documentation: None,
+        });
+    }
+
+    label.push(')');
+
+    if let Some(return_type) = hover::return_type(cx, f) {
+        write!(label, " -> {return_type}").ok()?;
+    }
+
+    let documentation = (!meta.docs.is_empty()).then(|| {
+        Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: meta.docs.join("\n"),
+        })
+    });
+
+    Some(SignatureInformation {
+        label,
+        documentation,
+        parameters: Some(parameters),
+        active_parameter: None,
+    })
+}
+
+/// Test if the given item ends with the given path, such as `String::new`.
+fn ends_with(item: &Item, path: &str) -> bool {
+    let mut components = item.iter();
+    path.rsplit("::")
+        .all(|name| components.next_back_str() == Some(name))
+}
+
+/// Test if the function with the given kind and item could be the one being
+/// called.
+fn matches(kind: &meta::Kind, item: &Item, call: &Call<'_>) -> bool {
+    match kind {
+        meta::Kind::AssociatedFunction {
+            kind: meta::AssociatedKind::Instance(name),
+            ..
+        } if call.instance => name.as_ref() == call.path,
+        meta::Kind::Function { .. } | meta::Kind::AssociatedFunction { .. } if !call.instance => {
+            ends_with(item, call.path)
+        }
+        _ => false,
+    }
+}
+
+/// The length of a string in UTF-16 code units, which is how offsets in labels
+/// are measured.
+fn utf16_len(string: &str) -> u32 {
+    string.encode_utf16().count() as u32
+}
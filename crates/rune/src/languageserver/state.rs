@@ -11,7 +11,7 @@ use lsp::Url;
 use ropey::Rope;
 use tokio::sync::Notify;
 
-use crate::ast::{Span, Spanned};
+use crate::ast::{self, Span, Spanned};
 use crate::compile::meta;
 use crate::compile::{
     self, CompileVisitor, ComponentRef, Item, ItemBuf, LinkerError, Location, MetaRef, SourceMeta,
@@ -21,7 +21,7 @@ use crate::doc::VisitorData;
 use crate::languageserver::connection::Output;
 use crate::languageserver::Language;
 use crate::workspace::{self, WorkspaceError};
-use crate::{BuildError, Context, Options, SourceId, Unit};
+use crate::{BuildError, Context, Hash, Options, SourceId, Unit};
 
 #[derive(Default)]
 struct Reporter {
@@ -172,7 +172,10 @@ impl<'a> State<'a> {
             None => uri.clone(),
         };
 
-        let source = source.build_sources.as_ref()?.get(def.source.source_id())?;
+        let source = source
+            .build_sources
+            .as_ref()?
+            .get(def.source.source_id()?)?;
 
         let (l, c) = source.pos_to_utf16cu_linecol(def.source.span().start.into_usize());
         let start = lsp::Position {
@@ -194,6 +197,415 @@ impl<'a> State<'a> {
         Some(location)
     }
 
+    /// Find hover information for the item at the given uri and LSP position.
+    pub(super) fn hover(&self, uri: &Url, position: lsp::Position) -> Option<lsp::Hover> {
+        let source = self.workspace.get(uri)?;
+        let offset = source.lsp_position_to_byte(position).ok()?;
+        let def = source.find_definition_at(Span::point(offset))?;
+        let hash = def.hash?;
+
+        let visitors = match &source.docs {
+            Some(docs) => std::slice::from_ref(&**docs),
+            None => &[],
+        };
+
+        let cx = crate::doc::Context::new(&self.context, visitors);
+        let meta = cx.meta_by_hash(hash).into_iter().next()?;
+        let contents = super::hover::contents(&cx, meta, def.kind, source.unit.as_ref())?;
+        tracing::trace!(?hash, "hover: {:?}", contents);
+
+        Some(lsp::Hover {
+            contents: lsp::HoverContents::Markup(contents),
+            range: None,
+        })
+    }
+
+    /// Find signature help for the call being typed at the given uri and LSP
+    /// position.
+    pub(super) fn signature_help(
+        &self,
+        uri: &Url,
+        position: lsp::Position,
+    ) -> Option<lsp::SignatureHelp> {
+        let source = self.workspace.get(uri)?;
+        let text = source.to_string();
+        let offset = source.lsp_position_to_byte(position).ok()?;
+        let call = super::signature::call_at(&text, offset)?;
+
+        let visitors = match &source.docs {
+            Some(docs) => std::slice::from_ref(&**docs),
+            None => &[],
+        };
+
+        let cx = crate::doc::Context::new(&self.context, visitors);
+        let unit = source.unit.as_ref();
+
+        // Prefer the function the call was compiled to, and fall back to
+        // finding functions by name while the call isn't compiled.
+        let definition = source
+            .find_definition_at(Span::point(call.span.end.into_usize().saturating_sub(1)))
+            .filter(|d| {
+                matches!(
+                    d.kind,
+                    DefinitionKind::Function | DefinitionKind::AssociatedFunction
+                )
+            })
+            .and_then(|d| d.hash);
+
+        let metas = match definition {
+            Some(hash) => cx.meta_by_hash(hash).into_iter().take(1).collect(),
+            None => {
+                super::signature::find_by_name(&cx, &self.context, source.docs.as_deref(), &call)
+            }
+        };
+
+        super::signature::help(&cx, metas, unit, &call)
+    }
+
+    /// Find inlay hints for the given uri and LSP range.
+    ///
+    /// This names the parameters arguments are passed to, and shows the values
+    /// of constants.
+    pub(super) fn inlay_hints(&self, uri: &Url, range: lsp::Range) -> Option<Vec<lsp::InlayHint>> {
+        let source = self.workspace.get(uri)?;
+        let span = source.lsp_range_to_span(range).ok()?;
+        let build_source = source.build_sources.as_ref()?.get(source.source_id?)?;
+
+        let visitors = match &source.docs {
+            Some(docs) => std::slice::from_ref(&**docs),
+            None => &[],
+        };
+
+        let cx = crate::doc::Context::new(&self.context, visitors);
+        let unit = source.unit.as_ref();
+
+        let mut hints = Vec::new();
+        let mut names = HashMap::new();
+
+        for (arg, &(hash, n)) in &source.index.arguments {
+            if !overlaps(span, *arg) {
+                continue;
+            }
+
+            let names = names.entry(hash).or_insert_with(|| {
+                cx.meta_by_hash(hash)
+                    .into_iter()
+                    .find_map(|meta| match meta.kind {
+                        crate::doc::context::Kind::Function(f) => {
+                            super::hover::arg_names(f, hash, unit)
+                        }
+                        _ => None,
+                    })
+            });
+
+            let Some(name) = names.as_ref().and_then(|names| names.get(n)) else {
+                continue;
+            };
+
+            // Hints for arguments which are named after the parameter, or for
+            // parameters without a proper name, are just noise.
+            if !is_identifier(name) || name.starts_with('_') {
+                continue;
+            }
+
+            if build_source.get(arg.range()) == Some(name.as_str()) {
+                continue;
+            }
+
+            let Some(range) = span_to_lsp_range(build_source, *arg) else {
+                continue;
+            };
+
+            hints.push(super::inlay::parameter(range.start, name));
+        }
+
+        for (item, hash) in &source.index.constants {
+            if !overlaps(span, *item) {
+                continue;
+            }
+
+            let Some(value) = unit
+                .and_then(|unit| unit.constant(*hash))
+                .and_then(super::inlay::render)
+            else {
+                continue;
+            };
+
+            // Constants which are already written out as their value don't
+            // need a hint.
+            let expr = source
+                .index
+                .references
+                .range(Span::point(item.start)..Span::point(item.end))
+                .find(|(_, references)| {
+                    references
+                        .iter()
+                        .any(|r| r.declaration && r.symbol == Symbol::Item(*hash))
+                })
+                .and_then(|(name, _)| {
+                    build_source.get(name.end.into_usize()..item.end.into_usize())
+                })
+                .and_then(|rest| rest.trim_start().strip_prefix('='))
+                .map(str::trim);
+
+            if expr == Some(value.as_str()) {
+                continue;
+            }
+
+            let Some(range) = span_to_lsp_range(build_source, *item) else {
+                continue;
+            };
+
+            hints.push(super::inlay::constant(range.end, &value));
+        }
+
+        hints.sort_by_key(|hint| (hint.position.line, hint.position.character));
+        Some(hints)
+    }
+
+    /// Find all references to the symbol at the given uri and LSP position.
+    pub(super) fn references(
+        &self,
+        uri: &Url,
+        position: lsp::Position,
+        include_declaration: bool,
+    ) -> Option<Vec<lsp::Location>> {
+        let (_, _, found) = self.find_references(uri, position)?;
+
+        let locations = found
+            .into_iter()
+            .filter(|f| include_declaration || !f.declaration)
+            .map(|f| lsp::Location {
+                uri: f.url.clone(),
+                range: f.range,
+            })
+            .collect::<Vec<_>>();
+
+        tracing::trace!("references: {:?}", locations);
+        Some(locations)
+    }
+
+    /// Rename the symbol at the given uri and LSP position.
+    ///
+    /// Only symbols which are declared in the workspace can be renamed.
+    pub(super) fn rename(
+        &self,
+        uri: &Url,
+        position: lsp::Position,
+        new_name: &str,
+    ) -> Option<lsp::WorkspaceEdit> {
+        if !is_identifier(new_name) {
+            tracing::warn!(new_name, "not a valid identifier");
+            return None;
+        }
+
+        let (name, symbol, found) = self.find_references(uri, position)?;
+
+        if !found.iter().any(|f| f.declaration) {
+            tracing::warn!(
+                name,
+                "cannot rename symbol declared outside of the workspace"
+            );
+            return None;
+        }
+
+        let mut changes = HashMap::<Url, Vec<lsp::TextEdit>>::new();
+
+        for f in found {
+            // Field shorthands both use the field and the local variable, so
+            // they are expanded to keep referring to the one not renamed.
+            let new_text = match (&symbol, f.shorthand) {
+                (Symbol::Field(..), true) => format!("{new_name}: {name}"),
+                (Symbol::Local(..), true) => format!("{name}: {new_name}"),
+                _ => new_name.to_owned(),
+            };
+
+            changes
+                .entry(f.url.clone())
+                .or_default()
+                .push(lsp::TextEdit::new(f.range, new_text));
+        }
+
+        Some(lsp::WorkspaceEdit::new(changes))
+    }
+
+    /// Find the symbol at the given uri and LSP position, its name and every
+    /// reference to it.
+    ///
+    /// Items and fields are searched for in every source which was part of
+    /// the same build, locals only in the source they are declared in.
+    fn find_references(
+        &self,
+        uri: &Url,
+        position: lsp::Position,
+    ) -> Option<(String, Symbol, Vec<FoundReference<'_>>)> {
+        let source = self.workspace.get(uri)?;
+        let offset = source.lsp_position_to_byte(position).ok()?;
+        let (span, symbol) = source.find_symbol_at(Span::point(offset))?;
+        let build_sources = source.build_sources.as_ref()?;
+
+        let name = match &symbol {
+            Symbol::Field(_, field) => field.to_string(),
+            _ => {
+                let text = build_sources.get(source.source_id?)?.get(span.range())?;
+                let name = text.rsplit("::").next()?.trim();
+
+                if !is_identifier(name) {
+                    return None;
+                }
+
+                name.to_owned()
+            }
+        };
+
+        let mut found = Vec::new();
+
+        for (url, other) in &self.workspace.sources {
+            if matches!(symbol, Symbol::Local(..)) && url != uri {
+                continue;
+            }
+
+            let (Some(other_sources), Some(source_id)) = (&other.build_sources, other.source_id)
+            else {
+                continue;
+            };
+
+            if !Arc::ptr_eq(build_sources, other_sources) {
+                continue;
+            }
+
+            let Some(input) = build_sources.get(source_id) else {
+                continue;
+            };
+
+            for (span, references) in &other.index.references {
+                let Some(reference) = references.iter().find(|r| r.symbol == symbol) else {
+                    continue;
+                };
+
+                let Some(text) = input.get(span.range()) else {
+                    continue;
+                };
+
+                // Only the trailing name of a path such as `foo::bar` refers
+                // to the symbol.
+                let span = if text == name {
+                    *span
+                } else if text.ends_with(&format!("::{name}")) {
+                    Span::new(span.end.into_usize() - name.len(), span.end)
+                } else {
+                    continue;
+                };
+
+                let Some(range) = span_to_lsp_range(input, span) else {
+                    continue;
+                };
+
+                found.push(FoundReference {
+                    url,
+                    range,
+                    declaration: reference.declaration,
+                    shorthand: references.len() > 1,
+                });
+            }
+        }
+
+        Some((name, symbol, found))
+    }
+
+    /// Collect the hierarchy of items declared in the source at the given uri.
+    pub(super) fn document_symbols(&self, uri: &Url) -> Option<Vec<lsp::DocumentSymbol>> {
+        let source = self.workspace.get(uri)?;
+        let input = crate::Source::new(uri, source.to_string());
+        super::symbols::document(&input)
+    }
+
+    /// Search for items matching the query in every built source and in the
+    /// context.
+    pub(super) fn workspace_symbols(&self, query: &str) -> Vec<lsp::WorkspaceSymbol> {
+        let mut matches = super::symbols::Matches::default();
+        let mut visited = Vec::<&Arc<crate::Sources>>::new();
+
+        for source in self.workspace.sources.values() {
+            let Some(build_sources) = &source.build_sources else {
+                continue;
+            };
+
+            if visited.iter().any(|v| Arc::ptr_eq(v, build_sources)) {
+                continue;
+            }
+
+            visited.push(build_sources);
+
+            for id in build_sources.source_ids() {
+                let Some(input) = build_sources.get(id) else {
+                    continue;
+                };
+
+                let Some(path) = input.path() else {
+                    continue;
+                };
+
+                let Ok(url) = crate::languageserver::url::from_file_path(path) else {
+                    continue;
+                };
+
+                super::symbols::workspace(&url, input, query, &mut matches);
+            }
+        }
+
+        super::symbols::context(&self.context, query, &mut matches);
+        let symbols = matches.into_sorted();
+        tracing::trace!(query, "workspace symbols: {}", symbols.len());
+        symbols
+    }
+
+    /// Format the source at the given uri.
+    pub(super) fn format(&self, uri: &Url) -> Result<Option<Vec<lsp::TextEdit>>> {
+        let Some(source) = self.workspace.get(uri) else {
+            return Ok(None);
+        };
+
+        let input = crate::Source::new(uri, source.to_string());
+        let output = String::from_utf8(crate::fmt::layout_source(&input)?)?;
+
+        if output == input.as_str() {
+            return Ok(Some(Vec::new()));
+        }
+
+        let end = input.len();
+        let range = span_to_lsp_range(&input, Span::new(0, end)).context("invalid range")?;
+        Ok(Some(vec![lsp::TextEdit::new(range, output)]))
+    }
+
+    /// Format the items in the source at the given uri which overlap with the
+    /// given range.
+    pub(super) fn format_range(
+        &self,
+        uri: &Url,
+        range: lsp::Range,
+    ) -> Result<Option<Vec<lsp::TextEdit>>> {
+        let Some(source) = self.workspace.get(uri) else {
+            return Ok(None);
+        };
+
+        let input = crate::Source::new(uri, source.to_string());
+        let span = source.lsp_range_to_span(range)?;
+
+        let Some((span, output)) = crate::fmt::layout_source_span(&input, span)? else {
+            return Ok(Some(Vec::new()));
+        };
+
+        let output = String::from_utf8(output)?;
+
+        if input.get(span.range()) == Some(output.as_str()) {
+            return Ok(Some(Vec::new()));
+        }
+
+        let range = span_to_lsp_range(&input, span).context("invalid range")?;
+        Ok(Some(vec![lsp::TextEdit::new(range, output)]))
+    }
+
     /// Find definition at the given uri and LSP position.
     #[tracing::instrument(skip_all)]
     pub(super) fn complete(
@@ -341,6 +753,7 @@ impl<'a> State<'a> {
                 };
 
                 source.index = value;
+                source.source_id = Some(source_id);
                 source.build_sources = Some(sources.clone());
 
                 if let Ok(unit) = unit.as_ref().map(|v| v.clone()) {
@@ -547,6 +960,7 @@ impl Workspace {
         let source = Source {
             content: Rope::from(text),
             index: Default::default(),
+            source_id: None,
             build_sources: None,
             language,
             unit: None,
@@ -579,6 +993,8 @@ pub(super) struct Source {
     content: Rope,
     /// Indexes used to answer queries.
     index: Index,
+    /// The id of the source in its loaded Rune sources.
+    source_id: Option<SourceId>,
     /// Loaded Rune sources for this source file. Will be present after the
     /// source file has been built.
     build_sources: Option<Arc<crate::Sources>>,
@@ -593,7 +1009,11 @@ pub(super) struct Source {
 impl Source {
     /// Find the definition at the given span.
     pub(super) fn find_definition_at(&self, span: Span) -> Option<&Definition> {
-        let (found_span, definition) = self.index.definitions.range(..=span).rev().next()?;
+        let (found_span, definition) = self
+            .index
+            .definitions
+            .range(..=starting_at(span))
+            .next_back()?;
 
         if span.start >= found_span.start && span.end <= found_span.end {
             tracing::trace!("found {:?}", definition);
@@ -603,6 +1023,23 @@ impl Source {
         None
     }
 
+    /// Find the symbol declared or used at the given span.
+    fn find_symbol_at(&self, span: Span) -> Option<(Span, Symbol)> {
+        let (found_span, references) = self
+            .index
+            .references
+            .range(..=starting_at(span))
+            .next_back()?;
+
+        if span.start >= found_span.start && span.end <= found_span.end {
+            let reference = references.first()?;
+            tracing::trace!("found {:?}", reference);
+            return Some((*found_span, reference.symbol.clone()));
+        }
+
+        None
+    }
+
     /// Modify the given lsp range in the file.
     pub(super) fn modify_lsp_range(&mut self, range: lsp::Range, content: &str) -> Result<()> {
         let start = rope_utf16_position(&self.content, range.start)?;
@@ -616,6 +1053,19 @@ impl Source {
         Ok(())
     }
 
+    /// Convert an lsp range into a span of byte offsets in the source.
+    fn lsp_range_to_span(&self, range: lsp::Range) -> Result<Span> {
+        let start = self.lsp_position_to_byte(range.start)?;
+        let end = self.lsp_position_to_byte(range.end)?;
+        Ok(Span::new(start, end))
+    }
+
+    /// Convert an lsp position into a byte offset in the source.
+    fn lsp_position_to_byte(&self, position: lsp::Position) -> Result<usize> {
+        let offset = rope_utf16_position(&self.content, position)?;
+        Ok(self.content.char_to_byte(offset))
+    }
+
     /// Offset in the rope to lsp position.
     fn lsp_position_to_offset(&self, position: lsp::Position) -> usize {
         let line = self.content.line_to_char(position.line as usize);
@@ -658,7 +1108,7 @@ impl fmt::Display for Source {
 }
 
 /// Convert the given span into an lsp range.
-fn span_to_lsp_range(source: &crate::Source, span: Span) -> Option<lsp::Range> {
+pub(super) fn span_to_lsp_range(source: &crate::Source, span: Span) -> Option<lsp::Range> {
     let (line, character) = source.pos_to_utf16cu_linecol(span.start.into_usize());
     let start = lsp::Position::new(line as u32, character as u32);
     let (line, character) = source.pos_to_utf16cu_linecol(span.end.into_usize());
@@ -671,7 +1121,9 @@ fn span_to_lsp_range(source: &crate::Source, span: Span) -> Option<lsp::Range> {
 /// Please go complain here:
 /// <https://github.com/microsoft/language-server-protocol/issues/376>
 fn rope_utf16_position(rope: &Rope, position: lsp::Position) -> Result<usize> {
-    let line = rope.line(position.line as usize);
+    let line = rope
+        .get_line(position.line as usize)
+        .ok_or_else(|| anyhow!("line is out of bounds"))?;
 
     // encoding target.
     let character = position.character as usize;
@@ -773,10 +1225,84 @@ where
     }
 }
 
+/// Test if the given string is a valid identifier.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    matches!(chars.next(), Some('a'..='z' | 'A'..='Z' | '_'))
+        && chars.all(|c| matches!(c, 'a'..='z' | 'A'..='Z' | '_' | '0'..='9'))
+        && ast::Kind::from_keyword(name).is_none()
+}
+
+/// Test if two spans overlap, including if they only touch.
+fn overlaps(a: Span, b: Span) -> bool {
+    a.start <= b.end && b.start <= a.end
+}
+
+/// The greatest span which starts at the same position as the given span,
+/// used as the upper bound when looking for spans which contain it.
+fn starting_at(span: Span) -> Span {
+    Span::new(span.start, u32::MAX)
+}
+
 #[derive(Default)]
 pub(super) struct Index {
     /// Spans mapping to their corresponding definitions.
     definitions: BTreeMap<Span, Definition>,
+    /// Spans mapping to the symbols declared or used at them.
+    references: BTreeMap<Span, Vec<Reference>>,
+    /// Spans of positional arguments mapping to the hash of the function they
+    /// are passed to, and their position.
+    arguments: BTreeMap<Span, (Hash, usize)>,
+    /// Spans of constant items mapping to their hashes.
+    constants: BTreeMap<Span, Hash>,
+}
+
+impl Index {
+    /// Record a declaration or use of a symbol at the given span.
+    fn insert_reference(&mut self, span: Span, symbol: Symbol, declaration: bool) {
+        let references = self.references.entry(span).or_default();
+
+        if !references.iter().any(|r| r.symbol == symbol) {
+            references.push(Reference {
+                symbol,
+                declaration,
+            });
+        }
+    }
+}
+
+/// Something which can be referenced and renamed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Symbol {
+    /// An item, such as a function or a struct.
+    Item(Hash),
+    /// A named field of the struct or variant with the given hash.
+    Field(Hash, Box<str>),
+    /// A local variable, identified by the span it is declared at.
+    Local(Span),
+}
+
+/// A declaration or use of a symbol.
+#[derive(Debug, Clone)]
+pub(super) struct Reference {
+    /// The symbol being referenced.
+    symbol: Symbol,
+    /// Indicates if this is where the symbol is declared.
+    declaration: bool,
+}
+
+/// A reference found in the workspace.
+struct FoundReference<'a> {
+    /// The url of the source the reference is in.
+    url: &'a Url,
+    /// The range of the name being referenced.
+    range: lsp::Range,
+    /// Indicates if this is where the symbol is declared.
+    declaration: bool,
+    /// Indicates that the reference is a field shorthand such as `Point { x }`,
+    /// which refers to both a field and a local variable.
+    shorthand: bool,
 }
 
 /// A definition source.
@@ -788,22 +1314,25 @@ pub(super) enum DefinitionSource {
     Location(Location),
     /// A complete compile source.
     SourceMeta(SourceMeta),
+    /// A definition in the context, which has no source.
+    Context,
 }
 
 impl DefinitionSource {
     fn span(&self) -> Span {
         match self {
-            Self::Source(..) => Span::empty(),
+            Self::Source(..) | Self::Context => Span::empty(),
             Self::Location(location) => location.span,
             Self::SourceMeta(compile_source) => compile_source.location.span,
         }
     }
 
-    fn source_id(&self) -> SourceId {
+    fn source_id(&self) -> Option<SourceId> {
         match self {
-            Self::Source(source_id) => *source_id,
-            Self::Location(location) => location.source_id,
-            Self::SourceMeta(compile_source) => compile_source.location.source_id,
+            Self::Source(source_id) => Some(*source_id),
+            Self::Location(location) => Some(location.source_id),
+            Self::SourceMeta(compile_source) => Some(compile_source.location.source_id),
+            Self::Context => None,
         }
     }
 
@@ -821,6 +1350,8 @@ pub(super) struct Definition {
     pub(super) kind: DefinitionKind,
     /// The id of the source id the definition corresponds to.
     pub(super) source: DefinitionSource,
+    /// The hash of the item being defined, if the definition is an item.
+    pub(super) hash: Option<Hash>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -843,6 +1374,8 @@ pub(super) enum DefinitionKind {
     Function,
     /// An associated function.
     AssociatedFunction,
+    /// A constant.
+    Const,
     /// A local variable.
     Local,
     /// A module that can be jumped to.
@@ -862,9 +1395,19 @@ impl Visitor {
 }
 
 impl CompileVisitor for Visitor {
+    fn register_meta(&mut self, meta: MetaRef<'_>) {
+        let (meta::Kind::Const, Some(source)) = (&meta.kind, meta.source) else {
+            return;
+        };
+
+        let index = self.indexes.entry(source.location.source_id).or_default();
+        index.constants.insert(source.location.span, meta.hash);
+    }
+
     fn visit_meta(&mut self, location: Location, meta: MetaRef<'_>) {
         let source = match meta.source {
-            Some(source) => source,
+            Some(source) => DefinitionSource::SourceMeta(source.clone()),
+            None if meta.context => DefinitionSource::Context,
             None => return,
         };
 
@@ -896,15 +1439,18 @@ impl CompileVisitor for Visitor {
             meta::Kind::Enum { .. } => DefinitionKind::Enum,
             meta::Kind::Function { .. } => DefinitionKind::Function,
             meta::Kind::AssociatedFunction { .. } => DefinitionKind::AssociatedFunction,
+            meta::Kind::Const => DefinitionKind::Const,
             _ => return,
         };
 
         let definition = Definition {
             kind,
-            source: DefinitionSource::SourceMeta(source.clone()),
+            source,
+            hash: Some(meta.hash),
         };
 
         let index = self.indexes.entry(location.source_id).or_default();
+        index.insert_reference(location.span, Symbol::Item(meta.hash), false);
 
         if let Some(d) = index.definitions.insert(location.span, definition) {
             tracing::warn!("Replaced definition: {:?}", d.kind)
@@ -920,9 +1466,13 @@ impl CompileVisitor for Visitor {
         let definition = Definition {
             kind: DefinitionKind::Local,
             source: DefinitionSource::Location(Location::new(source_id, var_span.span())),
+            hash: None,
         };
 
         let index = self.indexes.entry(source_id).or_default();
+        let symbol = Symbol::Local(var_span.span());
+        index.insert_reference(var_span.span(), symbol.clone(), true);
+        index.insert_reference(span.span(), symbol, false);
 
         if let Some(d) = index.definitions.insert(span.span(), definition) {
             tracing::warn!("replaced definition: {:?}", d.kind)
@@ -933,6 +1483,7 @@ impl CompileVisitor for Visitor {
         let definition = Definition {
             kind: DefinitionKind::Module,
             source: DefinitionSource::Source(source_id),
+            hash: None,
         };
 
         let index = self.indexes.entry(source_id).or_default();
@@ -941,6 +1492,31 @@ impl CompileVisitor for Visitor {
             tracing::warn!("replaced definition: {:?}", d.kind)
         }
     }
+
+    fn visit_item_name(&mut self, location: Location, hash: Hash) {
+        let index = self.indexes.entry(location.source_id).or_default();
+        index.insert_reference(location.span, Symbol::Item(hash), true);
+    }
+
+    fn visit_field_name(&mut self, location: Location, hash: Hash, field: &str) {
+        let index = self.indexes.entry(location.source_id).or_default();
+        index.insert_reference(location.span, Symbol::Field(hash, field.into()), true);
+    }
+
+    fn visit_field_use(&mut self, location: Location, hash: Hash, field: &str) {
+        let index = self.indexes.entry(location.source_id).or_default();
+        index.insert_reference(location.span, Symbol::Field(hash, field.into()), false);
+    }
+
+    fn visit_call(&mut self, source_id: SourceId, hash: Hash, ast: &ast::ExprCall) {
+        let index = self.indexes.entry(source_id).or_default();
+
+        let positional = ast.args.iter().take_while(|(arg, _)| arg.name.is_none());
+
+        for (n, (arg, _)) in positional.enumerate() {
+            index.arguments.insert(arg.value.span(), (hash, n));
+        }
+    }
 }
 
 struct ScriptSourceLoader<'a> {
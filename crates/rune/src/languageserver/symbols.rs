@@ -0,0 +1,282 @@
+use crate::no_std::prelude::*;
+
+use lsp::{DocumentSymbol, SymbolKind, Url, WorkspaceSymbol};
+
+use crate::ast::{self, Span, Spanned};
+use crate::compile::meta;
+use crate::{Context, SourceId};
+
+use super::state::span_to_lsp_range;
+
+/// Collect the hierarchy of items declared in the given source.
+///
+/// Returns `None` if the source cannot be parsed.
+pub(super) fn document(source: &crate::Source) -> Option<Vec<DocumentSymbol>> {
+    let file =
+        crate::parse::parse_all::<ast::File>(source.as_str(), SourceId::empty(), true).ok()?;
+    Some(items(source, &file))
+}
+
+/// Workspace symbols matching a query, together with how well they match.
+#[derive(Default)]
+pub(super) struct Matches {
+    matches: Vec<(Score, WorkspaceSymbol)>,
+}
+
+impl Matches {
+    /// Get the matched symbols, with the best matches first.
+    pub(super) fn into_sorted(mut self) -> Vec<WorkspaceSymbol> {
+        self.matches.sort_by_key(|(score, _)| *score);
+        self.matches.into_iter().map(|(_, symbol)| symbol).collect()
+    }
+
+    fn push(&mut self, query: &str, symbol: WorkspaceSymbol) {
+        if let Some(score) = Score::of(query, &symbol.name) {
+            self.matches.push((score, symbol));
+        }
+    }
+}
+
+/// How well a name matches a query, ordered from the best match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Score {
+    Exact,
+    Prefix,
+    Substring,
+    Fuzzy,
+}
+
+impl Score {
+    /// Match a name against a query, ignoring case. Every character in the
+    /// query has to appear in order in the name.
+    fn of(query: &str, name: &str) -> Option<Self> {
+        let query = query
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .flat_map(char::to_lowercase)
+            .collect::<String>();
+        let name = name.to_lowercase();
+
+        if name == query {
+            return Some(Self::Exact);
+        }
+
+        if name.starts_with(&query) {
+            return Some(Self::Prefix);
+        }
+
+        if name.contains(&query) {
+            return Some(Self::Substring);
+        }
+
+        let mut chars = name.chars();
+
+        if query.chars().all(|q| chars.any(|c| c == q)) {
+            return Some(Self::Fuzzy);
+        }
+
+        None
+    }
+}
+
+/// Collect the items declared in the given source which match the query.
+pub(super) fn workspace(url: &Url, source: &crate::Source, query: &str, out: &mut Matches) {
+    let Some(symbols) = document(source) else {
+        return;
+    };
+
+    flatten(url, symbols, None, query, out);
+}
+
+/// Collect the items in the context which match the query.
+///
+/// Items in the context have no source, so they are given a `rune:` url
+/// naming the item.
+pub(super) fn context(context: &Context, query: &str, out: &mut Matches) {
+    for meta in context.iter_meta() {
+        let Some(item) = &meta.item else {
+            continue;
+        };
+
+        let Some(name) = item.last() else {
+            continue;
+        };
+
+        let kind = match &meta.kind {
+            meta::Kind::Type { .. } | meta::Kind::Struct { .. } => SymbolKind::STRUCT,
+            meta::Kind::Variant { .. } => SymbolKind::ENUM_MEMBER,
+            meta::Kind::Enum { .. } => SymbolKind::ENUM,
+            meta::Kind::Function { .. } => SymbolKind::FUNCTION,
+            meta::Kind::AssociatedFunction { .. } => SymbolKind::METHOD,
+            meta::Kind::Const => SymbolKind::CONSTANT,
+            meta::Kind::Trait { .. } => SymbolKind::INTERFACE,
+            meta::Kind::Module => SymbolKind::MODULE,
+            _ => continue,
+        };
+
+        let path = item.to_string();
+
+        let Ok(uri) = Url::parse(&format!("rune:{}", path.trim_start_matches("::"))) else {
+            continue;
+        };
+
+        let container_name = item
+            .parent()
+            .filter(|p| !p.is_empty())
+            .map(|p| p.to_string().trim_start_matches("::").to_owned());
+
+        let symbol = WorkspaceSymbol {
+            name: name.to_string(),
+            kind,
+            tags: None,
+            container_name,
+            location: lsp::OneOf::Right(lsp::WorkspaceLocation { uri }),
+            data: None,
+        };
+
+        out.push(query, symbol);
+    }
+}
+
+/// Flatten document symbols into workspace symbols matching the query.
+fn flatten(
+    url: &Url,
+    symbols: Vec<DocumentSymbol>,
+    container_name: Option<&str>,
+    query: &str,
+    out: &mut Matches,
+) {
+    for symbol in symbols {
+        out.push(
+            query,
+            WorkspaceSymbol {
+                name: symbol.name.clone(),
+                kind: symbol.kind,
+                tags: None,
+                container_name: container_name.map(str::to_owned),
+                location: lsp::OneOf::Left(lsp::Location {
+                    uri: url.clone(),
+                    range: symbol.selection_range,
+                }),
+                data: None,
+            },
+        );
+
+        if let Some(children) = symbol.children {
+            flatten(url, children, Some(&symbol.name), query, out);
+        }
+    }
+}
+
+fn items(source: &crate::Source, file: &ast::File) -> Vec<DocumentSymbol> {
+    let mut symbols = Vec::new();
+
+    for (item, _) in &file.items {
+        let symbol = match item {
+            ast::Item::Fn(ast) => symbol(source, ast, ast.name.span(), SymbolKind::FUNCTION),
+            ast::Item::Enum(ast) => {
+                let variants = ast
+                    .variants
+                    .iter()
+                    .flat_map(|(variant, _)| {
+                        let symbol = symbol(
+                            source,
+                            variant,
+                            variant.name.span(),
+                            SymbolKind::ENUM_MEMBER,
+                        )?;
+                        Some(with_children(symbol, fields(source, &variant.body)))
+                    })
+                    .collect();
+
+                symbol(source, ast, ast.name.span(), SymbolKind::ENUM)
+                    .map(|s| with_children(s, variants))
+            }
+            ast::Item::Struct(ast) => symbol(source, ast, ast.ident.span(), SymbolKind::STRUCT)
+                .map(|s| with_children(s, fields(source, &ast.body))),
+            ast::Item::Impl(ast) => {
+                let functions = ast
+                    .functions
+                    .iter()
+                    .flat_map(|f| symbol(source, f, f.name.span(), SymbolKind::METHOD))
+                    .collect();
+
+                symbol(source, ast, ast.path.span(), SymbolKind::OBJECT).map(|mut s| {
+                    s.name = match &ast.trait_ {
+                        Some((path, _)) => match source.get(path.span().range()) {
+                            Some(trait_) => format!("impl {trait_} for {}", s.name),
+                            None => format!("impl {}", s.name),
+                        },
+                        None => format!("impl {}", s.name),
+                    };
+
+                    with_children(s, functions)
+                })
+            }
+            ast::Item::Trait(ast) => {
+                let functions = ast
+                    .functions
+                    .iter()
+                    .flat_map(|f| symbol(source, f, f.name.span(), SymbolKind::METHOD))
+                    .collect();
+
+                symbol(source, ast, ast.name.span(), SymbolKind::INTERFACE)
+                    .map(|s| with_children(s, functions))
+            }
+            ast::Item::Mod(ast) => {
+                let children = match &ast.body {
+                    ast::ItemModBody::InlineBody(body) => items(source, &body.file),
+                    ast::ItemModBody::EmptyBody(..) => Vec::new(),
+                };
+
+                symbol(source, ast, ast.name.span(), SymbolKind::MODULE)
+                    .map(|s| with_children(s, children))
+            }
+            ast::Item::Const(ast) => symbol(source, ast, ast.name.span(), SymbolKind::CONSTANT),
+            ast::Item::Use(..) | ast::Item::MacroCall(..) => None,
+        };
+
+        symbols.extend(symbol);
+    }
+
+    symbols
+}
+
+fn fields(source: &crate::Source, body: &ast::Fields) -> Vec<DocumentSymbol> {
+    let ast::Fields::Named(body) = body else {
+        return Vec::new();
+    };
+
+    body.iter()
+        .flat_map(|(field, _)| symbol(source, field, field.name.span(), SymbolKind::FIELD))
+        .collect()
+}
+
+/// Construct a symbol without children.
+fn symbol(
+    source: &crate::Source,
+    ast: &dyn Spanned,
+    name: Span,
+    kind: SymbolKind,
+) -> Option<DocumentSymbol> {
+    #[allow(deprecated)]
+    Some(DocumentSymbol {
+        name: source.get(name.range())?.to_owned(),
+        detail: None,
+        kind,
+        tags: None,
+        deprecated: None,
+        range: span_to_lsp_range(source, ast.span())?,
+        selection_range: span_to_lsp_range(source, name)?,
+        children: None,
+    })
+}
+
+/// Attach children to a symbol, if there are any.
+fn with_children(mut symbol: DocumentSymbol, children: Vec<DocumentSymbol>) -> DocumentSymbol {
+    if !children.is_empty() {
+        symbol.children = Some(children);
+    }
+
+    symbol
+}
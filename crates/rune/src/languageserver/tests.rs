@@ -0,0 +1,341 @@
+use serde_json::{json, Value};
+use tokio::io::{
+    AsyncBufReadExt as _, AsyncReadExt as _, AsyncWriteExt as _, BufReader, DuplexStream, ReadHalf,
+    WriteHalf,
+};
+
+use super::*;
+
+const URI: &str = "file:///rune-languageserver-tests/main.rn";
+
+const SCRIPT: &str = r#"struct Point { x, y }
+
+/// Scale the x coordinate of a point.
+fn scale(point, factor) {
+    let crab = "ünïcödé 🦀"; let total = point.x * factor;
+    total
+}
+
+pub fn main() {
+    let x = 1;
+    let p = Point { x, y: 2 };
+    let s = "日本"; scale(p, 2) + x
+}
+"#;
+
+/// A scripted client speaking the language server protocol.
+struct Client {
+    id: i64,
+    reader: BufReader<ReadHalf<DuplexStream>>,
+    writer: WriteHalf<DuplexStream>,
+}
+
+impl Client {
+    async fn request(&mut self, method: &str, params: Value) -> Value {
+        self.id += 1;
+
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "id": self.id,
+            "method": method,
+            "params": params,
+        }))
+        .await;
+
+        loop {
+            let message = self.receive().await;
+
+            if message["id"] == self.id {
+                return message["result"].clone();
+            }
+        }
+    }
+
+    async fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        }))
+        .await;
+    }
+
+    /// Wait for the given notification, skipping over anything else.
+    async fn notification(&mut self, method: &str) -> Value {
+        loop {
+            let message = self.receive().await;
+
+            if message["method"] == method {
+                return message["params"].clone();
+            }
+        }
+    }
+
+    async fn send(&mut self, message: Value) {
+        let bytes = serde_json::to_vec(&message).unwrap();
+        let header = format!("Content-Length: {}\r\n\r\n", bytes.len());
+        self.writer.write_all(header.as_bytes()).await.unwrap();
+        self.writer.write_all(&bytes).await.unwrap();
+    }
+
+    async fn receive(&mut self) -> Value {
+        let mut line = String::new();
+        let mut length = None;
+
+        loop {
+            line.clear();
+            self.reader.read_line(&mut line).await.unwrap();
+
+            let line = line.trim();
+
+            if line.is_empty() {
+                break;
+            }
+
+            if let Some(value) = line.strip_prefix("Content-Length:") {
+                length = Some(value.trim().parse::<usize>().unwrap());
+            }
+        }
+
+        let mut buf = vec![0; length.expect("content length")];
+        self.reader.read_exact(&mut buf).await.unwrap();
+        serde_json::from_slice(&buf).unwrap()
+    }
+}
+
+/// The position of the `nth` occurrence of `needle` in [SCRIPT], where
+/// characters are counted in UTF-16 code units.
+fn position(needle: &str, nth: usize) -> Value {
+    let (offset, _) = SCRIPT.match_indices(needle).nth(nth).expect("needle");
+    let line_start = SCRIPT[..offset].rfind('\n').map_or(0, |n| n + 1);
+    let line = SCRIPT[..offset].matches('\n').count();
+    let character = SCRIPT[line_start..offset].encode_utf16().count();
+    json!({ "line": line, "character": character })
+}
+
+/// The range covering the `nth` occurrence of `needle` in [SCRIPT].
+fn range(needle: &str, nth: usize) -> Value {
+    let start = position(needle, nth);
+    let character = start["character"].as_u64().unwrap() as usize + needle.encode_utf16().count();
+    let end = json!({ "line": start["line"], "character": character });
+    json!({ "start": start, "end": end })
+}
+
+/// Run a language server with [SCRIPT] opened and built, and drive it
+/// through the client used by `f`.
+async fn with_client<F, O>(f: F)
+where
+    F: FnOnce(Client) -> O,
+    O: core::future::Future<Output = Client>,
+{
+    let context = Context::with_default_modules().unwrap();
+
+    let (client_io, server_io) = tokio::io::duplex(1 << 16);
+    let (server_read, server_write) = tokio::io::split(server_io);
+    let (client_read, client_write) = tokio::io::split(client_io);
+
+    let (input, output) = connection::from_io(BufReader::new(server_read), server_write);
+    let server = serve(input, output, context, Options::default());
+
+    let client = async move {
+        let mut client = Client {
+            id: 0,
+            reader: BufReader::new(client_read),
+            writer: client_write,
+        };
+
+        client
+            .request("initialize", json!({ "capabilities": {} }))
+            .await;
+        client.notify("initialized", json!({})).await;
+
+        let document = json!({
+            "uri": URI,
+            "languageId": "rune",
+            "version": 1,
+            "text": SCRIPT,
+        });
+
+        client
+            .notify("textDocument/didOpen", json!({ "textDocument": document }))
+            .await;
+
+        let diagnostics = client.notification("textDocument/publishDiagnostics").await;
+        assert_eq!(diagnostics["diagnostics"], json!([]), "{diagnostics}");
+
+        let mut client = f(client).await;
+        client.request("shutdown", Value::Null).await;
+    };
+
+    let (result, ()) = tokio::join!(server, client);
+    result.unwrap();
+}
+
+#[tokio::test]
+async fn test_hover() {
+    with_client(|mut client| async move {
+        let hover = client
+            .request(
+                "textDocument/hover",
+                json!({ "textDocument": { "uri": URI }, "position": position("scale(p,", 0) }),
+            )
+            .await;
+
+        let contents = hover["contents"]["value"].as_str().unwrap();
+        assert!(contents.contains("fn scale(point, factor)"), "{contents}");
+        assert!(contents.contains("Scale the x coordinate"), "{contents}");
+
+        // Locals have no documentation to show.
+        let hover = client
+            .request(
+                "textDocument/hover",
+                json!({ "textDocument": { "uri": URI }, "position": position("total", 1) }),
+            )
+            .await;
+
+        assert_eq!(hover, Value::Null);
+        client
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_references_and_rename() {
+    with_client(|mut client| async move {
+        let references = client
+            .request(
+                "textDocument/references",
+                json!({
+                    "textDocument": { "uri": URI },
+                    "position": position("total", 1),
+                    "context": { "includeDeclaration": false },
+                }),
+            )
+            .await;
+
+        assert_eq!(
+            references,
+            json!([{ "uri": URI, "range": range("total", 1) }])
+        );
+
+        // Renaming a local expands the field shorthand it's used in.
+        let edit = client
+            .request(
+                "textDocument/rename",
+                json!({
+                    "textDocument": { "uri": URI },
+                    "position": position("x = 1", 0),
+                    "newName": "first",
+                }),
+            )
+            .await;
+
+        assert_eq!(
+            edit["changes"][URI],
+            json!([
+                { "range": range("x", 3), "newText": "first" },
+                { "range": range("x", 4), "newText": "x: first" },
+                { "range": range("x", 5), "newText": "first" },
+            ])
+        );
+
+        // Renaming a field keeps the shorthand referring to the local.
+        let edit = client
+            .request(
+                "textDocument/rename",
+                json!({
+                    "textDocument": { "uri": URI },
+                    "position": position("x", 0),
+                    "newName": "first",
+                }),
+            )
+            .await;
+
+        assert_eq!(
+            edit["changes"][URI],
+            json!([
+                { "range": range("x", 0), "newText": "first" },
+                { "range": range("x", 4), "newText": "first: x" },
+            ])
+        );
+
+        client
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_symbols() {
+    with_client(|mut client| async move {
+        let symbols = client
+            .request(
+                "textDocument/documentSymbol",
+                json!({ "textDocument": { "uri": URI } }),
+            )
+            .await;
+
+        let names = symbols
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|symbol| symbol["name"].as_str().unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, ["Point", "scale", "main"]);
+        assert_eq!(symbols[0]["children"][1]["name"], "y");
+        assert_eq!(symbols[0]["children"][1]["range"], range("y", 0));
+        assert_eq!(symbols[1]["selectionRange"], range("scale", 0));
+
+        let symbols = client
+            .request("workspace/symbol", json!({ "query": "scale" }))
+            .await;
+
+        assert_eq!(
+            symbols,
+            json!([{
+                "name": "scale",
+                "kind": 12,
+                "location": { "uri": URI, "range": range("scale", 0) },
+            }])
+        );
+
+        client
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_signature_help_and_inlay_hints() {
+    with_client(|mut client| async move {
+        let help = client
+            .request(
+                "textDocument/signatureHelp",
+                json!({ "textDocument": { "uri": URI }, "position": position("2) + x", 0) }),
+            )
+            .await;
+
+        assert_eq!(help["activeParameter"], 1);
+        assert_eq!(help["signatures"][0]["label"], "fn scale(point, factor)");
+
+        let hints = client
+            .request(
+                "textDocument/inlayHint",
+                json!({
+                    "textDocument": { "uri": URI },
+                    "range": { "start": position("pub fn main", 0), "end": position("}\n", 2) },
+                }),
+            )
+            .await;
+
+        assert_eq!(
+            hints,
+            json!([
+                { "position": position("p, 2", 0), "label": "point:", "kind": 2, "paddingRight": true },
+                { "position": position("2) + x", 0), "label": "factor:", "kind": 2, "paddingRight": true },
+            ])
+        );
+
+        client
+    })
+    .await;
+}
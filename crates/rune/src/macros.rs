@@ -10,6 +10,17 @@
 //! The attribute macros [`rune::macro_`](crate::macro_) for function macros (`some_macro!( ... )`) and
 //! [`rune::attribute_macro`](crate::attribute_macro) for attribute macros (`#[some_macro ...]`).
 //!
+//! Scripts can also define their own declarative macros with `macro_rules!`.
+//! These are visible in the module they are defined in, and in any of its
+//! submodules which are defined in the same file:
+//!
+//! ```text
+//! macro_rules! max {
+//!     ($a:expr) => { $a };
+//!     ($a:expr, $($rest:expr),+) => {{ let a = $a; let b = max!($($rest),+); if a > b { a } else { b } }};
+//! }
+//! ```
+//!
 //! ```
 //! use rune::{T, Context, Diagnostics, Module, Vm};
 //! use rune::ast;
@@ -107,6 +118,7 @@ mod format_args;
 mod into_lit;
 mod macro_compiler;
 mod macro_context;
+mod macro_rules;
 mod quote_fn;
 mod storage;
 mod token_stream;
@@ -115,6 +127,7 @@ pub use self::format_args::FormatArgs;
 pub use self::into_lit::IntoLit;
 pub(crate) use self::macro_compiler::MacroCompiler;
 pub use self::macro_context::MacroContext;
+pub(crate) use self::macro_rules::MacroRules;
 pub use self::quote_fn::{quote_fn, Quote};
 pub(crate) use self::storage::Storage;
 pub use self::storage::{SyntheticId, SyntheticKind};
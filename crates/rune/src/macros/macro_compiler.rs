@@ -1,13 +1,14 @@
 //! Macro compiler.
 
 use crate::no_std::prelude::*;
+use crate::no_std::sync::Arc;
 
 use crate::ast;
 use crate::ast::Spanned;
-use crate::compile::{self, CompileErrorKind, ItemMeta};
+use crate::compile::{self, CompileErrorKind, ItemId, ItemMeta};
 use crate::indexing::Indexer;
-use crate::macros::{MacroContext, ToTokens};
-use crate::parse::{Parse, Parser};
+use crate::macros::{MacroContext, MacroRules, ToTokens};
+use crate::parse::{Parse, Parser, Resolve};
 
 use super::TokenStream;
 
@@ -24,6 +25,16 @@ impl MacroCompiler<'_, '_> {
     {
         let span = macro_call.span();
 
+        if let Some(name) = &macro_call.name {
+            let kind = if MacroRules::is_definition(resolve_context!(self.idx.q), macro_call)? {
+                CompileErrorKind::UnsupportedMacroDefinition
+            } else {
+                CompileErrorKind::UnexpectedMacroName
+            };
+
+            return Err(compile::Error::new(name, kind));
+        }
+
         if !self.idx.q.options.macros {
             return Err(compile::Error::msg(
                 span,
@@ -44,23 +55,27 @@ impl MacroCompiler<'_, '_> {
         let path = crate::hir::lowering::path(&mut ctx, &macro_call.path)?;
         let named = self.idx.q.convert_path(&path)?;
 
-        let hash = self.idx.q.pool.item_type_hash(named.item);
-
-        let handler = match self.idx.q.context.lookup_macro(hash) {
-            Some(handler) => handler,
-            None => {
-                return Err(compile::Error::new(
-                    span,
-                    CompileErrorKind::MissingMacro {
-                        item: self.idx.q.pool.item(named.item).to_owned(),
-                    },
-                ));
-            }
-        };
+        let token_stream = if let Some((item, macro_rules)) =
+            self.lookup_macro_rules(&macro_call.path, named.item)?
+        {
+            macro_rules.expand(&mut self.idx.q, item, macro_call)?
+        } else {
+            let hash = self.idx.q.pool.item_type_hash(named.item);
+
+            let handler = match self.idx.q.context.lookup_macro(hash) {
+                Some(handler) => handler,
+                None => {
+                    return Err(compile::Error::new(
+                        span,
+                        CompileErrorKind::MissingMacro {
+                            item: self.idx.q.pool.item(named.item).to_owned(),
+                        },
+                    ));
+                }
+            };
 
-        let input_stream = &macro_call.input;
+            let input_stream = &macro_call.input;
 
-        let token_stream = {
             let mut macro_context = MacroContext {
                 macro_span: macro_call.span(),
                 input_span: macro_call.input_span(),
@@ -78,6 +93,44 @@ impl MacroCompiler<'_, '_> {
         Ok(output)
     }
 
+    /// Look up a `macro_rules!` definition for the given path.
+    ///
+    /// A macro referenced by a single identifier is looked up in the current
+    /// module and its parents, while any other path has to name the macro
+    /// exactly. Only macros defined in the current source are visible.
+    fn lookup_macro_rules(
+        &mut self,
+        path: &ast::Path,
+        item: ItemId,
+    ) -> compile::Result<Option<(ItemId, Arc<MacroRules>)>> {
+        let source_id = self.item_meta.location.source_id;
+
+        let Some(ident) = path.try_as_ident() else {
+            let macro_rules = self.idx.q.macro_rules_for(item);
+            return Ok(macro_rules
+                .filter(|m| m.source_id() == source_id)
+                .map(|m| (item, m)));
+        };
+
+        let name = ident.resolve(resolve_context!(self.idx.q))?;
+        let mut module = Some(self.item_meta.module);
+
+        while let Some(id) = module {
+            let item = self.idx.q.pool.module_item(id).extended(name);
+            let item = self.idx.q.pool.alloc_item(item);
+
+            if let Some(macro_rules) = self.idx.q.macro_rules_for(item) {
+                if macro_rules.source_id() == source_id {
+                    return Ok(Some((item, macro_rules)));
+                }
+            }
+
+            module = self.idx.q.pool.module(id).parent;
+        }
+
+        Ok(None)
+    }
+
     /// Compile the given macro into the given output type.
     pub(crate) fn eval_attribute_macro<T>(
         &mut self,
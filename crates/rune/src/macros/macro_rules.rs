@@ -0,0 +1,1254 @@
+//! Declarative macros defined in scripts through `macro_rules!`.
+
+use core::mem::discriminant;
+
+use crate::no_std::collections::{HashMap, HashSet};
+use crate::no_std::prelude::*;
+
+use crate::ast;
+use crate::ast::{Span, Spanned};
+use crate::compile::{self, CompileErrorKind, ItemId, ParseErrorKind};
+use crate::macros::TokenStream;
+use crate::parse::{Expectation, Parse, Parser, Resolve, ResolveContext};
+use crate::query::Query;
+use crate::SourceId;
+
+/// A declarative macro defined through `macro_rules!`.
+///
+/// ```text
+/// macro_rules! name {
+///     ($a:expr, $($rest:expr),*) => { $a + $($rest)+* };
+/// }
+/// ```
+pub(crate) struct MacroRules {
+    /// The source the macro was defined in.
+    source_id: SourceId,
+    /// Rules which are tried in order.
+    rules: Vec<Rule>,
+}
+
+impl MacroRules {
+    /// Test if the given macro call is a `macro_rules!` definition.
+    pub(crate) fn is_definition(
+        cx: ResolveContext<'_>,
+        macro_call: &ast::MacroCall,
+    ) -> compile::Result<bool> {
+        let Some(ident) = macro_call.path.try_as_ident() else {
+            return Ok(false);
+        };
+
+        Ok(ident.resolve(cx)? == "macro_rules")
+    }
+
+    /// Parse the rules of a `macro_rules!` definition.
+    pub(crate) fn parse(
+        cx: ResolveContext<'_>,
+        source_id: SourceId,
+        macro_call: &ast::MacroCall,
+    ) -> compile::Result<Self> {
+        let mut p = Parser::from_token_stream(&macro_call.input, macro_call.input_span());
+        let mut rules = Vec::new();
+
+        while !p.is_eof()? {
+            let matcher = matchers(cx, &group(&mut p)?)?;
+            p.parse::<T![=>]>()?;
+            let transcriber = transcribers(cx, &group(&mut p)?)?;
+
+            let mut names = HashSet::new();
+            check_variables(&matcher, &mut names)?;
+
+            rules.push(Rule {
+                matcher,
+                transcriber,
+            });
+
+            if p.parse::<Option<T![;]>>()?.is_none() {
+                break;
+            }
+        }
+
+        p.eof()?;
+        Ok(Self { source_id, rules })
+    }
+
+    /// The source the macro was defined in.
+    pub(crate) fn source_id(&self) -> SourceId {
+        self.source_id
+    }
+
+    /// Expand a call to the macro identified by `item`.
+    ///
+    /// Tokens produced by the macro keep the span they have in the macro
+    /// definition, while captured fragments and variables renamed for hygiene
+    /// have the span of the call site.
+    pub(crate) fn expand(
+        &self,
+        q: &mut Query<'_>,
+        item: ItemId,
+        macro_call: &ast::MacroCall,
+    ) -> compile::Result<TokenStream> {
+        let input = macro_call.input.iter().collect::<Vec<_>>();
+        let span = macro_call.input_span();
+
+        for rule in &self.rules {
+            let mut bindings = Bindings::new();
+
+            if match_seq(
+                resolve_context!(q),
+                &rule.matcher,
+                &input,
+                span,
+                &mut bindings,
+            )? != Some(input.len())
+            {
+                continue;
+            }
+
+            let mut output = Vec::new();
+            transcribe(&rule.transcriber, &bindings, &mut Vec::new(), &mut output)?;
+            hygiene(q, macro_call.span(), &mut output)?;
+
+            let output = output
+                .into_iter()
+                .map(|(token, _)| token)
+                .collect::<Vec<_>>();
+            return Ok(TokenStream::from(output));
+        }
+
+        Err(compile::Error::new(
+            macro_call,
+            CompileErrorKind::NoMatchingMacroRule {
+                item: q.pool.item(item).to_owned(),
+            },
+        ))
+    }
+}
+
+/// A single `(matcher) => { transcriber }` rule.
+struct Rule {
+    matcher: Vec<Matcher>,
+    transcriber: Vec<Transcriber>,
+}
+
+/// A part of the matcher of a rule.
+enum Matcher {
+    /// A token which has to be matched exactly.
+    Token(ast::Token),
+    /// A delimited group.
+    Group(ast::Delimiter, Vec<Matcher>),
+    /// A fragment like `$name:expr`.
+    Fragment {
+        span: Span,
+        name: Box<str>,
+        fragment: Fragment,
+    },
+    /// A repetition like `$($name:expr),*`.
+    Repeat(Repeat<Matcher>),
+}
+
+/// A part of the transcriber of a rule.
+enum Transcriber {
+    /// A token which is emitted as-is.
+    Token(ast::Token),
+    /// A macro variable like `$name`.
+    Variable { span: Span, name: Box<str> },
+    /// A repetition like `$($name),*`.
+    Repeat(Repeat<Transcriber>),
+}
+
+/// A repetition in a matcher or a transcriber.
+struct Repeat<T> {
+    span: Span,
+    inner: Vec<T>,
+    separator: Option<ast::Token>,
+    op: RepeatOp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RepeatOp {
+    /// `*`.
+    ZeroOrMore,
+    /// `+`.
+    OneOrMore,
+    /// `?`.
+    ZeroOrOne,
+}
+
+/// The kind of a fragment, as in `$name:<fragment>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fragment {
+    Block,
+    Expr,
+    Ident,
+    Item,
+    Lit,
+    Pat,
+    Path,
+    Tt,
+    Ty,
+}
+
+impl Fragment {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "block" => Self::Block,
+            "expr" => Self::Expr,
+            "ident" => Self::Ident,
+            "item" => Self::Item,
+            "literal" | "lit" => Self::Lit,
+            "pat" => Self::Pat,
+            "path" => Self::Path,
+            "tt" => Self::Tt,
+            "ty" => Self::Ty,
+            _ => return None,
+        })
+    }
+
+    /// Match the fragment at the start of the input, returning the number of
+    /// tokens it consists of.
+    fn parse(self, input: &[ast::Token], span: Span) -> Option<usize> {
+        let first = input.first()?;
+
+        match self {
+            Self::Block => parse_len::<ast::Block>(input, span),
+            Self::Expr => parse_len::<ast::Expr>(input, span),
+            Self::Ident => matches!(first.kind, K![ident]).then_some(1),
+            Self::Item => parse_len::<ast::Item>(input, span),
+            Self::Lit => match first.kind {
+                ast::Kind::Byte(..)
+                | ast::Kind::ByteStr(..)
+                | ast::Kind::Char(..)
+                | ast::Kind::Number(..)
+                | ast::Kind::Str(..)
+                | K![true]
+                | K![false] => Some(1),
+                K![-] => matches!(input.get(1)?.kind, ast::Kind::Number(..)).then_some(2),
+                _ => None,
+            },
+            Self::Pat => parse_len::<ast::Pat>(input, span),
+            Self::Path => parse_len::<ast::Path>(input, span),
+            Self::Tt => match first.kind {
+                ast::Kind::Open(..) => close_of(input, 0).map(|end| end + 1),
+                ast::Kind::Close(..) => None,
+                _ => Some(1),
+            },
+            Self::Ty => parse_len::<ast::Type>(input, span),
+        }
+    }
+}
+
+/// A macro variable bound by matching.
+enum Binding {
+    /// A single captured fragment.
+    One(Fragment, Vec<ast::Token>),
+    /// A variable bound inside of a repetition.
+    Many(Vec<Binding>),
+}
+
+type Bindings = HashMap<Box<str>, Binding>;
+
+/// Parse a delimited group, returning the tokens inside of it.
+fn group(p: &mut Parser<'_>) -> compile::Result<Vec<ast::Token>> {
+    let open = p.next()?;
+
+    if !matches!(open.kind, ast::Kind::Open(..)) {
+        return Err(compile::Error::expected(open, Expectation::OpenDelimiter));
+    }
+
+    let mut level = 1usize;
+    let mut tokens = Vec::new();
+
+    loop {
+        let token = p.next()?;
+
+        match token.kind {
+            ast::Kind::Open(..) => level += 1,
+            ast::Kind::Close(..) => {
+                level -= 1;
+
+                if level == 0 {
+                    return Ok(tokens);
+                }
+            }
+            _ => (),
+        }
+
+        tokens.push(token);
+    }
+}
+
+/// Find the index of the token closing the group opened at `open`.
+fn close_of(tokens: &[ast::Token], open: usize) -> Option<usize> {
+    let mut level = 0usize;
+
+    for (n, token) in tokens.iter().enumerate().skip(open) {
+        match token.kind {
+            ast::Kind::Open(..) => level += 1,
+            ast::Kind::Close(..) => {
+                level = level.checked_sub(1)?;
+
+                if level == 0 {
+                    return Some(n);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    None
+}
+
+/// Get the token at the given position, or error with an unexpected end of
+/// input following `last`.
+fn token_at(tokens: &[ast::Token], n: usize, last: Span) -> compile::Result<ast::Token> {
+    match tokens.get(n) {
+        Some(token) => Ok(*token),
+        None => Err(compile::Error::new(
+            last.tail(),
+            ParseErrorKind::UnexpectedEof,
+        )),
+    }
+}
+
+/// Parse the delimited group starting at `n`, returning the index of the
+/// token closing it.
+fn group_at(tokens: &[ast::Token], n: usize) -> compile::Result<usize> {
+    match close_of(tokens, n) {
+        Some(end) => Ok(end),
+        None => Err(compile::Error::new(
+            tokens[n].span,
+            ParseErrorKind::UnexpectedEof,
+        )),
+    }
+}
+
+/// Parse the optional separator and the operator of a repetition.
+fn repeat_op(
+    tokens: &[ast::Token],
+    n: &mut usize,
+    last: Span,
+) -> compile::Result<(Option<ast::Token>, RepeatOp, Span)> {
+    let mut separator = None;
+    let mut token = token_at(tokens, *n, last)?;
+    *n += 1;
+
+    if !matches!(token.kind, K![*] | K![+] | K![?]) {
+        if matches!(
+            token.kind,
+            ast::Kind::Open(..) | ast::Kind::Close(..) | K![$]
+        ) {
+            return Err(compile::Error::expected(token, "repetition separator"));
+        }
+
+        separator = Some(token);
+        token = token_at(tokens, *n, token.span)?;
+        *n += 1;
+    }
+
+    let op = match token.kind {
+        K![*] => RepeatOp::ZeroOrMore,
+        K![+] => RepeatOp::OneOrMore,
+        K![?] => RepeatOp::ZeroOrOne,
+        _ => {
+            return Err(compile::Error::expected(token, "`*`, `+`, or `?`"));
+        }
+    };
+
+    Ok((separator, op, token.span))
+}
+
+/// Resolve the given identifier token.
+fn ident<'a>(cx: ResolveContext<'a>, token: ast::Token) -> compile::Result<&'a str> {
+    match token.kind {
+        ast::Kind::Ident(source) => ast::Ident {
+            span: token.span,
+            source,
+        }
+        .resolve(cx),
+        _ => Err(compile::Error::expected(token, "ident")),
+    }
+}
+
+/// Parse the matcher of a rule.
+fn matchers(cx: ResolveContext<'_>, tokens: &[ast::Token]) -> compile::Result<Vec<Matcher>> {
+    let mut output = Vec::new();
+    let mut n = 0;
+
+    while let Some(&token) = tokens.get(n) {
+        n += 1;
+
+        match token.kind {
+            K![$] => {
+                let next = token_at(tokens, n, token.span)?;
+
+                match next.kind {
+                    K![ident] => {
+                        let name = ident(cx, next)?;
+
+                        let colon = token_at(tokens, n + 1, next.span)?;
+
+                        if colon.kind != K![:] {
+                            return Err(compile::Error::expected(colon, K![:]));
+                        }
+
+                        let kind = token_at(tokens, n + 2, colon.span)?;
+                        let kind_name = ident(cx, kind)?;
+
+                        let Some(fragment) = Fragment::from_name(kind_name) else {
+                            return Err(compile::Error::new(
+                                kind,
+                                CompileErrorKind::UnsupportedFragmentSpecifier {
+                                    name: kind_name.into(),
+                                },
+                            ));
+                        };
+
+                        n += 3;
+
+                        output.push(Matcher::Fragment {
+                            span: token.span.join(kind.span),
+                            name: name.into(),
+                            fragment,
+                        });
+                    }
+                    ast::Kind::Open(ast::Delimiter::Parenthesis) => {
+                        let end = group_at(tokens, n)?;
+                        let inner = matchers(cx, &tokens[n + 1..end])?;
+                        n = end + 1;
+                        let (separator, op, last) = repeat_op(tokens, &mut n, tokens[end].span)?;
+
+                        output.push(Matcher::Repeat(Repeat {
+                            span: token.span.join(last),
+                            inner,
+                            separator,
+                            op,
+                        }));
+                    }
+                    _ => {
+                        return Err(compile::Error::expected(
+                            next,
+                            "macro variable or repetition",
+                        ));
+                    }
+                }
+            }
+            ast::Kind::Open(delimiter) => {
+                let end = group_at(tokens, n - 1)?;
+                let inner = matchers(cx, &tokens[n..end])?;
+                n = end + 1;
+                output.push(Matcher::Group(delimiter, inner));
+            }
+            _ => {
+                output.push(Matcher::Token(token));
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Parse the transcriber of a rule.
+fn transcribers(
+    cx: ResolveContext<'_>,
+    tokens: &[ast::Token],
+) -> compile::Result<Vec<Transcriber>> {
+    let mut output = Vec::new();
+    let mut n = 0;
+
+    while let Some(&token) = tokens.get(n) {
+        n += 1;
+
+        if token.kind != K![$] {
+            output.push(Transcriber::Token(token));
+            continue;
+        }
+
+        let next = token_at(tokens, n, token.span)?;
+
+        match next.kind {
+            K![ident] => {
+                n += 1;
+
+                output.push(Transcriber::Variable {
+                    span: token.span.join(next.span),
+                    name: ident(cx, next)?.into(),
+                });
+            }
+            ast::Kind::Open(ast::Delimiter::Parenthesis) => {
+                let end = group_at(tokens, n)?;
+                let inner = transcribers(cx, &tokens[n + 1..end])?;
+                n = end + 1;
+                let (separator, op, last) = repeat_op(tokens, &mut n, tokens[end].span)?;
+
+                output.push(Transcriber::Repeat(Repeat {
+                    span: token.span.join(last),
+                    inner,
+                    separator,
+                    op,
+                }));
+            }
+            _ => {
+                return Err(compile::Error::expected(
+                    next,
+                    "macro variable or repetition",
+                ));
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Check that no macro variable is bound more than once.
+fn check_variables<'a>(
+    matchers: &'a [Matcher],
+    names: &mut HashSet<&'a str>,
+) -> compile::Result<()> {
+    for matcher in matchers {
+        match matcher {
+            Matcher::Token(..) => {}
+            Matcher::Group(_, inner) => {
+                check_variables(inner, names)?;
+            }
+            Matcher::Fragment { span, name, .. } => {
+                if !names.insert(name) {
+                    return Err(compile::Error::new(
+                        *span,
+                        CompileErrorKind::DuplicateMacroVariable { name: name.clone() },
+                    ));
+                }
+            }
+            Matcher::Repeat(repeat) => {
+                check_variables(&repeat.inner, names)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Collect the names of all variables bound by the given matchers.
+fn bound_variables<'a>(matchers: &'a [Matcher], output: &mut Vec<&'a str>) {
+    for matcher in matchers {
+        match matcher {
+            Matcher::Token(..) => {}
+            Matcher::Group(_, inner) => bound_variables(inner, output),
+            Matcher::Fragment { name, .. } => output.push(name),
+            Matcher::Repeat(repeat) => bound_variables(&repeat.inner, output),
+        }
+    }
+}
+
+/// Collect the names of all variables used by the given transcribers.
+fn used_variables<'a>(transcribers: &'a [Transcriber], output: &mut Vec<&'a str>) {
+    for transcriber in transcribers {
+        match transcriber {
+            Transcriber::Token(..) => {}
+            Transcriber::Variable { name, .. } => output.push(name),
+            Transcriber::Repeat(repeat) => used_variables(&repeat.inner, output),
+        }
+    }
+}
+
+/// Parse the given type from the start of the input, returning the number of
+/// tokens consumed.
+fn parse_len<T>(input: &[ast::Token], span: Span) -> Option<usize>
+where
+    T: Parse,
+{
+    let stream = TokenStream::from(input.to_vec());
+    let mut p = Parser::from_token_stream(&stream, span);
+    p.parse::<T>().ok()?;
+    let len = input.len() - p.remaining()?;
+    (len > 0).then_some(len)
+}
+
+/// Test if two tokens are equal in the source they represent.
+fn token_eq(cx: ResolveContext<'_>, a: ast::Token, b: ast::Token) -> compile::Result<bool> {
+    if let (K![ident], K![ident]) = (a.kind, b.kind) {
+        return Ok(ident(cx, a)? == ident(cx, b)?);
+    }
+
+    if let (ast::Kind::Label(a_source), ast::Kind::Label(b_source)) = (a.kind, b.kind) {
+        let a = ast::Label {
+            span: a.span,
+            source: a_source,
+        };
+
+        let b = ast::Label {
+            span: b.span,
+            source: b_source,
+        };
+
+        return Ok(a.resolve(cx)? == b.resolve(cx)?);
+    }
+
+    if discriminant(&a.kind) != discriminant(&b.kind) {
+        return Ok(false);
+    }
+
+    Ok(match (source_text(cx, a), source_text(cx, b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a.kind == b.kind,
+    })
+}
+
+/// Get the source text of a literal token, if it has any.
+fn source_text<'a>(cx: ResolveContext<'a>, token: ast::Token) -> Option<&'a str> {
+    let source_id = match token.kind {
+        ast::Kind::Number(ast::NumberSource::Text(text)) => text.source_id,
+        ast::Kind::Str(ast::StrSource::Text(text)) => text.source_id,
+        ast::Kind::ByteStr(ast::StrSource::Text(text)) => text.source_id,
+        ast::Kind::Char(ast::CopySource::Text(source_id)) => source_id,
+        ast::Kind::Byte(ast::CopySource::Text(source_id)) => source_id,
+        _ => return None,
+    };
+
+    cx.sources.source(source_id, token.span)
+}
+
+/// Match the given matchers against the start of the input, returning the
+/// number of tokens consumed if they matched.
+fn match_seq(
+    cx: ResolveContext<'_>,
+    matchers: &[Matcher],
+    input: &[ast::Token],
+    span: Span,
+    bindings: &mut Bindings,
+) -> compile::Result<Option<usize>> {
+    let mut n = 0;
+
+    for matcher in matchers {
+        match matcher {
+            Matcher::Token(expected) => {
+                let Some(&token) = input.get(n) else {
+                    return Ok(None);
+                };
+
+                if !token_eq(cx, *expected, token)? {
+                    return Ok(None);
+                }
+
+                n += 1;
+            }
+            Matcher::Group(delimiter, inner) => {
+                if input.get(n).map(|t| t.kind) != Some(ast::Kind::Open(*delimiter)) {
+                    return Ok(None);
+                }
+
+                let Some(end) = close_of(input, n) else {
+                    return Ok(None);
+                };
+
+                let group = &input[n + 1..end];
+
+                if match_seq(cx, inner, group, span, bindings)? != Some(group.len()) {
+                    return Ok(None);
+                }
+
+                n = end + 1;
+            }
+            Matcher::Fragment { name, fragment, .. } => {
+                let Some(len) = fragment.parse(&input[n..], span) else {
+                    return Ok(None);
+                };
+
+                let tokens = input[n..n + len].to_vec();
+                bindings.insert(name.clone(), Binding::One(*fragment, tokens));
+                n += len;
+            }
+            Matcher::Repeat(repeat) => {
+                let mut iterations = Vec::new();
+
+                loop {
+                    let mut m = n;
+
+                    if let (Some(separator), false) = (repeat.separator, iterations.is_empty()) {
+                        match input.get(m) {
+                            Some(&token) if token_eq(cx, separator, token)? => {
+                                m += 1;
+                            }
+                            _ => break,
+                        }
+                    }
+
+                    let mut inner = Bindings::new();
+
+                    match match_seq(cx, &repeat.inner, &input[m..], span, &mut inner)? {
+                        Some(len) if len > 0 => {
+                            n = m + len;
+                            iterations.push(inner);
+                        }
+                        _ => break,
+                    }
+
+                    if repeat.op == RepeatOp::ZeroOrOne {
+                        break;
+                    }
+                }
+
+                if repeat.op == RepeatOp::OneOrMore && iterations.is_empty() {
+                    return Ok(None);
+                }
+
+                let mut names = Vec::new();
+                bound_variables(&repeat.inner, &mut names);
+
+                for name in names {
+                    let many = iterations
+                        .iter_mut()
+                        .filter_map(|inner| inner.remove(name))
+                        .collect();
+
+                    bindings.insert(name.into(), Binding::Many(many));
+                }
+            }
+        }
+    }
+
+    Ok(Some(n))
+}
+
+/// Look up the binding of a variable at the current repetition.
+fn lookup<'a>(bindings: &'a Bindings, name: &str, indexes: &[usize]) -> Option<&'a Binding> {
+    let mut binding = bindings.get(name)?;
+
+    for &index in indexes {
+        match binding {
+            Binding::Many(many) => {
+                binding = many.get(index)?;
+            }
+            Binding::One(..) => break,
+        }
+    }
+
+    Some(binding)
+}
+
+/// Transcribe the output of a rule. Each output token is marked with whether
+/// it originates from the macro definition or not.
+fn transcribe(
+    transcribers: &[Transcriber],
+    bindings: &Bindings,
+    indexes: &mut Vec<usize>,
+    output: &mut Vec<(ast::Token, bool)>,
+) -> compile::Result<()> {
+    for transcriber in transcribers {
+        match transcriber {
+            Transcriber::Token(token) => {
+                output.push((*token, true));
+            }
+            Transcriber::Variable { span, name } => match lookup(bindings, name, indexes) {
+                Some(Binding::One(fragment, tokens)) => {
+                    // Expressions are grouped so that they retain their
+                    // precedence once they've been substituted.
+                    let group = match (fragment, tokens.first(), tokens.last()) {
+                        (Fragment::Expr, Some(first), Some(last)) if tokens.len() > 1 => {
+                            Some((first.span.head(), last.span.tail()))
+                        }
+                        _ => None,
+                    };
+
+                    if let Some((open, _)) = group {
+                        output.push((
+                            ast::Token {
+                                span: open,
+                                kind: ast::Kind::Open(ast::Delimiter::Parenthesis),
+                            },
+                            false,
+                        ));
+                    }
+
+                    output.extend(tokens.iter().map(|token| (*token, false)));
+
+                    if let Some((_, close)) = group {
+                        output.push((
+                            ast::Token {
+                                span: close,
+                                kind: ast::Kind::Close(ast::Delimiter::Parenthesis),
+                            },
+                            false,
+                        ));
+                    }
+                }
+                Some(Binding::Many(..)) => {
+                    return Err(compile::Error::new(
+                        *span,
+                        CompileErrorKind::MacroVariableStillRepeating { name: name.clone() },
+                    ));
+                }
+                None => {
+                    return Err(compile::Error::new(
+                        *span,
+                        CompileErrorKind::MissingMacroVariable { name: name.clone() },
+                    ));
+                }
+            },
+            Transcriber::Repeat(repeat) => {
+                let mut names = Vec::new();
+                used_variables(&repeat.inner, &mut names);
+
+                let mut count = None;
+
+                for name in names {
+                    let Some(Binding::Many(many)) = lookup(bindings, name, indexes) else {
+                        continue;
+                    };
+
+                    match count {
+                        Some(count) if count != many.len() => {
+                            return Err(compile::Error::new(
+                                repeat.span,
+                                CompileErrorKind::MacroRepetitionMismatch,
+                            ));
+                        }
+                        _ => {
+                            count = Some(many.len());
+                        }
+                    }
+                }
+
+                let Some(count) = count else {
+                    return Err(compile::Error::new(
+                        repeat.span,
+                        CompileErrorKind::MacroRepetitionWithoutVariables,
+                    ));
+                };
+
+                for index in 0..count {
+                    if let (Some(separator), true) = (repeat.separator, index > 0) {
+                        output.push((separator, true));
+                    }
+
+                    indexes.push(index);
+                    transcribe(&repeat.inner, bindings, indexes, output)?;
+                    indexes.pop();
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Rename variables which are bound by the macro definition itself, so that
+/// they can neither shadow nor be referenced by identifiers at the call site.
+///
+/// The output is parsed to find the patterns which bind variables and the
+/// expressions which refer to them. If it doesn't parse it's left as-is, since
+/// parsing the expanded macro reports the error.
+///
+/// Renamed identifiers don't exist in the macro definition, so they are given
+/// the span of the macro call.
+fn hygiene(
+    q: &mut Query<'_>,
+    span: Span,
+    tokens: &mut Vec<(ast::Token, bool)>,
+) -> compile::Result<()> {
+    // NB: every token is given a span matching its index, so that the parsed
+    // identifiers can be mapped back to the tokens they were parsed from.
+    let indexed = tokens
+        .iter()
+        .enumerate()
+        .map(|(n, (token, _))| ast::Token {
+            span: Span::new(n, n + 1),
+            kind: token.kind,
+        })
+        .collect::<Vec<_>>();
+
+    let indexed = TokenStream::from(indexed);
+    let mut p = Parser::from_token_stream(&indexed, Span::empty());
+    let mut scan = Scan::default();
+
+    while !p.is_eof()? {
+        let Ok(stmt) = p.parse::<ast::Stmt>() else {
+            return Ok(());
+        };
+
+        scan.stmt(&stmt);
+    }
+
+    let mut bound = HashSet::new();
+    let mut renamed = HashSet::new();
+
+    for (n, refutable) in scan.bindings {
+        let (token, from_macro) = tokens[n];
+
+        if !from_macro {
+            continue;
+        }
+
+        let name = ident(resolve_context!(q), token)?;
+
+        // Uppercase names in refutable patterns refer to items like `None`.
+        if refutable && name.starts_with(char::is_uppercase) {
+            continue;
+        }
+
+        bound.insert(name.to_owned());
+        renamed.insert(n);
+    }
+
+    if bound.is_empty() {
+        return Ok(());
+    }
+
+    let uses = scan.uses.iter().chain(&scan.shorthands).copied();
+    let inputs = scan
+        .macro_inputs
+        .iter()
+        .copied()
+        .filter(|&n| is_macro_input_variable(tokens, n));
+
+    for n in uses.chain(inputs) {
+        let (token, from_macro) = tokens[n];
+
+        if from_macro && bound.contains(ident(resolve_context!(q), token)?) {
+            renamed.insert(n);
+        }
+    }
+
+    let id = q.gen.next();
+    let mut output = Vec::with_capacity(tokens.len());
+
+    for (n, &(token, from_macro)) in tokens.iter().enumerate() {
+        if !renamed.contains(&n) {
+            output.push((token, from_macro));
+            continue;
+        }
+
+        // A shorthand field like `#{a}` is expanded into `#{a: a#0}`, so that
+        // the field keeps its name.
+        if scan.shorthands.contains(&n) {
+            output.push((token, from_macro));
+
+            let colon = ast::Token {
+                span: token.span,
+                kind: K![:],
+            };
+
+            output.push((colon, from_macro));
+        }
+
+        let name = format!("{}#{id}", ident(resolve_context!(q), token)?);
+        let source = ast::LitSource::Synthetic(q.storage.insert_string(name));
+
+        let token = ast::Token {
+            span,
+            kind: ast::Kind::Ident(source),
+        };
+
+        output.push((token, from_macro));
+    }
+
+    *tokens = output;
+    Ok(())
+}
+
+/// Test if the identifier at the given position in the input of a macro call
+/// in the output refers to a variable, as opposed to a field or a path.
+fn is_macro_input_variable(tokens: &[(ast::Token, bool)], n: usize) -> bool {
+    if !matches!(tokens[n].0.kind, K![ident]) {
+        return false;
+    }
+
+    let before = n.checked_sub(1).map(|n| tokens[n].0.kind);
+    let after = tokens.get(n + 1).map(|(token, _)| token.kind);
+    !matches!(before, Some(K![.] | K![::])) && !matches!(after, Some(K![::] | K![:]))
+}
+
+/// The identifiers in the output of a macro which are relevant to hygiene,
+/// identified by the index of their token.
+#[derive(Default)]
+struct Scan {
+    /// Identifiers bound by patterns, and whether the pattern is refutable.
+    bindings: Vec<(usize, bool)>,
+    /// Identifiers referring to variables.
+    uses: Vec<usize>,
+    /// Shorthand fields, which are both a field name and a binding or a
+    /// variable, like `a` in `#{a}`.
+    shorthands: Vec<usize>,
+    /// Tokens in the input of macro calls, which can't be parsed.
+    macro_inputs: Vec<usize>,
+}
+
+impl Scan {
+    fn stmt(&mut self, ast: &ast::Stmt) {
+        match ast {
+            ast::Stmt::Local(local) => {
+                self.expr(&local.expr);
+                self.pat(&local.pat, false);
+            }
+            ast::Stmt::Item(item, _) => {
+                self.item(item);
+            }
+            ast::Stmt::Expr(expr) | ast::Stmt::Semi(ast::StmtSemi { expr, .. }) => {
+                self.expr(expr);
+            }
+        }
+    }
+
+    fn item(&mut self, ast: &ast::Item) {
+        match ast {
+            ast::Item::Fn(item_fn) => {
+                for (arg, _) in &item_fn.args {
+                    self.fn_arg(arg);
+                }
+
+                self.block(&item_fn.body);
+            }
+            ast::Item::MacroCall(macro_call) => {
+                self.macro_call(macro_call);
+            }
+            _ => {}
+        }
+    }
+
+    fn block(&mut self, ast: &ast::Block) {
+        for stmt in &ast.statements {
+            self.stmt(stmt);
+        }
+    }
+
+    fn condition(&mut self, ast: &ast::Condition) {
+        match ast {
+            ast::Condition::Expr(expr) => {
+                self.expr(expr);
+            }
+            ast::Condition::ExprLet(expr_let) => {
+                self.expr(&expr_let.expr);
+                self.pat(&expr_let.pat, true);
+            }
+        }
+    }
+
+    fn fn_arg(&mut self, ast: &ast::FnArg) {
+        match ast {
+            ast::FnArg::SelfValue(..) => {}
+            ast::FnArg::Pat(pat) => {
+                self.pat(pat, false);
+            }
+            ast::FnArg::Typed(typed) => {
+                self.pat(&typed.pat, false);
+            }
+            ast::FnArg::Default(default) => {
+                self.expr(&default.value);
+                self.pat(&default.pat, false);
+            }
+        }
+    }
+
+    fn macro_call(&mut self, ast: &ast::MacroCall) {
+        for token in &ast.input {
+            self.macro_inputs.push(token.span.range().start);
+        }
+    }
+
+    fn expr(&mut self, ast: &ast::Expr) {
+        match ast {
+            ast::Expr::Path(path) => {
+                if let Some(ident) = path.try_as_ident() {
+                    self.uses.push(ident.span.range().start);
+                }
+            }
+            ast::Expr::Assign(expr) => {
+                self.expr(&expr.lhs);
+                self.expr(&expr.rhs);
+            }
+            ast::Expr::While(expr) => {
+                self.condition(&expr.condition);
+                self.block(&expr.body);
+            }
+            ast::Expr::Loop(expr) => {
+                self.block(&expr.body);
+            }
+            ast::Expr::For(expr) => {
+                self.expr(&expr.iter);
+                self.pat(&expr.binding, false);
+                self.block(&expr.body);
+            }
+            ast::Expr::Let(expr) => {
+                self.expr(&expr.expr);
+                self.pat(&expr.pat, true);
+            }
+            ast::Expr::If(expr) => {
+                self.condition(&expr.condition);
+                self.block(&expr.block);
+
+                for else_if in &expr.expr_else_ifs {
+                    self.condition(&else_if.condition);
+                    self.block(&else_if.block);
+                }
+
+                if let Some(expr_else) = &expr.expr_else {
+                    self.block(&expr_else.block);
+                }
+            }
+            ast::Expr::Match(expr) => {
+                self.expr(&expr.expr);
+
+                for (branch, _) in &expr.branches {
+                    self.pat(&branch.pat, true);
+
+                    if let Some((_, condition)) = &branch.condition {
+                        self.expr(condition);
+                    }
+
+                    self.expr(&branch.body);
+                }
+            }
+            ast::Expr::Call(expr) => {
+                self.expr(&expr.expr);
+
+                for (arg, _) in &expr.args {
+                    self.expr(&arg.value);
+                }
+            }
+            ast::Expr::FieldAccess(expr) => {
+                self.expr(&expr.expr);
+            }
+            ast::Expr::Binary(expr) => {
+                self.expr(&expr.lhs);
+                self.expr(&expr.rhs);
+            }
+            ast::Expr::Unary(expr) => {
+                self.expr(&expr.expr);
+            }
+            ast::Expr::Index(expr) => {
+                self.expr(&expr.target);
+                self.expr(&expr.index);
+            }
+            ast::Expr::Break(expr) => {
+                if let Some(ast::ExprBreakValue::Expr(value)) = expr.expr.as_deref() {
+                    self.expr(value);
+                }
+            }
+            ast::Expr::Yield(ast::ExprYield { expr, .. })
+            | ast::Expr::Return(ast::ExprReturn { expr, .. }) => {
+                if let Some(expr) = expr {
+                    self.expr(expr);
+                }
+            }
+            ast::Expr::Block(expr) => {
+                self.block(&expr.block);
+            }
+            ast::Expr::Await(ast::ExprAwait { expr, .. })
+            | ast::Expr::Try(ast::ExprTry { expr, .. })
+            | ast::Expr::Empty(ast::ExprEmpty { expr, .. })
+            | ast::Expr::Group(ast::ExprGroup { expr, .. }) => {
+                self.expr(expr);
+            }
+            ast::Expr::Select(expr) => {
+                for (branch, _) in &expr.branches {
+                    match branch {
+                        ast::ExprSelectBranch::Pat(branch) => {
+                            self.expr(&branch.expr);
+                            self.pat(&branch.pat, false);
+                            self.expr(&branch.body);
+                        }
+                        ast::ExprSelectBranch::Default(branch) => {
+                            self.expr(&branch.body);
+                        }
+                    }
+                }
+            }
+            ast::Expr::Closure(expr) => {
+                for (arg, _) in expr.args.as_slice() {
+                    self.fn_arg(arg);
+                }
+
+                self.expr(&expr.body);
+            }
+            ast::Expr::Object(expr) => {
+                for (field, _) in &expr.assignments {
+                    match (&field.key, &field.assign) {
+                        (_, Some((_, value))) => {
+                            self.expr(value);
+                        }
+                        (ast::ObjectKey::Path(path), None) => {
+                            if let Some(ident) = path.try_as_ident() {
+                                self.shorthands.push(ident.span.range().start);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            ast::Expr::Tuple(expr) => {
+                for (item, _) in &expr.items {
+                    self.expr(item);
+                }
+            }
+            ast::Expr::Vec(expr) => {
+                for (item, _) in &expr.items {
+                    self.expr(item);
+                }
+            }
+            ast::Expr::Range(expr) => {
+                if let Some(from) = &expr.from {
+                    self.expr(from);
+                }
+
+                if let Some(to) = &expr.to {
+                    self.expr(to);
+                }
+            }
+            ast::Expr::MacroCall(macro_call) => {
+                self.macro_call(macro_call);
+            }
+            ast::Expr::Continue(..) | ast::Expr::Lit(..) => {}
+        }
+    }
+
+    fn pat(&mut self, ast: &ast::Pat, refutable: bool) {
+        match ast {
+            ast::Pat::Path(pat) => {
+                if let Some(ident) = pat.path.try_as_ident() {
+                    self.bindings.push((ident.span.range().start, refutable));
+                }
+            }
+            ast::Pat::Vec(pat) => {
+                for (item, _) in &pat.items {
+                    self.pat(item, refutable);
+                }
+            }
+            ast::Pat::Tuple(pat) => {
+                for (item, _) in &pat.items {
+                    self.pat(item, refutable);
+                }
+            }
+            ast::Pat::Object(pat) => {
+                for (item, _) in &pat.items {
+                    // A shorthand field like `a` in `#{a}` always binds a
+                    // variable named after the field.
+                    if let ast::Pat::Path(path) = item {
+                        if let Some(ident) = path.path.try_as_ident() {
+                            let n = ident.span.range().start;
+                            self.bindings.push((n, false));
+                            self.shorthands.push(n);
+                            continue;
+                        }
+                    }
+
+                    self.pat(item, refutable);
+                }
+            }
+            ast::Pat::Binding(pat) => {
+                self.pat(&pat.pat, refutable);
+            }
+            ast::Pat::Or(pat) => {
+                self.pat(&pat.first, refutable);
+
+                for (_, pat) in &pat.rest {
+                    self.pat(pat, refutable);
+                }
+            }
+            ast::Pat::At(pat) => {
+                self.bindings.push((pat.name.span.range().start, false));
+                self.pat(&pat.pat, refutable);
+            }
+            ast::Pat::Ignore(..) | ast::Pat::Lit(..) => {}
+            ast::Pat::Rest(..) | ast::Pat::Range(..) => {}
+        }
+    }
+}
@@ -67,6 +67,13 @@ pub struct TokenStreamIter<'a> {
     iter: slice::Iter<'a, ast::Token>,
 }
 
+impl TokenStreamIter<'_> {
+    /// The number of tokens remaining in the iterator.
+    pub(crate) fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
 impl OptionSpanned for TokenStreamIter<'_> {
     fn option_span(&self) -> Option<Span> {
         self.iter.option_span()
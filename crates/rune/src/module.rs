@@ -15,8 +15,8 @@ use crate::no_std::sync::Arc;
 
 use crate::compile::{meta, ContextError, Docs, IntoComponent, Item, ItemBuf};
 use crate::runtime::{
-    AttributeMacroHandler, ConstValue, FullTypeOf, FunctionHandler, MacroHandler, StaticType,
-    TypeCheck, TypeInfo, TypeOf,
+    AttributeMacroHandler, ConstValue, FullTypeOf, FunctionHandler, MacroHandler, Protocol,
+    StaticType, TypeCheck, TypeInfo, TypeOf,
 };
 use crate::Hash;
 
@@ -244,6 +244,13 @@ pub(crate) struct ModuleConstant {
     pub(crate) docs: Docs,
 }
 
+/// A protocol registered in a module.
+pub(crate) struct ModuleProtocol {
+    pub(crate) item: ItemBuf,
+    pub(crate) functions: Box<[Protocol]>,
+    pub(crate) docs: Docs,
+}
+
 /// Handle to a an item inserted into a module which allows for mutation of item
 /// metadata.
 ///
@@ -1,4 +1,5 @@
 use core::marker::PhantomData;
+use core::ops;
 
 use crate::no_std::collections::{HashMap, HashSet};
 use crate::no_std::prelude::*;
@@ -13,14 +14,14 @@ use crate::module::function_meta::{
 use crate::module::{
     AssociatedKey, Async, EnumMut, Function, FunctionKind, InstallWith, InstanceFunction,
     InternalEnum, InternalEnumMut, ItemMut, ModuleAssociated, ModuleAttributeMacro, ModuleConstant,
-    ModuleFunction, ModuleMacro, ModuleType, Plain, TypeMut, TypeSpecification, UnitType,
-    VariantMut,
+    ModuleFunction, ModuleMacro, ModuleProtocol, ModuleType, Plain, TypeMut, TypeSpecification,
+    UnitType, VariantMut,
 };
 use crate::runtime::{
     AttributeMacroHandler, ConstValue, FromValue, GeneratorState, MacroHandler, MaybeTypeOf,
-    Protocol, Stack, ToValue, TypeCheck, TypeOf, Value, VmResult,
+    Protocol, Stack, ToValue, TypeCheck, TypeOf, UnsafeFromValue, Value, VmResult,
 };
-use crate::Hash;
+use crate::{Any, Hash};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 enum Name {
@@ -54,6 +55,8 @@ pub struct Module {
     pub(crate) attribute_macros: Vec<ModuleAttributeMacro>,
     /// Constant values.
     pub(crate) constants: Vec<ModuleConstant>,
+    /// Registered protocols.
+    pub(crate) protocols: Vec<ModuleProtocol>,
     /// Associated items.
     pub(crate) associated: Vec<ModuleAssociated>,
     /// Registered types.
@@ -120,6 +123,7 @@ impl Module {
             unit_type: None,
             internal_enums: Vec::new(),
             constants: Vec::new(),
+            protocols: Vec::new(),
             docs: Docs::EMPTY,
         }
     }
@@ -516,6 +520,56 @@ impl Module {
         Ok(ItemMut { docs: &mut c.docs })
     }
 
+    /// Register a protocol which scripts can implement.
+    ///
+    /// The protocol is declared as a trait with the given name, whose functions
+    /// are the given protocols. Each protocol must be constructed with
+    /// [`Protocol::custom`] using the full item of the trait, or installing the
+    /// module fails. Scripts implement it with `impl Protocol for Type`, at
+    /// which point the compiler checks that every function is provided. Native
+    /// code calls the implementation with [`Vm::call_protocol`].
+    ///
+    /// [`Protocol::custom`]: crate::runtime::Protocol::custom
+    /// [`Vm::call_protocol`]: crate::Vm::call_protocol
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rune::Module;
+    /// use rune::compile::ItemBuf;
+    /// use rune::runtime::Protocol;
+    ///
+    /// let serde = ItemBuf::with_crate_item("serde", ["Serialize"]);
+    /// let serialize = Protocol::custom(&serde, "serialize");
+    ///
+    /// let mut m = Module::with_crate("serde");
+    /// m.protocol(["Serialize"], [serialize])?
+    ///     .docs(["Types which can be serialized."]);
+    /// # Ok::<_, rune::Error>(())
+    /// ```
+    pub fn protocol<N, F>(&mut self, name: N, functions: F) -> Result<ItemMut<'_>, ContextError>
+    where
+        N: IntoIterator,
+        N::Item: IntoComponent,
+        F: IntoIterator<Item = Protocol>,
+    {
+        let item = ItemBuf::with_item(name);
+        let hash = Hash::type_hash(&item);
+
+        if !self.names.insert(Name::Item(hash)) {
+            return Err(ContextError::ConflictingProtocolName { item, hash });
+        }
+
+        self.protocols.push(ModuleProtocol {
+            item,
+            functions: functions.into_iter().collect(),
+            docs: Docs::EMPTY,
+        });
+
+        let p = self.protocols.last_mut().unwrap();
+        Ok(ItemMut { docs: &mut p.docs })
+    }
+
     /// Register a native macro handler through its meta.
     ///
     /// The metadata must be provided by annotating the function with
@@ -1066,6 +1120,86 @@ impl Module {
         self.index_function(protocol, index, f)
     }
 
+    /// Install the [`Protocol::NEG`] protocol for the type `T` using its
+    /// [`Neg`][ops::Neg] implementation, which allows it to be negated with
+    /// the unary `-` operator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rune::{Any, Module};
+    ///
+    /// #[derive(Clone, Any)]
+    /// struct Vector(f64, f64);
+    ///
+    /// impl std::ops::Neg for Vector {
+    ///     type Output = Vector;
+    ///
+    ///     fn neg(self) -> Vector {
+    ///         Vector(-self.0, -self.1)
+    ///     }
+    /// }
+    ///
+    /// let mut module = Module::new();
+    /// module.ty::<Vector>()?;
+    /// module.neg::<Vector>()?;
+    /// # Ok::<_, rune::Error>(())
+    /// ```
+    pub fn neg<T>(&mut self) -> Result<ItemMut<'_>, ContextError>
+    where
+        T: Any + TypeOf + MaybeTypeOf + Clone + ops::Neg<Output = T>,
+        for<'a> &'a T: UnsafeFromValue,
+    {
+        fn neg<T>(this: &T) -> T
+        where
+            T: Clone + ops::Neg<Output = T>,
+        {
+            -this.clone()
+        }
+
+        self.associated_function(Protocol::NEG, neg::<T>)
+    }
+
+    /// Install the [`Protocol::NOT`] protocol for the type `T` using its
+    /// [`Not`][ops::Not] implementation, which allows it to be used with the
+    /// unary `!` operator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rune::{Any, Module};
+    ///
+    /// #[derive(Clone, Any)]
+    /// struct Flags(u32);
+    ///
+    /// impl std::ops::Not for Flags {
+    ///     type Output = Flags;
+    ///
+    ///     fn not(self) -> Flags {
+    ///         Flags(!self.0)
+    ///     }
+    /// }
+    ///
+    /// let mut module = Module::new();
+    /// module.ty::<Flags>()?;
+    /// module.not::<Flags>()?;
+    /// # Ok::<_, rune::Error>(())
+    /// ```
+    pub fn not<T>(&mut self) -> Result<ItemMut<'_>, ContextError>
+    where
+        T: Any + TypeOf + MaybeTypeOf + Clone + ops::Not<Output = T>,
+        for<'a> &'a T: UnsafeFromValue,
+    {
+        fn not<T>(this: &T) -> T
+        where
+            T: Clone + ops::Not<Output = T>,
+        {
+            !this.clone()
+        }
+
+        self.associated_function(Protocol::NOT, not::<T>)
+    }
+
     /// Register a raw function which interacts directly with the virtual
     /// machine.
     ///
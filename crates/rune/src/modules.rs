@@ -18,6 +18,7 @@ pub mod float;
 pub mod fmt;
 pub mod future;
 pub mod generator;
+pub mod hash;
 pub mod int;
 #[cfg(feature = "std")]
 pub mod io;
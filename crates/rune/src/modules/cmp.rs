@@ -1,12 +1,47 @@
 //! The `std::cmp` module.
 
-use core::cmp;
+use core::cmp::Ordering;
 
+use crate::runtime::Protocol;
 use crate::{ContextError, Module};
 
 /// Construct the `std::cmp` module.
 pub fn module() -> Result<Module, ContextError> {
     let mut module = Module::with_crate_item("std", ["cmp"]);
-    module.ty::<cmp::Ordering>()?;
+
+    {
+        let mut ty = module
+            .ty::<Ordering>()?
+            .make_enum(&["Less", "Equal", "Greater"])?;
+
+        ty.variant_mut(0)?
+            .make_empty()?
+            .constructor(|| Ordering::Less)?;
+        ty.variant_mut(1)?
+            .make_empty()?
+            .constructor(|| Ordering::Equal)?;
+        ty.variant_mut(2)?
+            .make_empty()?
+            .constructor(|| Ordering::Greater)?;
+    }
+
+    module.associated_function(Protocol::IS_VARIANT, |this: &Ordering, index: usize| {
+        matches!(
+            (this, index),
+            (Ordering::Less, 0) | (Ordering::Equal, 1) | (Ordering::Greater, 2)
+        )
+    })?;
+
+    module.associated_function(Protocol::EQ, |this: &Ordering, other: &Ordering| {
+        this == other
+    })?;
+
+    module
+        .protocol(["PartialOrd"], [Protocol::PARTIAL_CMP])?
+        .docs(["Types which can be compared, where not all values are comparable."]);
+    module
+        .protocol(["Ord"], [Protocol::CMP])?
+        .docs(["Types which form a total order, and can be sorted."]);
+
     Ok(module)
 }
@@ -105,7 +105,7 @@ impl HashMap {
         let value = vm_try!(self.map.get(&key).ok_or_else(|| {
             VmErrorKind::MissingIndexKey {
                 target: Self::type_info(),
-                index: key,
+                index: format!("{:?}", key),
             }
         }));
 
@@ -1,5 +1,6 @@
 //! The `std::float` module.
 
+use core::cmp::Ordering;
 use core::num::ParseFloatError;
 
 use crate::{ContextError, Module};
@@ -14,6 +15,11 @@ fn to_integer(value: f64) -> i64 {
     value as i64
 }
 
+/// Compare two floats, which is `None` if either is NaN.
+fn partial_cmp(this: f64, other: f64) -> Option<Ordering> {
+    this.partial_cmp(&other)
+}
+
 crate::__internal_impl_any!(::std::float, ParseFloatError);
 
 /// Install the core package into the given functions namespace.
@@ -31,5 +37,6 @@ pub fn module() -> Result<Module, ContextError> {
     #[cfg(feature = "std")]
     module.associated_function("powi", f64::powi)?;
     module.associated_function("to_integer", to_integer)?;
+    module.associated_function("partial_cmp", partial_cmp)?;
     Ok(module)
 }
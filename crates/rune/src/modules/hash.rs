@@ -0,0 +1,77 @@
+//! The `std::hash` module.
+
+use crate as rune;
+use crate::runtime::{Hasher, Protocol, Value, VmResult};
+use crate::{ContextError, Module};
+
+/// Construct the `std::hash` module.
+pub fn module() -> Result<Module, ContextError> {
+    let mut module = Module::with_crate_item("std", ["hash"]);
+
+    module.ty::<Hasher>()?;
+    module.function_meta(new)?;
+    module.function_meta(write)?;
+    module.function_meta(finish)?;
+
+    module
+        .protocol(["Hash"], [Protocol::HASH])?
+        .docs(["Types which can be hashed, and used as keys in a `HashMap` or `HashSet`."]);
+
+    Ok(module)
+}
+
+/// Construct a new empty hasher.
+///
+/// # Examples
+///
+/// ```rune
+/// use std::hash::Hasher;
+///
+/// let a = Hasher::new();
+/// a.write(42);
+/// let b = Hasher::new();
+/// b.write(42);
+/// assert_eq!(a.finish(), b.finish());
+/// ```
+#[rune::function(path = Hasher::new)]
+fn new() -> Hasher {
+    Hasher::new()
+}
+
+/// Feed a value into the hasher.
+///
+/// The value must itself be hashable, which is the case for primitives,
+/// strings, collections of hashable values and types implementing the
+/// [`HASH`] protocol.
+///
+/// [`HASH`]: crate::runtime::Protocol::HASH
+///
+/// # Examples
+///
+/// ```rune
+/// use std::hash::Hasher;
+///
+/// let hasher = Hasher::new();
+/// hasher.write("hello");
+/// hasher.write((1, 2));
+/// ```
+#[rune::function(instance, path = Hasher::write)]
+fn write(hasher: &mut Hasher, value: Value) -> VmResult<()> {
+    hasher.write_value(&value)
+}
+
+/// Get the hash of all the values written so far.
+///
+/// # Examples
+///
+/// ```rune
+/// use std::hash::Hasher;
+///
+/// let hasher = Hasher::new();
+/// hasher.write(42);
+/// assert_eq!(hasher.finish(), hasher.finish());
+/// ```
+#[rune::function(instance, path = Hasher::finish)]
+fn finish(hasher: &Hasher) -> i64 {
+    core::hash::Hasher::finish(hasher) as i64
+}
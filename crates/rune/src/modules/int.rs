@@ -1,5 +1,6 @@
 //! The `std::int` module.
 
+use core::cmp::Ordering;
 use core::num::ParseIntError;
 
 use crate::{ContextError, Module};
@@ -12,6 +13,8 @@ pub fn module() -> Result<Module, ContextError> {
 
     module.function(["parse"], parse)?;
     module.associated_function("to_float", to_float)?;
+    module.associated_function("partial_cmp", partial_cmp)?;
+    module.associated_function("cmp", cmp)?;
 
     module.associated_function("max", i64::max)?;
     module.associated_function("min", i64::min)?;
@@ -49,4 +52,14 @@ fn to_float(value: i64) -> f64 {
     value as f64
 }
 
+/// Compare two integers.
+fn partial_cmp(this: i64, other: i64) -> Option<Ordering> {
+    this.partial_cmp(&other)
+}
+
+/// Compare two integers.
+fn cmp(this: i64, other: i64) -> Ordering {
+    this.cmp(&other)
+}
+
 crate::__internal_impl_any!(::std::int, ParseIntError);
@@ -22,6 +22,7 @@ pub fn module() -> Result<Module, ContextError> {
     module.associated_function("pop", Vec::pop)?;
     module.associated_function("push", Vec::push)?;
     module.associated_function("remove", Vec::remove)?;
+    module.function_meta(sort)?;
     module.function_meta(sort_by)?;
     module.associated_function("insert", Vec::insert)?;
     module.associated_function(Protocol::INTO_ITER, Vec::into_iterator)?;
@@ -55,6 +56,39 @@ fn get(vec: &Vec, index: usize) -> Option<Value> {
     vec.get(index).cloned()
 }
 
+/// Sort a vector using the [`CMP`] protocol of its values.
+///
+/// [`CMP`]: crate::runtime::Protocol::CMP
+///
+/// # Examples
+///
+/// ```rune
+/// let values = [3, 1, 2];
+/// values.sort();
+/// assert_eq!(values, [1, 2, 3]);
+/// ```
+#[rune::function(instance, path = Vec::sort)]
+fn sort(vec: &mut Vec) -> VmResult<()> {
+    let mut error = None;
+
+    vec.sort_by(|a, b| match a.cmp(b) {
+        VmResult::Ok(ordering) => ordering,
+        VmResult::Err(e) => {
+            if error.is_none() {
+                error = Some(e);
+            }
+
+            cmp::Ordering::Equal
+        }
+    });
+
+    if let Some(e) = error {
+        VmResult::Err(e)
+    } else {
+        VmResult::Ok(())
+    }
+}
+
 /// Sort a vector by the specified comparator function.
 ///
 /// # Examples
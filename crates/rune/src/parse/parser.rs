@@ -105,6 +105,13 @@ impl<'a> Parser<'a> {
         Ok(self.peeker.at(0)?.is_none())
     }
 
+    /// The number of tokens which have not yet been consumed, if the parser is
+    /// reading from a token stream.
+    pub(crate) fn remaining(&self) -> Option<usize> {
+        let remaining = self.peeker.source.remaining()?;
+        Some(remaining + self.peeker.buf.len())
+    }
+
     /// Construct a new parser with a source.
     fn with_source(source: Source<'a>, span: Span) -> Self {
         let default_span = source.span().unwrap_or(span);
@@ -355,6 +362,14 @@ impl Source<'_> {
         }
     }
 
+    /// Get the number of tokens remaining, if known.
+    fn remaining(&self) -> Option<usize> {
+        match &self.inner {
+            SourceInner::Lexer(..) => None,
+            SourceInner::TokenStream(token_stream) => Some(token_stream.len()),
+        }
+    }
+
     /// Get the next token in the stream.
     fn next(&mut self) -> compile::Result<Option<Token>> {
         match &mut self.inner {
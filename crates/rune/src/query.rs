@@ -125,6 +125,8 @@ pub(crate) struct BuiltInLine {
 pub(crate) enum Build {
     Function(indexing::Function),
     InstanceFunction(indexing::InstanceFunction),
+    /// An implementation of a trait to check and build default functions for.
+    TraitImpl(indexing::TraitImpl),
     Closure(indexing::Closure),
     AsyncBlock(indexing::AsyncBlock),
     Unused,
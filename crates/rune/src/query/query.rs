@@ -18,10 +18,11 @@ use crate::compile::{
 use crate::compile::{ir, SourceLoader};
 use crate::hir;
 use crate::indexing::{self, Indexed};
-use crate::macros::Storage;
-use crate::parse::{Id, NonZeroId, Opaque, Resolve, ResolveContext};
+use crate::macros::{MacroRules, Storage};
+use crate::parse::{Id, NonZeroId, Opaque, Resolve};
 use crate::query::{Build, BuildEntry, BuiltInMacro, ConstFn, Named, QueryPath, Used};
-use crate::runtime::ConstValue;
+use crate::runtime::unit::default_argument_hash;
+use crate::runtime::{ConstValue, FUTURE_TYPE, TUPLE_TYPE, UNIT_TYPE};
 use crate::shared::{Consts, Gen, Items};
 use crate::{ast, Options};
 use crate::{Context, Diagnostics, Hash, SourceId, Sources};
@@ -60,12 +61,16 @@ pub(crate) struct QueryInner {
     indexed: BTreeMap<ItemId, Vec<indexing::Entry>>,
     /// Compiled constant functions.
     const_fns: HashMap<NonZeroId, Arc<ConstFn>>,
+    /// Traits which have been queried for.
+    traits: HashMap<ItemId, Arc<indexing::Trait>>,
     /// Indexed constant values.
     constants: HashMap<Hash, ConstValue>,
     /// Query paths.
     query_paths: HashMap<NonZeroId, QueryPath>,
     /// The result of internally resolved macros.
     internal_macros: HashMap<NonZeroId, Arc<BuiltInMacro>>,
+    /// Declarative macros defined through `macro_rules!`.
+    macro_rules: HashMap<ItemId, Arc<MacroRules>>,
     /// Associated between `id` and `Item`. Use to look up items through
     /// `item_for` with an opaque id.
     ///
@@ -549,6 +554,31 @@ impl<'a> Query<'a> {
         Ok(id)
     }
 
+    /// Insert a `macro_rules!` definition for the given item.
+    pub(crate) fn insert_macro_rules(
+        &mut self,
+        span: Span,
+        item: ItemId,
+        macro_rules: MacroRules,
+    ) -> compile::Result<()> {
+        if self.inner.macro_rules.contains_key(&item) {
+            return Err(compile::Error::new(
+                span,
+                CompileErrorKind::MacroConflict {
+                    item: self.pool.item(item).to_owned(),
+                },
+            ));
+        }
+
+        self.inner.macro_rules.insert(item, Arc::new(macro_rules));
+        Ok(())
+    }
+
+    /// Get the `macro_rules!` definition associated with the given item.
+    pub(crate) fn macro_rules_for(&self, item: ItemId) -> Option<Arc<MacroRules>> {
+        self.inner.macro_rules.get(&item).cloned()
+    }
+
     /// Get the item for the given identifier.
     pub(crate) fn item_for<T>(&self, ast: T) -> compile::Result<ItemMeta, MissingId>
     where
@@ -680,6 +710,45 @@ impl<'a> Query<'a> {
         Ok(())
     }
 
+    /// Add a new trait item that can be queried.
+    #[tracing::instrument(skip_all)]
+    pub(crate) fn index_trait(
+        &mut self,
+        item_meta: ItemMeta,
+        functions: Vec<indexing::TraitFn>,
+    ) -> compile::Result<()> {
+        tracing::trace!(item = ?self.pool.item(item_meta.item));
+
+        self.index(indexing::Entry {
+            item_meta,
+            indexed: Indexed::Trait(indexing::Trait { functions }),
+        });
+
+        Ok(())
+    }
+
+    /// Queue up an implementation of a trait to be checked and built.
+    #[tracing::instrument(skip_all)]
+    pub(crate) fn index_trait_impl(
+        &mut self,
+        item_meta: ItemMeta,
+        trait_impl: indexing::TraitImpl,
+    ) {
+        tracing::trace!(item = ?self.pool.item(item_meta.item));
+
+        self.inner.queue.push_back(BuildEntry {
+            item_meta,
+            used: Used::Used,
+            build: Build::TraitImpl(trait_impl),
+        });
+    }
+
+    /// Get the trait associated with the given item, if it has been queried
+    /// for.
+    pub(crate) fn get_trait(&self, item: ItemId) -> Option<Arc<indexing::Trait>> {
+        self.inner.traits.get(&item).cloned()
+    }
+
     /// Add a new enum item.
     #[tracing::instrument(skip_all)]
     pub(crate) fn index_enum(&mut self, item_meta: ItemMeta) -> compile::Result<()> {
@@ -990,6 +1059,184 @@ impl<'a> Query<'a> {
         })
     }
 
+    /// Resolve the type hash of a type annotation.
+    ///
+    /// Returns `None` for types which are not checked, like the never type `!`.
+    pub(crate) fn resolve_type(
+        &mut self,
+        source_id: SourceId,
+        ast: &ast::Type,
+    ) -> compile::Result<Option<Hash>> {
+        self.resolve_type_in(source_id, ast, None)
+    }
+
+    /// Resolve the type hash of a type annotation which is part of the
+    /// declaration of `this` item.
+    fn resolve_type_in(
+        &mut self,
+        source_id: SourceId,
+        ast: &ast::Type,
+        this: Option<ItemId>,
+    ) -> compile::Result<Option<Hash>> {
+        let path = match ast {
+            ast::Type::Path(path) => path,
+            ast::Type::Bang(..) => return Ok(None),
+            ast::Type::Tuple(tuple) => {
+                if tuple.is_empty() {
+                    return Ok(Some(UNIT_TYPE.hash));
+                }
+
+                for (ast, _) in tuple {
+                    self.resolve_type_in(source_id, ast, this)?;
+                }
+
+                return Ok(Some(TUPLE_TYPE.hash));
+            }
+        };
+
+        let arena = hir::Arena::new();
+        let mut ctx = hir::lowering::Ctx::with_query(&arena, self.borrow(), source_id);
+        let path = hir::lowering::path(&mut ctx, path)?;
+        let named = self.convert_path(&path)?;
+
+        // NB: script types are resolved without building their metadata, since
+        // type annotations are allowed to refer to each other.
+        let is_script_type = self
+            .inner
+            .indexed
+            .get(&named.item)
+            .map_or(false, |entries| {
+                entries
+                    .iter()
+                    .any(|entry| matches!(entry.indexed, Indexed::Struct(..) | Indexed::Enum))
+            });
+
+        if is_script_type || this == Some(named.item) {
+            return Ok(Some(self.pool.item_type_hash(named.item)));
+        }
+
+        let location = Location::new(source_id, path.span());
+        let meta = self.lookup_meta(location, named.item, GenericsParameters::default())?;
+
+        match meta.kind {
+            meta::Kind::Type { .. } | meta::Kind::Struct { .. } | meta::Kind::Enum { .. } => {
+                Ok(Some(meta.hash))
+            }
+            _ => Err(compile::Error::expected_meta(
+                path,
+                meta.info(self.pool),
+                "type",
+            )),
+        }
+    }
+
+    /// Get the item of the type with the given hash, used when reporting type
+    /// errors.
+    pub(crate) fn type_item(&self, hash: Hash) -> ItemBuf {
+        let script = self.inner.meta.values().find(|meta| {
+            meta.hash == hash
+                && matches!(
+                    meta.kind,
+                    meta::Kind::Struct { .. } | meta::Kind::Enum { .. }
+                )
+        });
+
+        if let Some(meta) = script {
+            return self.pool.item(meta.item_meta.item).to_owned();
+        }
+
+        self.context
+            .lookup_meta_by_hash(hash)
+            .find_map(|meta| meta.item.clone())
+            .unwrap_or_else(|| ItemBuf::with_item([hash.to_string()]))
+    }
+
+    /// Get the variants of the enum which the variant identified by
+    /// `variant_hash` belongs to, together with their indexes.
+    ///
+    /// Variants which haven't been built yet are looked up among the indexed
+    /// items, so this doesn't cause anything new to be compiled.
+    pub(crate) fn enum_variants(
+        &mut self,
+        variant_hash: Hash,
+        enum_hash: Hash,
+    ) -> Option<Vec<(usize, ItemBuf)>> {
+        let mut variants = Vec::new();
+
+        // NB: items from the context are allocated in the pool as well when
+        // they are referenced, so the variant is only from a script if it has
+        // script metadata.
+        let script = self
+            .pool
+            .item_by_type_hash(variant_hash)
+            .filter(|item| self.inner.meta.contains_key(&(*item, Hash::EMPTY)));
+
+        if let Some(item) = script {
+            let item = self.pool.item(item).parent()?.to_owned();
+
+            let children = self
+                .iter_components(&item)
+                .map(|c| c.to_owned())
+                .collect::<Vec<_>>();
+
+            for c in children {
+                let child = item.extended(c);
+                let id = self.pool.alloc_item(&child);
+
+                if let Some(meta) = self.inner.meta.get(&(id, Hash::EMPTY)) {
+                    if let meta::Kind::Variant {
+                        enum_hash: hash,
+                        index,
+                        ..
+                    } = meta.kind
+                    {
+                        if hash == enum_hash {
+                            variants.push((index, child));
+                        }
+                    }
+
+                    continue;
+                }
+
+                let index = self.inner.indexed.get(&id).and_then(|entries| {
+                    entries.iter().find_map(|entry| match &entry.indexed {
+                        Indexed::Variant(variant) => Some(variant.index),
+                        _ => None,
+                    })
+                });
+
+                if let Some(index) = index {
+                    variants.push((index, child));
+                }
+            }
+        } else {
+            let item = self
+                .context
+                .lookup_meta_by_hash(variant_hash)
+                .find_map(|meta| meta.item.as_deref()?.parent())?;
+
+            for c in self.context.iter_components(item) {
+                let child = item.extended(c);
+
+                for meta in self.context.lookup_meta(&child).into_iter().flatten() {
+                    if let meta::Kind::Variant {
+                        enum_hash: hash,
+                        index,
+                        ..
+                    } = meta.kind
+                    {
+                        if hash == enum_hash {
+                            variants.push((index, child.clone()));
+                        }
+                    }
+                }
+            }
+        }
+
+        variants.sort_by_key(|(index, _)| *index);
+        Some(variants)
+    }
+
     /// Declare a new import.
     #[tracing::instrument(skip_all)]
     pub(crate) fn insert_import(
@@ -1195,7 +1442,8 @@ impl<'a> Query<'a> {
     ) -> compile::Result<meta::Meta> {
         /// Convert AST fields into meta fields.
         fn convert_fields(
-            ctx: ResolveContext<'_>,
+            q: &mut Query<'_>,
+            item_meta: &ItemMeta,
             body: ast::Fields,
         ) -> compile::Result<meta::Fields> {
             Ok(match body {
@@ -1203,17 +1451,139 @@ impl<'a> Query<'a> {
                 ast::Fields::Unnamed(tuple) => meta::Fields::Unnamed(tuple.len()),
                 ast::Fields::Named(st) => {
                     let mut fields = HashSet::new();
+                    let mut types = HashMap::new();
+
+                    for (ast::Field { name, ty, .. }, _) in st {
+                        let name = Box::<str>::from(name.resolve(resolve_context!(q))?);
+
+                        if let Some((_, ty)) = &ty {
+                            let source_id = item_meta.location.source_id;
+
+                            if let Some(hash) =
+                                q.resolve_type_in(source_id, ty, Some(item_meta.item))?
+                            {
+                                types.insert(name.clone(), hash);
+                            }
+                        }
 
-                    for (ast::Field { name, .. }, _) in st {
-                        let name = name.resolve(ctx)?;
-                        fields.insert(name.into());
+                        fields.insert(name);
                     }
 
-                    meta::Fields::Named(meta::FieldsNamed { fields })
+                    meta::Fields::Named(meta::FieldsNamed { fields, types })
                 }
             })
         }
 
+        /// Build the signature of a function, resolving its annotated types
+        /// and evaluating the default values of its arguments.
+        fn signature(
+            q: &mut Query<'_>,
+            item_meta: &ItemMeta,
+            ast: &ast::ItemFn,
+            used: Used,
+        ) -> compile::Result<meta::Signature> {
+            let source_id = item_meta.location.source_id;
+            let hash = q.pool.item_type_hash(item_meta.item);
+
+            let mut argument_types = Vec::with_capacity(ast.args.len());
+            let mut argument_names = Vec::with_capacity(ast.args.len());
+            let mut argument_defaults = Vec::with_capacity(ast.args.len());
+
+            for (index, (arg, _)) in ast.args.iter().enumerate() {
+                let (pat, ty, value) = match arg {
+                    ast::FnArg::SelfValue(..) => (None, None, None),
+                    ast::FnArg::Pat(pat) => (Some(pat), None, None),
+                    ast::FnArg::Typed(arg) => (Some(&arg.pat), Some(&arg.ty), None),
+                    ast::FnArg::Default(arg) => (
+                        Some(&arg.pat),
+                        arg.ty.as_ref().map(|(_, ty)| ty),
+                        Some(&arg.value),
+                    ),
+                };
+
+                argument_types.push(match ty {
+                    Some(ty) => q.resolve_type(source_id, ty)?,
+                    None => None,
+                });
+
+                let name = match pat {
+                    Some(ast::Pat::Path(pat)) => pat.path.try_as_ident(),
+                    _ => None,
+                };
+
+                argument_names.push(match name {
+                    Some(name) => Some(name.resolve(resolve_context!(q))?.into()),
+                    None => None,
+                });
+
+                let Some(value) = value else {
+                    if let Some(Some(..)) = argument_defaults.last() {
+                        return Err(compile::Error::new(
+                            arg,
+                            CompileErrorKind::ArgumentDefaultNotTrailing,
+                        ));
+                    }
+
+                    argument_defaults.push(None);
+                    continue;
+                };
+
+                let ir = {
+                    let arena = crate::hir::Arena::new();
+                    let mut hir_ctx =
+                        crate::hir::lowering::Ctx::with_const(&arena, q.borrow(), source_id);
+                    let hir = crate::hir::lowering::expr(&mut hir_ctx, value)?;
+
+                    let mut compiler = IrCompiler {
+                        source_id,
+                        q: q.borrow(),
+                    };
+                    ir::compiler::expr(&hir, &mut compiler)?
+                };
+
+                let mut const_compiler = IrInterpreter {
+                    budget: IrBudget::new(1_000_000),
+                    scopes: Default::default(),
+                    module: item_meta.module,
+                    item: item_meta.item,
+                    q: q.borrow(),
+                };
+
+                // NB: the value isn't cached since it's not a constant item.
+                let const_value = const_compiler.eval_value(&ir, used)?.into_const(&ir)?;
+
+                let hash = default_argument_hash(hash, index);
+                q.inner.constants.insert(hash, const_value);
+                argument_defaults.push(Some(hash));
+            }
+
+            let return_type = match &ast.output {
+                Some((_, ty)) => q.resolve_type(source_id, ty)?,
+                None => None,
+            };
+
+            let annotated_return = if ast.async_token.is_some() {
+                Some(FUTURE_TYPE.hash)
+            } else {
+                return_type
+            };
+
+            Ok(meta::Signature {
+                #[cfg(feature = "doc")]
+                is_async: ast.async_token.is_some(),
+                #[cfg(feature = "doc")]
+                args: Some(ast.args.len()),
+                #[cfg(feature = "doc")]
+                return_type,
+                #[cfg(feature = "doc")]
+                argument_types: argument_types.clone().into(),
+                annotated_return,
+                annotated_arguments: argument_types.into(),
+                argument_names: argument_names.into(),
+                argument_defaults: argument_defaults.into(),
+            })
+        }
+
         let indexing::Entry { item_meta, indexed } = entry;
 
         let kind = match indexed {
@@ -1231,12 +1601,12 @@ impl<'a> Query<'a> {
                 meta::Kind::Variant {
                     enum_hash: enum_meta.hash,
                     index: variant.index,
-                    fields: convert_fields(resolve_context!(self), variant.ast.body)?,
+                    fields: convert_fields(self, &item_meta, variant.ast.body)?,
                     constructor: None,
                 }
             }
             Indexed::Struct(st) => meta::Kind::Struct {
-                fields: convert_fields(resolve_context!(self), st.ast.body)?,
+                fields: convert_fields(self, &item_meta, st.ast.body)?,
                 constructor: None,
                 parameters: Hash::EMPTY,
             },
@@ -1244,16 +1614,7 @@ impl<'a> Query<'a> {
                 let kind = meta::Kind::Function {
                     is_test: f.is_test,
                     is_bench: f.is_bench,
-                    signature: meta::Signature {
-                        #[cfg(feature = "doc")]
-                        is_async: f.ast.async_token.is_some(),
-                        #[cfg(feature = "doc")]
-                        args: Some(f.ast.args.len()),
-                        #[cfg(feature = "doc")]
-                        return_type: None,
-                        #[cfg(feature = "doc")]
-                        argument_types: Box::from([]),
-                    },
+                    signature: signature(self, &item_meta, &f.ast, used)?,
                     parameters: Hash::EMPTY,
                 };
 
@@ -1270,16 +1631,7 @@ impl<'a> Query<'a> {
 
                 let kind = meta::Kind::AssociatedFunction {
                     kind: meta::AssociatedKind::Instance(name),
-                    signature: meta::Signature {
-                        #[cfg(feature = "doc")]
-                        is_async: f.ast.async_token.is_some(),
-                        #[cfg(feature = "doc")]
-                        args: Some(f.ast.args.len()),
-                        #[cfg(feature = "doc")]
-                        return_type: None,
-                        #[cfg(feature = "doc")]
-                        argument_types: Box::from([]),
-                    },
+                    signature: signature(self, &item_meta, &f.ast, used)?,
                     parameters: Hash::EMPTY,
                     #[cfg(feature = "doc")]
                     container: self.pool.item_type_hash(f.impl_item),
@@ -1420,6 +1772,11 @@ impl<'a> Query<'a> {
 
                 meta::Kind::Import(import.entry)
             }
+            Indexed::Trait(t) => {
+                let functions = t.functions.iter().map(|f| f.name.clone()).collect();
+                self.inner.traits.insert(item_meta.item, Arc::new(t));
+                meta::Kind::Trait { functions }
+            }
             Indexed::Module => meta::Kind::Module,
         };
 
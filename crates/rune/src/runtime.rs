@@ -20,15 +20,28 @@ pub mod budget;
 mod bytes;
 pub use self::bytes::Bytes;
 
+#[cfg(feature = "std")]
+pub mod cancellation;
+#[cfg(feature = "std")]
+pub use self::cancellation::CancellationToken;
+
 mod call;
 pub use self::call::Call;
 
 mod const_value;
 pub use self::const_value::ConstValue;
 
+#[cfg(feature = "std")]
+pub mod coverage;
+#[cfg(feature = "std")]
+pub use self::coverage::Coverage;
+
 pub mod debug;
 pub use self::debug::{DebugInfo, DebugInst};
 
+pub mod debugger;
+pub use self::debugger::Debugger;
+
 mod env;
 
 pub mod format;
@@ -53,6 +66,9 @@ pub use self::generator_state::GeneratorState;
 mod guarded_args;
 pub use self::guarded_args::GuardedArgs;
 
+mod hasher;
+pub use self::hasher::Hasher;
+
 mod inst;
 pub use self::inst::{
     Inst, InstAddress, InstAssignOp, InstOp, InstRangeLimits, InstTarget, InstValue, InstVariant,
@@ -78,6 +94,11 @@ pub use self::object::Object;
 mod panic;
 pub(crate) use self::panic::{BoxedPanic, Panic};
 
+#[cfg(feature = "std")]
+pub mod profiler;
+#[cfg(feature = "std")]
+pub use self::profiler::Profiler;
+
 mod protocol;
 pub use self::protocol::Protocol;
 
@@ -98,11 +119,17 @@ mod select;
 pub(crate) use self::select::Select;
 
 mod shared;
+pub(crate) use self::shared::Footprint;
 pub use self::shared::{Mut, RawMut, RawRef, Ref, Shared, SharedPointerGuard};
 
+pub mod snapshot;
+pub use self::snapshot::{SnapshotError, VmSnapshot};
+
 mod stack;
 pub use self::stack::{Stack, StackError};
 
+mod stepper;
+
 mod static_string;
 pub use self::static_string::StaticString;
 
@@ -153,7 +180,9 @@ pub(crate) use self::vm_call::VmCall;
 
 mod vm_error;
 pub(crate) use self::vm_error::VmErrorKind;
-pub use self::vm_error::{try_result, TryFromResult, VmError, VmIntegerRepr, VmResult};
+pub use self::vm_error::{
+    try_result, BacktraceFrame, TryFromResult, VmError, VmIntegerRepr, VmResult,
+};
 
 mod vm_execution;
 pub use self::vm_execution::{ExecutionState, VmExecution, VmSendExecution};
@@ -5,6 +5,9 @@
 //!
 //! By default the budget is disabled, but can be enabled by wrapping your
 //! function call in [with].
+//!
+//! Memory can be budgeted in a similar manner by wrapping your function call
+//! in [with_memory].
 
 #[cfg_attr(feature = "std", path = "budget/std.rs")]
 mod no_std;
@@ -77,3 +80,192 @@ where
         poll
     }
 }
+
+/// The state of a memory budget.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Memory {
+    /// The maximum number of bytes which may be in use.
+    limit: usize,
+    /// The number of bytes currently in use.
+    used: usize,
+}
+
+impl Memory {
+    /// A memory budget which is not being accounted.
+    const UNLIMITED: Self = Self {
+        limit: usize::MAX,
+        used: 0,
+    };
+
+    #[inline]
+    fn is_unlimited(&self) -> bool {
+        self.limit == usize::MAX
+    }
+}
+
+/// Something being budgeted by memory.
+#[pin_project]
+pub struct MemoryBudget<T> {
+    /// The current memory budget.
+    memory: Memory,
+    /// The thing being budgeted.
+    #[pin]
+    value: T,
+}
+
+/// Wrap the given value with a memory budget of `limit` bytes.
+///
+/// While the wrapped value is being called or polled, memory allocated through
+/// [Shared][crate::runtime::Shared] values is accounted for. This includes the
+/// heap allocations of strings, byte arrays, vectors, tuples and objects, as
+/// they are observed when constructed or mutated through native functions.
+/// Memory is released as the values are dropped.
+///
+/// The limit is checked periodically as instructions are executed, so once it
+/// has been exceeded the virtual machine errors shortly after.
+///
+/// Accounting is an approximation. It does not cover memory allocated by
+/// native types which are not tracked, and a native function can allocate
+/// beyond the limit before the virtual machine gets to observe it.
+///
+/// # Examples
+///
+/// ```
+/// use rune::{Context, Vm};
+/// use rune::runtime::budget;
+/// use std::sync::Arc;
+///
+/// let context = Context::with_default_modules()?;
+/// let runtime = Arc::new(context.runtime());
+///
+/// let mut sources = rune::sources! {
+///     entry => {
+///         pub fn main() {
+///             let values = [];
+///
+///             loop {
+///                 values.push("hello world");
+///             }
+///         }
+///     }
+/// };
+///
+/// let unit = rune::prepare(&mut sources).with_context(&context).build()?;
+/// let mut vm = Vm::new(runtime, Arc::new(unit));
+///
+/// let mut execution = vm.execute(["main"], ())?;
+/// let result = budget::with_memory(1 << 20, || execution.complete()).call();
+/// assert!(result.is_err());
+/// # Ok::<_, rune::Error>(())
+/// ```
+pub fn with_memory<T>(limit: usize, value: T) -> MemoryBudget<T> {
+    tracing::trace!(?limit);
+
+    MemoryBudget {
+        memory: Memory {
+            limit: limit.min(usize::MAX - 1),
+            used: 0,
+        },
+        value,
+    }
+}
+
+/// Charge the given number of bytes to the memory budget.
+///
+/// Returns the number of bytes charged, which is zero if memory is not being
+/// accounted for.
+#[inline]
+pub(crate) fn alloc(bytes: usize) -> usize {
+    let mut memory = self::no_std::rune_memory_get();
+
+    if memory.is_unlimited() {
+        return 0;
+    }
+
+    memory.used = memory.used.saturating_add(bytes);
+    let _ = self::no_std::rune_memory_replace(memory);
+    bytes
+}
+
+/// Release bytes previously charged through [alloc].
+#[inline]
+pub(crate) fn free(bytes: usize) {
+    if bytes == 0 {
+        return;
+    }
+
+    let mut memory = self::no_std::rune_memory_get();
+
+    if memory.is_unlimited() {
+        return;
+    }
+
+    memory.used = memory.used.saturating_sub(bytes);
+    let _ = self::no_std::rune_memory_replace(memory);
+}
+
+/// Re-charge an allocation which was previously charged with `old` bytes and
+/// now uses `new` bytes.
+///
+/// Returns the number of bytes now being charged for the allocation.
+#[inline]
+pub(crate) fn realloc(old: usize, new: usize) -> usize {
+    let mut memory = self::no_std::rune_memory_get();
+
+    if memory.is_unlimited() {
+        return old;
+    }
+
+    memory.used = memory.used.saturating_sub(old).saturating_add(new);
+    let _ = self::no_std::rune_memory_replace(memory);
+    new
+}
+
+/// Test if the memory budget has been exceeded, returning the limit which was
+/// exceeded if it has.
+#[inline(never)]
+pub(crate) fn memory_exceeded() -> Option<usize> {
+    let memory = self::no_std::rune_memory_get();
+
+    if memory.used > memory.limit {
+        Some(memory.limit)
+    } else {
+        None
+    }
+}
+
+#[repr(transparent)]
+struct MemoryGuard(Memory);
+
+impl Drop for MemoryGuard {
+    fn drop(&mut self) {
+        let _ = self::no_std::rune_memory_replace(self.0);
+    }
+}
+
+impl<T, O> MemoryBudget<T>
+where
+    T: FnOnce() -> O,
+{
+    /// Call the wrapped function.
+    pub fn call(self) -> O {
+        let _guard = MemoryGuard(self::no_std::rune_memory_replace(self.memory));
+        (self.value)()
+    }
+}
+
+impl<T> Future for MemoryBudget<T>
+where
+    T: Future,
+{
+    type Output = T::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        let _guard = MemoryGuard(self::no_std::rune_memory_replace(*this.memory));
+        let poll = this.value.poll(cx);
+        *this.memory = self::no_std::rune_memory_get();
+        poll
+    }
+}
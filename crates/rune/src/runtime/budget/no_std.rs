@@ -37,3 +37,13 @@ pub(super) fn rune_budget_replace(value: usize) -> usize {
     // implemented this correctly.
     unsafe { __rune_budget_replace(value) }
 }
+
+// NB: memory accounting is not supported in no-std environments, so the memory
+// budget is always unlimited.
+pub(super) fn rune_memory_get() -> super::Memory {
+    super::Memory::UNLIMITED
+}
+
+pub(super) fn rune_memory_replace(_: super::Memory) -> super::Memory {
+    super::Memory::UNLIMITED
+}
@@ -1,5 +1,7 @@
 use core::cell::Cell;
 
+use super::Memory;
+
 std::thread_local!(static BUDGET: Cell<usize> = Cell::new(usize::max_value()));
 
 pub(super) fn rune_budget_take() -> bool {
@@ -24,3 +26,13 @@ pub(super) fn rune_budget_get() -> usize {
 pub(super) fn rune_budget_replace(value: usize) -> usize {
     BUDGET.with(|tls| tls.replace(value))
 }
+
+std::thread_local!(static MEMORY: Cell<Memory> = const { Cell::new(Memory::UNLIMITED) });
+
+pub(super) fn rune_memory_get() -> Memory {
+    MEMORY.with(|tls| tls.get())
+}
+
+pub(super) fn rune_memory_replace(value: Memory) -> Memory {
+    MEMORY.with(|tls| tls.replace(value))
+}
@@ -14,7 +14,7 @@ use crate as rune;
 use crate::compile::Named;
 use crate::module::InstallWith;
 use crate::runtime::{
-    FromValue, Mut, RawMut, RawRef, RawStr, Ref, UnsafeFromValue, Value, VmResult,
+    Footprint, FromValue, RawMut, RawRef, RawStr, Ref, UnsafeFromValue, Value, VmResult,
 };
 
 /// A vector of bytes.
@@ -216,8 +216,7 @@ impl<'a> UnsafeFromValue for &'a mut Bytes {
 
     fn from_value(value: Value) -> VmResult<(Self::Output, Self::Guard)> {
         let bytes = vm_try!(value.into_bytes());
-        let bytes = vm_try!(bytes.into_mut());
-        VmResult::Ok(Mut::into_raw(bytes))
+        VmResult::Ok(vm_try!(bytes.into_measured_raw_mut()))
     }
 
     unsafe fn unsafe_coerce(output: Self::Output) -> Self {
@@ -243,6 +242,13 @@ impl<'a> UnsafeFromValue for &'a [u8] {
     }
 }
 
+impl Footprint for Bytes {
+    #[inline]
+    fn footprint(&self) -> usize {
+        self.bytes.capacity()
+    }
+}
+
 impl Named for Bytes {
     const BASE_NAME: RawStr = RawStr::from_str("Bytes");
 }
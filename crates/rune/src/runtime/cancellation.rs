@@ -0,0 +1,320 @@
+//! Cooperative cancellation of executions.
+//!
+//! A [CancellationToken] can be associated with an execution through
+//! [VmExecution::set_cancellation][crate::runtime::VmExecution::set_cancellation].
+//! Once the token is cancelled or its deadline has passed, the execution stops
+//! with an error the next time it checks the token.
+//!
+//! The token is checked periodically as instructions are being executed and
+//! while the execution is awaiting a future. Native functions which run for a
+//! long time without returning to the virtual machine will not observe it.
+
+use core::cell::Cell;
+use core::future::Future;
+use core::pin::Pin;
+use core::ptr;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Context, Poll, Waker};
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::no_std::prelude::*;
+use crate::no_std::sync::Arc;
+
+use crate::runtime::{VmErrorKind, VmResult};
+
+std::thread_local! {
+    static CURRENT: Cell<*const Inner> = const { Cell::new(ptr::null()) };
+}
+
+/// A token used to cancel a running execution, possibly from another thread.
+///
+/// The token is cheap to clone, and all clones refer to the same cancellation
+/// state.
+///
+/// # Examples
+///
+/// ```
+/// use rune::{Context, Vm};
+/// use rune::runtime::CancellationToken;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// let context = Context::with_default_modules()?;
+/// let runtime = Arc::new(context.runtime());
+///
+/// let mut sources = rune::sources! {
+///     entry => {
+///         pub fn main() {
+///             loop {}
+///         }
+///     }
+/// };
+///
+/// let unit = rune::prepare(&mut sources).with_context(&context).build()?;
+/// let mut vm = Vm::new(runtime, Arc::new(unit));
+///
+/// let mut execution = vm.execute(["main"], ())?;
+/// execution.set_cancellation(CancellationToken::with_timeout(Duration::from_millis(10)));
+///
+/// let error = execution.complete().into_result().unwrap_err();
+/// assert_eq!(error.to_string(), "Execution was cancelled");
+/// # Ok::<_, rune::Error>(())
+/// ```
+#[derive(Default, Clone)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    deadline: Option<Instant>,
+    wakers: Mutex<Wakers>,
+}
+
+/// Wakers registered by futures awaiting a token, keyed by registration.
+#[derive(Default)]
+struct Wakers {
+    next: usize,
+    entries: Vec<(usize, Waker)>,
+}
+
+impl Inner {
+    fn is_cancelled(&self) -> bool {
+        if self.cancelled.load(Ordering::Acquire) {
+            return true;
+        }
+
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => {
+                self.cancelled.store(true, Ordering::Release);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl CancellationToken {
+    /// Construct a new token which is cancelled by calling
+    /// [cancel][CancellationToken::cancel].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Construct a new token which is cancelled once the given deadline has
+    /// passed, or by calling [cancel][CancellationToken::cancel].
+    ///
+    /// Note that the deadline is only observed when the token is checked. If an
+    /// execution is awaiting a future which never wakes up, it will not be
+    /// cancelled until the token is cancelled explicitly.
+    pub fn with_deadline(deadline: Instant) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                deadline: Some(deadline),
+                ..Inner::default()
+            }),
+        }
+    }
+
+    /// Construct a new token which is cancelled once the given amount of time
+    /// has passed from now.
+    ///
+    /// See [CancellationToken::with_deadline].
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self::with_deadline(Instant::now() + timeout)
+    }
+
+    /// Get the deadline of the token, if one is set.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.inner.deadline
+    }
+
+    /// Cancel the token, waking up any execution which is currently awaiting
+    /// a future.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Release);
+
+        let entries = match self.inner.wakers.lock() {
+            Ok(mut wakers) => core::mem::take(&mut wakers.entries),
+            Err(..) => return,
+        };
+
+        for (_, waker) in entries {
+            waker.wake();
+        }
+    }
+
+    /// Test if the token has been cancelled, or if its deadline has passed.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.is_cancelled()
+    }
+
+    /// Register a waker to be woken up once the token is cancelled.
+    ///
+    /// The registration is stored in `key`, so that registering again replaces
+    /// the waker instead of adding another one.
+    fn register(&self, key: &mut Option<usize>, waker: &Waker) {
+        let Ok(mut wakers) = self.inner.wakers.lock() else {
+            return;
+        };
+
+        let wakers = &mut *wakers;
+
+        if let Some(key) = *key {
+            if let Some((_, existing)) = wakers.entries.iter_mut().find(|(k, _)| *k == key) {
+                if !existing.will_wake(waker) {
+                    *existing = waker.clone();
+                }
+
+                return;
+            }
+        }
+
+        let new = wakers.next;
+        wakers.next = wakers.next.wrapping_add(1);
+        wakers.entries.push((new, waker.clone()));
+        *key = Some(new);
+    }
+
+    /// Get the number of wakers which are registered with the token.
+    #[cfg(test)]
+    pub(crate) fn registered(&self) -> usize {
+        self.inner
+            .wakers
+            .lock()
+            .map_or(0, |wakers| wakers.entries.len())
+    }
+
+    /// Remove the waker registered under the given key.
+    fn unregister(&self, key: &mut Option<usize>) {
+        let Some(key) = key.take() else {
+            return;
+        };
+
+        let Ok(mut wakers) = self.inner.wakers.lock() else {
+            return;
+        };
+
+        wakers.entries.retain(|(k, _)| *k != key);
+    }
+}
+
+/// Install the given token for the current thread while the returned guard is
+/// live.
+///
+/// If no token is specified, any token which is already installed is kept.
+/// This means that virtual machines which are called from native functions
+/// observe the token of the execution calling them.
+pub(crate) fn install(token: Option<&CancellationToken>) -> Guard {
+    let Some(token) = token else {
+        return Guard { old: None };
+    };
+
+    let old = CURRENT.with(|tls| tls.replace(Arc::as_ptr(&token.inner)));
+    Guard { old: Some(old) }
+}
+
+/// Test if the installed token has been cancelled, or if its deadline has
+/// passed.
+#[inline]
+pub(crate) fn check() -> bool {
+    CURRENT.with(|tls| {
+        let current = tls.get();
+
+        if current.is_null() {
+            return false;
+        }
+
+        // Safety: tokens are only installed for the duration of a guard,
+        // during which the token is being kept alive by the caller.
+        let current = unsafe { &*current };
+
+        current.is_cancelled()
+    })
+}
+
+pub(crate) struct Guard {
+    old: Option<*const Inner>,
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if let Some(old) = self.old {
+            CURRENT.with(|tls| tls.set(old));
+        }
+    }
+}
+
+/// A future which resolves to a cancellation error if the token is cancelled
+/// before the wrapped future completes.
+#[pin_project::pin_project]
+pub(crate) struct Cancellable<'a, F> {
+    registration: Registration<'a>,
+    #[pin]
+    future: F,
+}
+
+impl<'a, F> Cancellable<'a, F> {
+    pub(crate) fn new(token: Option<&'a CancellationToken>, future: F) -> Self {
+        Self {
+            registration: Registration { token, key: None },
+            future,
+        }
+    }
+}
+
+/// The waker registered with a token, which is removed once the future
+/// registering it completes or is dropped.
+struct Registration<'a> {
+    token: Option<&'a CancellationToken>,
+    key: Option<usize>,
+}
+
+impl Drop for Registration<'_> {
+    fn drop(&mut self) {
+        if let Some(token) = self.token {
+            token.unregister(&mut self.key);
+        }
+    }
+}
+
+impl<F> Future for Cancellable<'_, F>
+where
+    F: Future<Output = VmResult<()>>,
+{
+    type Output = VmResult<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let registration = this.registration;
+
+        if let Some(token) = registration.token {
+            if token.is_cancelled() {
+                token.unregister(&mut registration.key);
+                return Poll::Ready(VmResult::err(VmErrorKind::Cancelled));
+            }
+
+            token.register(&mut registration.key, cx.waker());
+
+            // NB: check again in case the token was cancelled before the waker
+            // was registered.
+            if token.is_cancelled() {
+                token.unregister(&mut registration.key);
+                return Poll::Ready(VmResult::err(VmErrorKind::Cancelled));
+            }
+        }
+
+        let poll = this.future.poll(cx);
+
+        if poll.is_ready() {
+            if let Some(token) = registration.token {
+                token.unregister(&mut registration.key);
+            }
+        }
+
+        poll
+    }
+}
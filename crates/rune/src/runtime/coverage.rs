@@ -0,0 +1,284 @@
+//! Instruction coverage for virtual machines.
+//!
+//! [Coverage] counts how many times every instruction of a [VmExecution] is
+//! executed. Through the
+//! [DebugInfo][crate::runtime::DebugInfo] of the unit the counts can then be
+//! aggregated into the lines of the sources they were compiled from, and
+//! written as an [lcov] tracefile through [Coverage::write_lcov].
+//!
+//! [lcov]: https://github.com/linux-test-project/lcov
+
+use core::convert::Infallible;
+use core::ops::ControlFlow;
+
+use std::io;
+
+use crate::no_std::collections::{BTreeMap, HashMap};
+use crate::no_std::prelude::*;
+use crate::no_std::sync::Arc;
+
+use crate::runtime::stepper::{self, StepHook};
+use crate::runtime::{Unit, Value, Vm, VmExecution, VmResult};
+use crate::{SourceId, Sources};
+
+/// The coverage of a single line in a source file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LineCoverage {
+    /// The one-based line number.
+    pub line: usize,
+    /// The number of times the line was executed, which is the highest number
+    /// of times any instruction on the line was executed.
+    pub hits: u64,
+}
+
+/// The coverage of a single function.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct FunctionCoverage {
+    /// The name of the function.
+    pub name: String,
+    /// The one-based line number the function starts at.
+    pub line: usize,
+    /// The number of times the function was called.
+    pub hits: u64,
+}
+
+/// The coverage of a single source file.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct FileCoverage {
+    /// The identifier of the source.
+    pub source_id: SourceId,
+    /// The name of the source.
+    pub name: String,
+    /// Every line which has instructions associated with it, ordered by line.
+    pub lines: Vec<LineCoverage>,
+    /// Every function defined in the source, ordered by line.
+    pub functions: Vec<FunctionCoverage>,
+}
+
+impl FileCoverage {
+    /// The number of lines which have instructions associated with them.
+    pub fn lines_found(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// The number of lines which have been executed at least once.
+    pub fn lines_hit(&self) -> usize {
+        self.lines.iter().filter(|line| line.hits > 0).count()
+    }
+}
+
+/// Coverage collected for a single unit.
+///
+/// Coverage accumulates over every execution completed through it, so a single
+/// instance can be used to collect the coverage of a whole test suite.
+///
+/// # Examples
+///
+/// ```
+/// use rune::{Context, Source, Sources, Vm};
+/// use rune::runtime::Coverage;
+/// use std::sync::Arc;
+///
+/// let context = Context::with_default_modules()?;
+///
+/// let mut sources = Sources::new();
+/// sources.insert(Source::new("entry", r#"
+/// pub fn main(n) {
+///     if n > 0 {
+///         1
+///     } else {
+///         2
+///     }
+/// }
+/// "#));
+///
+/// let unit = Arc::new(rune::prepare(&mut sources).with_context(&context).build()?);
+/// let mut vm = Vm::new(Arc::new(context.runtime()), unit.clone());
+///
+/// let mut coverage = Coverage::new(unit);
+/// let mut execution = vm.execute(["main"], (1,))?;
+/// coverage.complete(&mut execution).into_result()?;
+///
+/// let files = coverage.files(&sources);
+/// let lines = &files[0].lines;
+/// assert!(lines.iter().any(|l| l.line == 4 && l.hits == 1));
+/// assert!(lines.iter().any(|l| l.line == 6 && l.hits == 0));
+/// # Ok::<_, rune::Error>(())
+/// ```
+pub struct Coverage {
+    /// The unit coverage is collected for.
+    unit: Arc<Unit>,
+    /// The number of times each instruction has been executed.
+    hits: HashMap<usize, u64>,
+}
+
+impl Coverage {
+    /// Construct a new empty coverage collector for the given unit.
+    pub fn new(unit: Arc<Unit>) -> Self {
+        Self {
+            unit,
+            hits: HashMap::new(),
+        }
+    }
+
+    /// Complete the given execution while collecting coverage for it.
+    ///
+    /// Use [Coverage::async_complete] if the execution uses async
+    /// instructions.
+    pub fn complete<T>(&mut self, execution: &mut VmExecution<T>) -> VmResult<Value>
+    where
+        T: AsRef<Vm> + AsMut<Vm>,
+    {
+        VmResult::Ok(vm_try!(stepper::run(execution, self)).into_complete())
+    }
+
+    /// Complete the given execution while collecting coverage for it, with
+    /// support for async instructions.
+    pub async fn async_complete<T>(&mut self, execution: &mut VmExecution<T>) -> VmResult<Value>
+    where
+        T: AsRef<Vm> + AsMut<Vm>,
+    {
+        VmResult::Ok(vm_try!(stepper::async_run(execution, self).await).into_complete())
+    }
+
+    /// Get the number of times the instruction at the given offset has been
+    /// executed.
+    pub fn hits(&self, ip: usize) -> u64 {
+        self.hits.get(&ip).copied().unwrap_or_default()
+    }
+
+    /// Aggregate the collected coverage into the lines of every source with
+    /// debug information, ordered by source.
+    ///
+    /// Sources which are not present in `sources` are skipped. If the unit was
+    /// built without debug information this is empty.
+    pub fn files(&self, sources: &Sources) -> Vec<FileCoverage> {
+        let Some(debug) = self.unit.debug_info() else {
+            return Vec::new();
+        };
+
+        let mut lines = BTreeMap::<SourceId, BTreeMap<usize, u64>>::new();
+
+        for (&ip, inst) in &debug.instructions {
+            let Some(source) = sources.get(inst.source_id) else {
+                continue;
+            };
+
+            let (line, _) = source.pos_to_utf8_linecol(inst.span.start.into_usize());
+            let hits = lines
+                .entry(inst.source_id)
+                .or_default()
+                .entry(line + 1)
+                .or_default();
+            *hits = (*hits).max(self.hits(ip));
+        }
+
+        let mut functions = BTreeMap::<SourceId, Vec<FunctionCoverage>>::new();
+
+        for (&ip, hash) in &debug.functions_rev {
+            let (Some(inst), Some(signature)) =
+                (debug.instruction_at(ip), debug.functions.get(hash))
+            else {
+                continue;
+            };
+
+            let Some(source) = sources.get(inst.source_id) else {
+                continue;
+            };
+
+            let (line, _) = source.pos_to_utf8_linecol(inst.span.start.into_usize());
+
+            functions
+                .entry(inst.source_id)
+                .or_default()
+                .push(FunctionCoverage {
+                    name: signature.path.to_string(),
+                    line: line + 1,
+                    hits: self.hits(ip),
+                });
+        }
+
+        let mut files = Vec::with_capacity(lines.len());
+
+        for (source_id, lines) in lines {
+            let Some(source) = sources.get(source_id) else {
+                continue;
+            };
+
+            let name = match source.path() {
+                Some(path) => path.display().to_string(),
+                None => source.name().to_owned(),
+            };
+
+            let mut functions = functions.remove(&source_id).unwrap_or_default();
+            functions.sort_by(|a, b| (a.line, &a.name).cmp(&(b.line, &b.name)));
+
+            files.push(FileCoverage {
+                source_id,
+                name,
+                lines: lines
+                    .into_iter()
+                    .map(|(line, hits)| LineCoverage { line, hits })
+                    .collect(),
+                functions,
+            });
+        }
+
+        files
+    }
+
+    /// Write the collected coverage as an [lcov] tracefile, with one record per
+    /// source file.
+    ///
+    /// [lcov]: https://github.com/linux-test-project/lcov
+    pub fn write_lcov<W>(&self, sources: &Sources, out: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        for file in self.files(sources) {
+            writeln!(out, "SF:{}", file.name)?;
+
+            for function in &file.functions {
+                writeln!(out, "FN:{},{}", function.line, function.name)?;
+            }
+
+            for function in &file.functions {
+                writeln!(out, "FNDA:{},{}", function.hits, function.name)?;
+            }
+
+            let functions_hit = file.functions.iter().filter(|f| f.hits > 0).count();
+            writeln!(out, "FNF:{}", file.functions.len())?;
+            writeln!(out, "FNH:{}", functions_hit)?;
+
+            for line in &file.lines {
+                writeln!(out, "DA:{},{}", line.line, line.hits)?;
+            }
+
+            writeln!(out, "LF:{}", file.lines_found())?;
+            writeln!(out, "LH:{}", file.lines_hit())?;
+            writeln!(out, "end_of_record")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl StepHook for Coverage {
+    type Break = Infallible;
+
+    fn before<T>(&mut self, execution: &VmExecution<T>) -> ControlFlow<Infallible>
+    where
+        T: AsRef<Vm> + AsMut<Vm>,
+    {
+        let vm = execution.vm();
+
+        if Arc::ptr_eq(vm.unit(), &self.unit) {
+            *self.hits.entry(vm.ip()).or_default() += 1;
+        }
+
+        ControlFlow::Continue(())
+    }
+}
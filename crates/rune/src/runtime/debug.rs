@@ -50,6 +50,8 @@ pub struct DebugInst {
     pub comment: Option<Box<str>>,
     /// Label associated with the location.
     pub labels: Vec<DebugLabel>,
+    /// Named variables which become available starting at this instruction.
+    pub variables: Vec<DebugVariable>,
 }
 
 impl DebugInst {
@@ -65,10 +67,29 @@ impl DebugInst {
             span,
             comment,
             labels,
+            variables: Vec::new(),
         }
     }
 }
 
+/// Debug information on a named variable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct DebugVariable {
+    /// The name of the variable.
+    pub name: Box<str>,
+    /// The offset of the variable relative to the bottom of the stack frame it
+    /// belongs to.
+    pub offset: usize,
+}
+
+impl DebugVariable {
+    /// Construct a new debug variable.
+    pub fn new(name: Box<str>, offset: usize) -> Self {
+        Self { name, offset }
+    }
+}
+
 /// Debug information on function arguments.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DebugArgs {
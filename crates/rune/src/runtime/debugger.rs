@@ -0,0 +1,522 @@
+//! An instruction-level debugger for virtual machines.
+//!
+//! The [Debugger] pauses a [VmExecution] between instructions, and uses the
+//! [DebugInfo][crate::runtime::DebugInfo] of the unit being executed to map
+//! instructions back to source lines. This allows for setting breakpoints on
+//! lines, stepping over, into and out of function calls and inspecting the
+//! named locals of every frame in the call stack.
+
+use core::mem;
+use core::ops::ControlFlow;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::no_std::collections::{BTreeMap, BTreeSet, HashMap};
+use crate::no_std::prelude::*;
+use crate::no_std::sync::Arc;
+
+use crate::runtime::stepper::{self, StepHook, Stepped};
+use crate::runtime::{Unit, Value, Vm, VmExecution, VmResult};
+use crate::{Hash, SourceId, Sources};
+
+/// A source location resolved from debug information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub struct DebugLocation {
+    /// The source the location belongs to.
+    pub source_id: SourceId,
+    /// The zero-based line of the location.
+    pub line: usize,
+    /// The zero-based column of the location.
+    pub column: usize,
+}
+
+/// How to step through an execution when it's being resumed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StepMode {
+    /// Run until a breakpoint is hit or the execution completes.
+    Continue,
+    /// Run until a new line is reached, entering any function being called.
+    Into,
+    /// Run until a new line is reached in the current function or one of its
+    /// callers.
+    Over,
+    /// Run until the current function returns to its caller.
+    Out,
+}
+
+/// The outcome of resuming an execution through the debugger.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DebugEvent {
+    /// Execution paused because a breakpoint was hit.
+    Breakpoint {
+        /// The instruction pointer execution paused at.
+        ip: usize,
+    },
+    /// Execution paused because a step was completed.
+    Step {
+        /// The instruction pointer execution paused at.
+        ip: usize,
+    },
+    /// Execution paused because it was requested through a [PauseHandle].
+    Pause {
+        /// The instruction pointer execution paused at.
+        ip: usize,
+    },
+    /// Execution completed with the given value.
+    Complete(Value),
+}
+
+/// A frame in the call stack of a paused virtual machine.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct DebugFrame {
+    /// The instruction pointer the frame is currently at. For calling frames
+    /// this is the location that will be returned to.
+    pub ip: usize,
+    /// The offset on the stack where the frame starts.
+    pub stack_bottom: usize,
+    /// The offset on the stack where the frame ends, exclusive.
+    pub stack_top: usize,
+    /// The hash of the function the frame belongs to, if known.
+    pub function: Option<Hash>,
+    /// The source location of the frame, if known.
+    pub location: Option<DebugLocation>,
+}
+
+/// A named local variable in a [DebugFrame].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct DebugLocal {
+    /// The name of the variable.
+    pub name: Box<str>,
+    /// The offset of the variable relative to the bottom of its frame.
+    pub offset: usize,
+    /// The current value of the variable.
+    pub value: Value,
+}
+
+/// An instruction-level debugger for a single [Unit].
+///
+/// # Examples
+///
+/// ```
+/// use rune::runtime::debugger::{DebugEvent, Debugger, StepMode};
+/// use rune::{Source, Sources, Vm};
+/// use std::sync::Arc;
+///
+/// let mut sources = Sources::new();
+///
+/// let source_id = sources.insert(Source::new("entry", r#"
+/// pub fn main() {
+///     let a = 1;
+///     let b = 2;
+///     a + b
+/// }
+/// "#));
+///
+/// let unit = Arc::new(rune::prepare(&mut sources).build()?);
+/// let mut debugger = Debugger::new(unit.clone(), &sources);
+///
+/// assert_eq!(debugger.set_breakpoint(source_id, 3), Some(3));
+///
+/// let mut vm = Vm::without_runtime(unit);
+/// let mut execution = vm.execute(["main"], ())?;
+///
+/// let DebugEvent::Breakpoint { .. } = debugger.resume(&mut execution, StepMode::Continue).into_result()? else {
+///     panic!("expected breakpoint");
+/// };
+///
+/// let frames = debugger.frames(execution.vm());
+/// let locals = debugger.locals(execution.vm(), &frames[0]);
+/// assert_eq!(&*locals[0].name, "a");
+///
+/// let DebugEvent::Complete(value) = debugger.resume(&mut execution, StepMode::Continue).into_result()? else {
+///     panic!("expected completion");
+/// };
+///
+/// assert_eq!(rune::from_value::<i64>(value)?, 3);
+/// # Ok::<_, rune::Error>(())
+/// ```
+pub struct Debugger {
+    /// The unit being debugged.
+    unit: Arc<Unit>,
+    /// Source locations of instructions which have debug information.
+    locations: BTreeMap<usize, DebugLocation>,
+    /// Instructions which start a run of instructions on a single line,
+    /// indexed by source and line.
+    line_starts: BTreeMap<(SourceId, usize), Vec<usize>>,
+    /// Functions indexed by the instruction they start at.
+    functions: BTreeMap<usize, Hash>,
+    /// Lines which have breakpoints.
+    breakpoints: BTreeSet<(SourceId, usize)>,
+    /// Instructions which will trigger a breakpoint.
+    breakpoint_ips: HashMap<usize, (SourceId, usize)>,
+    /// Whether the current execution has been started.
+    started: bool,
+    /// Set when a pause has been requested through a [PauseHandle].
+    pause: Arc<AtomicBool>,
+}
+
+impl Debugger {
+    /// Construct a new debugger for the given unit, resolving source lines
+    /// from the sources it was compiled from.
+    pub fn new(unit: Arc<Unit>, sources: &Sources) -> Self {
+        let mut locations = BTreeMap::new();
+        let mut functions = BTreeMap::new();
+
+        if let Some(debug) = unit.debug_info() {
+            for (&ip, inst) in &debug.instructions {
+                let Some(source) = sources.get(inst.source_id) else {
+                    continue;
+                };
+
+                let (line, column) = source.pos_to_utf8_linecol(inst.span.start.into_usize());
+
+                locations.insert(
+                    ip,
+                    DebugLocation {
+                        source_id: inst.source_id,
+                        line,
+                        column,
+                    },
+                );
+            }
+
+            for (&ip, &hash) in &debug.functions_rev {
+                functions.insert(ip, hash);
+            }
+        }
+
+        let mut line_starts = BTreeMap::<_, Vec<usize>>::new();
+        let mut previous = None;
+
+        for (&ip, location) in &locations {
+            let key = (location.source_id, location.line);
+
+            if previous != Some(key) || functions.contains_key(&ip) {
+                line_starts.entry(key).or_default().push(ip);
+            }
+
+            previous = Some(key);
+        }
+
+        Self {
+            unit,
+            locations,
+            line_starts,
+            functions,
+            breakpoints: BTreeSet::new(),
+            breakpoint_ips: HashMap::new(),
+            started: false,
+            pause: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Access the unit being debugged.
+    pub fn unit(&self) -> &Arc<Unit> {
+        &self.unit
+    }
+
+    /// Get the source location of the given instruction pointer.
+    pub fn location_at(&self, ip: usize) -> Option<DebugLocation> {
+        self.locations.get(&ip).copied()
+    }
+
+    /// Set a breakpoint on the given zero-based line.
+    ///
+    /// If the line has no instructions associated with it, the breakpoint is
+    /// moved to the next line that does. Returns the line the breakpoint was
+    /// set on, or `None` if no such line exists.
+    pub fn set_breakpoint(&mut self, source_id: SourceId, line: usize) -> Option<usize> {
+        let (&(found, line), ips) = self
+            .line_starts
+            .range((source_id, line)..)
+            .next()
+            .filter(|((found, _), _)| *found == source_id)?;
+
+        for &ip in ips {
+            self.breakpoint_ips.insert(ip, (found, line));
+        }
+
+        self.breakpoints.insert((found, line));
+        Some(line)
+    }
+
+    /// Remove the breakpoint on the given zero-based line, returning `true` if
+    /// one was removed.
+    pub fn remove_breakpoint(&mut self, source_id: SourceId, line: usize) -> bool {
+        if !self.breakpoints.remove(&(source_id, line)) {
+            return false;
+        }
+
+        self.breakpoint_ips
+            .retain(|_, key| *key != (source_id, line));
+        true
+    }
+
+    /// Remove all breakpoints in the given source.
+    pub fn clear_breakpoints(&mut self, source_id: SourceId) {
+        self.breakpoints.retain(|(id, _)| *id != source_id);
+        self.breakpoint_ips.retain(|_, (id, _)| *id != source_id);
+    }
+
+    /// Iterate over all breakpoints as pairs of sources and zero-based lines.
+    pub fn breakpoints(&self) -> impl Iterator<Item = (SourceId, usize)> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    /// Get a handle which can pause an execution while it's being resumed,
+    /// possibly from another thread.
+    pub fn pause_handle(&self) -> PauseHandle {
+        PauseHandle {
+            pause: self.pause.clone(),
+        }
+    }
+
+    /// Resume the given execution until the condition described by `mode` is
+    /// met, a breakpoint is hit or the execution completes.
+    ///
+    /// If the execution has not been started yet and the first instruction
+    /// has a breakpoint, this pauses before running any instructions.
+    ///
+    /// The execution is stepped through [VmExecution::step], so async
+    /// instructions and executions which yield are not supported.
+    pub fn resume<T>(
+        &mut self,
+        execution: &mut VmExecution<T>,
+        mode: StepMode,
+    ) -> VmResult<DebugEvent>
+    where
+        T: AsRef<Vm> + AsMut<Vm>,
+    {
+        let mut hook = Resume {
+            origin_depth: execution.call_depth(),
+            origin: self.current_line(execution.vm()),
+            first: true,
+            starting: !mem::replace(&mut self.started, true),
+            mode,
+            debugger: self,
+        };
+
+        match vm_try!(stepper::run(execution, &mut hook)) {
+            Stepped::Break(event) => VmResult::Ok(event),
+            Stepped::Complete(value) => {
+                self.started = false;
+                VmResult::Ok(DebugEvent::Complete(value))
+            }
+        }
+    }
+
+    /// Get the call stack of the given virtual machine, starting with the
+    /// innermost frame.
+    pub fn frames(&self, vm: &Vm) -> Vec<DebugFrame> {
+        let mut frames = Vec::with_capacity(vm.call_frames().len() + 1);
+        let mut ip = vm.ip();
+        let mut stack_bottom = vm.stack().stack_bottom();
+        let mut stack_top = vm.stack().len();
+        let same = Arc::ptr_eq(vm.unit(), &self.unit);
+
+        let mut calling = false;
+
+        for call_frame in vm.call_frames().iter().rev() {
+            frames.push(self.frame(same, calling, ip, stack_bottom, stack_top));
+            calling = true;
+            ip = call_frame.ip;
+            stack_top = stack_bottom;
+            stack_bottom = call_frame.stack_bottom;
+        }
+
+        frames.push(self.frame(same, calling, ip, stack_bottom, stack_top));
+        frames
+    }
+
+    /// Get the named locals which are in scope in the given frame.
+    pub fn locals(&self, vm: &Vm, frame: &DebugFrame) -> Vec<DebugLocal> {
+        let Some(debug) = self.unit.debug_info() else {
+            return Vec::new();
+        };
+
+        if !Arc::ptr_eq(vm.unit(), &self.unit) {
+            return Vec::new();
+        }
+
+        let start = self
+            .functions
+            .range(..=frame.ip)
+            .next_back()
+            .map(|(&ip, _)| ip)
+            .unwrap_or_default();
+
+        let mut names = BTreeMap::new();
+
+        for ip in self.locations.range(start..=frame.ip).map(|(&ip, _)| ip) {
+            let Some(inst) = debug.instruction_at(ip) else {
+                continue;
+            };
+
+            for variable in &inst.variables {
+                names.insert(variable.offset, &variable.name);
+            }
+        }
+
+        let mut locals = Vec::new();
+
+        for (offset, name) in names {
+            let index = frame.stack_bottom + offset;
+
+            if index >= frame.stack_top {
+                continue;
+            }
+
+            let Some(value) = vm.stack().get(index) else {
+                continue;
+            };
+
+            locals.push(DebugLocal {
+                name: name.clone(),
+                offset,
+                value: value.clone(),
+            });
+        }
+
+        locals
+    }
+
+    fn frame(
+        &self,
+        same: bool,
+        calling: bool,
+        ip: usize,
+        stack_bottom: usize,
+        stack_top: usize,
+    ) -> DebugFrame {
+        let (function, location) = if same {
+            let function = self.functions.range(..=ip).next_back().map(|(_, &h)| h);
+
+            // NB: calling frames point to the instruction after the call, so
+            // the location is that of the closest preceding instruction.
+            let location = if calling {
+                self.locations.range(..ip).next_back()
+            } else {
+                self.locations.range(..=ip).next_back()
+            };
+
+            (function, location.map(|(_, &l)| l))
+        } else {
+            (None, None)
+        };
+
+        DebugFrame {
+            ip,
+            stack_bottom,
+            stack_top,
+            function,
+            location,
+        }
+    }
+
+    fn current_line(&self, vm: &Vm) -> Option<(SourceId, usize)> {
+        if !Arc::ptr_eq(vm.unit(), &self.unit) {
+            return None;
+        }
+
+        let location = self.locations.get(&vm.ip())?;
+        Some((location.source_id, location.line))
+    }
+
+    fn breakpoint(&self, vm: &Vm) -> Option<usize> {
+        if !Arc::ptr_eq(vm.unit(), &self.unit) {
+            return None;
+        }
+
+        let ip = vm.ip();
+        self.breakpoint_ips.contains_key(&ip).then_some(ip)
+    }
+}
+
+/// A handle used to pause an execution being resumed through a [Debugger].
+///
+/// See [Debugger::pause_handle].
+#[derive(Debug, Clone)]
+pub struct PauseHandle {
+    pause: Arc<AtomicBool>,
+}
+
+impl PauseHandle {
+    /// Request that the execution being resumed pauses before its next
+    /// instruction.
+    ///
+    /// If no execution is being resumed, the next one to be resumed pauses
+    /// after its first instruction.
+    pub fn pause(&self) {
+        self.pause.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Pauses an execution being resumed through [Debugger::resume].
+struct Resume<'a> {
+    debugger: &'a Debugger,
+    mode: StepMode,
+    /// The call depth the execution was resumed at.
+    origin_depth: usize,
+    /// The line the execution was resumed at.
+    origin: Option<(SourceId, usize)>,
+    /// Whether the next instruction is the one the execution was resumed at.
+    first: bool,
+    /// Whether the execution is being started, in which case a breakpoint on
+    /// the first instruction pauses it before anything is executed.
+    starting: bool,
+}
+
+impl StepHook for Resume<'_> {
+    type Break = DebugEvent;
+
+    fn before<T>(&mut self, execution: &VmExecution<T>) -> ControlFlow<DebugEvent>
+    where
+        T: AsRef<Vm> + AsMut<Vm>,
+    {
+        let vm = execution.vm();
+
+        if mem::take(&mut self.first) {
+            if self.starting {
+                if let Some(ip) = self.debugger.breakpoint(vm) {
+                    return ControlFlow::Break(DebugEvent::Breakpoint { ip });
+                }
+            }
+
+            return ControlFlow::Continue(());
+        }
+
+        if let Some(ip) = self.debugger.breakpoint(vm) {
+            return ControlFlow::Break(DebugEvent::Breakpoint { ip });
+        }
+
+        if self.debugger.pause.swap(false, Ordering::Relaxed) {
+            return ControlFlow::Break(DebugEvent::Pause { ip: vm.ip() });
+        }
+
+        let Some(line) = self.debugger.current_line(vm) else {
+            return ControlFlow::Continue(());
+        };
+
+        let depth = execution.call_depth();
+        let origin_depth = self.origin_depth;
+
+        let pause = match self.mode {
+            StepMode::Continue => false,
+            StepMode::Into => depth != origin_depth || Some(line) != self.origin,
+            StepMode::Over => {
+                depth < origin_depth || depth == origin_depth && Some(line) != self.origin
+            }
+            StepMode::Out => depth < origin_depth,
+        };
+
+        if pause {
+            return ControlFlow::Break(DebugEvent::Step { ip: vm.ip() });
+        }
+
+        ControlFlow::Continue(())
+    }
+}
@@ -392,11 +392,7 @@ impl UnsafeFromValue for &mut String {
 
     fn from_value(value: Value) -> VmResult<(Self::Output, Self::Guard)> {
         VmResult::Ok(match value {
-            Value::String(string) => {
-                let string = vm_try!(string.into_mut());
-                let (s, guard) = Mut::into_raw(string);
-                (s, guard)
-            }
+            Value::String(string) => vm_try!(string.into_measured_raw_mut()),
             actual => {
                 return VmResult::err(VmErrorKind::expected::<String>(vm_try!(actual.type_info())));
             }
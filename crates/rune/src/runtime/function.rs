@@ -6,6 +6,7 @@ use crate::no_std::sync::Arc;
 
 use crate::compile::Named;
 use crate::module::{self, InstallWith};
+use crate::runtime::snapshot::{FunctionSnapshot, Reader, SnapshotError, Writer};
 use crate::runtime::{
     Args, Call, ConstValue, FromValue, FunctionHandler, RawRef, RawStr, Ref, Rtti, RuntimeContext,
     Shared, Stack, Tuple, Unit, UnsafeFromValue, Value, VariantRtti, Vm, VmCall, VmErrorKind,
@@ -224,6 +225,111 @@ impl Function {
         Self(FunctionImpl::from_tuple_variant(rtti, args))
     }
 
+    /// Take a snapshot of the function.
+    pub(crate) fn snapshot(&self, cx: &mut Writer<'_>) -> Result<FunctionSnapshot, SnapshotError> {
+        Ok(match &self.0.inner {
+            Inner::FnHandler(FnHandler { hash, .. }) => {
+                if hash.is_empty() {
+                    return Err(SnapshotError::NativeFunction);
+                }
+
+                FunctionSnapshot::Handler { hash: *hash }
+            }
+            Inner::FnOffset(f) => {
+                if !cx.is_same(&f.context, &f.unit) {
+                    return Err(SnapshotError::ForeignUnit);
+                }
+
+                FunctionSnapshot::Offset {
+                    offset: f.offset,
+                    call: f.call,
+                    args: f.args,
+                    hash: f.hash,
+                }
+            }
+            Inner::FnClosureOffset(FnClosureOffset {
+                fn_offset: f,
+                environment,
+            }) => {
+                if !cx.is_same(&f.context, &f.unit) {
+                    return Err(SnapshotError::ForeignUnit);
+                }
+
+                FunctionSnapshot::Closure {
+                    offset: f.offset,
+                    call: f.call,
+                    args: f.args,
+                    hash: f.hash,
+                    environment: cx.values(environment.iter())?,
+                }
+            }
+            Inner::FnUnitStruct(f) => FunctionSnapshot::UnitStruct { hash: f.rtti.hash },
+            Inner::FnTupleStruct(f) => FunctionSnapshot::TupleStruct {
+                hash: f.rtti.hash,
+                args: f.args,
+            },
+            Inner::FnUnitVariant(f) => FunctionSnapshot::UnitVariant { hash: f.rtti.hash },
+            Inner::FnTupleVariant(f) => FunctionSnapshot::TupleVariant {
+                hash: f.rtti.hash,
+                args: f.args,
+            },
+        })
+    }
+
+    /// Restore a function from a snapshot.
+    pub(crate) fn restore(
+        snapshot: &FunctionSnapshot,
+        cx: &mut Reader<'_>,
+    ) -> Result<Self, SnapshotError> {
+        Ok(match snapshot {
+            FunctionSnapshot::Handler { hash } => {
+                let Some(handler) = cx.context().function(*hash) else {
+                    return Err(SnapshotError::MissingFunction { hash: *hash });
+                };
+
+                Self::from_handler(handler.clone(), *hash)
+            }
+            FunctionSnapshot::Offset {
+                offset,
+                call,
+                args,
+                hash,
+            } => Self::from_vm_offset(
+                cx.context().clone(),
+                cx.unit().clone(),
+                *offset,
+                *call,
+                *args,
+                *hash,
+            ),
+            FunctionSnapshot::Closure {
+                offset,
+                call,
+                args,
+                hash,
+                environment,
+            } => Self::from_vm_closure(
+                cx.context().clone(),
+                cx.unit().clone(),
+                *offset,
+                *call,
+                *args,
+                cx.values(environment)?.into(),
+                *hash,
+            ),
+            FunctionSnapshot::UnitStruct { hash } => Self::from_unit_struct(cx.rtti(*hash)?),
+            FunctionSnapshot::TupleStruct { hash, args } => {
+                Self::from_tuple_struct(cx.rtti(*hash)?, *args)
+            }
+            FunctionSnapshot::UnitVariant { hash } => {
+                Self::from_unit_variant(cx.variant_rtti(*hash)?)
+            }
+            FunctionSnapshot::TupleVariant { hash, args } => {
+                Self::from_tuple_variant(cx.variant_rtti(*hash)?, *args)
+            }
+        })
+    }
+
     /// Type [Hash][struct@Hash] of the underlying function.
     ///
     /// # Examples
@@ -529,7 +635,7 @@ where
     pub(crate) fn call_with_vm(&self, vm: &mut Vm, args: usize) -> VmResult<Option<VmHalt>> {
         let reason = match &self.inner {
             Inner::FnHandler(handler) => {
-                vm_try!((handler.handler)(vm.stack_mut(), args));
+                vm_try!((handler.handler)(vm.stack_mut(), args).with_native(handler.hash));
                 None
             }
             Inner::FnOffset(fn_offset) => {
@@ -801,17 +907,22 @@ struct FnOffset {
 
 impl FnOffset {
     /// Perform a call into the specified offset and return the produced value.
+    ///
+    /// Trailing arguments which are missing are filled in with their default
+    /// values.
     fn call<A, E>(&self, args: A, extra: E) -> VmResult<Value>
     where
         A: Args,
         E: Args,
     {
-        vm_try!(check_args(args.count(), self.args));
-
+        let count = args.count();
         let mut vm = Vm::new(self.context.clone(), self.unit.clone());
 
         vm.set_ip(self.offset);
         vm_try!(args.into_stack(vm.stack_mut()));
+        vm_try!(self
+            .unit
+            .push_default_arguments(vm.stack_mut(), self.hash, count, self.args));
         vm_try!(extra.into_stack(vm.stack_mut()));
 
         self.call.call_with_vm(vm)
@@ -825,7 +936,10 @@ impl FnOffset {
     where
         E: Args,
     {
-        vm_try!(check_args(args, self.args));
+        vm_try!(self
+            .unit
+            .push_default_arguments(vm.stack_mut(), self.hash, args, self.args));
+        let args = self.args;
 
         // Fast past, just allocate a call frame and keep running.
         if let Call::Immediate = self.call {
@@ -34,6 +34,17 @@ where
         }
     }
 
+    /// Construct a generator which has already completed.
+    pub(crate) fn completed() -> Self {
+        Self { execution: None }
+    }
+
+    /// Access the underlying execution, or `None` if the generator has
+    /// completed.
+    pub(crate) fn execution(&self) -> Option<&VmExecution<T>> {
+        self.execution.as_ref()
+    }
+
     /// Get the next value produced by this stream.
     #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> VmResult<Option<Value>> {
@@ -0,0 +1,73 @@
+use core::hash::{BuildHasher, BuildHasherDefault, Hash};
+
+use twox_hash::XxHash64;
+
+use crate::runtime::{Key, Value, VmResult};
+use crate::Any;
+
+/// The hasher passed to the [`HASH`] protocol.
+///
+/// It implements [`core::hash::Hasher`], so a Rust type can forward its
+/// [`Hash`] implementation to it.
+///
+/// [`HASH`]: crate::runtime::Protocol::HASH
+///
+/// # Examples
+///
+/// ```rune
+/// use std::collections::HashSet;
+///
+/// struct Point { x, y }
+///
+/// impl Point {
+///     fn hash(self, hasher) {
+///         hasher.write(self.x);
+///         hasher.write(self.y);
+///     }
+/// }
+///
+/// let set = HashSet::new();
+/// set.insert(Point { x: 1, y: 2 });
+/// assert!(set.contains(Point { x: 1, y: 2 }));
+/// ```
+#[derive(Any)]
+#[rune(module = crate, item = ::std::hash)]
+pub struct Hasher {
+    hasher: XxHash64,
+}
+
+impl Hasher {
+    /// Construct a new empty hasher.
+    pub fn new() -> Self {
+        Self {
+            hasher: BuildHasherDefault::<XxHash64>::default().build_hasher(),
+        }
+    }
+
+    /// Hash a value, calling the [`HASH`] protocol if needed.
+    ///
+    /// [`HASH`]: crate::runtime::Protocol::HASH
+    pub fn write_value(&mut self, value: &Value) -> VmResult<()> {
+        vm_try!(Key::from_value(value)).hash(self);
+        VmResult::Ok(())
+    }
+}
+
+impl Default for Hasher {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::hash::Hasher for Hasher {
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.hasher.finish()
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        self.hasher.write(bytes);
+    }
+}
@@ -891,6 +891,54 @@ pub enum Inst {
         /// The slot to test against.
         slot: usize,
     },
+    /// Test if the top of the stack is an integer within the inclusive range
+    /// `start..=end`.
+    ///
+    /// # Operation
+    ///
+    /// ```text
+    /// <value>
+    /// => <boolean>
+    /// ```
+    #[musli(packed)]
+    RangeInteger {
+        /// The lower bound of the range.
+        start: i64,
+        /// The upper bound of the range.
+        end: i64,
+    },
+    /// Test if the top of the stack is a character within the inclusive range
+    /// `start..=end`.
+    ///
+    /// # Operation
+    ///
+    /// ```text
+    /// <value>
+    /// => <boolean>
+    /// ```
+    #[musli(packed)]
+    RangeChar {
+        /// The lower bound of the range.
+        start: char,
+        /// The upper bound of the range.
+        end: char,
+    },
+    /// Test if the top of the stack is a byte within the inclusive range
+    /// `start..=end`.
+    ///
+    /// # Operation
+    ///
+    /// ```text
+    /// <value>
+    /// => <boolean>
+    /// ```
+    #[musli(packed)]
+    RangeByte {
+        /// The lower bound of the range.
+        start: u8,
+        /// The upper bound of the range.
+        end: u8,
+    },
     /// Test that the top of the stack has the given type.
     ///
     /// # Operation
@@ -1011,13 +1059,19 @@ pub enum Inst {
         /// The kind of built-in variant to construct.
         variant: InstVariant,
     },
-    /// A built-in operation like `a + b` that takes its operands and pushes its
-    /// result to and from the stack.
+    /// A built-in operation like `a + b`.
+    ///
+    /// Operands and the output are addressed. Operands addressed at the top of
+    /// the stack are popped, and an output addressed at the top of the stack
+    /// is pushed. Otherwise the operand is read from, or the output replaces,
+    /// the value at the given offset.
     ///
     /// # Operation
     ///
     /// ```text
-    /// => <value>
+    /// <a>?
+    /// <b>?
+    /// => <value>?
     /// ```
     #[musli(packed)]
     Op {
@@ -1027,16 +1081,22 @@ pub enum Inst {
         a: InstAddress,
         /// The address of the second argument.
         b: InstAddress,
+        /// Where to write the result. If this is the top of the stack the
+        /// result is pushed, otherwise it replaces the value at the given
+        /// offset.
+        out: InstAddress,
     },
     /// A built-in operation that assigns to the left-hand side operand. Like
     /// `a += b`.
     ///
-    /// The target determines the left hand side operation.
+    /// The target determines the left hand side operation. The right-hand side
+    /// operand is addressed, and is only popped if it's addressed at the top
+    /// of the stack.
     ///
     /// # Operation
     ///
     /// ```text
-    /// <value>
+    /// <value>?
     /// =>
     /// ```
     #[musli(packed)]
@@ -1045,6 +1105,8 @@ pub enum Inst {
         target: InstTarget,
         /// The actual operation.
         op: InstAssignOp,
+        /// The address of the right-hand side operand.
+        value: InstAddress,
     },
     /// Advance an iterator at the given position.
     #[musli(packed)]
@@ -3,15 +3,18 @@ use core::fmt;
 use core::hash;
 
 use crate::no_std::prelude::*;
+use crate::no_std::rc::Rc;
 use crate::no_std::sync::Arc;
 use crate::no_std::vec;
 
 use serde::{de, ser};
 
 use crate::runtime::{
-    Bytes, FromValue, FullTypeOf, MaybeTypeOf, Object, Shared, StaticString, ToValue, Tuple,
-    TypeInfo, Value, Variant, VariantData, VariantRtti, Vec, VmErrorKind, VmResult,
+    AnyObj, Bytes, EnvProtocolCaller, FromValue, FullTypeOf, Hasher, MaybeTypeOf, Object, Protocol,
+    ProtocolCaller, Ref, Shared, StaticString, ToValue, Tuple, TypeInfo, Value, Variant,
+    VariantData, VariantRtti, Vec, VmErrorKind, VmResult,
 };
+use crate::Hash;
 
 /// A key that can be used as an anonymous object key.
 #[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -38,12 +41,16 @@ pub enum Key {
     Option(Option<Box<Key>>),
     /// A variant.
     Variant(VariantKey),
+    /// A struct or a native value which implements the [`HASH`] protocol.
+    ///
+    /// [`HASH`]: Protocol::HASH
+    Value(ValueKey),
 }
 
 impl Key {
     /// Convert a value reference into a key.
     pub fn from_value(value: &Value) -> VmResult<Self> {
-        return VmResult::Ok(match value {
+        VmResult::Ok(match value {
             Value::Unit => Self::Unit,
             Value::Byte(b) => Self::Byte(*b),
             Value::Char(c) => Self::Char(*c),
@@ -94,32 +101,15 @@ impl Key {
                     data,
                 })
             }
+            Value::UnitStruct(..) | Value::TupleStruct(..) | Value::Struct(..) | Value::Any(..) => {
+                Key::Value(vm_try!(ValueKey::new(value)))
+            }
             value => {
                 return VmResult::err(VmErrorKind::KeyNotSupported {
                     actual: vm_try!(value.type_info()),
                 });
             }
-        });
-
-        fn tuple_from_value(tuple: &Tuple) -> VmResult<Box<[Key]>> {
-            let mut output = vec::Vec::with_capacity(tuple.len());
-
-            for value in tuple {
-                output.push(vm_try!(Key::from_value(value)));
-            }
-
-            VmResult::Ok(output.into_boxed_slice())
-        }
-
-        fn struct_from_value(object: &Object) -> VmResult<Box<[(Box<str>, Key)]>> {
-            let mut output = vec::Vec::with_capacity(object.len());
-
-            for (key, value) in object {
-                output.push((key.as_str().into(), vm_try!(Key::from_value(value))));
-            }
-
-            VmResult::Ok(output.into_boxed_slice())
-        }
+        })
     }
 
     /// Convert into virtual machine value.
@@ -164,6 +154,7 @@ impl Key {
                     data,
                 }))
             }
+            Self::Value(key) => key.value,
         };
 
         fn tuple_into_value(data: Box<[Key]>) -> Tuple {
@@ -209,6 +200,7 @@ impl Key {
             Self::Tuple(..) => TypeInfo::StaticType(crate::runtime::TUPLE_TYPE),
             Self::Option(..) => TypeInfo::StaticType(crate::runtime::OPTION_TYPE),
             Self::Variant(variant) => TypeInfo::Variant(variant.rtti.clone()),
+            Self::Value(key) => key.type_info.clone(),
         }
     }
 }
@@ -227,6 +219,7 @@ impl fmt::Debug for Key {
             Key::Tuple(tuple) => write!(f, "{:?}", tuple),
             Key::Option(opt) => write!(f, "{:?}", opt),
             Key::Variant(variant) => write!(f, "{:?}", variant),
+            Key::Value(key) => write!(f, "{:?}", key.value),
         }
     }
 }
@@ -298,6 +291,7 @@ impl ser::Serialize for Key {
             }
             Self::Option(option) => <Option<Box<Key>>>::serialize(option, serializer),
             Self::Variant(..) => Err(ser::Error::custom("cannot serialize variants")),
+            Self::Value(..) => Err(ser::Error::custom("cannot serialize values")),
         }
     }
 }
@@ -478,6 +472,26 @@ impl From<i64> for Key {
     }
 }
 
+fn tuple_from_value(tuple: &Tuple) -> VmResult<Box<[Key]>> {
+    let mut output = vec::Vec::with_capacity(tuple.len());
+
+    for value in tuple {
+        output.push(vm_try!(Key::from_value(value)));
+    }
+
+    VmResult::Ok(output.into_boxed_slice())
+}
+
+fn struct_from_value(object: &Object) -> VmResult<Box<[(Box<str>, Key)]>> {
+    let mut output = vec::Vec::with_capacity(object.len());
+
+    for (key, value) in object {
+        output.push((key.as_str().into(), vm_try!(Key::from_value(value))));
+    }
+
+    VmResult::Ok(output.into_boxed_slice())
+}
+
 /// A key that can be used as an anonymous object key.
 #[derive(Debug, Clone)]
 pub enum StringKey {
@@ -571,3 +585,147 @@ pub enum VariantKeyData {
     /// An struct variant with a specific type hash.
     Struct(Box<[(Box<str>, Key)]>),
 }
+
+/// A struct or a native value used as a key.
+///
+/// Script structs are keys if they implement the [`HASH`] protocol. They are
+/// hashed and compared by their fields, which are converted into keys when the
+/// key is constructed, so mutating the struct afterwards doesn't affect the
+/// key.
+///
+/// Native values must implement the [`HASH`] and [`CMP`] protocols, which are
+/// used to hash and compare them. The key holds a shared borrow of the value
+/// for as long as it lives, so the value can't be mutated while it's being
+/// used as a key.
+///
+/// [`HASH`]: Protocol::HASH
+/// [`CMP`]: Protocol::CMP
+#[derive(Clone)]
+pub struct ValueKey {
+    type_hash: Hash,
+    type_info: TypeInfo,
+    kind: ValueKeyKind,
+    value: Value,
+}
+
+/// How a [ValueKey] is hashed and compared.
+#[derive(Clone)]
+enum ValueKeyKind {
+    /// The fields of a script struct.
+    Fields(VariantKeyData),
+    /// A native value hashed through the [`HASH`] protocol.
+    ///
+    /// [`HASH`]: Protocol::HASH
+    Any {
+        hash: u64,
+        /// Keeps the value from being mutated while it's used as a key.
+        _guard: Rc<Ref<AnyObj>>,
+    },
+}
+
+impl ValueKey {
+    fn new(value: &Value) -> VmResult<Self> {
+        let type_hash = vm_try!(value.type_hash());
+        let type_info = vm_try!(value.type_info());
+
+        let fields = match value {
+            Value::UnitStruct(..) => VariantKeyData::Unit,
+            Value::TupleStruct(st) => {
+                VariantKeyData::Tuple(vm_try!(tuple_from_value(&vm_try!(st.borrow_ref()).data)))
+            }
+            Value::Struct(st) => {
+                VariantKeyData::Struct(vm_try!(struct_from_value(&vm_try!(st.borrow_ref()).data)))
+            }
+            Value::Any(any) => {
+                for protocol in [Protocol::HASH, Protocol::CMP] {
+                    if !vm_try!(EnvProtocolCaller.has_protocol_fn(protocol, type_hash)) {
+                        return VmResult::err(VmErrorKind::KeyNotSupported { actual: type_info });
+                    }
+                }
+
+                let guard = vm_try!(any.clone().into_ref());
+
+                let mut hasher = Hasher::new();
+                vm_try!(EnvProtocolCaller.call_protocol_fn(
+                    Protocol::HASH,
+                    value.clone(),
+                    (&mut hasher,)
+                ));
+
+                return VmResult::Ok(Self {
+                    type_hash,
+                    type_info,
+                    kind: ValueKeyKind::Any {
+                        hash: hash::Hasher::finish(&hasher),
+                        _guard: Rc::new(guard),
+                    },
+                    value: value.clone(),
+                });
+            }
+            _ => {
+                return VmResult::err(VmErrorKind::KeyNotSupported { actual: type_info });
+            }
+        };
+
+        if !vm_try!(EnvProtocolCaller.has_protocol_fn(Protocol::HASH, type_hash)) {
+            return VmResult::err(VmErrorKind::KeyNotSupported { actual: type_info });
+        }
+
+        VmResult::Ok(Self {
+            type_hash,
+            type_info,
+            kind: ValueKeyKind::Fields(fields),
+            value: value.clone(),
+        })
+    }
+}
+
+impl cmp::PartialEq for ValueKey {
+    fn eq(&self, other: &Self) -> bool {
+        cmp::Ord::cmp(self, other) == cmp::Ordering::Equal
+    }
+}
+
+impl cmp::Eq for ValueKey {}
+
+impl hash::Hash for ValueKey {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.type_hash.hash(state);
+
+        match &self.kind {
+            ValueKeyKind::Fields(fields) => fields.hash(state),
+            ValueKeyKind::Any { hash, .. } => hash.hash(state),
+        }
+    }
+}
+
+impl cmp::PartialOrd for ValueKey {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(cmp::Ord::cmp(self, other))
+    }
+}
+
+impl cmp::Ord for ValueKey {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        match self.type_hash.cmp(&other.type_hash) {
+            cmp::Ordering::Equal => {}
+            ordering => return ordering,
+        }
+
+        match (&self.kind, &other.kind) {
+            (ValueKeyKind::Fields(a), ValueKeyKind::Fields(b)) => a.cmp(b),
+            (ValueKeyKind::Any { hash: a, .. }, ValueKeyKind::Any { hash: b, .. }) => {
+                // The CMP protocol was checked for when the key was
+                // constructed, so this only fails if the implementation itself
+                // errors.
+                match self.value.cmp(&other.value) {
+                    VmResult::Ok(ordering) => ordering,
+                    VmResult::Err(..) => a.cmp(b),
+                }
+            }
+            // Keys of the same type are always of the same kind.
+            (ValueKeyKind::Fields(..), _) => cmp::Ordering::Less,
+            (ValueKeyKind::Any { .. }, _) => cmp::Ordering::Greater,
+        }
+    }
+}
@@ -3,6 +3,7 @@ use core::cmp;
 use core::fmt;
 use core::hash;
 use core::iter;
+use core::mem;
 
 use crate::no_std::collections::{btree_map, BTreeMap};
 use crate::no_std::prelude::*;
@@ -11,8 +12,8 @@ use crate as rune;
 use crate::compile::{ItemBuf, Named};
 use crate::module::InstallWith;
 use crate::runtime::{
-    FromValue, Iterator, Mut, RawMut, RawRef, RawStr, Ref, ToValue, UnsafeFromValue, Value, Vm,
-    VmResult,
+    Footprint, FromValue, Iterator, Mut, RawMut, RawRef, RawStr, Ref, ToValue, UnsafeFromValue,
+    Value, Vm, VmResult,
 };
 
 /// An owning iterator over the entries of a `Object`.
@@ -365,8 +366,7 @@ impl UnsafeFromValue for &mut Object {
 
     fn from_value(value: Value) -> VmResult<(Self::Output, Self::Guard)> {
         let object = vm_try!(value.into_object());
-        let object = vm_try!(object.into_mut());
-        VmResult::Ok(Mut::into_raw(object))
+        VmResult::Ok(vm_try!(object.into_measured_raw_mut()))
     }
 
     unsafe fn unsafe_coerce(output: Self::Output) -> Self {
@@ -374,6 +374,17 @@ impl UnsafeFromValue for &mut Object {
     }
 }
 
+impl Footprint for Object {
+    /// Estimated as the size of each entry, since the keys and the nodes of
+    /// the underlying map are not cheaply measured.
+    #[inline]
+    fn footprint(&self) -> usize {
+        self.inner
+            .len()
+            .saturating_mul(mem::size_of::<(String, Value)>())
+    }
+}
+
 impl Named for Object {
     const BASE_NAME: RawStr = RawStr::from_str("Object");
 }
@@ -0,0 +1,362 @@
+//! A profiler for virtual machines.
+//!
+//! The [Profiler] records the time spent on every instruction of a
+//! [VmExecution], and in every call stack of functions as resolved through the
+//! [DebugInfo][crate::runtime::DebugInfo] of the unit being executed.
+//!
+//! The recorded call stacks can be written in the collapsed stack format used
+//! by flamegraph tools through [Profiler::write_collapsed].
+
+use core::cmp::Reverse;
+use core::convert::Infallible;
+use core::ops::ControlFlow;
+use core::time::Duration;
+
+use std::io;
+use std::time::Instant;
+
+use crate::no_std::collections::{BTreeMap, HashMap};
+use crate::no_std::prelude::*;
+use crate::no_std::sync::Arc;
+
+use crate::runtime::stepper::{self, StepHook};
+use crate::runtime::{Unit, Value, Vm, VmExecution, VmResult};
+use crate::Hash;
+
+/// Marker used in call stacks for instructions which do not belong to a known
+/// function.
+const UNKNOWN: usize = usize::MAX;
+
+/// The time spent on a single instruction.
+#[derive(Debug, Default, Clone, Copy)]
+#[non_exhaustive]
+pub struct InstructionProfile {
+    /// The number of times the instruction was sampled.
+    pub count: u64,
+    /// The total time spent on the instruction.
+    pub time: Duration,
+}
+
+/// The time spent in a single function.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct FunctionProfile {
+    /// The hash of the function.
+    pub hash: Hash,
+    /// The name of the function.
+    pub name: String,
+    /// The time spent in the function itself.
+    pub self_time: Duration,
+    /// The time spent in the function, including the functions it called.
+    pub total_time: Duration,
+}
+
+/// A profiler for a single unit.
+///
+/// # Examples
+///
+/// ```
+/// use rune::{Context, Vm};
+/// use rune::runtime::Profiler;
+/// use std::sync::Arc;
+///
+/// let context = Context::with_default_modules()?;
+///
+/// let mut sources = rune::sources! {
+///     entry => {
+///         fn add(a, b) {
+///             a + b
+///         }
+///
+///         pub fn main() {
+///             add(1, 2)
+///         }
+///     }
+/// };
+///
+/// let unit = Arc::new(rune::prepare(&mut sources).with_context(&context).build()?);
+/// let mut vm = Vm::new(Arc::new(context.runtime()), unit.clone());
+/// let mut execution = vm.execute(["main"], ())?;
+///
+/// let mut profiler = Profiler::new(unit);
+/// let value = profiler.complete(&mut execution).into_result()?;
+/// assert_eq!(rune::from_value::<i64>(value)?, 3);
+///
+/// let mut collapsed = Vec::new();
+/// profiler.write_collapsed(&mut collapsed)?;
+/// let collapsed = String::from_utf8(collapsed)?;
+/// assert!(collapsed.lines().any(|line| line.starts_with("main;add ")));
+/// # Ok::<_, rune::Error>(())
+/// ```
+pub struct Profiler {
+    /// The unit being profiled.
+    unit: Arc<Unit>,
+    /// Functions indexed by the instruction they start at.
+    functions: BTreeMap<usize, Hash>,
+    /// Number of instructions to execute between each sample.
+    interval: usize,
+    /// Time spent in each call stack, where each stack is identified by the
+    /// entry instruction of each function in it.
+    stacks: HashMap<Vec<usize>, Duration>,
+    /// Time spent on each instruction.
+    instructions: HashMap<usize, InstructionProfile>,
+    /// The call stack captured for the current sample.
+    stack: Vec<usize>,
+    /// The instruction captured for the current sample.
+    ip: usize,
+}
+
+impl Profiler {
+    /// Construct a new profiler for the given unit.
+    ///
+    /// By default every instruction executed is timed.
+    pub fn new(unit: Arc<Unit>) -> Self {
+        let mut functions = BTreeMap::new();
+
+        if let Some(debug) = unit.debug_info() {
+            for (&ip, &hash) in &debug.functions_rev {
+                functions.insert(ip, hash);
+            }
+        }
+
+        Self {
+            unit,
+            functions,
+            interval: 1,
+            stacks: HashMap::new(),
+            instructions: HashMap::new(),
+            stack: Vec::new(),
+            ip: UNKNOWN,
+        }
+    }
+
+    /// Set the number of instructions to execute between each sample.
+    ///
+    /// The time spent since the previous sample is attributed to the
+    /// instruction and call stack observed when the sample is taken. An
+    /// interval of `1` times every instruction.
+    pub fn set_interval(&mut self, interval: usize) {
+        self.interval = interval.max(1);
+    }
+
+    /// Complete the given execution while profiling it.
+    ///
+    /// Async instructions are not supported, profile executions which use
+    /// them through [Profiler::async_complete] instead.
+    pub fn complete<T>(&mut self, execution: &mut VmExecution<T>) -> VmResult<Value>
+    where
+        T: AsRef<Vm> + AsMut<Vm>,
+    {
+        let mut sampler = Sampler::new(self);
+        VmResult::Ok(vm_try!(stepper::run(execution, &mut sampler)).into_complete())
+    }
+
+    /// Complete the given execution while profiling it, with support for
+    /// async instructions.
+    ///
+    /// Time spent awaiting a future is attributed to the instruction awaiting
+    /// it.
+    pub async fn async_complete<T>(&mut self, execution: &mut VmExecution<T>) -> VmResult<Value>
+    where
+        T: AsRef<Vm> + AsMut<Vm>,
+    {
+        let mut sampler = Sampler::new(self);
+        VmResult::Ok(vm_try!(stepper::async_run(execution, &mut sampler).await).into_complete())
+    }
+
+    /// Get the time spent on each instruction which has been sampled, ordered
+    /// by instruction.
+    pub fn instructions(&self) -> Vec<(usize, InstructionProfile)> {
+        let mut instructions = self
+            .instructions
+            .iter()
+            .map(|(&ip, &profile)| (ip, profile))
+            .collect::<Vec<_>>();
+
+        instructions.sort_by_key(|&(ip, _)| ip);
+        instructions
+    }
+
+    /// Get the time spent in each function which has been sampled, ordered by
+    /// the time spent in the function itself with the most expensive first.
+    pub fn functions(&self) -> Vec<FunctionProfile> {
+        let mut functions = BTreeMap::<usize, FunctionProfile>::new();
+        let mut seen = Vec::new();
+
+        for (stack, &time) in &self.stacks {
+            seen.clear();
+
+            for (n, &entry) in stack.iter().enumerate() {
+                let Some(&hash) = self.functions.get(&entry) else {
+                    continue;
+                };
+
+                let profile = functions.entry(entry).or_insert_with(|| FunctionProfile {
+                    hash,
+                    name: self.name(entry),
+                    self_time: Duration::ZERO,
+                    total_time: Duration::ZERO,
+                });
+
+                if n + 1 == stack.len() {
+                    profile.self_time += time;
+                }
+
+                // NB: recursive functions are only counted once per stack.
+                if !seen.contains(&entry) {
+                    seen.push(entry);
+                    profile.total_time += time;
+                }
+            }
+        }
+
+        let mut functions = functions.into_values().collect::<Vec<_>>();
+        functions.sort_by_key(|f| Reverse(f.self_time));
+        functions
+    }
+
+    /// Write the sampled call stacks in the collapsed stack format, where each
+    /// line is a semicolon-separated call stack followed by the number of
+    /// nanoseconds spent in it.
+    ///
+    /// This format is understood by flamegraph tools such as [inferno].
+    ///
+    /// [inferno]: https://github.com/jonhoo/inferno
+    pub fn write_collapsed<W>(&self, out: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        let mut lines = Vec::with_capacity(self.stacks.len());
+
+        for (stack, time) in &self.stacks {
+            let nanos = time.as_nanos();
+
+            if nanos == 0 {
+                continue;
+            }
+
+            let names = stack
+                .iter()
+                .map(|&entry| self.name(entry))
+                .collect::<Vec<_>>();
+
+            lines.push((names.join(";"), nanos));
+        }
+
+        lines.sort();
+
+        for (stack, nanos) in lines {
+            writeln!(out, "{} {}", stack, nanos)?;
+        }
+
+        Ok(())
+    }
+
+    /// Capture the call stack of the virtual machine about to execute an
+    /// instruction.
+    fn capture(&mut self, vm: &Vm) {
+        self.stack.clear();
+        self.ip = vm.ip();
+
+        if !Arc::ptr_eq(vm.unit(), &self.unit) {
+            self.ip = UNKNOWN;
+            self.stack.push(UNKNOWN);
+            return;
+        }
+
+        // NB: calling frames point to the instruction after the call.
+        for frame in vm.call_frames() {
+            let entry = self.entry(frame.ip.saturating_sub(1));
+            self.stack.push(entry);
+        }
+
+        let entry = self.entry(vm.ip());
+        self.stack.push(entry);
+    }
+
+    /// Record the time spent executing the captured instruction.
+    fn record(&mut self, time: Duration) {
+        if self.ip != UNKNOWN {
+            let instruction = self.instructions.entry(self.ip).or_default();
+            instruction.count += 1;
+            instruction.time += time;
+        }
+
+        if let Some(total) = self.stacks.get_mut(self.stack.as_slice()) {
+            *total += time;
+        } else {
+            self.stacks.insert(self.stack.clone(), time);
+        }
+    }
+
+    /// Get the entry instruction of the function the given instruction belongs
+    /// to.
+    fn entry(&self, ip: usize) -> usize {
+        match self.functions.range(..=ip).next_back() {
+            Some((&entry, _)) => entry,
+            None => UNKNOWN,
+        }
+    }
+
+    /// Get the name of the function starting at the given instruction.
+    fn name(&self, entry: usize) -> String {
+        let signature = self
+            .unit
+            .debug_info()
+            .and_then(|debug| debug.function_at(entry));
+
+        match signature {
+            Some((_, signature)) => signature.path.to_string(),
+            None => String::from("<unknown>"),
+        }
+    }
+}
+
+/// Samples the instructions of an execution being completed through a
+/// [Profiler].
+struct Sampler<'a> {
+    profiler: &'a mut Profiler,
+    /// When the previous sample was recorded.
+    last: Instant,
+    /// The number of instructions executed so far.
+    count: usize,
+    /// Whether the instruction being executed is sampled.
+    sample: bool,
+}
+
+impl<'a> Sampler<'a> {
+    fn new(profiler: &'a mut Profiler) -> Self {
+        Self {
+            profiler,
+            last: Instant::now(),
+            count: 0,
+            sample: false,
+        }
+    }
+}
+
+impl StepHook for Sampler<'_> {
+    type Break = Infallible;
+
+    fn before<T>(&mut self, execution: &VmExecution<T>) -> ControlFlow<Infallible>
+    where
+        T: AsRef<Vm> + AsMut<Vm>,
+    {
+        self.count += 1;
+        self.sample = self.count % self.profiler.interval == 0;
+
+        if self.sample {
+            self.profiler.capture(execution.vm());
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn after(&mut self) {
+        if self.sample {
+            let now = Instant::now();
+            self.profiler.record(now.duration_since(self.last));
+            self.last = now;
+        }
+    }
+}
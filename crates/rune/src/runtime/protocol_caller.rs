@@ -17,6 +17,16 @@ pub(crate) trait ProtocolCaller {
 /// This allocates its own stack and virtual machine for the call.
 pub(crate) struct EnvProtocolCaller;
 
+impl EnvProtocolCaller {
+    /// Test if the given protocol is implemented for the given type.
+    pub(crate) fn has_protocol_fn(self, protocol: Protocol, type_hash: Hash) -> VmResult<bool> {
+        crate::runtime::env::with(|context, unit| {
+            let hash = Hash::associated_function(type_hash, protocol.hash);
+            VmResult::Ok(unit.function(hash).is_some() || context.function(hash).is_some())
+        })
+    }
+}
+
 impl ProtocolCaller for EnvProtocolCaller {
     fn call_protocol_fn<A>(self, protocol: Protocol, target: Value, args: A) -> VmResult<Value>
     where
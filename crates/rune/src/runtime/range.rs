@@ -1,6 +1,8 @@
 use core::fmt;
 use core::ops;
 
+use serde::{Deserialize, Serialize};
+
 use crate as rune;
 use crate::compile::Named;
 use crate::module::InstallWith;
@@ -129,7 +131,7 @@ impl fmt::Debug for Range {
 }
 
 /// The limits of a range.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum RangeLimits {
     /// A half-open range `..`.
     HalfOpen,
@@ -11,7 +11,8 @@ use core::task::{Context, Poll};
 use crate::no_std::prelude::*;
 
 use crate::runtime::{
-    Access, AccessError, AccessKind, AnyObj, AnyObjError, BorrowMut, BorrowRef, RawAccessGuard,
+    budget, Access, AccessError, AccessKind, AnyObj, AnyObjError, BorrowMut, BorrowRef,
+    RawAccessGuard,
 };
 use crate::Any;
 
@@ -23,9 +24,18 @@ pub struct Shared<T: ?Sized> {
 impl<T> Shared<T> {
     /// Construct a new shared value.
     pub fn new(data: T) -> Self {
+        Self::with_footprint(data, 0)
+    }
+
+    /// Construct a new shared value, charging its allocation and `heap` bytes
+    /// to the memory [budget][crate::runtime::budget].
+    fn with_footprint(data: T, heap: usize) -> Self {
+        let footprint = budget::alloc(mem::size_of::<SharedBox<T>>().saturating_add(heap));
+
         let inner = Box::leak(Box::new(SharedBox {
             access: Access::new(false),
             count: Cell::new(1),
+            footprint: Cell::new(footprint),
             data: data.into(),
         }));
 
@@ -273,7 +283,62 @@ impl<T> Shared<T> {
     }
 }
 
+impl<T> Shared<T> {
+    /// Construct a new shared value, which also charges the heap footprint of
+    /// the value to the memory [budget][crate::runtime::budget].
+    pub(crate) fn new_measured(data: T) -> Self
+    where
+        T: Footprint,
+    {
+        let heap = data.footprint();
+        Self::with_footprint(data, heap)
+    }
+
+    /// Get a raw exclusive reference to the interior value, where the heap
+    /// footprint of the value is measured again once the guard is dropped.
+    ///
+    /// This is used when handing out mutable references to native functions,
+    /// which might cause the value to grow.
+    pub(crate) fn into_measured_raw_mut(self) -> Result<(*mut T, RawMut), AccessError>
+    where
+        T: Footprint,
+    {
+        let inner = self.inner;
+        let (data, mut guard) = Mut::into_raw(self.into_mut()?);
+        guard._measure = Some(RawMeasure::shared_box(inner));
+        Ok((data, guard))
+    }
+
+    /// Measure the heap footprint of the value again, such as after it's been
+    /// modified through [Shared::borrow_mut].
+    ///
+    /// Does nothing if the value is currently being exclusively accessed.
+    pub(crate) fn measure(&self)
+    where
+        T: Footprint,
+    {
+        let Ok(data) = self.borrow_ref() else {
+            return;
+        };
+
+        let heap = data.footprint();
+        drop(data);
+
+        // Safety: Since we have a reference to this shared, we know that the
+        // inner is available.
+        unsafe {
+            SharedBox::charge(self.inner.as_ptr(), heap);
+        }
+    }
+}
+
 impl<T: ?Sized> Shared<T> {
+    /// Get a pointer to the shared allocation, which uniquely identifies the
+    /// value for as long as it is alive.
+    pub(crate) fn as_ptr(&self) -> *const () {
+        self.inner.as_ptr() as *const ()
+    }
+
     /// Get a reference to the interior value while checking for shared access.
     ///
     /// This prevents other exclusive accesses from being performed while the
@@ -443,6 +508,7 @@ impl Shared<AnyObj> {
         let inner = ptr::NonNull::from(Box::leak(Box::new(SharedBox {
             access: Access::new(true),
             count: Cell::new(2),
+            footprint: Cell::new(0),
             data: any.into(),
         })));
 
@@ -749,6 +815,8 @@ struct SharedBox<T: ?Sized> {
     access: Access,
     /// The number of strong references to the shared data.
     count: Cell<usize>,
+    /// The number of bytes charged to the memory budget for this allocation.
+    footprint: Cell<usize>,
     /// The value being held. Guarded by the `access` field to determine if it
     /// can be access shared or exclusively.
     data: UnsafeCell<T>,
@@ -787,6 +855,7 @@ impl<T: ?Sized> SharedBox<T> {
         }
 
         let this = Box::from_raw(this);
+        budget::free(this.footprint.get());
 
         if this.access.is_taken() {
             // NB: This prevents the inner `T` from being dropped in case it
@@ -808,6 +877,33 @@ impl<T: ?Sized> SharedBox<T> {
     }
 }
 
+impl<T> SharedBox<T>
+where
+    T: Footprint,
+{
+    /// Measure the heap footprint of the value again, updating the charge to
+    /// the memory budget.
+    ///
+    /// # Safety
+    ///
+    /// Caller needs to ensure that `this` is a valid pointer, and that the
+    /// data is not being concurrently mutated.
+    unsafe fn measure(this: *const Self) {
+        Self::charge(this, (*(*this).data.get()).footprint());
+    }
+
+    /// Update the charge to the memory budget to account for `heap` bytes.
+    ///
+    /// # Safety
+    ///
+    /// Caller needs to ensure that `this` is a valid pointer.
+    unsafe fn charge(this: *const Self, heap: usize) {
+        let footprint = mem::size_of::<Self>().saturating_add(heap);
+        let old = (*this).footprint.get();
+        (*this).footprint.set(budget::realloc(old, footprint));
+    }
+}
+
 type DropFn = unsafe fn(*const ());
 
 struct RawDrop {
@@ -1086,6 +1182,7 @@ impl<T: ?Sized> Mut<T> {
     /// the current.
     pub fn into_raw(this: Self) -> (*mut T, RawMut) {
         let guard = RawMut {
+            _measure: None,
             _guard: this.guard,
             _inner: this.inner,
         };
@@ -1136,10 +1233,69 @@ where
 
 /// A raw guard to a [Ref].
 pub struct RawMut {
+    // NB: declared first so that it's dropped while exclusive access is still
+    // being held.
+    _measure: Option<RawMeasure>,
     _guard: RawAccessGuard,
     _inner: RawDrop,
 }
 
+/// Measures the footprint of a shared box as it's being dropped.
+struct RawMeasure {
+    data: *const (),
+    measure_fn: unsafe fn(*const ()),
+}
+
+impl RawMeasure {
+    /// Construct a raw measure for the given shared box.
+    ///
+    /// # Safety
+    ///
+    /// Should only be constructed over a pointer that is kept alive for the
+    /// lifetime of the measure.
+    fn shared_box<T>(inner: ptr::NonNull<SharedBox<T>>) -> Self
+    where
+        T: Footprint,
+    {
+        return Self {
+            data: inner.as_ptr() as *const (),
+            measure_fn: measure_fn_impl::<T>,
+        };
+
+        unsafe fn measure_fn_impl<T>(data: *const ())
+        where
+            T: Footprint,
+        {
+            SharedBox::measure(data as *const SharedBox<T>);
+        }
+    }
+}
+
+impl Drop for RawMeasure {
+    fn drop(&mut self) {
+        // Safety: type and referential safety is guaranteed at construction
+        // time.
+        unsafe {
+            (self.measure_fn)(self.data);
+        }
+    }
+}
+
+/// A value whose heap footprint is charged to the memory
+/// [budget][crate::runtime::budget].
+pub(crate) trait Footprint {
+    /// The number of bytes allocated on the heap by the value, not including
+    /// the value itself.
+    fn footprint(&self) -> usize;
+}
+
+impl Footprint for String {
+    #[inline]
+    fn footprint(&self) -> usize {
+        self.capacity()
+    }
+}
+
 /// A guard for an `Any` containing a pointer.
 ///
 /// Constructing using [Shared::from_ref] or [Shared::from_mut].
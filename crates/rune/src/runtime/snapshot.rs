@@ -0,0 +1,763 @@
+//! Serializable snapshots of virtual machines.
+//!
+//! A [VmSnapshot] captures everything needed to resume a [VmExecution] which
+//! is suspended, like the instruction pointer, call frames and stack of every
+//! virtual machine participating in it. Snapshots implement [Serialize] and
+//! [Deserialize], so they can be persisted and later restored against the same
+//! [Unit] and [RuntimeContext] using [VmSnapshot::restore].
+//!
+//! Shared values are only stored once, so values which are referenced from
+//! multiple places are still shared after they've been restored.
+//!
+//! Values which are external to the virtual machine such as [Any][crate::Any]
+//! types, futures and iterators cannot be captured and cause a
+//! [SnapshotError] to be raised.
+
+use crate::no_std::collections::HashMap;
+use crate::no_std::prelude::*;
+use crate::no_std::sync::Arc;
+use crate::no_std::thiserror;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::runtime::{
+    self, AccessError, Bytes, CallFrame, ExecutionState, Function, Generator, GeneratorState,
+    Object, Range, RangeLimits, Rtti, RuntimeContext, Shared, Stack, StaticString, Stream, Struct,
+    Tuple, TupleStruct, Type, TypeInfo, Unit, UnitStruct, Value, Variant, VariantData, VariantRtti,
+    Vm, VmExecution,
+};
+use crate::Hash;
+
+/// An error raised when taking or restoring a [VmSnapshot].
+#[derive(Debug, Error)]
+#[allow(missing_docs)]
+#[non_exhaustive]
+pub enum SnapshotError {
+    #[error("Cannot snapshot value of type `{type_info}`")]
+    Unsupported { type_info: TypeInfo },
+    #[error("Cannot snapshot native function which is not registered in the context")]
+    NativeFunction,
+    #[error("Cannot snapshot value which belongs to a different unit or context")]
+    ForeignUnit,
+    #[error("{error}")]
+    AccessError {
+        #[source]
+        #[from]
+        error: AccessError,
+    },
+    #[error("Snapshot was taken from a different unit")]
+    UnitMismatch,
+    #[error("Missing type `{hash}` in unit")]
+    MissingType { hash: Hash },
+    #[error("Missing function `{hash}` in context")]
+    MissingFunction { hash: Hash },
+    #[error("Snapshot contains a function or generator which references itself")]
+    Cycle,
+    #[error("Snapshot is corrupt")]
+    Corrupt,
+}
+
+/// A serializable snapshot of a [VmExecution].
+///
+/// # Examples
+///
+/// ```
+/// use rune::{Context, Vm};
+/// use rune::runtime::{GeneratorState, VmSnapshot};
+/// use std::sync::Arc;
+///
+/// let context = Context::with_default_modules()?;
+/// let runtime = Arc::new(context.runtime());
+///
+/// let mut sources = rune::sources! {
+///     entry => {
+///         pub fn main() {
+///             let total = yield "first";
+///             total += yield "second";
+///             total
+///         }
+///     }
+/// };
+///
+/// let unit = Arc::new(rune::prepare(&mut sources).with_context(&context).build()?);
+///
+/// let mut vm = Vm::new(runtime.clone(), unit.clone());
+/// let mut execution = vm.execute(["main"], ())?;
+/// assert!(matches!(execution.resume().into_result()?, GeneratorState::Yielded(..)));
+///
+/// let bytes = bincode::serialize(&execution.snapshot()?)?;
+/// drop(execution);
+///
+/// let snapshot: VmSnapshot = bincode::deserialize(&bytes)?;
+/// let mut execution = snapshot.restore(runtime, unit)?;
+///
+/// let GeneratorState::Yielded(value) = execution.resume_with(rune::to_value(1i64)?).into_result()? else {
+///     panic!("expected yield");
+/// };
+///
+/// assert_eq!(rune::from_value::<String>(value)?, "second");
+///
+/// let GeneratorState::Complete(value) = execution.resume_with(rune::to_value(2i64)?).into_result()? else {
+///     panic!("expected completion");
+/// };
+///
+/// assert_eq!(rune::from_value::<i64>(value)?, 3);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VmSnapshot {
+    /// Fingerprint of the unit the snapshot was taken from.
+    fingerprint: Hash,
+    /// Every shared value referenced from the snapshot.
+    shared: Vec<SharedSnapshot>,
+    /// The snapshotted execution.
+    execution: ExecutionSnapshot,
+}
+
+impl VmSnapshot {
+    /// Take a snapshot of an execution.
+    pub(crate) fn from_execution<T>(execution: &VmExecution<T>) -> Result<Self, SnapshotError>
+    where
+        T: AsRef<Vm> + AsMut<Vm>,
+    {
+        let (head, ..) = execution.parts();
+        let head = head.as_ref();
+        let mut writer = Writer::new(head.context(), head.unit());
+        let execution = writer.execution(execution)?;
+        writer.finish(execution)
+    }
+
+    /// Take a snapshot of a single virtual machine.
+    pub(crate) fn from_vm(vm: &Vm) -> Result<Self, SnapshotError> {
+        let mut writer = Writer::new(vm.context(), vm.unit());
+
+        let execution = ExecutionSnapshot {
+            state: ExecutionState::Initial,
+            head: writer.vm(vm)?,
+            vms: Vec::new(),
+        };
+
+        writer.finish(execution)
+    }
+
+    /// Restore the snapshot into an execution.
+    ///
+    /// The `context` and `unit` must be the same as the ones used by the
+    /// virtual machine the snapshot was taken from, or else the restored
+    /// execution will misbehave. A mismatching unit is detected and results in
+    /// [SnapshotError::UnitMismatch].
+    pub fn restore(
+        &self,
+        context: Arc<RuntimeContext>,
+        unit: Arc<Unit>,
+    ) -> Result<VmExecution<Vm>, SnapshotError> {
+        if unit.fingerprint() != self.fingerprint {
+            return Err(SnapshotError::UnitMismatch);
+        }
+
+        let mut reader = Reader {
+            context: &context,
+            unit: &unit,
+            shared: &self.shared,
+            values: Vec::with_capacity(self.shared.len()),
+        };
+
+        reader.allocate()?;
+        reader.fill()?;
+        reader.execution(&self.execution)
+    }
+}
+
+/// A snapshot of a [VmExecution].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExecutionSnapshot {
+    state: ExecutionState,
+    head: VmState,
+    vms: Vec<(VmState, ExecutionState)>,
+}
+
+/// A snapshot of a single [Vm].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VmState {
+    ip: usize,
+    stack: Vec<ValueSnapshot>,
+    stack_bottom: usize,
+    call_frames: Vec<CallFrame>,
+}
+
+/// A snapshot of a [Value].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum ValueSnapshot {
+    Unit,
+    Bool(bool),
+    Byte(u8),
+    Char(char),
+    Integer(i64),
+    Float(f64),
+    Type(Type),
+    StaticString(String),
+    /// Reference to a shared value by index.
+    Shared(usize),
+}
+
+/// A snapshot of a value stored in a [Shared] container.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum SharedSnapshot {
+    String(String),
+    Bytes(Vec<u8>),
+    Vec(Vec<ValueSnapshot>),
+    Tuple(Vec<ValueSnapshot>),
+    Object(Vec<(String, ValueSnapshot)>),
+    Range {
+        start: Option<ValueSnapshot>,
+        end: Option<ValueSnapshot>,
+        limits: RangeLimits,
+    },
+    Generator(Option<ExecutionSnapshot>),
+    Stream(Option<ExecutionSnapshot>),
+    Yielded(ValueSnapshot),
+    Complete(ValueSnapshot),
+    Option(Option<ValueSnapshot>),
+    Result(Result<ValueSnapshot, ValueSnapshot>),
+    UnitStruct(Hash),
+    TupleStruct(Hash, Vec<ValueSnapshot>),
+    Struct(Hash, Vec<(String, ValueSnapshot)>),
+    UnitVariant(Hash),
+    TupleVariant(Hash, Vec<ValueSnapshot>),
+    StructVariant(Hash, Vec<(String, ValueSnapshot)>),
+    Function(FunctionSnapshot),
+}
+
+/// A snapshot of a [Function].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum FunctionSnapshot {
+    /// A native function registered in the context.
+    Handler {
+        hash: Hash,
+    },
+    /// A function in the unit.
+    Offset {
+        offset: usize,
+        call: runtime::Call,
+        args: usize,
+        hash: Hash,
+    },
+    /// A closure in the unit.
+    Closure {
+        offset: usize,
+        call: runtime::Call,
+        args: usize,
+        hash: Hash,
+        environment: Vec<ValueSnapshot>,
+    },
+    UnitStruct {
+        hash: Hash,
+    },
+    TupleStruct {
+        hash: Hash,
+        args: usize,
+    },
+    UnitVariant {
+        hash: Hash,
+    },
+    TupleVariant {
+        hash: Hash,
+        args: usize,
+    },
+}
+
+/// Helper used when taking a snapshot.
+pub(crate) struct Writer<'a> {
+    context: &'a Arc<RuntimeContext>,
+    unit: &'a Arc<Unit>,
+    /// Indexes of shared values which have already been visited.
+    indexes: HashMap<*const (), usize>,
+    /// Shared values, `None` while a value is being snapshotted.
+    shared: Vec<Option<SharedSnapshot>>,
+}
+
+impl<'a> Writer<'a> {
+    fn new(context: &'a Arc<RuntimeContext>, unit: &'a Arc<Unit>) -> Self {
+        Self {
+            context,
+            unit,
+            indexes: HashMap::new(),
+            shared: Vec::new(),
+        }
+    }
+
+    fn finish(self, execution: ExecutionSnapshot) -> Result<VmSnapshot, SnapshotError> {
+        let shared = self
+            .shared
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+            .ok_or(SnapshotError::Corrupt)?;
+
+        Ok(VmSnapshot {
+            fingerprint: self.unit.fingerprint(),
+            shared,
+            execution,
+        })
+    }
+
+    /// Test if the given context and unit is the one being snapshotted.
+    pub(crate) fn is_same(&self, context: &Arc<RuntimeContext>, unit: &Arc<Unit>) -> bool {
+        Arc::ptr_eq(self.context, context) && Arc::ptr_eq(self.unit, unit)
+    }
+
+    fn execution<T>(
+        &mut self,
+        execution: &VmExecution<T>,
+    ) -> Result<ExecutionSnapshot, SnapshotError>
+    where
+        T: AsRef<Vm> + AsMut<Vm>,
+    {
+        let (head, state, vms) = execution.parts();
+        let head = self.vm(head.as_ref())?;
+
+        let vms = vms
+            .iter()
+            .map(|(vm, state)| Ok((self.vm(vm)?, *state)))
+            .collect::<Result<_, SnapshotError>>()?;
+
+        Ok(ExecutionSnapshot { state, head, vms })
+    }
+
+    fn vm(&mut self, vm: &Vm) -> Result<VmState, SnapshotError> {
+        if !self.is_same(vm.context(), vm.unit()) {
+            return Err(SnapshotError::ForeignUnit);
+        }
+
+        let stack = vm.stack();
+
+        Ok(VmState {
+            ip: vm.ip(),
+            stack: self.values(stack.iter())?,
+            stack_bottom: stack.stack_bottom(),
+            call_frames: vm.call_frames().to_vec(),
+        })
+    }
+
+    /// Snapshot a sequence of values.
+    pub(crate) fn values<'v, I>(&mut self, values: I) -> Result<Vec<ValueSnapshot>, SnapshotError>
+    where
+        I: IntoIterator<Item = &'v Value>,
+    {
+        values.into_iter().map(|value| self.value(value)).collect()
+    }
+
+    fn object<'v, I>(&mut self, object: I) -> Result<Vec<(String, ValueSnapshot)>, SnapshotError>
+    where
+        I: IntoIterator<Item = (&'v String, &'v Value)>,
+    {
+        object
+            .into_iter()
+            .map(|(key, value)| Ok((key.clone(), self.value(value)?)))
+            .collect()
+    }
+
+    /// Snapshot a single value.
+    pub(crate) fn value(&mut self, value: &Value) -> Result<ValueSnapshot, SnapshotError> {
+        Ok(match value {
+            Value::Unit => ValueSnapshot::Unit,
+            Value::Bool(value) => ValueSnapshot::Bool(*value),
+            Value::Byte(value) => ValueSnapshot::Byte(*value),
+            Value::Char(value) => ValueSnapshot::Char(*value),
+            Value::Integer(value) => ValueSnapshot::Integer(*value),
+            Value::Float(value) => ValueSnapshot::Float(*value),
+            Value::Type(value) => ValueSnapshot::Type(*value),
+            Value::StaticString(value) => ValueSnapshot::StaticString(value.as_str().to_owned()),
+            Value::String(value) => {
+                self.shared(value, |_, value| Ok(SharedSnapshot::String(value.clone())))?
+            }
+            Value::Bytes(value) => self.shared(value, |_, value| {
+                Ok(SharedSnapshot::Bytes(value.to_vec()))
+            })?,
+            Value::Vec(value) => self.shared(value, |cx, value| {
+                Ok(SharedSnapshot::Vec(cx.values(value.iter())?))
+            })?,
+            Value::Tuple(value) => self.shared(value, |cx, value| {
+                Ok(SharedSnapshot::Tuple(cx.values(value.iter())?))
+            })?,
+            Value::Object(value) => self.shared(value, |cx, value| {
+                Ok(SharedSnapshot::Object(cx.object(value.iter())?))
+            })?,
+            Value::Range(value) => self.shared(value, |cx, value| {
+                Ok(SharedSnapshot::Range {
+                    start: value.start.as_ref().map(|v| cx.value(v)).transpose()?,
+                    end: value.end.as_ref().map(|v| cx.value(v)).transpose()?,
+                    limits: value.limits,
+                })
+            })?,
+            Value::Generator(value) => self.shared(value, |cx, value| {
+                let execution = value.execution().map(|e| cx.execution(e)).transpose()?;
+                Ok(SharedSnapshot::Generator(execution))
+            })?,
+            Value::Stream(value) => self.shared(value, |cx, value| {
+                let execution = value.execution().map(|e| cx.execution(e)).transpose()?;
+                Ok(SharedSnapshot::Stream(execution))
+            })?,
+            Value::GeneratorState(value) => self.shared(value, |cx, value| {
+                Ok(match value {
+                    GeneratorState::Yielded(value) => SharedSnapshot::Yielded(cx.value(value)?),
+                    GeneratorState::Complete(value) => SharedSnapshot::Complete(cx.value(value)?),
+                })
+            })?,
+            Value::Option(value) => self.shared(value, |cx, value| {
+                Ok(SharedSnapshot::Option(
+                    value.as_ref().map(|v| cx.value(v)).transpose()?,
+                ))
+            })?,
+            Value::Result(value) => self.shared(value, |cx, value| {
+                Ok(SharedSnapshot::Result(match value {
+                    Ok(value) => Ok(cx.value(value)?),
+                    Err(value) => Err(cx.value(value)?),
+                }))
+            })?,
+            Value::UnitStruct(value) => self.shared(value, |_, value| {
+                Ok(SharedSnapshot::UnitStruct(value.rtti.hash))
+            })?,
+            Value::TupleStruct(value) => self.shared(value, |cx, value| {
+                Ok(SharedSnapshot::TupleStruct(
+                    value.rtti.hash,
+                    cx.values(value.data.iter())?,
+                ))
+            })?,
+            Value::Struct(value) => self.shared(value, |cx, value| {
+                Ok(SharedSnapshot::Struct(
+                    value.rtti.hash,
+                    cx.object(value.data.iter())?,
+                ))
+            })?,
+            Value::Variant(value) => self.shared(value, |cx, value| {
+                let hash = value.rtti.hash;
+
+                Ok(match &value.data {
+                    VariantData::Unit => SharedSnapshot::UnitVariant(hash),
+                    VariantData::Tuple(tuple) => {
+                        SharedSnapshot::TupleVariant(hash, cx.values(tuple.iter())?)
+                    }
+                    VariantData::Struct(object) => {
+                        SharedSnapshot::StructVariant(hash, cx.object(object.iter())?)
+                    }
+                })
+            })?,
+            Value::Function(value) => self.shared(value, |cx, value| {
+                Ok(SharedSnapshot::Function(value.snapshot(cx)?))
+            })?,
+            Value::Future(..) => {
+                return Err(SnapshotError::Unsupported {
+                    type_info: TypeInfo::StaticType(runtime::FUTURE_TYPE),
+                });
+            }
+            Value::Format(..) => {
+                return Err(SnapshotError::Unsupported {
+                    type_info: TypeInfo::StaticType(runtime::FORMAT_TYPE),
+                });
+            }
+            Value::Iterator(..) => {
+                return Err(SnapshotError::Unsupported {
+                    type_info: TypeInfo::StaticType(runtime::ITERATOR_TYPE),
+                });
+            }
+            Value::Any(value) => {
+                return Err(SnapshotError::Unsupported {
+                    type_info: value.borrow_ref()?.type_info(),
+                });
+            }
+        })
+    }
+
+    /// Snapshot a shared value, making sure that it is only stored once.
+    fn shared<T, F>(&mut self, shared: &Shared<T>, f: F) -> Result<ValueSnapshot, SnapshotError>
+    where
+        F: FnOnce(&mut Self, &T) -> Result<SharedSnapshot, SnapshotError>,
+    {
+        let ptr = shared.as_ptr();
+
+        if let Some(index) = self.indexes.get(&ptr) {
+            return Ok(ValueSnapshot::Shared(*index));
+        }
+
+        let index = self.shared.len();
+        self.shared.push(None);
+        self.indexes.insert(ptr, index);
+
+        let value = shared.borrow_ref()?;
+        let snapshot = f(self, &value)?;
+        self.shared[index] = Some(snapshot);
+        Ok(ValueSnapshot::Shared(index))
+    }
+}
+
+/// A restored shared value.
+enum Slot {
+    /// The value has been allocated.
+    Value(Value),
+    /// The value is allocated on demand since it can't be modified once
+    /// constructed.
+    Deferred,
+    /// The deferred value is currently being restored.
+    Restoring,
+}
+
+/// Helper used when restoring a snapshot.
+pub(crate) struct Reader<'a> {
+    context: &'a Arc<RuntimeContext>,
+    unit: &'a Arc<Unit>,
+    shared: &'a [SharedSnapshot],
+    values: Vec<Slot>,
+}
+
+impl<'a> Reader<'a> {
+    /// Access the context being restored into.
+    pub(crate) fn context(&self) -> &'a Arc<RuntimeContext> {
+        self.context
+    }
+
+    /// Access the unit being restored into.
+    pub(crate) fn unit(&self) -> &'a Arc<Unit> {
+        self.unit
+    }
+
+    /// Lookup runtime type information in the unit.
+    pub(crate) fn rtti(&self, hash: Hash) -> Result<Arc<Rtti>, SnapshotError> {
+        match self.unit.lookup_rtti(hash) {
+            Some(rtti) => Ok(rtti.clone()),
+            None => Err(SnapshotError::MissingType { hash }),
+        }
+    }
+
+    /// Lookup variant runtime type information in the unit.
+    pub(crate) fn variant_rtti(&self, hash: Hash) -> Result<Arc<VariantRtti>, SnapshotError> {
+        match self.unit.lookup_variant_rtti(hash) {
+            Some(rtti) => Ok(rtti.clone()),
+            None => Err(SnapshotError::MissingType { hash }),
+        }
+    }
+
+    /// Allocate every shared value with empty content.
+    ///
+    /// This ensures that shared values which reference each other can be
+    /// restored, since they all exist before any content is restored.
+    fn allocate(&mut self) -> Result<(), SnapshotError> {
+        for snapshot in self.shared {
+            let value = match snapshot {
+                SharedSnapshot::String(string) => Value::from(string.clone()),
+                SharedSnapshot::Bytes(bytes) => Value::from(Bytes::from_vec(bytes.clone())),
+                SharedSnapshot::Vec(..) => Value::from(runtime::Vec::new()),
+                SharedSnapshot::Tuple(..) => Value::from(Tuple::from(Vec::new())),
+                SharedSnapshot::Object(..) => Value::from(Object::new()),
+                SharedSnapshot::Range { limits, .. } => {
+                    Value::from(Range::new(None, None, *limits))
+                }
+                SharedSnapshot::Yielded(..) | SharedSnapshot::Complete(..) => {
+                    Value::from(GeneratorState::Complete(Value::Unit))
+                }
+                SharedSnapshot::Option(..) => Value::Option(Shared::new(None)),
+                SharedSnapshot::Result(..) => Value::Result(Shared::new(Ok(Value::Unit))),
+                SharedSnapshot::UnitStruct(hash) => Value::from(UnitStruct {
+                    rtti: self.rtti(*hash)?,
+                }),
+                SharedSnapshot::TupleStruct(hash, ..) => Value::from(TupleStruct {
+                    rtti: self.rtti(*hash)?,
+                    data: Tuple::from(Vec::new()),
+                }),
+                SharedSnapshot::Struct(hash, ..) => Value::from(Struct {
+                    rtti: self.rtti(*hash)?,
+                    data: Object::new(),
+                }),
+                SharedSnapshot::UnitVariant(hash)
+                | SharedSnapshot::TupleVariant(hash, ..)
+                | SharedSnapshot::StructVariant(hash, ..) => {
+                    Value::from(Variant::unit(self.variant_rtti(*hash)?))
+                }
+                SharedSnapshot::Generator(..)
+                | SharedSnapshot::Stream(..)
+                | SharedSnapshot::Function(..) => {
+                    self.values.push(Slot::Deferred);
+                    continue;
+                }
+            };
+
+            self.values.push(Slot::Value(value));
+        }
+
+        Ok(())
+    }
+
+    /// Fill in the content of every shared value.
+    fn fill(&mut self) -> Result<(), SnapshotError> {
+        for (index, snapshot) in self.shared.iter().enumerate() {
+            let value = match &self.values[index] {
+                Slot::Value(value) => value.clone(),
+                _ => {
+                    self.shared(index)?;
+                    continue;
+                }
+            };
+
+            match (snapshot, value) {
+                (SharedSnapshot::Vec(values), Value::Vec(shared)) => {
+                    let values = self.values(values)?;
+                    *shared.borrow_mut()? = runtime::Vec::from(values);
+                }
+                (SharedSnapshot::Tuple(values), Value::Tuple(shared)) => {
+                    let values = self.values(values)?;
+                    *shared.borrow_mut()? = Tuple::from(values);
+                }
+                (SharedSnapshot::Object(object), Value::Object(shared)) => {
+                    let object = self.object(object)?;
+                    *shared.borrow_mut()? = object;
+                }
+                (SharedSnapshot::Range { start, end, .. }, Value::Range(shared)) => {
+                    let start = start.as_ref().map(|v| self.value(v)).transpose()?;
+                    let end = end.as_ref().map(|v| self.value(v)).transpose()?;
+                    let mut range = shared.borrow_mut()?;
+                    range.start = start;
+                    range.end = end;
+                }
+                (SharedSnapshot::Yielded(value), Value::GeneratorState(shared)) => {
+                    let value = self.value(value)?;
+                    *shared.borrow_mut()? = GeneratorState::Yielded(value);
+                }
+                (SharedSnapshot::Complete(value), Value::GeneratorState(shared)) => {
+                    let value = self.value(value)?;
+                    *shared.borrow_mut()? = GeneratorState::Complete(value);
+                }
+                (SharedSnapshot::Option(value), Value::Option(shared)) => {
+                    let value = value.as_ref().map(|v| self.value(v)).transpose()?;
+                    *shared.borrow_mut()? = value;
+                }
+                (SharedSnapshot::Result(value), Value::Result(shared)) => {
+                    let value = match value {
+                        Ok(value) => Ok(self.value(value)?),
+                        Err(value) => Err(self.value(value)?),
+                    };
+
+                    *shared.borrow_mut()? = value;
+                }
+                (SharedSnapshot::TupleStruct(_, values), Value::TupleStruct(shared)) => {
+                    let values = self.values(values)?;
+                    shared.borrow_mut()?.data = Tuple::from(values);
+                }
+                (SharedSnapshot::Struct(_, object), Value::Struct(shared)) => {
+                    let object = self.object(object)?;
+                    shared.borrow_mut()?.data = object;
+                }
+                (SharedSnapshot::TupleVariant(_, values), Value::Variant(shared)) => {
+                    let values = self.values(values)?;
+                    shared.borrow_mut()?.data = VariantData::Tuple(Tuple::from(values));
+                }
+                (SharedSnapshot::StructVariant(_, object), Value::Variant(shared)) => {
+                    let object = self.object(object)?;
+                    shared.borrow_mut()?.data = VariantData::Struct(object);
+                }
+                (
+                    SharedSnapshot::String(..)
+                    | SharedSnapshot::Bytes(..)
+                    | SharedSnapshot::UnitStruct(..)
+                    | SharedSnapshot::UnitVariant(..),
+                    _,
+                ) => {}
+                _ => return Err(SnapshotError::Corrupt),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the shared value at the given index, restoring it if it is
+    /// deferred.
+    fn shared(&mut self, index: usize) -> Result<Value, SnapshotError> {
+        let slot = self.values.get_mut(index).ok_or(SnapshotError::Corrupt)?;
+
+        match slot {
+            Slot::Value(value) => return Ok(value.clone()),
+            Slot::Restoring => return Err(SnapshotError::Cycle),
+            Slot::Deferred => {
+                *slot = Slot::Restoring;
+            }
+        }
+
+        let value = match &self.shared[index] {
+            SharedSnapshot::Generator(execution) => Value::from(match execution {
+                Some(execution) => Generator::from_execution(self.execution(execution)?),
+                None => Generator::completed(),
+            }),
+            SharedSnapshot::Stream(execution) => Value::from(match execution {
+                Some(execution) => Stream::from_execution(self.execution(execution)?),
+                None => Stream::completed(),
+            }),
+            SharedSnapshot::Function(function) => Value::from(Function::restore(function, self)?),
+            _ => return Err(SnapshotError::Corrupt),
+        };
+
+        self.values[index] = Slot::Value(value.clone());
+        Ok(value)
+    }
+
+    fn execution(
+        &mut self,
+        execution: &ExecutionSnapshot,
+    ) -> Result<VmExecution<Vm>, SnapshotError> {
+        let head = self.vm(&execution.head)?;
+
+        let vms = execution
+            .vms
+            .iter()
+            .map(|(vm, state)| Ok((self.vm(vm)?, *state)))
+            .collect::<Result<_, SnapshotError>>()?;
+
+        Ok(VmExecution::from_parts(head, execution.state, vms))
+    }
+
+    fn vm(&mut self, vm: &VmState) -> Result<Vm, SnapshotError> {
+        if vm.stack_bottom > vm.stack.len() {
+            return Err(SnapshotError::Corrupt);
+        }
+
+        let stack = Stack::from_parts(self.values(&vm.stack)?, vm.stack_bottom);
+
+        Ok(Vm::from_parts(
+            self.context.clone(),
+            self.unit.clone(),
+            vm.ip,
+            stack,
+            vm.call_frames.clone(),
+        ))
+    }
+
+    /// Restore a sequence of values.
+    pub(crate) fn values(&mut self, values: &[ValueSnapshot]) -> Result<Vec<Value>, SnapshotError> {
+        values.iter().map(|value| self.value(value)).collect()
+    }
+
+    fn object(&mut self, object: &[(String, ValueSnapshot)]) -> Result<Object, SnapshotError> {
+        let mut output = Object::with_capacity(object.len());
+
+        for (key, value) in object {
+            output.insert(key.clone(), self.value(value)?);
+        }
+
+        Ok(output)
+    }
+
+    /// Restore a single value.
+    pub(crate) fn value(&mut self, value: &ValueSnapshot) -> Result<Value, SnapshotError> {
+        Ok(match value {
+            ValueSnapshot::Unit => Value::Unit,
+            ValueSnapshot::Bool(value) => Value::Bool(*value),
+            ValueSnapshot::Byte(value) => Value::Byte(*value),
+            ValueSnapshot::Char(value) => Value::Char(*value),
+            ValueSnapshot::Integer(value) => Value::Integer(*value),
+            ValueSnapshot::Float(value) => Value::Float(*value),
+            ValueSnapshot::Type(value) => Value::Type(*value),
+            ValueSnapshot::StaticString(value) => {
+                Value::StaticString(Arc::new(StaticString::new(value)))
+            }
+            ValueSnapshot::Shared(index) => self.shared(*index)?,
+        })
+    }
+}
@@ -73,6 +73,15 @@ impl Stack {
         }
     }
 
+    /// Construct a stack from its values and the bottom of the current stack
+    /// frame.
+    pub(crate) fn from_parts(stack: Vec<Value>, stack_bottom: usize) -> Self {
+        Self {
+            stack,
+            stack_bottom,
+        }
+    }
+
     /// Check if the stack is empty.
     ///
     /// This ignores [stack_bottom] and will just check if the full stack is
@@ -301,6 +310,21 @@ impl Stack {
         })
     }
 
+    /// Store a value at the given address, where storing to the top of the
+    /// stack pushes it.
+    #[inline(always)]
+    pub(crate) fn store<T>(&mut self, address: InstAddress, value: T) -> Result<(), StackError>
+    where
+        Value: From<T>,
+    {
+        match address {
+            InstAddress::Top => self.push(value),
+            InstAddress::Offset(offset) => *self.at_offset_mut(offset)? = Value::from(value),
+        }
+
+        Ok(())
+    }
+
     /// Address a value on the stack.
     pub(crate) fn address_ref(
         &mut self,
@@ -0,0 +1,99 @@
+//! Drive a [VmExecution] one instruction at a time.
+//!
+//! This is what the [Debugger][crate::runtime::Debugger], the
+//! [Profiler][crate::runtime::Profiler] and
+//! [Coverage][crate::runtime::Coverage] are built on. Each of them provides a
+//! [StepHook] which observes the virtual machine around every instruction,
+//! and which can pause the execution before an instruction is executed.
+//!
+//! Since the execution is stepped, the blocking driver [run] errors on async
+//! instructions and on executions which yield, just like
+//! [VmExecution::step]. Use [async_run] to support async instructions.
+
+use core::convert::Infallible;
+use core::ops::ControlFlow;
+
+use crate::runtime::{Value, Vm, VmExecution, VmResult};
+
+/// A hook called around every instruction executed by [run] or [async_run].
+pub(crate) trait StepHook {
+    /// The value produced when the hook pauses the execution.
+    type Break;
+
+    /// Called before the next instruction of the execution is executed.
+    ///
+    /// Returning [ControlFlow::Break] pauses the execution before the
+    /// instruction, which is then the first instruction executed once the
+    /// execution is driven again.
+    fn before<T>(&mut self, execution: &VmExecution<T>) -> ControlFlow<Self::Break>
+    where
+        T: AsRef<Vm> + AsMut<Vm>;
+
+    /// Called after an instruction has been executed, including the one which
+    /// completes the execution.
+    fn after(&mut self) {}
+}
+
+/// The outcome of driving an execution.
+pub(crate) enum Stepped<B> {
+    /// The execution was paused by the hook.
+    Break(B),
+    /// The execution completed with the given value.
+    Complete(Value),
+}
+
+impl Stepped<Infallible> {
+    /// Get the value an execution which can't be paused completed with.
+    pub(crate) fn into_complete(self) -> Value {
+        match self {
+            Self::Break(never) => match never {},
+            Self::Complete(value) => value,
+        }
+    }
+}
+
+/// Drive the execution until it completes or the hook pauses it.
+pub(crate) fn run<T, H>(execution: &mut VmExecution<T>, hook: &mut H) -> VmResult<Stepped<H::Break>>
+where
+    T: AsRef<Vm> + AsMut<Vm>,
+    H: StepHook,
+{
+    loop {
+        if let ControlFlow::Break(value) = hook.before(execution) {
+            return VmResult::Ok(Stepped::Break(value));
+        }
+
+        let result = vm_try!(execution.step());
+        hook.after();
+
+        if let Some(value) = result {
+            return VmResult::Ok(Stepped::Complete(value));
+        }
+    }
+}
+
+/// Drive the execution until it completes or the hook pauses it, with
+/// support for async instructions.
+///
+/// Time spent awaiting a future counts as part of the instruction awaiting it.
+pub(crate) async fn async_run<T, H>(
+    execution: &mut VmExecution<T>,
+    hook: &mut H,
+) -> VmResult<Stepped<H::Break>>
+where
+    T: AsRef<Vm> + AsMut<Vm>,
+    H: StepHook,
+{
+    loop {
+        if let ControlFlow::Break(value) = hook.before(execution) {
+            return VmResult::Ok(Stepped::Break(value));
+        }
+
+        let result = vm_try!(execution.async_step().await);
+        hook.after();
+
+        if let Some(value) = result {
+            return VmResult::Ok(Stepped::Complete(value));
+        }
+    }
+}
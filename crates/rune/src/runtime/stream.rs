@@ -33,6 +33,17 @@ where
         }
     }
 
+    /// Construct a stream which has already completed.
+    pub(crate) fn completed() -> Self {
+        Self { execution: None }
+    }
+
+    /// Access the underlying execution, or `None` if the stream has
+    /// completed.
+    pub(crate) fn execution(&self) -> Option<&VmExecution<T>> {
+        self.execution.as_ref()
+    }
+
     /// Get the next value produced by this stream.
     pub async fn next(&mut self) -> VmResult<Option<Value>> {
         VmResult::Ok(match vm_try!(self.resume(Value::Unit).await) {
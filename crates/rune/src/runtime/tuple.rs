@@ -1,4 +1,5 @@
 use core::fmt;
+use core::mem;
 use core::ops;
 use core::slice;
 
@@ -7,7 +8,8 @@ use crate::no_std::prelude::*;
 use crate::compile::Named;
 use crate::module::InstallWith;
 use crate::runtime::{
-    ConstValue, FromValue, Mut, RawStr, Ref, ToValue, Value, Vm, VmErrorKind, VmResult, TUPLE_TYPE,
+    ConstValue, Footprint, FromValue, Mut, RawStr, Ref, ToValue, Value, Vm, VmErrorKind, VmResult,
+    TUPLE_TYPE,
 };
 
 /// Struct representing a dynamic anonymous object.
@@ -183,6 +185,13 @@ impl FromValue for Tuple {
     }
 }
 
+impl Footprint for Tuple {
+    #[inline]
+    fn footprint(&self) -> usize {
+        self.inner.len().saturating_mul(mem::size_of::<Value>())
+    }
+}
+
 impl Named for Tuple {
     const BASE_NAME: RawStr = RawStr::from_str("Tuple");
 }
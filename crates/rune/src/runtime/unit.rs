@@ -16,8 +16,10 @@ use crate::no_std::sync::Arc;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+use crate::hash::ParametersBuilder;
 use crate::runtime::{
-    Call, ConstValue, DebugInfo, Inst, Rtti, StaticString, VariantRtti, VmError, VmErrorKind,
+    Call, ConstValue, DebugInfo, Inst, Rtti, Stack, StaticString, VariantRtti, VmError,
+    VmErrorKind,
 };
 use crate::Hash;
 
@@ -35,6 +37,12 @@ pub type DefaultStorage = ArrayUnit;
 #[cfg(rune_byte_code)]
 pub type DefaultStorage = ByteCodeUnit;
 
+/// Calculate the hash of the constant holding the default value of the
+/// argument at `index` of the function identified by `hash`.
+pub(crate) fn default_argument_hash(hash: Hash, index: usize) -> Hash {
+    Hash::associated_function(hash, Hash::index(index))
+}
+
 /// Instructions and debug info from a single source file.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(bound = "S: Serialize + DeserializeOwned")]
@@ -203,6 +211,31 @@ impl<S> Unit<S> {
     pub(crate) fn constant(&self, hash: Hash) -> Option<&ConstValue> {
         self.logic.constants.get(&hash)
     }
+
+    /// Push the default values of any trailing arguments which are missing
+    /// from a call with `actual` arguments to the function identified by
+    /// `hash` that expects `expected` arguments.
+    pub(crate) fn push_default_arguments(
+        &self,
+        stack: &mut Stack,
+        hash: Hash,
+        actual: usize,
+        expected: usize,
+    ) -> Result<(), VmErrorKind> {
+        if actual > expected {
+            return Err(VmErrorKind::BadArgumentCount { actual, expected });
+        }
+
+        for index in actual..expected {
+            let Some(value) = self.constant(default_argument_hash(hash, index)) else {
+                return Err(VmErrorKind::BadArgumentCount { actual, expected });
+            };
+
+            stack.push(value.clone().into_value());
+        }
+
+        Ok(())
+    }
 }
 
 impl<S> Unit<S>
@@ -222,6 +255,35 @@ where
         self.logic.storage.get(ip)
     }
 
+    /// Calculate a fingerprint of the instructions and functions in the unit.
+    ///
+    /// This is recorded in snapshots of virtual machines to detect when they
+    /// are restored against a different unit than the one they were taken
+    /// from.
+    pub(crate) fn fingerprint(&self) -> Hash {
+        let mut functions = self
+            .logic
+            .functions
+            .iter()
+            .map(|(hash, f)| match f {
+                UnitFn::Offset { offset, .. } => (*hash, Some(*offset)),
+                _ => (*hash, None),
+            })
+            .collect::<Vec<_>>();
+
+        functions.sort();
+
+        let mut builder = ParametersBuilder::new();
+        builder.add(self.logic.storage.end());
+        builder.add(self.logic.storage.bytes());
+
+        for function in functions {
+            builder.add(function);
+        }
+
+        builder.finish()
+    }
+
     /// Iterate over all instructions in order.
     #[cfg(feature = "emit")]
     pub(crate) fn iter_instructions(&self) -> impl Iterator<Item = (usize, Inst)> + '_ {
@@ -590,14 +590,137 @@ impl Value {
         })
     }
 
+    /// Compare two values using the [Protocol::PARTIAL_CMP] protocol.
+    ///
+    /// Primitives, strings, vectors, tuples and options are compared
+    /// natively, anything else calls the protocol.
+    ///
+    /// You must use [Vm::with] to specify which virtual machine this function
+    /// is called inside.
+    ///
+    /// # Errors
+    ///
+    /// This function errors in case the values cannot be compared without the
+    /// use of a [`Vm`] and one is not provided through the environment.
+    pub fn partial_cmp(&self, b: &Value) -> VmResult<Option<cmp::Ordering>> {
+        VmResult::Ok(match (self, b) {
+            (Self::Float(a), Self::Float(b)) => a.partial_cmp(b),
+            (Self::Vec(a), Self::Vec(b)) => {
+                let a = vm_try!(a.borrow_ref());
+                let b = vm_try!(b.borrow_ref());
+                vm_try!(Self::slice_partial_cmp(&a, &b))
+            }
+            (Self::Tuple(a), Self::Tuple(b)) => {
+                let a = vm_try!(a.borrow_ref());
+                let b = vm_try!(b.borrow_ref());
+                vm_try!(Self::slice_partial_cmp(&a, &b))
+            }
+            (Self::Option(a), Self::Option(b)) => {
+                match (&*vm_try!(a.borrow_ref()), &*vm_try!(b.borrow_ref())) {
+                    (Some(a), Some(b)) => vm_try!(a.partial_cmp(b)),
+                    (a, b) => Some(a.is_some().cmp(&b.is_some())),
+                }
+            }
+            (a, b) => match vm_try!(a.primitive_cmp(b)) {
+                Some(ordering) => Some(ordering),
+                None => vm_try!(<Option<cmp::Ordering>>::from_value(vm_try!(
+                    EnvProtocolCaller.call_protocol_fn(Protocol::PARTIAL_CMP, a.clone(), (b,))
+                ))),
+            },
+        })
+    }
+
+    /// Compare two values using the [Protocol::CMP] protocol.
+    ///
+    /// Primitives, strings, vectors, tuples and options are compared
+    /// natively, anything else calls the protocol.
+    ///
+    /// You must use [Vm::with] to specify which virtual machine this function
+    /// is called inside.
+    ///
+    /// # Errors
+    ///
+    /// This function errors in case the values cannot be compared without the
+    /// use of a [`Vm`] and one is not provided through the environment.
+    pub fn cmp(&self, b: &Value) -> VmResult<cmp::Ordering> {
+        VmResult::Ok(match (self, b) {
+            (Self::Vec(a), Self::Vec(b)) => {
+                let a = vm_try!(a.borrow_ref());
+                let b = vm_try!(b.borrow_ref());
+                vm_try!(Self::slice_cmp(&a, &b))
+            }
+            (Self::Tuple(a), Self::Tuple(b)) => {
+                let a = vm_try!(a.borrow_ref());
+                let b = vm_try!(b.borrow_ref());
+                vm_try!(Self::slice_cmp(&a, &b))
+            }
+            (Self::Option(a), Self::Option(b)) => {
+                match (&*vm_try!(a.borrow_ref()), &*vm_try!(b.borrow_ref())) {
+                    (Some(a), Some(b)) => vm_try!(a.cmp(b)),
+                    (a, b) => a.is_some().cmp(&b.is_some()),
+                }
+            }
+            (a, b) => match vm_try!(a.primitive_cmp(b)) {
+                Some(ordering) => ordering,
+                None => vm_try!(cmp::Ordering::from_value(vm_try!(
+                    EnvProtocolCaller.call_protocol_fn(Protocol::CMP, a.clone(), (b,))
+                ))),
+            },
+        })
+    }
+
+    /// Compare two primitive values which have a total order.
+    fn primitive_cmp(&self, b: &Value) -> VmResult<Option<cmp::Ordering>> {
+        VmResult::Ok(Some(match (self, b) {
+            (Self::Unit, Self::Unit) => cmp::Ordering::Equal,
+            (Self::Bool(a), Self::Bool(b)) => a.cmp(b),
+            (Self::Byte(a), Self::Byte(b)) => a.cmp(b),
+            (Self::Char(a), Self::Char(b)) => a.cmp(b),
+            (Self::Integer(a), Self::Integer(b)) => a.cmp(b),
+            (Self::String(a), Self::String(b)) => vm_try!(a.borrow_ref())
+                .as_str()
+                .cmp(vm_try!(b.borrow_ref()).as_str()),
+            (Self::StaticString(a), Self::String(b)) => {
+                a.as_str().cmp(vm_try!(b.borrow_ref()).as_str())
+            }
+            (Self::String(a), Self::StaticString(b)) => {
+                vm_try!(a.borrow_ref()).as_str().cmp(b.as_str())
+            }
+            (Self::StaticString(a), Self::StaticString(b)) => a.as_str().cmp(b.as_str()),
+            _ => return VmResult::Ok(None),
+        }))
+    }
+
+    fn slice_partial_cmp(a: &[Value], b: &[Value]) -> VmResult<Option<cmp::Ordering>> {
+        for (a, b) in a.iter().zip(b) {
+            match vm_try!(a.partial_cmp(b)) {
+                Some(cmp::Ordering::Equal) => continue,
+                ordering => return VmResult::Ok(ordering),
+            }
+        }
+
+        VmResult::Ok(Some(a.len().cmp(&b.len())))
+    }
+
+    fn slice_cmp(a: &[Value], b: &[Value]) -> VmResult<cmp::Ordering> {
+        for (a, b) in a.iter().zip(b) {
+            match vm_try!(a.cmp(b)) {
+                cmp::Ordering::Equal => continue,
+                ordering => return VmResult::Ok(ordering),
+            }
+        }
+
+        VmResult::Ok(a.len().cmp(&b.len()))
+    }
+
     /// Construct a vector.
     pub fn vec(vec: vec::Vec<Value>) -> Self {
-        Self::Vec(Shared::new(Vec::from(vec)))
+        Self::Vec(Shared::new_measured(Vec::from(vec)))
     }
 
     /// Construct a tuple.
     pub fn tuple(vec: vec::Vec<Value>) -> Self {
-        Self::Tuple(Shared::new(Tuple::from(vec)))
+        Self::Tuple(Shared::new_measured(Tuple::from(vec)))
     }
 
     /// Construct an empty.
@@ -634,11 +757,11 @@ impl Value {
             Self::Float(value) => Self::Float(value),
             Self::Type(value) => Self::Type(value),
             Self::StaticString(value) => Self::StaticString(value),
-            Self::String(value) => Self::String(Shared::new(vm_try!(value.take()))),
-            Self::Bytes(value) => Self::Bytes(Shared::new(vm_try!(value.take()))),
-            Self::Vec(value) => Self::Vec(Shared::new(vm_try!(value.take()))),
-            Self::Tuple(value) => Self::Tuple(Shared::new(vm_try!(value.take()))),
-            Self::Object(value) => Self::Object(Shared::new(vm_try!(value.take()))),
+            Self::String(value) => Self::String(Shared::new_measured(vm_try!(value.take()))),
+            Self::Bytes(value) => Self::Bytes(Shared::new_measured(vm_try!(value.take()))),
+            Self::Vec(value) => Self::Vec(Shared::new_measured(vm_try!(value.take()))),
+            Self::Tuple(value) => Self::Tuple(Shared::new_measured(vm_try!(value.take()))),
+            Self::Object(value) => Self::Object(Shared::new_measured(vm_try!(value.take()))),
             Self::Range(value) => Self::Range(Shared::new(vm_try!(value.take()))),
             Self::Future(value) => Self::Future(Shared::new(vm_try!(value.take()))),
             Self::Stream(value) => Self::Stream(Shared::new(vm_try!(value.take()))),
@@ -1337,6 +1460,28 @@ macro_rules! impl_from_wrapper {
     };
 }
 
+macro_rules! impl_from_measured {
+    ($($variant:ident => Shared<$ty:ty>),* $(,)?) => {
+        impl_from!($($variant => Shared<$ty>),*);
+
+        $(
+            impl From<$ty> for Value {
+                #[inline]
+                fn from(value: $ty) -> Self {
+                    Self::$variant(Shared::new_measured(value))
+                }
+            }
+
+            impl ToValue for $ty {
+                #[inline]
+                fn to_value(self) -> VmResult<Value> {
+                    VmResult::Ok(Value::from(self))
+                }
+            }
+        )*
+    };
+}
+
 impl_from! {
     Byte => u8,
     Bool => bool,
@@ -1352,11 +1497,6 @@ impl_from_wrapper! {
     StaticString => Arc<StaticString>,
     Format => Box<Format>,
     Iterator => Shared<Iterator>,
-    Bytes => Shared<Bytes>,
-    String => Shared<String>,
-    Vec => Shared<Vec>,
-    Tuple => Shared<Tuple>,
-    Object => Shared<Object>,
     Range => Shared<Range>,
     Future => Shared<Future>,
     Stream => Shared<Stream<Vm>>,
@@ -1370,6 +1510,14 @@ impl_from_wrapper! {
     Any => Shared<AnyObj>,
 }
 
+impl_from_measured! {
+    Bytes => Shared<Bytes>,
+    String => Shared<String>,
+    Vec => Shared<Vec>,
+    Tuple => Shared<Tuple>,
+    Object => Shared<Object>,
+}
+
 /// Deserialize implementation for value pointers.
 impl<'de> de::Deserialize<'de> for Value {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -1,5 +1,6 @@
 use core::cmp;
 use core::fmt;
+use core::mem;
 use core::ops;
 use core::slice;
 
@@ -9,8 +10,8 @@ use crate::no_std::vec;
 use crate::compile::Named;
 use crate::module::InstallWith;
 use crate::runtime::{
-    FromValue, Iterator, Mut, RawMut, RawRef, RawStr, Ref, Shared, ToValue, UnsafeFromValue, Value,
-    Vm, VmErrorKind, VmResult,
+    Footprint, FromValue, Iterator, Mut, RawMut, RawRef, RawStr, Ref, Shared, ToValue,
+    UnsafeFromValue, Value, Vm, VmErrorKind, VmResult,
 };
 
 /// Struct representing a dynamic vector.
@@ -185,6 +186,15 @@ impl Vec {
     }
 }
 
+impl Footprint for Vec {
+    #[inline]
+    fn footprint(&self) -> usize {
+        self.inner
+            .capacity()
+            .saturating_mul(mem::size_of::<Value>())
+    }
+}
+
 impl Named for Vec {
     const BASE_NAME: RawStr = RawStr::from_str("Vec");
 }
@@ -327,7 +337,7 @@ impl<'a> UnsafeFromValue for &'a mut Vec {
 
     fn from_value(value: Value) -> VmResult<(Self::Output, Self::Guard)> {
         let vec = vm_try!(value.into_vec());
-        VmResult::Ok(Mut::into_raw(vm_try!(vec.into_mut())))
+        VmResult::Ok(vm_try!(vec.into_measured_raw_mut()))
     }
 
     unsafe fn unsafe_coerce(output: Self::Output) -> Self {
@@ -1,3 +1,4 @@
+use core::cmp::Ordering;
 use core::fmt;
 use core::mem;
 use core::ops;
@@ -7,6 +8,9 @@ use crate::hash::{Hash, IntoHash, ToTypeHash};
 use crate::no_std::prelude::*;
 use crate::no_std::sync::Arc;
 use crate::no_std::vec;
+
+use serde::{Deserialize, Serialize};
+
 use crate::runtime::budget;
 use crate::runtime::future::SelectFuture;
 use crate::runtime::unit::{UnitFn, UnitStorage};
@@ -14,11 +18,15 @@ use crate::runtime::{
     Args, Awaited, BorrowMut, Bytes, Call, Format, FormatSpec, FromValue, Function, Future,
     Generator, GuardedArgs, Inst, InstAddress, InstAssignOp, InstOp, InstRangeLimits, InstTarget,
     InstValue, InstVariant, Object, Panic, Protocol, Range, RangeLimits, RuntimeContext, Select,
-    Shared, Stack, Stream, Struct, Tuple, Type, TypeCheck, Unit, UnitStruct, Value, Variant,
-    VariantData, Vec, VmError, VmErrorKind, VmExecution, VmHalt, VmIntegerRepr, VmResult,
-    VmSendExecution,
+    Shared, SnapshotError, Stack, Stream, Struct, Tuple, Type, TypeCheck, Unit, UnitStruct, Value,
+    Variant, VariantData, Vec, VmError, VmErrorKind, VmExecution, VmHalt, VmIntegerRepr, VmResult,
+    VmSendExecution, VmSnapshot,
 };
 
+/// How many instructions are executed between checking if the memory budget
+/// has been exceeded or if the execution has been cancelled.
+const CHECK_INTERVAL: usize = 256;
+
 /// Small helper function to build errors.
 fn err<T, E>(error: E) -> VmResult<T>
 where
@@ -52,8 +60,8 @@ enum TargetValue<'a, 'b> {
 }
 
 macro_rules! target_value {
-    ($vm:ident, $target:expr, $guard:ident, $lhs:ident) => {{
-        let rhs = vm_try!($vm.stack.pop());
+    ($vm:ident, $target:expr, $value:expr, $guard:ident, $lhs:ident) => {{
+        let rhs = vm_try!($vm.stack.address($value));
 
         match $target {
             InstTarget::Offset(offset) => {
@@ -116,6 +124,23 @@ impl Vm {
         }
     }
 
+    /// Construct a virtual machine from its raw parts.
+    pub(crate) fn from_parts(
+        context: Arc<RuntimeContext>,
+        unit: Arc<Unit>,
+        ip: usize,
+        stack: Stack,
+        call_frames: vec::Vec<CallFrame>,
+    ) -> Self {
+        Self {
+            context,
+            unit,
+            ip,
+            stack,
+            call_frames,
+        }
+    }
+
     /// Construct a vm with a default empty [RuntimeContext]. This is useful
     /// when the [Unit] was constructed with an empty
     /// [Context][crate::compile::Context].
@@ -170,6 +195,15 @@ impl Vm {
         self.ip
     }
 
+    /// Take a serializable snapshot of the virtual machine.
+    ///
+    /// The snapshot doesn't know if the virtual machine is suspended in a
+    /// `yield`, so prefer to use [VmExecution::snapshot] when snapshotting an
+    /// execution which is in progress.
+    pub fn snapshot(&self) -> Result<VmSnapshot, SnapshotError> {
+        VmSnapshot::from_vm(self)
+    }
+
     /// Reset this virtual machine, freeing all memory used.
     pub fn clear(&mut self) {
         self.ip = 0;
@@ -421,6 +455,117 @@ impl Vm {
         Result::Ok(value)
     }
 
+    /// Call the given protocol function on `target`, returning the produced
+    /// value.
+    ///
+    /// The protocol can either be implemented natively, or by the script
+    /// through an `impl` block for the trait which declares it. The latter is
+    /// how scripts implement protocols registered with [`Module::protocol`].
+    ///
+    /// [`Module::protocol`]: crate::Module::protocol
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rune::{Context, Module, Unit, Vm};
+    /// use rune::compile::ItemBuf;
+    /// use rune::runtime::Protocol;
+    ///
+    /// use std::sync::Arc;
+    ///
+    /// let serde = ItemBuf::with_crate_item("serde", ["Serialize"]);
+    /// let serialize = Protocol::custom(&serde, "serialize");
+    ///
+    /// let mut m = Module::with_crate("serde");
+    /// m.protocol(["Serialize"], [serialize])?;
+    ///
+    /// let mut context = Context::with_default_modules()?;
+    /// context.install(m)?;
+    /// let runtime = Arc::new(context.runtime());
+    ///
+    /// let mut sources = rune::sources! {
+    ///     entry => {
+    ///         struct Point { x, y }
+    ///
+    ///         impl serde::Serialize for Point {
+    ///             fn serialize(self) {
+    ///                 format!("{},{}", self.x, self.y)
+    ///             }
+    ///         }
+    ///
+    ///         pub fn main() {
+    ///             Point { x: 1, y: 2 }
+    ///         }
+    ///     }
+    /// };
+    ///
+    /// let unit = rune::prepare(&mut sources).with_context(&context).build()?;
+    /// let mut vm = Vm::new(runtime, Arc::new(unit));
+    ///
+    /// let point = vm.call(["main"], ())?;
+    /// let output: String = rune::from_value(vm.call_protocol(serialize, point, ())?)?;
+    /// assert_eq!(output, "1,2");
+    /// # Ok::<_, rune::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If any of the arguments passed in are references, and that references is
+    /// captured somewhere in the call as [`Mut<T>`] or [`Ref<T>`]
+    /// this call will panic as we are trying to free the metadata related to
+    /// the reference.
+    ///
+    /// [`Mut<T>`]: crate::runtime::Mut
+    /// [`Ref<T>`]: crate::runtime::Ref
+    pub fn call_protocol<A>(
+        &mut self,
+        protocol: Protocol,
+        target: Value,
+        args: A,
+    ) -> Result<Value, VmError>
+    where
+        A: GuardedArgs,
+    {
+        let count = args.count() + 1;
+        let hash = Hash::associated_function(target.type_hash()?, protocol.hash);
+
+        if let Some(handler) = self.context.function(hash) {
+            let mut stack = Stack::with_capacity(count);
+            stack.push(target);
+
+            // Safety: We hold onto the guard until the handler has completed.
+            let _guard = unsafe { args.unsafe_into_stack(&mut stack).into_result()? };
+
+            handler(&mut stack, count).with_native(hash).into_result()?;
+            return Ok(stack.pop()?);
+        }
+
+        if self.unit.function(hash).is_none() {
+            return Err(VmError::from(VmErrorKind::MissingInstanceFunction {
+                hash: protocol.hash,
+                instance: target.type_info().into_result()?,
+            }));
+        }
+
+        self.set_entrypoint(hash, count)?;
+        self.stack.push(target);
+
+        // Safety: We hold onto the guard until the vm has completed and
+        // `VmExecution` will clear the stack before this function returns.
+        // Erronously or not.
+        let guard = unsafe { args.unsafe_into_stack(&mut self.stack).into_result()? };
+
+        let value = {
+            // Clearing the stack here on panics has safety implications - see
+            // above.
+            let vm = ClearStack(self);
+            VmExecution::new(&mut *vm.0).complete().into_result()?
+        };
+
+        drop(guard);
+        Result::Ok(value)
+    }
+
     /// Update the instruction pointer to match the function matching the given
     /// name and check that the number of argument matches.
     fn set_entrypoint<N>(&mut self, name: N, count: usize) -> Result<(), VmErrorKind>
@@ -487,13 +632,15 @@ impl Vm {
             args: expected,
         }) = self.unit.function(hash)
         {
-            vm_try!(check_args(full_count, expected));
-            vm_try!(self.call_offset_fn(offset, call, full_count));
+            vm_try!(self
+                .unit
+                .push_default_arguments(&mut self.stack, hash, full_count, expected));
+            vm_try!(self.call_offset_fn(offset, call, expected));
             return VmResult::Ok(CallResult::Ok(()));
         }
 
         if let Some(handler) = self.context.function(hash) {
-            vm_try!(handler(&mut self.stack, full_count));
+            vm_try!(handler(&mut self.stack, full_count).with_native(hash));
             return VmResult::Ok(CallResult::Ok(()));
         }
 
@@ -524,7 +671,7 @@ impl Vm {
         vm_try!(args.into_stack(&mut self.stack));
 
         if let Some(handler) = self.context.function(hash) {
-            vm_try!(handler(&mut self.stack, full_count));
+            vm_try!(handler(&mut self.stack, full_count).with_native(hash));
             return VmResult::Ok(CallResult::Ok(()));
         }
 
@@ -554,7 +701,7 @@ impl Vm {
         vm_try!(args.into_stack(&mut self.stack));
 
         if let Some(handler) = self.context.function(hash) {
-            vm_try!(handler(&mut self.stack, full_count));
+            vm_try!(handler(&mut self.stack, full_count).with_native(hash));
             return VmResult::Ok(CallResult::Ok(()));
         }
 
@@ -564,30 +711,61 @@ impl Vm {
         VmResult::Ok(CallResult::Unsupported(target))
     }
 
-    fn internal_boolean_ops(
+    /// Internal impl of a comparison operation.
+    ///
+    /// Values which are not numbers are compared through the given protocol,
+    /// falling back to [`Value::partial_cmp`] if it is not implemented.
+    fn internal_cmp(
         &mut self,
+        protocol: Protocol,
+        op: &'static str,
         int_op: fn(i64, i64) -> bool,
         float_op: fn(f64, f64) -> bool,
-        op: &'static str,
+        ordering_op: fn(Ordering) -> bool,
         lhs: InstAddress,
         rhs: InstAddress,
+        out: InstAddress,
     ) -> VmResult<()> {
         let rhs = vm_try!(self.stack.address(rhs));
         let lhs = vm_try!(self.stack.address(lhs));
 
-        let out = match (lhs, rhs) {
-            (Value::Integer(lhs), Value::Integer(rhs)) => int_op(lhs, rhs),
-            (Value::Float(lhs), Value::Float(rhs)) => float_op(lhs, rhs),
-            (lhs, rhs) => {
-                return err(VmErrorKind::UnsupportedBinaryOperation {
-                    op,
-                    lhs: vm_try!(lhs.type_info()),
-                    rhs: vm_try!(rhs.type_info()),
-                });
+        let (lhs, rhs) = match (lhs, rhs) {
+            (Value::Integer(lhs), Value::Integer(rhs)) => {
+                vm_try!(self.stack.store(out, int_op(lhs, rhs)));
+                return VmResult::Ok(());
+            }
+            (Value::Float(lhs), Value::Float(rhs)) => {
+                vm_try!(self.stack.store(out, float_op(lhs, rhs)));
+                return VmResult::Ok(());
             }
+            (lhs, rhs) => (lhs, rhs),
         };
 
-        self.stack.push(out);
+        let lhs = match vm_try!(self.call_instance_fn(lhs, protocol, (&rhs,))) {
+            CallResult::Ok(()) => return self.protocol_output(out),
+            CallResult::Unsupported(lhs) => lhs,
+        };
+
+        let ordering = match lhs.partial_cmp(&rhs) {
+            VmResult::Ok(ordering) => ordering,
+            VmResult::Err(error) => {
+                let hash =
+                    Hash::associated_function(vm_try!(lhs.type_hash()), Protocol::PARTIAL_CMP);
+
+                return match error.kind() {
+                    VmErrorKind::MissingFunction { hash: missing } if *missing == hash => {
+                        err(VmErrorKind::UnsupportedBinaryOperation {
+                            op,
+                            lhs: vm_try!(lhs.type_info()),
+                            rhs: vm_try!(rhs.type_info()),
+                        })
+                    }
+                    _ => VmResult::Err(error),
+                };
+            }
+        };
+
+        vm_try!(self.stack.store(out, ordering.map_or(false, ordering_op)));
         VmResult::Ok(())
     }
 
@@ -948,8 +1126,8 @@ impl Vm {
 
         VmResult::Ok(match target {
             Value::Object(object) => {
-                let mut object = vm_try!(object.borrow_mut());
-                object.insert(field.as_str().to_owned(), value);
+                vm_try!(object.borrow_mut()).insert(field.as_str().to_owned(), value);
+                object.measure();
                 return VmResult::Ok(CallResult::Ok(()));
             }
             Value::Struct(typed_object) => {
@@ -1058,11 +1236,12 @@ impl Vm {
         op: &'static str,
         lhs: InstAddress,
         rhs: InstAddress,
+        out: InstAddress,
     ) -> VmResult<()> {
         let rhs = vm_try!(self.stack.address(rhs));
         let lhs = vm_try!(self.stack.address(lhs));
 
-        let out = match (lhs, rhs) {
+        let value = match (lhs, rhs) {
             (Value::Bool(lhs), Value::Bool(rhs)) => bool_op(lhs, rhs),
             (lhs, rhs) => {
                 return err(VmErrorKind::UnsupportedBinaryOperation {
@@ -1073,7 +1252,7 @@ impl Vm {
             }
         };
 
-        self.stack.push(out);
+        vm_try!(self.stack.store(out, value));
         VmResult::Ok(())
     }
 
@@ -1138,6 +1317,7 @@ impl Vm {
     fn internal_num_assign(
         &mut self,
         target: InstTarget,
+        value: InstAddress,
         protocol: Protocol,
         error: fn() -> VmErrorKind,
         integer_op: fn(i64, i64) -> Option<i64>,
@@ -1146,7 +1326,7 @@ impl Vm {
         let lhs;
         let mut guard;
 
-        let fallback = match target_value!(self, target, guard, lhs) {
+        let fallback = match target_value!(self, target, value, guard, lhs) {
             TargetValue::Value(lhs, rhs) => match (lhs, rhs) {
                 (Value::Integer(lhs), Value::Integer(rhs)) => {
                     let out = vm_try!(integer_op(*lhs, rhs).ok_or_else(error));
@@ -1215,18 +1395,19 @@ impl Vm {
         float_op: fn(f64, f64) -> f64,
         lhs: InstAddress,
         rhs: InstAddress,
+        out: InstAddress,
     ) -> VmResult<()> {
         let rhs = vm_try!(self.stack.address(rhs));
         let lhs = vm_try!(self.stack.address(lhs));
 
         let (lhs, rhs) = match (lhs, rhs) {
             (Value::Integer(lhs), Value::Integer(rhs)) => {
-                self.stack
-                    .push(vm_try!(integer_op(lhs, rhs).ok_or_else(error)));
+                let value = vm_try!(integer_op(lhs, rhs).ok_or_else(error));
+                vm_try!(self.stack.store(out, value));
                 return VmResult::Ok(());
             }
             (Value::Float(lhs), Value::Float(rhs)) => {
-                self.stack.push(float_op(lhs, rhs));
+                vm_try!(self.stack.store(out, float_op(lhs, rhs)));
                 return VmResult::Ok(());
             }
             (lhs, rhs) => (lhs, rhs),
@@ -1240,7 +1421,7 @@ impl Vm {
                 rhs: vm_try!(rhs.type_info()),
             })
         } else {
-            VmResult::Ok(())
+            self.protocol_output(out)
         }
     }
 
@@ -1251,13 +1432,14 @@ impl Vm {
         integer_op: fn(i64, i64) -> i64,
         lhs: InstAddress,
         rhs: InstAddress,
+        out: InstAddress,
     ) -> VmResult<()> {
         let rhs = vm_try!(self.stack.address(rhs));
         let lhs = vm_try!(self.stack.address(lhs));
 
         let (lhs, rhs) = match (lhs, rhs) {
             (Value::Integer(lhs), Value::Integer(rhs)) => {
-                self.stack.push(integer_op(lhs, rhs));
+                vm_try!(self.stack.store(out, integer_op(lhs, rhs)));
                 return VmResult::Ok(());
             }
             (lhs, rhs) => (lhs, rhs),
@@ -1271,7 +1453,7 @@ impl Vm {
                 rhs: vm_try!(rhs.type_info()),
             })
         } else {
-            VmResult::Ok(())
+            self.protocol_output(out)
         }
     }
 
@@ -1283,17 +1465,18 @@ impl Vm {
         bool_op: fn(bool, bool) -> bool,
         lhs: InstAddress,
         rhs: InstAddress,
+        out: InstAddress,
     ) -> VmResult<()> {
         let rhs = vm_try!(self.stack.address(rhs));
         let lhs = vm_try!(self.stack.address(lhs));
 
         let (lhs, rhs) = match (lhs, rhs) {
             (Value::Integer(lhs), Value::Integer(rhs)) => {
-                self.stack.push(integer_op(lhs, rhs));
+                vm_try!(self.stack.store(out, integer_op(lhs, rhs)));
                 return VmResult::Ok(());
             }
             (Value::Bool(lhs), Value::Bool(rhs)) => {
-                self.stack.push(bool_op(lhs, rhs));
+                vm_try!(self.stack.store(out, bool_op(lhs, rhs)));
                 return VmResult::Ok(());
             }
             (lhs, rhs) => (lhs, rhs),
@@ -1307,20 +1490,21 @@ impl Vm {
                 rhs: vm_try!(rhs.type_info()),
             })
         } else {
-            VmResult::Ok(())
+            self.protocol_output(out)
         }
     }
 
     fn internal_infallible_bitwise_assign(
         &mut self,
         target: InstTarget,
+        value: InstAddress,
         protocol: Protocol,
         integer_op: fn(&mut i64, i64),
     ) -> VmResult<()> {
         let lhs;
         let mut guard;
 
-        let fallback = match target_value!(self, target, guard, lhs) {
+        let fallback = match target_value!(self, target, value, guard, lhs) {
             TargetValue::Value(lhs, rhs) => match (lhs, rhs) {
                 (Value::Integer(lhs), Value::Integer(rhs)) => {
                     integer_op(lhs, rhs);
@@ -1341,14 +1525,15 @@ impl Vm {
         integer_op: fn(i64, i64) -> Option<i64>,
         lhs: InstAddress,
         rhs: InstAddress,
+        out: InstAddress,
     ) -> VmResult<()> {
         let rhs = vm_try!(self.stack.address(rhs));
         let lhs = vm_try!(self.stack.address(lhs));
 
         let (lhs, rhs) = match (lhs, rhs) {
             (Value::Integer(lhs), Value::Integer(rhs)) => {
-                self.stack
-                    .push(vm_try!(integer_op(lhs, rhs).ok_or_else(error)));
+                let value = vm_try!(integer_op(lhs, rhs).ok_or_else(error));
+                vm_try!(self.stack.store(out, value));
                 return VmResult::Ok(());
             }
             (lhs, rhs) => (lhs, rhs),
@@ -1362,13 +1547,14 @@ impl Vm {
                 rhs: vm_try!(rhs.type_info()),
             })
         } else {
-            VmResult::Ok(())
+            self.protocol_output(out)
         }
     }
 
     fn internal_bitwise_assign(
         &mut self,
         target: InstTarget,
+        value: InstAddress,
         protocol: Protocol,
         error: fn() -> VmErrorKind,
         integer_op: fn(i64, i64) -> Option<i64>,
@@ -1376,7 +1562,7 @@ impl Vm {
         let lhs;
         let mut guard;
 
-        let fallback = match target_value!(self, target, guard, lhs) {
+        let fallback = match target_value!(self, target, value, guard, lhs) {
             TargetValue::Value(lhs, rhs) => match (lhs, rhs) {
                 (Value::Integer(lhs), Value::Integer(rhs)) => {
                     let out = vm_try!(integer_op(*lhs, rhs).ok_or_else(error));
@@ -1561,7 +1747,7 @@ impl Vm {
     #[cfg_attr(feature = "bench", inline(never))]
     fn op_vec(&mut self, count: usize) -> VmResult<()> {
         let vec = Vec::from(vm_try!(self.stack.pop_sequence(count)));
-        self.stack.push(Shared::new(vec));
+        self.stack.push(Shared::new_measured(vec));
         VmResult::Ok(())
     }
 
@@ -1603,8 +1789,14 @@ impl Vm {
             Value::Bool(value) => Value::from(!value),
             Value::Integer(value) => Value::from(!value),
             other => {
-                let operand = vm_try!(other.type_info());
-                return err(VmErrorKind::UnsupportedUnaryOperation { op: "!", operand });
+                if let CallResult::Unsupported(other) =
+                    vm_try!(self.call_instance_fn(other, Protocol::NOT, ()))
+                {
+                    let operand = vm_try!(other.type_info());
+                    return err(VmErrorKind::UnsupportedUnaryOperation { op: "!", operand });
+                }
+
+                return VmResult::Ok(());
             }
         };
 
@@ -1620,8 +1812,14 @@ impl Vm {
             Value::Float(value) => Value::from(-value),
             Value::Integer(value) => Value::from(-value),
             other => {
-                let operand = vm_try!(other.type_info());
-                return err(VmErrorKind::UnsupportedUnaryOperation { op: "-", operand });
+                if let CallResult::Unsupported(other) =
+                    vm_try!(self.call_instance_fn(other, Protocol::NEG, ()))
+                {
+                    let operand = vm_try!(other.type_info());
+                    return err(VmErrorKind::UnsupportedUnaryOperation { op: "-", operand });
+                }
+
+                return VmResult::Ok(());
             }
         };
 
@@ -1630,7 +1828,13 @@ impl Vm {
     }
 
     #[cfg_attr(feature = "bench", inline(never))]
-    fn op_op(&mut self, op: InstOp, lhs: InstAddress, rhs: InstAddress) -> VmResult<()> {
+    fn op_op(
+        &mut self,
+        op: InstOp,
+        lhs: InstAddress,
+        rhs: InstAddress,
+        out: InstAddress,
+    ) -> VmResult<()> {
         match op {
             InstOp::Add => {
                 vm_try!(self.internal_num(
@@ -1640,6 +1844,7 @@ impl Vm {
                     ops::Add::add,
                     lhs,
                     rhs,
+                    out,
                 ));
             }
             InstOp::Sub => {
@@ -1650,6 +1855,7 @@ impl Vm {
                     ops::Sub::sub,
                     lhs,
                     rhs,
+                    out,
                 ));
             }
             InstOp::Mul => {
@@ -1660,6 +1866,7 @@ impl Vm {
                     ops::Mul::mul,
                     lhs,
                     rhs,
+                    out,
                 ));
             }
             InstOp::Div => {
@@ -1670,6 +1877,7 @@ impl Vm {
                     ops::Div::div,
                     lhs,
                     rhs,
+                    out,
                 ));
             }
             InstOp::Rem => {
@@ -1680,6 +1888,7 @@ impl Vm {
                     ops::Rem::rem,
                     lhs,
                     rhs,
+                    out,
                 ));
             }
             InstOp::BitAnd => {
@@ -1690,6 +1899,7 @@ impl Vm {
                     bool::bitand,
                     lhs,
                     rhs,
+                    out,
                 ));
             }
             InstOp::BitXor => {
@@ -1700,6 +1910,7 @@ impl Vm {
                     bool::bitxor,
                     lhs,
                     rhs,
+                    out,
                 ));
             }
             InstOp::BitOr => {
@@ -1710,6 +1921,7 @@ impl Vm {
                     bool::bitor,
                     lhs,
                     rhs,
+                    out,
                 ));
             }
             InstOp::Shl => {
@@ -1719,48 +1931,91 @@ impl Vm {
                     |a, b| a.checked_shl(u32::try_from(b).ok()?),
                     lhs,
                     rhs,
+                    out,
                 ));
             }
             InstOp::Shr => {
-                vm_try!(self.internal_infallible_bitwise(Protocol::SHR, ops::Shr::shr, lhs, rhs));
+                vm_try!(self.internal_infallible_bitwise(
+                    Protocol::SHR,
+                    ops::Shr::shr,
+                    lhs,
+                    rhs,
+                    out,
+                ));
             }
             InstOp::Gt => {
-                vm_try!(self.internal_boolean_ops(|a, b| a > b, |a, b| a > b, ">", lhs, rhs));
+                vm_try!(self.internal_cmp(
+                    Protocol::GT,
+                    ">",
+                    |a, b| a > b,
+                    |a, b| a > b,
+                    Ordering::is_gt,
+                    lhs,
+                    rhs,
+                    out,
+                ));
             }
             InstOp::Gte => {
-                vm_try!(self.internal_boolean_ops(|a, b| a >= b, |a, b| a >= b, ">=", lhs, rhs));
+                vm_try!(self.internal_cmp(
+                    Protocol::GE,
+                    ">=",
+                    |a, b| a >= b,
+                    |a, b| a >= b,
+                    Ordering::is_ge,
+                    lhs,
+                    rhs,
+                    out,
+                ));
             }
             InstOp::Lt => {
-                vm_try!(self.internal_boolean_ops(|a, b| a < b, |a, b| a < b, "<", lhs, rhs));
+                vm_try!(self.internal_cmp(
+                    Protocol::LT,
+                    "<",
+                    |a, b| a < b,
+                    |a, b| a < b,
+                    Ordering::is_lt,
+                    lhs,
+                    rhs,
+                    out,
+                ));
             }
             InstOp::Lte => {
-                vm_try!(self.internal_boolean_ops(|a, b| a <= b, |a, b| a <= b, "<=", lhs, rhs));
+                vm_try!(self.internal_cmp(
+                    Protocol::LE,
+                    "<=",
+                    |a, b| a <= b,
+                    |a, b| a <= b,
+                    Ordering::is_le,
+                    lhs,
+                    rhs,
+                    out,
+                ));
             }
             InstOp::Eq => {
                 let rhs = vm_try!(self.stack.address(rhs));
                 let lhs = vm_try!(self.stack.address(lhs));
                 let test = vm_try!(Value::value_ptr_eq(self, &lhs, &rhs));
-                self.stack.push(test);
+                vm_try!(self.stack.store(out, test));
             }
             InstOp::Neq => {
                 let rhs = vm_try!(self.stack.address(rhs));
                 let lhs = vm_try!(self.stack.address(lhs));
                 let test = vm_try!(Value::value_ptr_eq(self, &lhs, &rhs));
-                self.stack.push(!test);
+                vm_try!(self.stack.store(out, !test));
             }
             InstOp::And => {
-                vm_try!(self.internal_boolean_op(|a, b| a && b, "&&", lhs, rhs));
+                vm_try!(self.internal_boolean_op(|a, b| a && b, "&&", lhs, rhs, out));
             }
             InstOp::Or => {
-                vm_try!(self.internal_boolean_op(|a, b| a || b, "||", lhs, rhs));
+                vm_try!(self.internal_boolean_op(|a, b| a || b, "||", lhs, rhs, out));
             }
             InstOp::Is => {
                 let is_instance = vm_try!(self.test_is_instance(lhs, rhs));
-                self.stack.push(is_instance);
+                vm_try!(self.stack.store(out, is_instance));
             }
             InstOp::IsNot => {
                 let is_instance = vm_try!(self.test_is_instance(lhs, rhs));
-                self.stack.push(!is_instance);
+                vm_try!(self.stack.store(out, !is_instance));
             }
         }
 
@@ -1768,11 +2023,17 @@ impl Vm {
     }
 
     #[cfg_attr(feature = "bench", inline(never))]
-    fn op_assign(&mut self, target: InstTarget, op: InstAssignOp) -> VmResult<()> {
+    fn op_assign(
+        &mut self,
+        target: InstTarget,
+        op: InstAssignOp,
+        value: InstAddress,
+    ) -> VmResult<()> {
         match op {
             InstAssignOp::Add => {
                 vm_try!(self.internal_num_assign(
                     target,
+                    value,
                     Protocol::ADD_ASSIGN,
                     || VmErrorKind::Overflow,
                     i64::checked_add,
@@ -1782,6 +2043,7 @@ impl Vm {
             InstAssignOp::Sub => {
                 vm_try!(self.internal_num_assign(
                     target,
+                    value,
                     Protocol::SUB_ASSIGN,
                     || VmErrorKind::Underflow,
                     i64::checked_sub,
@@ -1791,6 +2053,7 @@ impl Vm {
             InstAssignOp::Mul => {
                 vm_try!(self.internal_num_assign(
                     target,
+                    value,
                     Protocol::MUL_ASSIGN,
                     || VmErrorKind::Overflow,
                     i64::checked_mul,
@@ -1800,6 +2063,7 @@ impl Vm {
             InstAssignOp::Div => {
                 vm_try!(self.internal_num_assign(
                     target,
+                    value,
                     Protocol::DIV_ASSIGN,
                     || VmErrorKind::DivideByZero,
                     i64::checked_div,
@@ -1809,6 +2073,7 @@ impl Vm {
             InstAssignOp::Rem => {
                 vm_try!(self.internal_num_assign(
                     target,
+                    value,
                     Protocol::REM_ASSIGN,
                     || VmErrorKind::DivideByZero,
                     i64::checked_rem,
@@ -1818,6 +2083,7 @@ impl Vm {
             InstAssignOp::BitAnd => {
                 vm_try!(self.internal_infallible_bitwise_assign(
                     target,
+                    value,
                     Protocol::BIT_AND_ASSIGN,
                     ops::BitAndAssign::bitand_assign,
                 ));
@@ -1825,6 +2091,7 @@ impl Vm {
             InstAssignOp::BitXor => {
                 vm_try!(self.internal_infallible_bitwise_assign(
                     target,
+                    value,
                     Protocol::BIT_XOR_ASSIGN,
                     ops::BitXorAssign::bitxor_assign,
                 ));
@@ -1832,6 +2099,7 @@ impl Vm {
             InstAssignOp::BitOr => {
                 vm_try!(self.internal_infallible_bitwise_assign(
                     target,
+                    value,
                     Protocol::BIT_OR_ASSIGN,
                     ops::BitOrAssign::bitor_assign,
                 ));
@@ -1839,6 +2107,7 @@ impl Vm {
             InstAssignOp::Shl => {
                 vm_try!(self.internal_bitwise_assign(
                     target,
+                    value,
                     Protocol::SHL_ASSIGN,
                     || VmErrorKind::Overflow,
                     |a, b| a.checked_shl(u32::try_from(b).ok()?),
@@ -1847,6 +2116,7 @@ impl Vm {
             InstAssignOp::Shr => {
                 vm_try!(self.internal_infallible_bitwise_assign(
                     target,
+                    value,
                     Protocol::SHR_ASSIGN,
                     ops::ShrAssign::shr_assign,
                 ));
@@ -1856,6 +2126,17 @@ impl Vm {
         VmResult::Ok(())
     }
 
+    /// Move the result of a protocol function called by an operation, which
+    /// has been pushed onto the stack, to the output of the operation.
+    fn protocol_output(&mut self, out: InstAddress) -> VmResult<()> {
+        if let InstAddress::Offset(offset) = out {
+            let value = vm_try!(self.stack.pop());
+            *vm_try!(self.stack.at_offset_mut(offset)) = value;
+        }
+
+        VmResult::Ok(())
+    }
+
     /// Perform an index set operation.
     #[cfg_attr(feature = "bench", inline(never))]
     fn op_index_set(&mut self) -> VmResult<()> {
@@ -1880,8 +2161,8 @@ impl Vm {
 
             match &target {
                 Value::Object(object) => {
-                    let mut object = vm_try!(object.borrow_mut());
-                    object.insert(field.to_owned(), value);
+                    vm_try!(object.borrow_mut()).insert(field.to_owned(), value);
+                    object.measure();
                     return VmResult::Ok(());
                 }
                 Value::Struct(typed_object) => {
@@ -2221,7 +2502,7 @@ impl Vm {
             object.insert(key.clone(), value);
         }
 
-        self.stack.push(Shared::new(object));
+        self.stack.push(Shared::new_measured(object));
         VmResult::Ok(())
     }
 
@@ -2447,6 +2728,42 @@ impl Vm {
         VmResult::Ok(())
     }
 
+    #[cfg_attr(feature = "bench", inline(never))]
+    fn op_range_integer(&mut self, start: i64, end: i64) -> VmResult<()> {
+        let value = vm_try!(self.stack.pop());
+
+        self.stack.push(match value {
+            Value::Integer(actual) => start <= actual && actual <= end,
+            _ => false,
+        });
+
+        VmResult::Ok(())
+    }
+
+    #[cfg_attr(feature = "bench", inline(never))]
+    fn op_range_character(&mut self, start: char, end: char) -> VmResult<()> {
+        let value = vm_try!(self.stack.pop());
+
+        self.stack.push(match value {
+            Value::Char(actual) => start <= actual && actual <= end,
+            _ => false,
+        });
+
+        VmResult::Ok(())
+    }
+
+    #[cfg_attr(feature = "bench", inline(never))]
+    fn op_range_byte(&mut self, start: u8, end: u8) -> VmResult<()> {
+        let value = vm_try!(self.stack.pop());
+
+        self.stack.push(match value {
+            Value::Byte(actual) => start <= actual && actual <= end,
+            _ => false,
+        });
+
+        VmResult::Ok(())
+    }
+
     /// Test if the top of stack is equal to the string at the given static
     /// string slot.
     #[cfg_attr(feature = "bench", inline(never))]
@@ -2756,7 +3073,7 @@ impl Vm {
                     .function(hash)
                     .ok_or(VmErrorKind::MissingFunction { hash }));
 
-                vm_try!(handler(&mut self.stack, args));
+                vm_try!(handler(&mut self.stack, args).with_native(hash));
             }
         }
 
@@ -2777,13 +3094,15 @@ impl Vm {
             args: expected,
         }) = self.unit.function(hash)
         {
-            vm_try!(check_args(args, expected));
-            vm_try!(self.call_offset_fn(offset, call, args));
+            vm_try!(self
+                .unit
+                .push_default_arguments(&mut self.stack, hash, args, expected));
+            vm_try!(self.call_offset_fn(offset, call, expected));
             return VmResult::Ok(());
         }
 
         if let Some(handler) = self.context.function(hash) {
-            vm_try!(handler(&mut self.stack, args));
+            vm_try!(handler(&mut self.stack, args).with_native(hash));
             return VmResult::Ok(());
         }
 
@@ -2885,11 +3204,30 @@ impl Vm {
         // unit.
         let _guard = crate::runtime::env::Guard::new(&self.context, &self.unit);
 
+        // NB: checked before the first instruction, so that executions which
+        // are stepped are checked on every step.
+        let mut until_check = 0;
+
         loop {
             if !budget::take() {
                 return VmResult::Ok(VmHalt::Limited);
             }
 
+            if until_check == 0 {
+                until_check = CHECK_INTERVAL;
+
+                if let Some(limit) = budget::memory_exceeded() {
+                    return VmResult::err(VmErrorKind::MemoryLimitExceeded { limit });
+                }
+
+                #[cfg(feature = "std")]
+                if crate::runtime::cancellation::check() {
+                    return VmResult::err(VmErrorKind::Cancelled);
+                }
+            }
+
+            until_check -= 1;
+
             let Some((inst, inst_len)) = vm_try!(self.unit.instruction_at(self.ip)) else {
                 return VmResult::err(VmErrorKind::IpOutOfBounds {
                     ip: self.ip,
@@ -3099,6 +3437,15 @@ impl Vm {
                 Inst::EqBytes { slot } => {
                     vm_try!(self.op_eq_bytes(slot));
                 }
+                Inst::RangeInteger { start, end } => {
+                    vm_try!(self.op_range_integer(start, end));
+                }
+                Inst::RangeChar { start, end } => {
+                    vm_try!(self.op_range_character(start, end));
+                }
+                Inst::RangeByte { start, end } => {
+                    vm_try!(self.op_range_byte(start, end));
+                }
                 Inst::MatchSequence {
                     type_check,
                     len,
@@ -3132,11 +3479,11 @@ impl Vm {
                 Inst::Variant { variant } => {
                     vm_try!(self.op_variant(variant));
                 }
-                Inst::Op { op, a, b } => {
-                    vm_try!(self.op_op(op, a, b));
+                Inst::Op { op, a, b, out } => {
+                    vm_try!(self.op_op(op, a, b, out));
                 }
-                Inst::Assign { target, op } => {
-                    vm_try!(self.op_assign(target, op));
+                Inst::Assign { target, op, value } => {
+                    vm_try!(self.op_assign(target, op, value));
                 }
                 Inst::IterNext { offset, jump } => {
                     vm_try!(self.op_iter_next(offset, jump));
@@ -3168,7 +3515,7 @@ impl AsRef<Vm> for Vm {
 /// A call frame.
 ///
 /// This is used to store the return point after an instruction has been run.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[non_exhaustive]
 pub struct CallFrame {
     /// The stored instruction pointer.
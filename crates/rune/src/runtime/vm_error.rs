@@ -7,13 +7,16 @@ use crate::no_std::thiserror;
 
 use thiserror::Error;
 
+use crate::ast::Span;
 use crate::compile::ItemBuf;
 use crate::hash::Hash;
+use crate::runtime::debug::DebugSignature;
 use crate::runtime::unit::{BadInstruction, BadJump};
 use crate::runtime::{
-    AccessError, BoxedPanic, CallFrame, ExecutionState, FullTypeOf, Key, MaybeTypeOf, Panic,
+    AccessError, BoxedPanic, CallFrame, DebugInfo, ExecutionState, FullTypeOf, MaybeTypeOf, Panic,
     StackError, TypeInfo, TypeOf, Unit, Value, Vm, VmHaltInfo,
 };
+use crate::{SourceId, Sources};
 
 /// Trait used to convert result types to [`VmResult`].
 #[doc(hidden)]
@@ -83,6 +86,145 @@ pub struct VmErrorLocation {
     pub ip: usize,
     /// All lower call frames before the unwind trigger point
     pub frames: Vec<CallFrame>,
+    /// The native function which was being called when the error was raised,
+    /// if any.
+    pub native: Option<Hash>,
+}
+
+impl VmErrorLocation {
+    /// Resolve the script frames of this location, starting with the innermost
+    /// frame.
+    fn script_frames(&self, backtrace: &mut Vec<BacktraceFrame>) {
+        let debug = self.unit.debug_info();
+        let index = debug.map(DebugIndex::new).unwrap_or_default();
+
+        for ip in [self.ip]
+            .into_iter()
+            .chain(self.frames.iter().rev().map(|frame| frame.ip))
+        {
+            backtrace.push(index.frame(debug, ip));
+        }
+    }
+}
+
+/// Instructions and functions with debug information, ordered by their
+/// offset.
+#[derive(Default)]
+struct DebugIndex {
+    instructions: Vec<usize>,
+    functions: Vec<(usize, Hash)>,
+}
+
+impl DebugIndex {
+    fn new(debug: &DebugInfo) -> Self {
+        let mut instructions = debug.instructions.keys().copied().collect::<Vec<_>>();
+        instructions.sort_unstable();
+
+        let mut functions = debug
+            .functions_rev
+            .iter()
+            .map(|(&ip, &hash)| (ip, hash))
+            .collect::<Vec<_>>();
+        functions.sort_unstable_by_key(|&(ip, _)| ip);
+
+        Self {
+            instructions,
+            functions,
+        }
+    }
+
+    /// Resolve the frame at the given instruction pointer.
+    ///
+    /// Instruction pointers which are recorded in errors and call frames point
+    /// to the instruction after the one being executed, so the frame is
+    /// resolved to the closest preceding instruction.
+    fn frame(&self, debug: Option<&DebugInfo>, ip: usize) -> BacktraceFrame {
+        let at = match self.instructions.binary_search(&ip) {
+            Ok(n) | Err(n) => n.checked_sub(1).map(|n| self.instructions[n]),
+        };
+
+        let hash = at.and_then(|at| {
+            let n = self.functions.partition_point(|&(entry, _)| entry <= at);
+            Some(self.functions.get(n.checked_sub(1)?)?.1)
+        });
+
+        let inst = debug
+            .zip(at)
+            .and_then(|(debug, at)| debug.instruction_at(at));
+        let signature = debug
+            .zip(hash)
+            .and_then(|(debug, hash)| debug.functions.get(&hash))
+            .cloned();
+
+        BacktraceFrame {
+            ip: at.unwrap_or(ip),
+            hash,
+            signature,
+            source_id: inst.map(|inst| inst.source_id),
+            span: inst.map(|inst| inst.span),
+            native: false,
+        }
+    }
+}
+
+/// A single frame in the backtrace of a [VmError].
+///
+/// See [VmError::backtrace].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct BacktraceFrame {
+    /// The instruction the frame is executing. For native frames this is
+    /// unspecified.
+    pub ip: usize,
+    /// The hash of the function the frame belongs to, if known.
+    pub hash: Option<Hash>,
+    /// The signature of the function the frame belongs to, if debug
+    /// information is available.
+    pub signature: Option<DebugSignature>,
+    /// The source the frame is executing in, if debug information is
+    /// available.
+    pub source_id: Option<SourceId>,
+    /// The span of the expression the frame is executing, if debug
+    /// information is available.
+    pub span: Option<Span>,
+    /// Whether the frame belongs to a native function.
+    pub native: bool,
+}
+
+impl BacktraceFrame {
+    fn native(hash: Hash) -> Self {
+        Self {
+            ip: 0,
+            hash: Some(hash),
+            signature: None,
+            source_id: None,
+            span: None,
+            native: true,
+        }
+    }
+
+    /// Get the zero-based line and column of the frame in the given sources.
+    pub fn line_column(&self, sources: &Sources) -> Option<(usize, usize)> {
+        let source = sources.get(self.source_id?)?;
+        let span = self.span?;
+        Some(source.pos_to_utf8_linecol(span.start.into_usize()))
+    }
+}
+
+impl fmt::Display for BacktraceFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.signature, self.hash) {
+            (Some(signature), _) => signature.fmt(f)?,
+            (None, Some(hash)) => write!(f, "<function {}>", hash)?,
+            (None, None) => write!(f, "<unknown>")?,
+        }
+
+        if self.native {
+            write!(f, " (native)")?;
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -118,6 +260,9 @@ pub(crate) struct VmErrorInner {
     pub(crate) error: VmErrorAt,
     pub(crate) chain: Vec<VmErrorAt>,
     pub(crate) stacktrace: Vec<VmErrorLocation>,
+    /// Native function being called when the error was raised, which has not
+    /// yet been associated with a location.
+    pub(crate) native: Option<Hash>,
 }
 
 /// A virtual machine error which includes tracing information.
@@ -166,6 +311,76 @@ impl VmError {
         self.inner.stacktrace.first()
     }
 
+    /// Get the full backtrace of the error, starting with the innermost frame.
+    ///
+    /// Script frames are resolved through the [DebugInfo] of the unit they
+    /// belong to, and native functions which were called by the script are
+    /// included as frames marked as [native][BacktraceFrame::native].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rune::{Context, Source, Sources, Vm};
+    /// use std::sync::Arc;
+    ///
+    /// let context = Context::with_default_modules()?;
+    ///
+    /// let mut sources = Sources::new();
+    /// sources.insert(Source::new("entry", r#"
+    /// fn inner(value) {
+    ///     [value].iter().map(|v| v / 0).collect::<Vec>()
+    /// }
+    ///
+    /// pub fn main() {
+    ///     inner(1)
+    /// }
+    /// "#));
+    ///
+    /// let unit = rune::prepare(&mut sources).with_context(&context).build()?;
+    /// let mut vm = Vm::new(Arc::new(context.runtime()), Arc::new(unit));
+    ///
+    /// let error = vm.call(["main"], ()).unwrap_err();
+    ///
+    /// let frames = error
+    ///     .backtrace()
+    ///     .iter()
+    ///     .map(|frame| frame.to_string())
+    ///     .collect::<Vec<_>>();
+    ///
+    /// // The closure passed to `map`.
+    /// assert!(frames[0].ends_with("(v)"));
+    /// assert!(frames[1].ends_with("(native)"));
+    /// assert_eq!(frames[frames.len() - 2], "inner(value)");
+    /// assert_eq!(frames[frames.len() - 1], "main()");
+    /// # Ok::<_, rune::Error>(())
+    /// ```
+    pub fn backtrace(&self) -> Vec<BacktraceFrame> {
+        self.backtrace_with_locations().0
+    }
+
+    /// Resolve the backtrace of the error, along with the offset in it of the
+    /// innermost script frame of every location.
+    pub(crate) fn backtrace_with_locations(&self) -> (Vec<BacktraceFrame>, Vec<usize>) {
+        let mut backtrace = Vec::new();
+        let mut locations = Vec::with_capacity(self.inner.stacktrace.len());
+
+        for location in &self.inner.stacktrace {
+            if let Some(hash) = location.native {
+                backtrace.push(BacktraceFrame::native(hash));
+            }
+
+            locations.push(backtrace.len());
+            location.script_frames(&mut backtrace);
+        }
+
+        (backtrace, locations)
+    }
+
+    /// Access the kind of the error.
+    pub(crate) fn kind(&self) -> &VmErrorKind {
+        &self.inner.error.kind
+    }
+
     #[cfg(test)]
     pub(crate) fn into_kind(self) -> VmErrorKind {
         self.inner.error.kind
@@ -241,10 +456,13 @@ impl<T> VmResult<T> {
         match self {
             Self::Ok(ok) => Self::Ok(ok),
             Self::Err(mut err) => {
+                let native = err.inner.native.take();
+
                 err.inner.stacktrace.push(VmErrorLocation {
                     unit: vm.unit().clone(),
                     ip: vm.ip(),
                     frames: vm.call_frames().to_vec(),
+                    native,
                 });
 
                 Self::Err(err)
@@ -252,6 +470,18 @@ impl<T> VmResult<T> {
         }
     }
 
+    /// Mark the error as raised by the native function with the given hash.
+    #[inline]
+    pub(crate) fn with_native(self, hash: Hash) -> Self {
+        match self {
+            Self::Ok(ok) => Self::Ok(ok),
+            Self::Err(mut err) => {
+                err.inner.native.get_or_insert(hash);
+                Self::Err(err)
+            }
+        }
+    }
+
     /// Add auxilliary errors if appropriate.
     #[inline]
     pub(crate) fn with_error<E, O>(self, error: E) -> Self
@@ -374,6 +604,7 @@ where
                 },
                 chain: Vec::new(),
                 stacktrace: Vec::new(),
+                native: None,
             }),
         }
     }
@@ -417,6 +648,10 @@ pub(crate) enum VmErrorKind {
     NoRunningVm,
     #[error("Halted for unexpected reason `{halt}`")]
     Halted { halt: VmHaltInfo },
+    #[error("Memory limit of {limit} bytes exceeded")]
+    MemoryLimitExceeded { limit: usize },
+    #[error("Execution was cancelled")]
+    Cancelled,
     #[error("Failed to format argument")]
     FormatError,
     #[error("Numerical overflow")]
@@ -493,8 +728,8 @@ pub(crate) enum VmErrorKind {
         target: TypeInfo,
         index: VmIntegerRepr,
     },
-    #[error("Type `{target}` missing index `{index:?}`")]
-    MissingIndexKey { target: TypeInfo, index: Key },
+    #[error("Type `{target}` missing index `{index}`")]
+    MissingIndexKey { target: TypeInfo, index: String },
     #[error("Index out of bounds, the length is `{length}` but the index is `{index}`")]
     OutOfRange {
         index: VmIntegerRepr,
@@ -4,9 +4,14 @@ use core::mem::take;
 
 use crate::no_std::prelude::*;
 
+use serde::{Deserialize, Serialize};
+
 use crate::runtime::budget;
+#[cfg(feature = "std")]
+use crate::runtime::cancellation::{Cancellable, CancellationToken};
 use crate::runtime::{
-    Generator, GeneratorState, Stream, Value, Vm, VmErrorKind, VmHalt, VmHaltInfo, VmResult,
+    Awaited, Generator, GeneratorState, SnapshotError, Stream, Value, Vm, VmErrorKind, VmHalt,
+    VmHaltInfo, VmResult, VmSnapshot,
 };
 use crate::shared::AssertSend;
 
@@ -14,7 +19,7 @@ use crate::shared::AssertSend;
 /// correctly interact with functions that yield (like generators and streams)
 /// by initially just calling the function, then by providing a value pushed
 /// onto the stack.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[non_exhaustive]
 pub enum ExecutionState {
     /// The initial state of an execution.
@@ -48,6 +53,26 @@ where
     /// The current stack of virtual machines and the execution state that must
     /// be restored once one is popped.
     vms: Vec<(Vm, ExecutionState)>,
+    /// Token used to cancel the execution.
+    #[cfg(feature = "std")]
+    cancellation: Option<CancellationToken>,
+}
+
+#[cfg(feature = "std")]
+type Cancellation = CancellationToken;
+
+/// Placeholder for when cancellation is not supported.
+#[cfg(not(feature = "std"))]
+type Cancellation = ();
+
+macro_rules! cancellation {
+    ($slf:expr) => {{
+        #[cfg(feature = "std")]
+        let cancellation = $slf.cancellation.as_ref();
+        #[cfg(not(feature = "std"))]
+        let cancellation = None;
+        cancellation
+    }};
 }
 
 macro_rules! vm {
@@ -78,9 +103,45 @@ where
             head,
             vms: vec![],
             state: ExecutionState::Initial,
+            #[cfg(feature = "std")]
+            cancellation: None,
         }
     }
 
+    /// Construct an execution from its raw parts.
+    pub(crate) fn from_parts(
+        head: T,
+        state: ExecutionState,
+        vms: Vec<(Vm, ExecutionState)>,
+    ) -> Self {
+        Self {
+            head,
+            state,
+            vms,
+            #[cfg(feature = "std")]
+            cancellation: None,
+        }
+    }
+
+    /// Access the raw parts of the execution.
+    pub(crate) fn parts(&self) -> (&T, ExecutionState, &[(Vm, ExecutionState)]) {
+        (&self.head, self.state, &self.vms)
+    }
+
+    /// Associate a cancellation token with the execution.
+    ///
+    /// Once the token is cancelled or its deadline passes, the execution
+    /// errors the next time it checks the token. This happens periodically
+    /// while instructions are being executed, and while the execution awaits a
+    /// future.
+    ///
+    /// Virtual machines called from native functions as part of the execution
+    /// also observe the token.
+    #[cfg(feature = "std")]
+    pub fn set_cancellation(&mut self, token: CancellationToken) {
+        self.cancellation = Some(token);
+    }
+
     /// Test if the current execution state is resumed.
     pub(crate) fn is_resumed(&self) -> bool {
         matches!(self.state, ExecutionState::Resumed)
@@ -166,6 +227,35 @@ where
         vm_mut!(self)
     }
 
+    /// Take a serializable snapshot of the execution.
+    ///
+    /// The snapshot can be restored against the same unit and context using
+    /// [VmSnapshot::restore], which allows for persisting an execution which
+    /// is suspended in a `yield`.
+    pub fn snapshot(&self) -> Result<VmSnapshot, SnapshotError>
+    where
+        T: AsRef<Vm>,
+    {
+        VmSnapshot::from_execution(self)
+    }
+
+    /// Get the total number of call frames across every virtual machine
+    /// participating in the execution.
+    ///
+    /// Each nested virtual machine counts as an additional frame.
+    pub(crate) fn call_depth(&self) -> usize
+    where
+        T: AsRef<Vm>,
+    {
+        let mut depth = self.head.as_ref().call_frames().len();
+
+        for (vm, _) in &self.vms {
+            depth += vm.call_frames().len() + 1;
+        }
+
+        depth
+    }
+
     /// Complete the current execution without support for async instructions.
     ///
     /// This will error if the execution is suspended through yielding.
@@ -224,10 +314,10 @@ where
             let len = self.vms.len();
             let vm = vm_mut!(self);
 
-            match vm_try!(Self::run(vm)) {
+            match vm_try!(Self::run(vm, cancellation!(self))) {
                 VmHalt::Exited => (),
                 VmHalt::Awaited(awaited) => {
-                    vm_try!(awaited.into_vm(vm).await);
+                    vm_try!(Self::wait(vm, awaited, cancellation!(self)).await);
                     continue;
                 }
                 VmHalt::VmCall(vm_call) => {
@@ -291,7 +381,7 @@ where
             let len = self.vms.len();
             let vm = vm_mut!(self);
 
-            match vm_try!(Self::run(vm)) {
+            match vm_try!(Self::run(vm, cancellation!(self))) {
                 VmHalt::Exited => (),
                 VmHalt::VmCall(vm_call) => {
                     vm_try!(vm_call.into_execution(self));
@@ -325,7 +415,7 @@ where
         let len = self.vms.len();
         let vm = vm_mut!(self);
 
-        match vm_try!(budget::with(1, || Self::run(vm)).call()) {
+        match vm_try!(budget::with(1, || Self::run(vm, cancellation!(self))).call()) {
             VmHalt::Exited => (),
             VmHalt::VmCall(vm_call) => {
                 vm_try!(vm_call.into_execution(self));
@@ -354,10 +444,10 @@ where
         let len = self.vms.len();
         let vm = vm_mut!(self);
 
-        match vm_try!(budget::with(1, || Self::run(vm)).call()) {
+        match vm_try!(budget::with(1, || Self::run(vm, cancellation!(self))).call()) {
             VmHalt::Exited => (),
             VmHalt::Awaited(awaited) => {
-                vm_try!(awaited.into_vm(vm).await);
+                vm_try!(Self::wait(vm, awaited, cancellation!(self)).await);
                 return VmResult::Ok(None);
             }
             VmHalt::VmCall(vm_call) => {
@@ -411,9 +501,31 @@ where
     }
 
     #[inline]
-    fn run(vm: &mut Vm) -> VmResult<VmHalt> {
+    fn run(vm: &mut Vm, cancellation: Option<&Cancellation>) -> VmResult<VmHalt> {
+        #[cfg(feature = "std")]
+        let _guard = crate::runtime::cancellation::install(cancellation);
+        #[cfg(not(feature = "std"))]
+        let _ = cancellation;
         vm.run().with_vm(vm)
     }
+
+    /// Wait for the given awaited value, erroring if the execution is
+    /// cancelled before it completes.
+    async fn wait(
+        vm: &mut Vm,
+        awaited: Awaited,
+        cancellation: Option<&Cancellation>,
+    ) -> VmResult<()> {
+        #[cfg(feature = "std")]
+        let result = Cancellable::new(cancellation, awaited.into_vm(vm)).await;
+        #[cfg(not(feature = "std"))]
+        let result = {
+            let _ = cancellation;
+            awaited.into_vm(vm).await
+        };
+
+        result.with_vm(vm)
+    }
 }
 
 impl VmExecution<&mut Vm> {
@@ -426,6 +538,8 @@ impl VmExecution<&mut Vm> {
             head,
             vms: self.vms,
             state: self.state,
+            #[cfg(feature = "std")]
+            cancellation: self.cancellation,
         }
     }
 }
@@ -445,6 +559,14 @@ pub struct VmSendExecution(pub(crate) VmExecution<Vm>);
 unsafe impl Send for VmSendExecution {}
 
 impl VmSendExecution {
+    /// Associate a cancellation token with the execution.
+    ///
+    /// See [VmExecution::set_cancellation].
+    #[cfg(feature = "std")]
+    pub fn set_cancellation(&mut self, token: CancellationToken) {
+        self.0.set_cancellation(token);
+    }
+
     /// Complete the current execution with support for async instructions.
     ///
     /// This requires that the result of the Vm is converted into a
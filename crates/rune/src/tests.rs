@@ -33,7 +33,7 @@ use crate::no_std::sync::Arc;
 use anyhow::{Context as _, Error, Result};
 use thiserror::Error;
 
-use crate::compile::{IntoComponent, ItemBuf};
+use crate::compile::{IntoComponent, ItemBuf, Options};
 use crate::runtime::{Args, VmError, VmResult};
 use crate::{termcolor, BuildError, Context, Diagnostics, FromValue, Source, Sources, Unit, Vm};
 
@@ -70,10 +70,23 @@ pub fn vm(
     context: &Context,
     sources: &mut Sources,
     diagnostics: &mut Diagnostics,
+) -> Result<Vm, RunError> {
+    vm_with_options(context, sources, diagnostics, &Options::default())
+}
+
+/// Construct a virtual machine for the given sources, compiled with the given
+/// options.
+#[doc(hidden)]
+pub fn vm_with_options(
+    context: &Context,
+    sources: &mut Sources,
+    diagnostics: &mut Diagnostics,
+    options: &Options,
 ) -> Result<Vm, RunError> {
     let result = crate::prepare(sources)
         .with_context(context)
         .with_diagnostics(diagnostics)
+        .with_options(options)
         .build();
 
     let Ok(unit) = result else {
@@ -365,6 +378,7 @@ mod compiler_expr_assign;
 mod compiler_fn;
 mod compiler_general;
 mod compiler_literals;
+mod compiler_optimize;
 mod compiler_paths;
 mod compiler_patterns;
 mod compiler_use;
@@ -373,16 +387,19 @@ mod compiler_warnings;
 mod continue_;
 mod core_macros;
 mod custom_macros;
+mod default_args;
 mod destructuring;
 mod external_ops;
 mod float;
 mod for_loop;
 mod generics;
 mod getter_setter;
+mod hash_cmp;
 mod instance;
 mod int;
 mod iter;
 mod iterator;
+mod macro_rules;
 mod macros;
 mod match_external;
 mod moved;
@@ -393,6 +410,8 @@ mod range;
 mod reference_error;
 mod result;
 mod stmt_reordering;
+mod traits;
+mod type_annotations;
 mod type_name_native;
 mod type_name_rune;
 mod unit_constants;
@@ -400,9 +419,13 @@ mod variants;
 mod vm_arithmetic;
 mod vm_assign_exprs;
 mod vm_async_block;
+mod vm_backtrace;
 mod vm_blocks;
+mod vm_cancellation;
 mod vm_closures;
 mod vm_const_exprs;
+mod vm_coverage;
+mod vm_debugger;
 mod vm_early_termination;
 mod vm_function;
 mod vm_general;
@@ -411,10 +434,13 @@ mod vm_is;
 mod vm_lazy_and_or;
 mod vm_literals;
 mod vm_match;
+mod vm_memory_budget;
 mod vm_not_used;
 mod vm_option;
 mod vm_pat;
+mod vm_profiler;
 mod vm_result;
+mod vm_snapshot;
 mod vm_streams;
 mod vm_test_external_fn_ptr;
 mod vm_test_from_value_derive;
@@ -0,0 +1,216 @@
+prelude!();
+
+use crate::compile::Options;
+use crate::runtime::{Inst, InstOp, InstValue, Unit};
+use crate::tests::{sources, vm_with_options};
+
+const LOOPS: &str = r#"fn foo() { 1 }
+
+pub fn main() {
+    let a = 0;
+
+    while a < 2 {
+        let b = foo();
+        { let c = foo(); foo(); }
+        a += 1;
+    }
+
+    let v = loop {
+        let q = foo();
+        break q;
+    };
+
+    a + v
+}
+"#;
+
+/// Build a virtual machine for the given source, with or without
+/// optimizations.
+fn build(source: &str, optimize: bool) -> (Sources, Vm) {
+    let context = Context::with_default_modules().unwrap();
+    let mut sources = sources(source);
+
+    let mut options = Options::default();
+    options.optimize(optimize);
+
+    let vm = vm_with_options(&context, &mut sources, &mut Diagnostics::new(), &options).unwrap();
+    (sources, vm)
+}
+
+fn instructions(unit: &Unit) -> Vec<Inst> {
+    unit.iter_instructions().map(|(_, inst)| inst).collect()
+}
+
+#[test]
+fn test_fold_constants() {
+    const SOURCE: &str = "pub fn main() {\n    1 + 2 * 3\n}\n";
+
+    let (sources, mut vm) = build(SOURCE, true);
+    let unit = vm.unit().clone();
+    let insts = instructions(&unit);
+
+    assert!(!insts.iter().any(|inst| matches!(inst, Inst::Op { .. })));
+
+    let (ip, _) = unit
+        .iter_instructions()
+        .find(|(_, inst)| {
+            matches!(
+                inst,
+                Inst::Push {
+                    value: InstValue::Integer(7)
+                }
+            )
+        })
+        .expect("missing folded constant");
+
+    // The folded constant keeps the span of the expression it replaces.
+    let debug = unit.debug_info().unwrap().instruction_at(ip).unwrap();
+    let source = sources.get(debug.source_id).unwrap();
+    assert_eq!(source.get(debug.span.range()), Some("1 + 2 * 3"));
+
+    let value: i64 = from_value(vm.call(["main"], ()).unwrap()).unwrap();
+    assert_eq!(value, 7);
+}
+
+#[test]
+fn test_fold_constants_overflow() {
+    const SOURCE: &str = "pub fn main() {\n    9223372036854775807 + 1\n}\n";
+
+    // NB: the overflow is left to be raised at runtime.
+    let (_, mut vm) = build(SOURCE, true);
+    let insts = instructions(vm.unit());
+
+    assert!(insts.iter().any(|inst| matches!(
+        inst,
+        Inst::Op {
+            op: InstOp::Add,
+            ..
+        }
+    )));
+
+    assert!(vm.call(["main"], ()).is_err());
+}
+
+#[test]
+fn test_jumps_and_unreachable_code() {
+    let (_, mut plain) = build(LOOPS, false);
+    let (_, mut optimized) = build(LOOPS, true);
+
+    let plain_insts = instructions(plain.unit());
+    let insts = instructions(optimized.unit());
+    assert!(insts.len() < plain_insts.len());
+
+    for inst in &insts {
+        let Inst::Jump { jump } = *inst else {
+            continue;
+        };
+
+        let target = optimized.unit().translate(jump).unwrap();
+        assert!(!matches!(insts[target], Inst::Jump { .. }));
+    }
+
+    let count = |insts: &[Inst]| {
+        insts
+            .iter()
+            .filter(|inst| matches!(inst, Inst::Pop | Inst::PopN { .. }))
+            .count()
+    };
+
+    assert!(insts
+        .iter()
+        .any(|inst| matches!(inst, Inst::PopN { count: 2 })));
+    assert!(count(&insts) < count(&plain_insts));
+
+    let plain: i64 = from_value(plain.call(["main"], ()).unwrap()).unwrap();
+    let optimized: i64 = from_value(optimized.call(["main"], ()).unwrap()).unwrap();
+    assert_eq!(plain, 3);
+    assert_eq!(optimized, plain);
+}
+
+#[test]
+fn test_labels_are_kept() {
+    let labels = |unit: &Unit| {
+        let mut labels = unit
+            .debug_info()
+            .unwrap()
+            .instructions
+            .values()
+            .flat_map(|inst| inst.labels.iter().map(|label| label.to_string()))
+            .map(|label| label.split(' ').next().unwrap().to_owned())
+            .collect::<Vec<_>>();
+
+        labels.sort();
+        labels
+    };
+
+    let (_, plain) = build(LOOPS, false);
+    let (_, optimized) = build(LOOPS, true);
+    assert_eq!(labels(optimized.unit()), labels(plain.unit()));
+}
+
+#[test]
+fn test_same_result_as_unoptimized() {
+    const SOURCE: &str = r#"
+    struct Point { x, y }
+
+    fn sum(values) {
+        let total = 0;
+
+        for value in values {
+            total += value;
+        }
+
+        total
+    }
+
+    fn find(values, needle) {
+        for value in values {
+            if value == needle {
+                return value;
+            }
+        }
+
+        None
+    }
+
+    pub fn main() {
+        let name = "rune";
+        let names = [];
+
+        for n in 0..3 {
+            let copy = name;
+            names.push(`${copy}-${n}`);
+        }
+
+        let point = Point { x: 1 + 2, y: 4 * 5 };
+        let add = |a| a + point.x;
+
+        let kind = match point {
+            Point { x: 3, y } if y > 10 => "big",
+            _ => "small",
+        };
+
+        let values = [1, 2, 3];
+        let first = values;
+        let doubled = first.iter().map(|v| v * 2).collect::<Vec>();
+
+        let last = for v in doubled {
+            if v > 2 {
+                break v;
+            }
+        };
+
+        (sum(values), add(10), kind, names, doubled, find(values, 2) + last)
+    }
+    "#;
+
+    type Output = (i64, i64, String, Vec<String>, Vec<i64>, i64);
+
+    let (_, mut plain) = build(SOURCE, false);
+    let (_, mut optimized) = build(SOURCE, true);
+
+    let plain: Output = from_value(plain.call(["main"], ()).unwrap()).unwrap();
+    let optimized: Output = from_value(optimized.call(["main"], ()).unwrap()).unwrap();
+    assert_eq!((plain.0, plain.5), (6, 6));
+    assert_eq!(optimized, plain);
+}
@@ -30,3 +30,33 @@ fn illegal_pattern_in_match() {
         }
     };
 }
+
+#[test]
+fn non_exhaustive_enum_match() {
+    assert_errors! {
+        r#"
+        enum Foo { A, B(a), C { c } }
+
+        pub fn main() {
+            match Foo::A { Foo::B(..) => 1 }
+        }
+        "#,
+        span!(76, 108), PatternMissingVariants { item, variants } => {
+            assert_eq!(item.to_string(), "Foo");
+            assert_eq!(&variants[..], [Box::from("A"), Box::from("C")]);
+        }
+    };
+
+    assert_errors! {
+        r#"
+        enum Foo { A, B }
+
+        pub fn main() {
+            match Some(Foo::A) { Some(Foo::A) => 1, None => 2 }
+        }
+        "#,
+        span!(64, 115), PatternMissingVariants { variants, .. } => {
+            assert_eq!(&variants[..], [Box::from("B")]);
+        }
+    };
+}
@@ -25,3 +25,21 @@ fn test_remove_variant_parens() {
         span!(20, 22), RemoveTupleCallParams { variant: span!(16, 20), .. }
     };
 }
+
+#[test]
+fn test_unreachable_pattern() {
+    assert_warnings! {
+        r#"pub fn main() { match 3 { _ => 1, 2 => 3 } }"#,
+        span!(34, 35), UnreachablePattern { context: Some(span!(14, 44)), .. }
+    };
+
+    assert_warnings! {
+        r#"pub fn main() { match (1, 2) { (1, b) => 1, (a, b) if a > 1 => 2, (1, 2) => 3 } }"#,
+        span!(66, 72), UnreachablePattern { .. }
+    };
+
+    assert_warnings! {
+        r#"pub fn main() { match Some(1) { Some(1) | None => 1, Some(..) => 2, None => 3 } }"#,
+        span!(68, 72), UnreachablePattern { .. }
+    };
+}
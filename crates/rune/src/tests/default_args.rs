@@ -0,0 +1,190 @@
+prelude!();
+
+use CompileErrorKind::*;
+
+#[test]
+fn test_default_args() {
+    let out: (i64, i64, bool) = rune! {
+        const BASE = 8000;
+
+        fn connect(host, port = BASE + 80, secure: bool = false) {
+            (host, port, secure)
+        }
+
+        pub fn main() {
+            let (_, a, _) = connect("a");
+            let (_, b, _) = connect("a", 1);
+            let (_, _, c) = connect("a", 1, true);
+            (a, b, c)
+        }
+    };
+
+    assert_eq!(out, (8080, 1, true));
+}
+
+#[test]
+fn test_named_args() {
+    let out: (String, i64, bool) = rune! {
+        fn connect(host, port = 80, secure = false) {
+            (host, port, secure)
+        }
+
+        pub fn main() {
+            connect(secure: true, host: "example.com")
+        }
+    };
+
+    assert_eq!(out, (String::from("example.com"), 80, true));
+
+    let out: (String, i64) = rune! {
+        struct Client { name }
+
+        impl Client {
+            fn send(self, message, retries = 3) {
+                (message, retries)
+            }
+        }
+
+        pub fn main() {
+            let client = Client { name: "a" };
+            Client::send(client, retries: 1, message: "hello")
+        }
+    };
+
+    assert_eq!(out, (String::from("hello"), 1));
+}
+
+#[test]
+fn test_named_args_evaluation_order() {
+    let out: ((i64, i64, i64), Vec<String>, i64) = rune! {
+        fn f(a, b, c = 3) {
+            (a, b, c)
+        }
+
+        fn g(log) {
+            log.push("g");
+            2
+        }
+
+        fn h(log) {
+            log.push("h");
+            1
+        }
+
+        pub fn main() {
+            let log = [];
+            let before = 10;
+            let out = f(b: g(log), a: h(log));
+            (out, log, before)
+        }
+    };
+
+    assert_eq!(
+        out,
+        ((1, 2, 3), vec![String::from("g"), String::from("h")], 10)
+    );
+
+    let out: (String, i64) = rune! {
+        fn f(a: String, b: int) {
+            (a, b)
+        }
+
+        pub fn main() {
+            f(b: 1, a: "a")
+        }
+    };
+
+    assert_eq!(out, (String::from("a"), 1));
+}
+
+#[test]
+fn test_runtime_defaults() {
+    let function: Function = rune! {
+        fn connect(host, port = 80) { (host, port) }
+        pub fn main() { connect }
+    };
+
+    let out: (String, i64) = function.call(("a",)).unwrap();
+    assert_eq!(out, (String::from("a"), 80));
+    let out: (String, i64) = function.call(("a", 8080i64)).unwrap();
+    assert_eq!(out, (String::from("a"), 8080));
+    assert!(function.call::<_, Value>(()).into_result().is_err());
+
+    let out: (i64, i64) = rune! {
+        struct Client;
+
+        impl Client {
+            fn retries(self, retries = 3) { retries }
+        }
+
+        pub fn main() {
+            let client = Client;
+            let f = Client::retries;
+            (client.retries(), f(client))
+        }
+    };
+
+    assert_eq!(out, (3, 3));
+}
+
+#[test]
+fn test_default_args_errors() {
+    assert_errors! {
+        r#"fn foo(a = 1, b) {}"#,
+        span!(14, 15), ArgumentDefaultNotTrailing
+    };
+
+    assert_errors! {
+        r#"fn foo(a, b = a) {} pub fn main() { foo(1) }"#,
+        span!(14, 15), MissingLocal { .. }
+    };
+
+    assert_errors! {
+        r#"const fn foo(a = 1) {}"#,
+        span!(13, 18), UnsupportedArgumentDefault
+    };
+
+    assert_errors! {
+        r#"fn foo(a, b = 1) {} pub fn main() { foo() }"#,
+        span!(39, 41), MissingArgument { name } => {
+            assert_eq!(name.as_ref(), "a");
+        }
+    };
+
+    assert_errors! {
+        r#"fn foo(a, b = 1) {} pub fn main() { foo(1, 2, 3) }"#,
+        span!(39, 48), UnsupportedArgumentCount { expected: 2, actual: 3 }
+    };
+}
+
+#[test]
+fn test_named_args_errors() {
+    assert_errors! {
+        r#"fn foo(a, b) {} pub fn main() { foo(b: 1, 2) }"#,
+        span!(42, 43), PositionalAfterNamedArgument
+    };
+
+    assert_errors! {
+        r#"fn foo(a, b) {} pub fn main() { foo(1, c: 2) }"#,
+        span!(39, 43), UnknownNamedArgument { name } => {
+            assert_eq!(name.as_ref(), "c");
+        }
+    };
+
+    assert_errors! {
+        r#"fn foo(a, b) {} pub fn main() { foo(1, a: 2) }"#,
+        span!(39, 43), DuplicateArgument { name } => {
+            assert_eq!(name.as_ref(), "a");
+        }
+    };
+
+    assert_errors! {
+        r#"pub fn main() { let f = |a| a; f(a: 1) }"#,
+        span!(33, 34), UnsupportedNamedArgument
+    };
+
+    assert_errors! {
+        r#"pub fn main() { dbg(value: 1) }"#,
+        span!(20, 25), UnsupportedNamedArgument
+    };
+}
@@ -2,6 +2,8 @@ prelude!();
 
 use std::sync::Arc;
 
+use VmErrorKind::*;
+
 #[test]
 fn test_external_ops_struct() -> Result<()> {
     /// Test case for a single operation.
@@ -175,3 +177,100 @@ fn test_external_ops_tuple() -> Result<()> {
     test_case!([%=], REM_ASSIGN, rem_assign, 25, 10, 5);
     Ok(())
 }
+
+#[test]
+fn test_external_unary_and_cmp() -> Result<()> {
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Any)]
+    #[rune(partial_ord)]
+    struct Vector {
+        #[rune(get)]
+        x: i64,
+        #[rune(get)]
+        y: i64,
+    }
+
+    impl std::ops::Neg for Vector {
+        type Output = Vector;
+
+        fn neg(self) -> Vector {
+            Vector {
+                x: -self.x,
+                y: -self.y,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, Any)]
+    struct Flag(bool);
+
+    let mut module = Module::new();
+    module.ty::<Vector>()?;
+    module.neg::<Vector>()?;
+    module.ty::<Flag>()?;
+    module.associated_function(Protocol::NOT, |flag: &Flag| !flag.0)?;
+    module.associated_function(Protocol::LT, |a: &Flag, b: &Flag| !a.0 && b.0)?;
+
+    let mut context = Context::with_default_modules()?;
+    context.install(module)?;
+
+    let mut sources = sources! {
+        entry => {
+            pub fn main(a, b, flag) {
+                let n = -a;
+                (n.x, n.y, a < b, a <= b, a > b, a >= b, !flag, flag < flag)
+            }
+        }
+    };
+
+    let unit = prepare(&mut sources).with_context(&context).build()?;
+    let mut vm = Vm::new(Arc::new(context.runtime()), Arc::new(unit));
+
+    let a = Vector { x: 1, y: 2 };
+    let b = Vector { x: 1, y: 3 };
+
+    let output: (i64, i64, bool, bool, bool, bool, bool, bool) =
+        from_value(vm.call(["main"], (a, b, Flag(true)))?)?;
+    assert_eq!(output, (-1, -2, true, true, false, false, false, false));
+    Ok(())
+}
+
+#[test]
+fn test_script_cmp_fallback() {
+    let out: (bool, bool, bool) = rune! {
+        use std::cmp::Ordering;
+
+        struct Version(major, minor);
+
+        impl Version {
+            fn partial_cmp(self, other) {
+                match self.0.partial_cmp(other.0) {
+                    Some(Ordering::Equal) => self.1.partial_cmp(other.1),
+                    ordering => ordering,
+                }
+            }
+        }
+
+        pub fn main() {
+            (Version(1, 2) < Version(1, 3), Version(2, 0) <= Version(1, 9), Version(2, 0) >= Version(2, 0))
+        }
+    };
+
+    assert_eq!(out, (true, false, true));
+}
+
+#[test]
+fn test_unsupported_unary() {
+    assert_vm_error!(
+        r#"pub fn main() { -"hello" }"#,
+        UnsupportedUnaryOperation { op, .. } => {
+            assert_eq!(op, "-");
+        }
+    );
+
+    assert_vm_error!(
+        r#"struct Foo; pub fn main() { Foo < Foo }"#,
+        UnsupportedBinaryOperation { op, .. } => {
+            assert_eq!(op, "<");
+        }
+    );
+}
@@ -0,0 +1,328 @@
+prelude!();
+
+use std::sync::Arc;
+
+use VmErrorKind::*;
+
+#[test]
+fn test_script_hash() {
+    let out: i64 = rune! {
+        use std::collections::{HashMap, HashSet};
+
+        struct Point { x, y }
+
+        impl Point {
+            fn hash(self, hasher) {
+                hasher.write(self.x);
+                hasher.write(self.y);
+            }
+        }
+
+        pub fn main() {
+            let set = HashSet::new();
+            set.insert(Point { x: 1, y: 2 });
+            set.insert(Point { x: 1, y: 2 });
+            set.insert(Point { x: 2, y: 1 });
+            assert!(set.contains(Point { x: 2, y: 1 }));
+            assert!(!set.contains(Point { x: 3, y: 3 }));
+
+            let map = HashMap::new();
+            map.insert(Point { x: 1, y: 2 }, 10);
+            map.insert(Point { x: 1, y: 2 }, 20);
+            map[Point { x: 1, y: 2 }] + set.len()
+        }
+    };
+
+    assert_eq!(out, 22);
+}
+
+#[test]
+fn test_script_hash_trait() {
+    let out: bool = rune! {
+        use std::collections::HashSet;
+        use std::hash::Hash;
+
+        struct Id(n);
+
+        impl Hash for Id {
+            fn hash(self, hasher) {
+                hasher.write(self.0);
+            }
+        }
+
+        pub fn main() {
+            let set = HashSet::new();
+            set.insert(Id(1));
+            set.contains(Id(1))
+        }
+    };
+
+    assert!(out);
+}
+
+#[test]
+fn test_script_partial_hash() {
+    let out: (i64, bool, bool) = rune! {
+        use std::collections::HashSet;
+
+        struct Point { x, y }
+
+        impl Point {
+            fn hash(self, hasher) {
+                hasher.write(self.x);
+            }
+        }
+
+        pub fn main() {
+            let set = HashSet::new();
+            set.insert(Point { x: 1, y: 2 });
+            set.insert(Point { x: 1, y: 3 });
+            set.insert(Point { x: 1, y: 2 });
+            (set.len(), set.contains(Point { x: 1, y: 3 }), set.contains(Point { x: 1, y: 4 }))
+        }
+    };
+
+    assert_eq!(out, (2, true, false));
+}
+
+#[test]
+fn test_script_key_mutated_after_insertion() {
+    let out: (bool, bool, i64) = rune! {
+        use std::collections::HashMap;
+
+        struct Point { x, y }
+
+        impl Point {
+            fn hash(self, hasher) {
+                hasher.write(self.x);
+                hasher.write(self.y);
+            }
+        }
+
+        pub fn main() {
+            let map = HashMap::new();
+            let point = Point { x: 1, y: 2 };
+            map.insert(point, 10);
+            point.x = 3;
+            (map.contains_key(Point { x: 1, y: 2 }), map.contains_key(point), map[Point { x: 1, y: 2 }])
+        }
+    };
+
+    assert_eq!(out, (true, false, 10));
+}
+
+#[test]
+fn test_missing_hash() {
+    assert_vm_error!(
+        r#"
+        use std::collections::HashSet;
+        struct Point { x, y }
+        pub fn main() { HashSet::new().insert(Point { x: 1, y: 2 }) }
+        "#,
+        KeyNotSupported { actual } => {
+            assert_eq!(actual.to_string(), "Point");
+        }
+    );
+}
+
+#[test]
+fn test_unsupported_struct_field() {
+    assert_vm_error!(
+        r#"
+        use std::collections::HashSet;
+        struct Callback { f }
+        pub fn main() { HashSet::new().insert(Callback { f: || 42 }) }
+        "#,
+        KeyNotSupported { actual } => {
+            assert_eq!(actual.to_string(), "Function");
+        }
+    );
+}
+
+#[test]
+fn test_script_cmp() {
+    let out: Vec<i64> = rune! {
+        use std::cmp::{Ord, Ordering};
+
+        struct Version(major, minor);
+
+        impl Ord for Version {
+            fn cmp(self, other) {
+                match self.0.cmp(other.0) {
+                    Ordering::Equal => self.1.cmp(other.1),
+                    ordering => ordering,
+                }
+            }
+        }
+
+        pub fn main() {
+            let versions = [Version(2, 0), Version(1, 3), Version(1, 1)];
+            versions.sort();
+            versions.iter().map(|v| v.0 * 10 + v.1).collect::<Vec>()
+        }
+    };
+
+    assert_eq!(out, [11, 13, 20]);
+}
+
+#[test]
+fn test_sort_primitives() {
+    let out: Vec<(i64, String)> = rune! {
+        pub fn main() {
+            let values = [(2, "b"), (1, "z"), (2, "a")];
+            values.sort();
+            values
+        }
+    };
+
+    assert_eq!(
+        out,
+        [
+            (1, String::from("z")),
+            (2, String::from("a")),
+            (2, String::from("b"))
+        ]
+    );
+}
+
+#[test]
+fn test_derive_hash_ord() -> Result<()> {
+    #[derive(Any, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[rune(partial_eq, hash, partial_ord, ord)]
+    struct Native {
+        value: i64,
+    }
+
+    let mut m = Module::new();
+    m.ty::<Native>()?;
+    m.function(["Native", "new"], |value: i64| Native { value })?;
+    m.field_function(Protocol::GET, "value", |this: &Native| this.value)?;
+
+    let mut context = Context::with_default_modules()?;
+    context.install(m)?;
+
+    let mut sources = sources! {
+        entry => {
+            use std::collections::HashSet;
+
+            pub fn main() {
+                let set = HashSet::new();
+                set.insert(Native::new(3));
+                set.insert(Native::new(1));
+                set.insert(Native::new(3));
+
+                let values = set.iter().collect::<Vec>();
+                values.sort();
+                values.iter().map(|v| v.value).collect::<Vec>()
+            }
+        }
+    };
+
+    let unit = prepare(&mut sources).with_context(&context).build()?;
+    let mut vm = Vm::new(Arc::new(context.runtime()), Arc::new(unit));
+
+    let output: Vec<i64> = from_value(vm.call(["main"], ())?)?;
+    assert_eq!(output, [1, 3]);
+
+    let a = rune::to_value(Native { value: 1 })?;
+    let b = rune::to_value(Native { value: 2 })?;
+
+    let ordering = vm.with(|| a.partial_cmp(&b)).into_result()?;
+    assert_eq!(ordering, Some(std::cmp::Ordering::Less));
+    Ok(())
+}
+
+#[test]
+fn test_native_key_requires_cmp() -> Result<()> {
+    #[derive(Any, Clone, Copy, PartialEq, Eq, Hash)]
+    #[rune(partial_eq, hash)]
+    struct Native {
+        value: i64,
+    }
+
+    let mut m = Module::new();
+    m.ty::<Native>()?;
+    m.function(["Native", "new"], |value: i64| Native { value })?;
+
+    let mut context = Context::with_default_modules()?;
+    context.install(m)?;
+
+    let mut sources = sources! {
+        entry => {
+            use std::collections::HashSet;
+
+            pub fn main() {
+                let set = HashSet::new();
+                set.insert(Native::new(1));
+            }
+        }
+    };
+
+    let unit = prepare(&mut sources).with_context(&context).build()?;
+    let mut vm = Vm::new(Arc::new(context.runtime()), Arc::new(unit));
+
+    let error = vm.call(["main"], ()).unwrap_err();
+
+    match error.into_kind() {
+        KeyNotSupported { actual } => assert_eq!(actual.to_string(), "Native"),
+        actual => panic!("Expected KeyNotSupported, got {:?}", actual),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_native_key_mutated_after_insertion() -> Result<()> {
+    #[derive(Any, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[rune(partial_eq, hash, partial_ord, ord)]
+    struct Native {
+        value: i64,
+    }
+
+    let mut m = Module::new();
+    m.ty::<Native>()?;
+    m.function(["Native", "new"], |value: i64| Native { value })?;
+    m.associated_function("set", |this: &mut Native, value: i64| {
+        this.value = value;
+    })?;
+
+    let mut context = Context::with_default_modules()?;
+    context.install(m)?;
+
+    let mut sources = sources! {
+        entry => {
+            use std::collections::HashSet;
+
+            pub fn new_set() {
+                HashSet::new()
+            }
+
+            pub fn insert(set, native) {
+                set.insert(native);
+            }
+
+            pub fn remove(set, native) {
+                set.remove(native);
+            }
+
+            pub fn set(native, value) {
+                native.set(value);
+            }
+        }
+    };
+
+    let unit = prepare(&mut sources).with_context(&context).build()?;
+    let mut vm = Vm::new(Arc::new(context.runtime()), Arc::new(unit));
+
+    let set = vm.call(["new_set"], ())?;
+    let native = rune::to_value(Native { value: 1 })?;
+
+    vm.call(["insert"], (&set, &native))?;
+
+    let error = vm.call(["set"], (&native, 2i64)).unwrap_err();
+    assert!(matches!(error.into_kind(), AccessError { .. }));
+
+    vm.call(["remove"], (&set, &native))?;
+    vm.call(["set"], (&native, 2i64))?;
+    Ok(())
+}
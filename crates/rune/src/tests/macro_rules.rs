@@ -0,0 +1,323 @@
+prelude!();
+
+use CompileErrorKind::*;
+
+#[test]
+fn test_macro_rules() {
+    let out: (i64, i64, i64) = rune_s! { r#"
+        macro_rules! add {
+            ($a:expr) => { $a };
+            ($a:expr, $($rest:expr),+) => { $a + add!($($rest),+) };
+        }
+
+        macro_rules! square {
+            ($e:expr) => { $e * $e };
+        }
+
+        macro_rules! count {
+            () => { 0 };
+            ($head:tt $($tail:tt)*) => { 1 + count!($($tail)*) };
+        }
+
+        pub fn main() {
+            (add!(1, 2, 3), square!(1 + 2), count!(a (b c) [d]))
+        }
+    "# };
+
+    assert_eq!(out, (6, 9, 3));
+}
+
+#[test]
+fn test_macro_rules_repetitions() {
+    let out: (Vec<i64>, Vec<(String, i64)>) = rune_s! { r#"
+        macro_rules! vec_of {
+            ($($e:expr),* $(,)?) => {{
+                let out = [];
+                $(out.push($e);)*
+                out
+            }};
+        }
+
+        macro_rules! pairs {
+            ($($key:ident => [$($value:expr),*]);*) => {
+                [$($((stringify!($key), $value)),*),*]
+            };
+        }
+
+        pub fn main() {
+            (vec_of![1, 2, 3,], pairs!(a => [1, 2]; c => [3]))
+        }
+    "# };
+
+    assert_eq!(out.0, vec![1, 2, 3]);
+    assert_eq!(
+        out.1,
+        vec![
+            (String::from("a"), 1),
+            (String::from("a"), 2),
+            (String::from("c"), 3)
+        ]
+    );
+}
+
+#[test]
+fn test_macro_rules_items() {
+    let out: (i64, i64, i64) = rune_s! { r#"
+        macro_rules! constant_fn {
+            ($name:ident, $value:expr) => {
+                fn $name() { $value }
+            };
+        }
+
+        constant_fn!(forty_two, 42);
+
+        mod a {
+            pub fn twice(x) { double!(x) }
+
+            pub mod b {
+                pub fn nested(x) { double!(x) + 1 }
+            }
+        }
+
+        macro_rules! double {
+            ($e:expr) => { $e * 2 };
+        }
+
+        pub fn main() {
+            (forty_two(), a::twice(2), a::b::nested(2))
+        }
+    "# };
+
+    assert_eq!(out, (42, 4, 5));
+}
+
+#[test]
+fn test_macro_rules_hygiene() {
+    let out: (i64, i64, i64) = rune_s! { r#"
+        macro_rules! swap {
+            ($a:ident, $b:ident) => {{
+                let tmp = $a;
+                $a = $b;
+                $b = tmp;
+            }};
+        }
+
+        macro_rules! with_tmp {
+            ($e:expr) => {{
+                let tmp = 10;
+                tmp + $e
+            }};
+        }
+
+        pub fn main() {
+            let tmp = 1;
+            let other = 2;
+            swap!(tmp, other);
+            (tmp, other, with_tmp!(tmp))
+        }
+    "# };
+
+    assert_eq!(out, (2, 1, 12));
+}
+
+#[test]
+fn test_macro_rules_hygiene_annotated_let() {
+    let out: i64 = rune_s! { r#"
+        macro_rules! with_tmp {
+            ($e:expr) => {{
+                let tmp: int = 10;
+                tmp + $e
+            }};
+        }
+
+        pub fn main() {
+            let tmp = 1;
+            with_tmp!(tmp)
+        }
+    "# };
+
+    assert_eq!(out, 11);
+}
+
+#[test]
+fn test_macro_rules_hygiene_closure() {
+    let out: (i64, i64) = rune_s! { r#"
+        macro_rules! apply {
+            ($e:expr) => {{
+                let f = |tmp| tmp + $e;
+                let g = |n, tmp: int| n * tmp + $e;
+                (f(10), g(2, 10))
+            }};
+        }
+
+        pub fn main() {
+            let tmp = 1;
+            apply!(tmp)
+        }
+    "# };
+
+    assert_eq!(out, (11, 21));
+}
+
+#[test]
+fn test_macro_rules_hygiene_match() {
+    let out: (i64, i64) = rune_s! { r#"
+        macro_rules! unwrap_or_tmp {
+            ($value:expr, $e:expr) => {
+                match $value {
+                    Some(tmp) if tmp > 0 => { tmp + $e }
+                    Some((tmp, _)) => tmp,
+                    None => $e,
+                }
+            };
+        }
+
+        pub fn main() {
+            let tmp = 1;
+            (unwrap_or_tmp!(Some(10), tmp), unwrap_or_tmp!(None, tmp))
+        }
+    "# };
+
+    assert_eq!(out, (11, 1));
+}
+
+#[test]
+fn test_macro_rules_hygiene_struct_pattern() {
+    let out: (i64, i64, i64) = rune_s! { r#"
+        struct Foo { x, y }
+
+        macro_rules! sum_foo {
+            ($foo:expr, $e:expr) => {{
+                let Foo { x: X, y } = $foo;
+                X + y + $e
+            }};
+        }
+
+        pub fn main() {
+            let X = 100;
+            let y = 1000;
+            (sum_foo!(Foo { x: 1, y: 2 }, X), sum_foo!(Foo { x: 1, y: 2 }, y), X + y)
+        }
+    "# };
+
+    assert_eq!(out, (103, 1003, 1100));
+}
+
+#[test]
+fn test_macro_rules_hygiene_object_pattern() {
+    let out: (i64, i64, i64) = rune_s! { r#"
+        macro_rules! digits {
+            ($object:expr, $e:expr) => {{
+                let #{a, b} = $object;
+                a * 10 + b + $e
+            }};
+        }
+
+        pub fn main() {
+            let a = 100;
+            let b = 1000;
+            (digits!(#{a: 1, b: 2}, a), digits!(#{a: 1, b: 2}, b), a + b)
+        }
+    "# };
+
+    assert_eq!(out, (112, 1012, 1100));
+}
+
+#[test]
+fn test_macro_rules_hygiene_object_shorthand() {
+    let out: (i64, i64) = rune_s! { r#"
+        macro_rules! wrap {
+            ($e:expr) => {{
+                let value = $e;
+                #{value}
+            }};
+        }
+
+        pub fn main() {
+            let value = 2;
+            let object = wrap!(value + 1);
+            (object.value, value)
+        }
+    "# };
+
+    assert_eq!(out, (3, 2));
+}
+
+#[test]
+fn test_macro_rules_errors() {
+    assert_errors! {
+        r#"macro_rules! one { ($a:ident) => { $a }; } pub fn main() { one!(1) }"#,
+        span!(59, 66),
+        NoMatchingMacroRule { item } => {
+            assert_eq!(item.to_string(), "one");
+        }
+    };
+
+    assert_errors! {
+        r#"macro_rules! one { ($a:expr) => { $a + missing }; } pub fn main() { one!(1) }"#,
+        span!(39, 46),
+        MissingLocal { name } => {
+            assert_eq!(name, "missing");
+        }
+    };
+
+    assert_errors! {
+        r#"macro_rules! one { () => {{ for tmp in [] {} tmp }}; } pub fn main() { one!() }"#,
+        span!(71, 77),
+        MissingLocal { name } => {
+            assert!(name.starts_with("tmp#"));
+        }
+    };
+
+    assert_errors! {
+        r#"macro_rules! one { ($a:expr) => { $b }; } pub fn main() { one!(1) }"#,
+        span!(34, 36),
+        MissingMacroVariable { name } => {
+            assert_eq!(&*name, "b");
+        }
+    };
+
+    assert_errors! {
+        r#"macro_rules! one { ($a:float) => { $a }; }"#,
+        span!(23, 28),
+        UnsupportedFragmentSpecifier { name } => {
+            assert_eq!(&*name, "float");
+        }
+    };
+
+    assert_errors! {
+        r#"macro_rules! one { ($a:expr, $a:expr) => { $a }; }"#,
+        span!(29, 36),
+        DuplicateMacroVariable { name } => {
+            assert_eq!(&*name, "a");
+        }
+    };
+
+    assert_errors! {
+        r#"macro_rules! one { ($($a:expr),*) => { $a }; } pub fn main() { one!(1) }"#,
+        span!(39, 41),
+        MacroVariableStillRepeating { name } => {
+            assert_eq!(&*name, "a");
+        }
+    };
+
+    assert_errors! {
+        r#"macro_rules! one { () => {} } macro_rules! one { () => {} }"#,
+        span!(43, 46),
+        MacroConflict { item } => {
+            assert_eq!(item.to_string(), "one");
+        }
+    };
+
+    assert_errors! {
+        r#"pub fn main() { macro_rules! one { () => {} } }"#,
+        span!(29, 32),
+        UnsupportedMacroDefinition
+    };
+
+    assert_errors! {
+        r#"macro_rules! { () => {} }"#,
+        span!(0, 11),
+        MissingMacroName
+    };
+}
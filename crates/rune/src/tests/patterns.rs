@@ -259,3 +259,191 @@ fn test_bad_pattern() {
         }
     );
 }
+
+#[test]
+fn test_or_patterns() {
+    let out: i64 = rune! {
+        fn classify(n) {
+            match n {
+                1 | 2 | 3 => 1,
+                4 | 5 => 2,
+                _ => 3,
+            }
+        }
+
+        pub fn main() {
+            classify(2) * 100 + classify(5) * 10 + classify(6)
+        }
+    };
+    assert_eq!(out, 123);
+
+    let out: i64 = rune! {
+        enum Shape { Circle(r), Square(s), Empty }
+
+        fn size(shape) {
+            match shape {
+                Shape::Circle(n) | Shape::Square(n) => n,
+                Shape::Empty => 0,
+            }
+        }
+
+        pub fn main() {
+            size(Shape::Circle(4)) + size(Shape::Square(5)) + size(Shape::Empty)
+        }
+    };
+    assert_eq!(out, 9);
+
+    let out: i64 = rune! {
+        pub fn main() {
+            let total = 0;
+
+            for value in [(1, 10), (2, 20), (3, 30)] {
+                total += match value {
+                    (1 | 3, n) => n,
+                    (_, n) => -n,
+                };
+            }
+
+            total
+        }
+    };
+    assert_eq!(out, 20);
+
+    let out: bool = rune! {
+        pub fn main() {
+            if let Some(1 | 2) = Some(2) { true } else { false }
+        }
+    };
+    assert_eq!(out, true);
+}
+
+#[test]
+fn test_range_patterns() {
+    let out: i64 = rune! {
+        fn classify(n) {
+            match n {
+                ..=-1 => 0,
+                0..10 => 1,
+                10..=99 => 2,
+                100.. => 3,
+            }
+        }
+
+        pub fn main() {
+            classify(-5) * 1000 + classify(9) * 100 + classify(99) * 10 + classify(100)
+        }
+    };
+    assert_eq!(out, 123);
+
+    let out: i64 = rune! {
+        pub fn main() {
+            let count = 0;
+
+            for c in "Hello, World 42!".chars() {
+                match c {
+                    'a'..='z' | 'A'..='Z' => count += 1,
+                    '0'..='9' => count += 100,
+                    _ => (),
+                }
+            }
+
+            count
+        }
+    };
+    assert_eq!(out, 210);
+
+    let out: bool = rune! {
+        pub fn main() {
+            match b'7' {
+                b'0'..=b'9' => true,
+                _ => false,
+            }
+        }
+    };
+    assert_eq!(out, true);
+
+    let out: bool = rune! {
+        pub fn main() {
+            match "5" {
+                0..=9 => true,
+                _ => false,
+            }
+        }
+    };
+    assert_eq!(out, false);
+}
+
+#[test]
+fn test_at_patterns() {
+    let out: i64 = rune! {
+        pub fn main() {
+            match 42 {
+                n @ 0..=9 => n,
+                n @ 10..=50 => n * 2,
+                _ => 0,
+            }
+        }
+    };
+    assert_eq!(out, 84);
+
+    let out: i64 = rune! {
+        pub fn main() {
+            match Some((1, 2)) {
+                Some(pair @ (a, _)) => pair.1 * 10 + a,
+                None => 0,
+            }
+        }
+    };
+    assert_eq!(out, 21);
+}
+
+#[test]
+fn test_bad_or_and_range_patterns() {
+    assert_errors! {
+        r#"pub fn main() { match 1 { (a, 1) | (1, b) => 0 } }"#,
+        span!(35, 41), CompileErrorKind::PatternMissingBinding { name } => {
+            assert_eq!(&*name, "a");
+        }
+    };
+
+    assert_errors! {
+        r#"pub fn main() { match 1 { 5..=1 => 0 } }"#,
+        span!(26, 31), CompileErrorKind::EmptyRangePattern
+    };
+
+    assert_errors! {
+        r#"pub fn main() { match 1 { 1..='a' => 0 } }"#,
+        span!(30, 33), CompileErrorKind::UnsupportedPatternExpr
+    };
+}
+
+#[test]
+fn test_exhaustive_match() {
+    let out: i64 = rune! {
+        enum Foo { A, B(a), C { c } }
+
+        fn value(foo) {
+            match foo {
+                Foo::A => 1,
+                Foo::B(n) if n > 10 => n,
+                Foo::B(..) | Foo::C { .. } => 3,
+            }
+        }
+
+        pub fn main() {
+            value(Foo::A) + value(Foo::B(20)) + value(Foo::B(1)) + value(Foo::C { c: 1 })
+        }
+    };
+
+    assert_eq!(out, 27);
+
+    let out: i64 = rune! {
+        pub fn main() {
+            let a = match 2 { n => n };
+            let b = match (1, true) { (_, true) => 10, (_, false) => 20 };
+            a + b
+        }
+    };
+
+    assert_eq!(out, 12);
+}
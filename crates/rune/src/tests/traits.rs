@@ -0,0 +1,269 @@
+prelude!();
+
+use std::sync::Arc;
+
+use crate::compile::ItemBuf;
+use crate::parse::Expectation;
+
+use CompileErrorKind::*;
+
+#[test]
+fn test_default_functions() {
+    let out: i64 = rune! {
+        trait Shape {
+            fn area(self);
+
+            fn describe(self) {
+                format!("area: {}", self.area())
+            }
+
+            fn double_area(self) {
+                self.area() * 2
+            }
+        }
+
+        struct Square { side }
+        struct Rect { w, h }
+
+        impl Shape for Square {
+            fn area(self) {
+                self.side * self.side
+            }
+        }
+
+        impl Shape for Rect {
+            fn area(self) {
+                self.w * self.h
+            }
+
+            fn double_area(self) {
+                0
+            }
+        }
+
+        pub fn main() {
+            let shapes = [Square { side: 3 }, Rect { w: 2, h: 5 }];
+            let total = 0;
+
+            for shape in shapes {
+                total += shape.double_area();
+            }
+
+            assert_eq!(Square { side: 2 }.describe(), "area: 4");
+            total
+        }
+    };
+
+    assert_eq!(out, 18);
+}
+
+#[test]
+fn test_trait_in_module() {
+    let out: i64 = rune! {
+        mod shapes {
+            pub trait Shape {
+                fn area(self);
+
+                fn scaled(self, n) {
+                    let f = |v| v * n;
+                    f(self.area())
+                }
+            }
+        }
+
+        enum Kind { Small, Large }
+
+        impl shapes::Shape for Kind {
+            fn area(self) {
+                match self {
+                    Kind::Small => 1,
+                    Kind::Large => 10,
+                }
+            }
+        }
+
+        pub fn main() {
+            Kind::Small.scaled(3) + Kind::Large.scaled(2)
+        }
+    };
+
+    assert_eq!(out, 23);
+}
+
+#[test]
+fn test_trait_errors() {
+    assert_errors! {
+        r#"trait Foo { fn a(self); fn b(self); } struct Bar; impl Foo for Bar { fn a(self) {} }"#,
+        span!(55, 58), TraitFunctionMissing { name, .. } => {
+            assert_eq!(&*name, "b");
+        }
+    };
+
+    assert_errors! {
+        r#"trait Foo { fn a(self); } struct Bar; impl Foo for Bar { fn a(self) {} fn c(self) {} }"#,
+        span!(71, 81), TraitFunctionNotMember { name, .. } => {
+            assert_eq!(&*name, "c");
+        }
+    };
+
+    assert_errors! {
+        r#"trait Foo { fn a(self); fn a(self) {} }"#,
+        span!(24, 34), TraitFunctionConflict { name } => {
+            assert_eq!(&*name, "a");
+        }
+    };
+
+    assert_errors! {
+        r#"struct Foo; struct Bar; impl Foo for Bar {}"#,
+        span!(29, 32), ExpectedMeta { .. }
+    };
+}
+
+#[test]
+fn test_native_protocol() -> Result<()> {
+    #[derive(Any)]
+    #[rune(item = ::serde)]
+    struct Native {
+        value: i64,
+    }
+
+    let serde = ItemBuf::with_crate_item("serde", ["Serialize"]);
+    let serialize = Protocol::custom(&serde, "serialize");
+
+    let mut m = Module::with_crate("serde");
+    m.protocol(["Serialize"], [serialize])?;
+    m.ty::<Native>()?;
+    m.associated_function(serialize, |this: &Native| this.value.to_string())?;
+
+    let mut context = Context::with_default_modules()?;
+    context.install(m)?;
+
+    let mut sources = sources! {
+        entry => {
+            use serde::Serialize;
+
+            struct Point { x, y }
+
+            impl Serialize for Point {
+                fn serialize(self) {
+                    format!("{},{}", self.x, self.y)
+                }
+            }
+
+            pub fn main(native) {
+                [Point { x: 1, y: 2 }, native]
+            }
+        }
+    };
+
+    let unit = prepare(&mut sources).with_context(&context).build()?;
+    let mut vm = Vm::new(Arc::new(context.runtime()), Arc::new(unit));
+
+    let values: Vec<Value> = from_value(vm.call(["main"], (Native { value: 42 },))?)?;
+    let mut output = Vec::new();
+
+    for value in values {
+        let value: String = from_value(vm.call_protocol(serialize, value, ())?)?;
+        output.push(value);
+    }
+
+    assert_eq!(output, ["1,2", "42"]);
+
+    let error = vm
+        .call_protocol(serialize, Value::from(1i64), ())
+        .unwrap_err();
+    assert!(matches!(
+        error.into_kind(),
+        VmErrorKind::MissingInstanceFunction { .. }
+    ));
+
+    let mut sources = sources! {
+        entry => {
+            struct Point;
+            impl serde::Serialize for Point {}
+        }
+    };
+
+    let mut diagnostics = Diagnostics::new();
+
+    let _ = prepare(&mut sources)
+        .with_context(&context)
+        .with_diagnostics(&mut diagnostics)
+        .build()
+        .unwrap_err();
+
+    assert_eq!(diagnostics.diagnostics().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_protocols_are_scoped_to_their_trait() -> Result<()> {
+    let json = Protocol::custom(&ItemBuf::with_crate_item("json", ["Encode"]), "encode");
+    let text = Protocol::custom(&ItemBuf::with_crate_item("text", ["Encode"]), "encode");
+    assert_ne!(json.hash, text.hash);
+
+    let mut context = Context::with_default_modules()?;
+
+    let mut m = Module::with_crate("json");
+    m.protocol(["Encode"], [json])?;
+    context.install(m)?;
+
+    let mut m = Module::with_crate("text");
+    m.protocol(["Encode"], [text])?;
+    context.install(m)?;
+
+    let mut sources = sources! {
+        entry => {
+            struct Point { x, y }
+
+            impl json::Encode for Point {
+                fn encode(self) {
+                    format!("[{},{}]", self.x, self.y)
+                }
+            }
+
+            pub fn main() {
+                Point { x: 1, y: 2 }
+            }
+        }
+    };
+
+    let unit = prepare(&mut sources).with_context(&context).build()?;
+    let mut vm = Vm::new(Arc::new(context.runtime()), Arc::new(unit));
+
+    let point = vm.call(["main"], ())?;
+    let output: String = from_value(vm.call_protocol(json, point.clone(), ())?)?;
+    assert_eq!(output, "[1,2]");
+
+    let error = vm.call_protocol(text, point, ()).unwrap_err();
+    assert!(matches!(
+        error.into_kind(),
+        VmErrorKind::MissingInstanceFunction { .. }
+    ));
+
+    let mut m = Module::with_crate("yaml");
+    m.protocol(["Encode"], [json])?;
+
+    let mut context = Context::new();
+    let error = context.install(m).unwrap_err();
+
+    assert!(matches!(
+        error,
+        ContextError::ForeignProtocolFunction { .. }
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_trait_is_reserved() {
+    assert_errors! {
+        r#"pub fn main() { let trait = 1; }"#,
+        span!(20, 25), Expected { actual: Expectation::Keyword("trait"), .. }
+    };
+
+    assert_errors! {
+        r#"struct Foo { trait }"#,
+        span!(13, 18), Expected { actual: Expectation::Keyword("trait"), .. }
+    };
+}
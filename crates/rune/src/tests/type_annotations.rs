@@ -0,0 +1,147 @@
+prelude!();
+
+use CompileErrorKind::*;
+
+#[test]
+fn test_annotated() {
+    let out: i64 = rune! {
+        struct Point { x: int, y: int }
+
+        fn add(a: int, b: int) -> int {
+            a + b
+        }
+
+        fn origin() -> Point {
+            Point { x: 0, y: 0 }
+        }
+
+        pub fn main() {
+            let p: Point = origin();
+            let sum: int = add(p.x, 10);
+            let pair: (int, String) = (sum, "hello");
+            let unit: () = ();
+            let f = |n: int| n * 2;
+            f(pair.0)
+        }
+    };
+
+    assert_eq!(out, 20);
+}
+
+#[test]
+fn test_unannotated() {
+    let out: i64 = rune! {
+        struct Point { x, y }
+
+        fn add(a, b) {
+            a + b
+        }
+
+        pub fn main() {
+            let p = Point { x: "a", y: 1 };
+            let n = add(p.y, 2);
+            n = "now a string";
+            add(1, 2)
+        }
+    };
+
+    assert_eq!(out, 3);
+}
+
+#[test]
+fn test_type_mismatch() {
+    assert_errors! {
+        r#"pub fn main() { let a: int = "hello"; }"#,
+        span!(29, 36), TypeMismatch { expected, actual } => {
+            assert_eq!(expected.to_string(), "::std::int");
+            assert_eq!(actual.to_string(), "::std::string::String");
+        }
+    };
+
+    assert_errors! {
+        r#"fn foo(a: int) {} pub fn main() { foo(1.0) }"#,
+        span!(38, 41), TypeMismatch { .. }
+    };
+
+    assert_errors! {
+        r#"fn foo() -> int { "hello" } pub fn main() { foo() }"#,
+        span!(18, 25), TypeMismatch { .. }
+    };
+
+    assert_errors! {
+        r#"fn foo() -> int { return true; } pub fn main() { foo() }"#,
+        span!(25, 29), TypeMismatch { .. }
+    };
+
+    assert_errors! {
+        r#"fn foo() -> int { return; } pub fn main() { foo() }"#,
+        span!(18, 24), TypeMismatch { .. }
+    };
+
+    assert_errors! {
+        r#"struct Foo { a: int } pub fn main() { Foo { a: 'a' } }"#,
+        span!(47, 50), TypeMismatch { .. }
+    };
+
+    assert_errors! {
+        r#"pub fn main() { let a: int = 1; a = "hello"; }"#,
+        span!(36, 43), TypeMismatch { .. }
+    };
+
+    assert_errors! {
+        r#"fn foo() -> String { "a" } pub fn main() { let a: int = foo(); }"#,
+        span!(56, 61), TypeMismatch { .. }
+    };
+}
+
+#[test]
+fn test_unknown_type() {
+    assert_errors! {
+        r#"pub fn main() { let a: Missing = 1; }"#,
+        span!(23, 30), MissingItemParameters { .. }
+    };
+
+    assert_errors! {
+        r#"fn foo() {} pub fn main() { let a: foo = 1; }"#,
+        span!(35, 38), ExpectedMeta { .. }
+    };
+}
+
+#[test]
+fn test_binding_form_is_annotation() {
+    // NB: `ident: pat` used to parse as an object binding outside of object
+    // patterns, it's now a type annotation.
+    assert_errors! {
+        r#"pub fn main() { let a: b = 1; }"#,
+        span!(23, 24), MissingItemParameters { .. }
+    };
+
+    assert_errors! {
+        r#"fn foo(a: b) {} pub fn main() { foo(1) }"#,
+        span!(10, 11), MissingItemParameters { .. }
+    };
+
+    assert_errors! {
+        r#"pub fn main() { let f = |a: b| a; f(1) }"#,
+        span!(28, 29), MissingItemParameters { .. }
+    };
+}
+
+#[test]
+fn test_native_return_types_are_not_checked() {
+    let mut m = Module::with_crate("native");
+    m.function(["value"], || VmResult::Ok(42i64)).unwrap();
+    m.function(["maybe"], || Some(42i64)).unwrap();
+
+    let out: i64 = rune_n! {
+        m,
+        (),
+        i64 => pub fn main() {
+            let a: int = native::value();
+            let b: Option = native::maybe();
+            a + b.unwrap()
+        }
+    };
+
+    assert_eq!(out, 84);
+}
@@ -75,3 +75,76 @@ fn test_assign_assign_exprs() {
     };
     assert_eq!(out, (4, (), ()));
 }
+
+#[test]
+fn test_assign_binary_op() {
+    let out: i64 = rune! {
+        pub fn main() {
+            let a = 1;
+            let b = 2;
+            let c = 0;
+            c = a + b;
+            a = a * 10 + c;
+            b = b - a;
+            a + b + c
+        }
+    };
+
+    assert_eq!(out, 13 + -11 + 3);
+}
+
+#[test]
+fn test_assign_binary_op_protocol() {
+    let out: String = rune! {
+        pub fn main() {
+            let s = "foo";
+            let t = "bar";
+            s = s + t;
+            s = s + s;
+            s
+        }
+    };
+
+    assert_eq!(out, "foobarfoobar");
+}
+
+#[test]
+fn test_assign_op_variable_operand() {
+    let out: i64 = rune! {
+        struct Foo { a, b }
+
+        pub fn main() {
+            let step = 3;
+            let n = 1;
+            let foo = Foo { a: 1, b: (1, 2) };
+            n += step;
+            n *= n;
+            foo.a += step;
+            foo.b.1 -= step;
+            n + foo.a + foo.b.1
+        }
+    };
+
+    assert_eq!(out, 16 + 4 + -1);
+}
+
+#[test]
+fn test_assign_binary_op_comparisons() {
+    let out: (bool, bool, bool, bool) = rune! {
+        pub fn main() {
+            let a = 1;
+            let b = 2.5;
+            let c = false;
+            let d = false;
+            let e = false;
+            let f = true;
+            c = a < 2;
+            d = b >= 2.5;
+            e = a != 2;
+            f = c && !d;
+            (c, d, e, f)
+        }
+    };
+
+    assert_eq!(out, (true, true, true, false));
+}
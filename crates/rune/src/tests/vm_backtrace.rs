@@ -0,0 +1,106 @@
+prelude!();
+
+use crate::compile::Options;
+use crate::runtime::{VmError, VmResult};
+use crate::termcolor::Buffer;
+use crate::tests::{sources, vm, vm_with_options};
+use crate::Hash;
+
+const SOURCE: &str = r#"fn inner(n) {
+    let values = [n];
+    values.iter().map(|v| v / 0).collect::<Vec>()
+}
+
+fn outer(n) {
+    inner(n + 1)
+}
+
+pub fn main() {
+    outer(1)
+}
+"#;
+
+fn call(options: &Options) -> (Sources, VmError) {
+    let context = Context::with_default_modules().unwrap();
+    let mut sources = sources(SOURCE);
+    let mut vm = vm_with_options(&context, &mut sources, &mut Diagnostics::new(), options).unwrap();
+    let error = vm.call(["main"], ()).unwrap_err();
+    (sources, error)
+}
+
+#[test]
+fn test_backtrace_frames() {
+    let (sources, error) = call(&Options::default());
+    let backtrace = error.backtrace();
+
+    let frames = backtrace
+        .iter()
+        .map(|frame| {
+            let signature = frame.signature.as_ref().map(|s| s.to_string());
+            (signature, frame.native, frame.line_column(&sources))
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(frames.len(), 5, "{frames:?}");
+
+    // The closure, where the division happens.
+    assert!(matches!(&frames[0].0, Some(s) if s.ends_with("(v)")));
+    assert!(!frames[0].1);
+    assert_eq!(frames[0].2, Some((2, 26)));
+
+    // The native `collect` function calling the closure.
+    assert_eq!(frames[1], (None, true, None));
+    assert!(backtrace[1].hash.is_some());
+
+    assert_eq!(
+        frames[2],
+        (Some(String::from("inner(n)")), false, Some((2, 4)))
+    );
+    assert_eq!(
+        frames[3],
+        (Some(String::from("outer(n)")), false, Some((6, 4)))
+    );
+    assert_eq!(
+        frames[4],
+        (Some(String::from("main()")), false, Some((10, 4)))
+    );
+}
+
+#[test]
+fn test_backtrace_native_error() {
+    let mut module = Module::new();
+    module
+        .function(["fail"], || VmResult::<()>::panic("failed"))
+        .unwrap();
+
+    let mut context = Context::with_default_modules().unwrap();
+    context.install(module).unwrap();
+
+    let mut sources = sources("pub fn main() {\n    fail()\n}\n");
+    let mut vm = vm(&context, &mut sources, &mut Diagnostics::new()).unwrap();
+    let error = vm.call(["main"], ()).unwrap_err();
+    let backtrace = error.backtrace();
+
+    assert_eq!(backtrace.len(), 2);
+    assert!(backtrace[0].native);
+    assert_eq!(backtrace[0].hash, Some(Hash::type_hash(["fail"])));
+    assert!(!backtrace[1].native);
+    assert_eq!(backtrace[1].to_string(), "main()");
+    assert_eq!(backtrace[1].line_column(&sources), Some((1, 4)));
+}
+
+#[test]
+fn test_backtrace_emit() {
+    let (sources, error) = call(&Options::default());
+
+    let mut out = Buffer::no_color();
+    error.emit(&mut out, &sources).unwrap();
+    let out = String::from_utf8(out.into_inner()).unwrap();
+
+    let backtrace = &out[out.find("Backtrace:").expect("missing backtrace")..];
+    assert!(backtrace.contains("#1 <function"), "{out}");
+    assert!(backtrace.contains("(native)"), "{out}");
+    assert!(backtrace.contains("note: #2 inner(n)"), "{out}");
+    assert!(backtrace.contains("main:7:5"), "{out}");
+    assert!(backtrace.contains("note: #4 main()"), "{out}");
+}
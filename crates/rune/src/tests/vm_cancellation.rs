@@ -0,0 +1,163 @@
+prelude!();
+
+use std::thread;
+use std::time::Duration;
+
+use crate::runtime::CancellationToken;
+use crate::tests::{sources, vm};
+use futures_executor::block_on;
+
+/// Cancel the given token from another thread after a short delay.
+fn cancel_later(token: &CancellationToken) -> thread::JoinHandle<()> {
+    let token = token.clone();
+
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        token.cancel();
+    })
+}
+
+#[test]
+fn test_cancel_from_thread() {
+    let context = Context::with_default_modules().unwrap();
+
+    let mut vm = vm(
+        &context,
+        &mut sources(
+            r#"
+        fn spin() {
+            loop {}
+        }
+
+        pub fn main() {
+            spin()
+        }
+        "#,
+        ),
+        &mut Diagnostics::new(),
+    )
+    .unwrap();
+
+    let token = CancellationToken::new();
+    let mut execution = vm.execute(["main"], ()).unwrap();
+    execution.set_cancellation(token.clone());
+
+    let handle = cancel_later(&token);
+    let error = execution.complete().into_result().unwrap_err();
+    handle.join().unwrap();
+
+    // The backtrace includes the frame for `main` calling into `spin`.
+    let location = error.first_location().expect("missing location");
+    assert_eq!(location.frames.len(), 1);
+    assert_matches!(error.into_kind(), VmErrorKind::Cancelled);
+}
+
+#[test]
+fn test_deadline() {
+    let context = Context::with_default_modules().unwrap();
+    let mut vm = vm(
+        &context,
+        &mut sources("pub fn main() { loop {} }"),
+        &mut Diagnostics::new(),
+    )
+    .unwrap();
+
+    let mut execution = vm.execute(["main"], ()).unwrap();
+    execution.set_cancellation(CancellationToken::with_timeout(Duration::from_millis(10)));
+
+    let error = execution.complete().into_result().unwrap_err();
+    assert_matches!(error.into_kind(), VmErrorKind::Cancelled);
+}
+
+#[test]
+fn test_cancel_while_awaiting() -> Result<()> {
+    let mut module = Module::new();
+
+    module.async_function(["pending"], || async {
+        core::future::pending::<()>().await;
+    })?;
+
+    let mut context = Context::with_default_modules()?;
+    context.install(module)?;
+
+    let vm = vm(
+        &context,
+        &mut sources(
+            r#"
+        pub async fn main() {
+            pending().await
+        }
+        "#,
+        ),
+        &mut Diagnostics::new(),
+    )
+    .unwrap();
+
+    let token = CancellationToken::new();
+    let mut execution = vm.send_execute(["main"], ())?;
+    execution.set_cancellation(token.clone());
+
+    let handle = cancel_later(&token);
+    let error = block_on(execution.async_complete())
+        .into_result()
+        .unwrap_err();
+    handle.join().unwrap();
+
+    assert!(error.first_location().is_some());
+    assert_matches!(error.into_kind(), VmErrorKind::Cancelled);
+    Ok(())
+}
+
+#[test]
+fn test_not_cancelled() {
+    let context = Context::with_default_modules().unwrap();
+    let mut vm = vm(
+        &context,
+        &mut sources("pub fn main() { let n = 0; for i in 0..1000 { n += i; } n }"),
+        &mut Diagnostics::new(),
+    )
+    .unwrap();
+
+    let mut execution = vm.execute(["main"], ()).unwrap();
+    execution.set_cancellation(CancellationToken::with_timeout(Duration::from_secs(60)));
+
+    let value = execution.complete().into_result().unwrap();
+    assert_eq!(from_value::<i64>(value).unwrap(), 499500);
+}
+
+#[test]
+fn test_wakers_are_removed() -> Result<()> {
+    let mut module = Module::new();
+
+    module.async_function(["ready"], || async {
+        tokio::task::yield_now().await;
+    })?;
+
+    let mut context = Context::with_default_modules()?;
+    context.install(module)?;
+
+    let vm = vm(
+        &context,
+        &mut sources(
+            r#"
+            pub async fn main() {
+                for n in 0..10 {
+                    ready().await;
+                }
+            }
+            "#,
+        ),
+        &mut Diagnostics::new(),
+    )
+    .unwrap();
+
+    let token = CancellationToken::new();
+    let mut execution = vm.send_execute(["main"], ())?;
+    execution.set_cancellation(token.clone());
+
+    let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+    runtime.block_on(execution.async_complete()).into_result()?;
+
+    assert_eq!(token.registered(), 0);
+    Ok(())
+}
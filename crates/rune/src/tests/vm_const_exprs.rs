@@ -264,3 +264,173 @@ fn test_const_block() {
 
     assert_eq!(result, "Hello World");
 }
+
+#[test]
+fn test_const_fn_loops() {
+    let result: (i64, i64, i64) = rune! {
+        const fn sum(n) {
+            let out = 0;
+
+            for i in 0..=n {
+                out += i;
+            }
+
+            out
+        }
+
+        const fn count(n) {
+            let i = 0;
+
+            while i < n {
+                i += 1;
+
+                if i == 5 {
+                    break;
+                }
+            }
+
+            i
+        }
+
+        const fn nested() {
+            let out = 0;
+
+            'outer: for a in [1, 2, 3] {
+                for b in (10, 20, 30) {
+                    if a * b > 40 {
+                        break 'outer;
+                    }
+
+                    out += a * b;
+                }
+            }
+
+            out
+        }
+
+        const VALUE = (sum(10), count(10), nested());
+
+        pub fn main() {
+            VALUE
+        }
+    };
+
+    assert_eq!(result, (55, 5, 10 + 20 + 30 + 20 + 40));
+}
+
+#[test]
+fn test_const_fn_match() {
+    let result: Vec<String> = rune_s! { r#"
+        const fn fizzbuzz(n) {
+            match (n % 3, n % 5) {
+                (0, 0) => "FizzBuzz",
+                (0, _) => "Fizz",
+                (_, 0) => "Buzz",
+                _ => `${n}`,
+            }
+        }
+
+        const fn table(n) {
+            let out = [];
+
+            for i in 1..n {
+                out.push(fizzbuzz(i));
+            }
+
+            out
+        }
+
+        const TABLE = table(16);
+
+        pub fn main() {
+            TABLE
+        }
+    "# };
+
+    assert_eq!(
+        result,
+        [
+            "1", "2", "Fizz", "4", "Buzz", "Fizz", "7", "8", "Fizz", "Buzz", "11", "Fizz", "13",
+            "14", "FizzBuzz",
+        ]
+    );
+
+    let result: (i64, i64, i64, i64, bool) = rune_s! { r#"
+        const fn classify(value) {
+            match value {
+                n @ 0..=9 if n % 2 == 0 => 1,
+                0..=9 => 2,
+                [a, b, ..] => a + b,
+                #{ value } => value,
+                'a' | 'b' => 3,
+                _ => -1,
+            }
+        }
+
+        const fn score(word) {
+            let out = 0;
+
+            for c in word {
+                out += classify(c);
+            }
+
+            out
+        }
+
+        const VALUE = (
+            classify(4),
+            classify([10, 20, 30]),
+            classify(#{ value: 7 }),
+            score("abba!"),
+            !(classify(3) != 2) && classify("x") == -1,
+        );
+
+        pub fn main() {
+            VALUE
+        }
+    "# };
+
+    assert_eq!(result, (1, 30, 7, 11, true));
+}
+
+#[test]
+fn test_const_fn_collections() {
+    let result: (i64, String, i64) = rune_s! { r#"
+        const TABLE = squares(4);
+
+        const fn squares(n) {
+            let out = [];
+
+            for i in 0..n {
+                out.push(i * i);
+            }
+
+            out
+        }
+
+        const fn keys(object) {
+            let out = "";
+
+            for (key, value) in object {
+                out += `${key}=${value};`;
+            }
+
+            out
+        }
+
+        pub fn main() {
+            (TABLE[3], keys(#{ b: 2, a: 1 }), TABLE.len())
+        }
+    "# };
+
+    assert_eq!(result, (9, String::from("a=1;b=2;"), 4));
+}
+
+#[test]
+fn test_const_fn_budget() {
+    assert_errors! {
+        r#"const fn spin() { loop {} } const VALUE = spin(); pub fn main() { VALUE }"#,
+        span!(23, 25),
+        CompileErrorKind::IrError(compile::IrErrorKind::BudgetExceeded)
+    };
+}
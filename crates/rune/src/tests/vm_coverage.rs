@@ -0,0 +1,95 @@
+prelude!();
+
+use crate::runtime::Coverage;
+use crate::tests::{sources, vm};
+
+const SOURCE: &str = r#"fn sign(n) {
+    if n > 0 {
+        1
+    } else if n < 0 {
+        -1
+    } else {
+        0
+    }
+}
+
+pub fn positive() {
+    sign(10)
+}
+
+pub fn zero() {
+    sign(0)
+}
+"#;
+
+fn setup() -> (Sources, Vm, Coverage) {
+    let context = Context::with_default_modules().unwrap();
+    let mut sources = sources(SOURCE);
+    let vm = vm(&context, &mut sources, &mut Diagnostics::new()).unwrap();
+    let coverage = Coverage::new(vm.unit().clone());
+    (sources, vm, coverage)
+}
+
+fn line_hits(coverage: &Coverage, sources: &Sources) -> Vec<(usize, u64)> {
+    let files = coverage.files(sources);
+    assert_eq!(files.len(), 1);
+    files[0].lines.iter().map(|l| (l.line, l.hits)).collect()
+}
+
+#[test]
+fn test_accumulated_lines() {
+    let (sources, mut vm, mut coverage) = setup();
+
+    let mut execution = vm.execute(["positive"], ()).unwrap();
+    coverage.complete(&mut execution).into_result().unwrap();
+
+    let hits = line_hits(&coverage, &sources);
+    assert!(hits.contains(&(3, 1)));
+    assert!(hits.contains(&(5, 0)));
+    assert!(hits.contains(&(7, 0)));
+
+    let mut execution = vm.execute(["zero"], ()).unwrap();
+    coverage.complete(&mut execution).into_result().unwrap();
+
+    let hits = line_hits(&coverage, &sources);
+    assert!(hits.contains(&(2, 2)));
+    assert!(hits.contains(&(3, 1)));
+    assert!(hits.contains(&(5, 0)));
+    assert!(hits.contains(&(7, 1)));
+
+    let files = coverage.files(&sources);
+    let functions = files[0]
+        .functions
+        .iter()
+        .map(|f| (f.name.as_str(), f.line, f.hits))
+        .collect::<Vec<_>>();
+    assert_eq!(functions[0], ("sign", 1, 2));
+}
+
+#[test]
+fn test_lcov() {
+    let (sources, mut vm, mut coverage) = setup();
+
+    let mut execution = vm.execute(["positive"], ()).unwrap();
+    futures_executor::block_on(coverage.async_complete(&mut execution))
+        .into_result()
+        .unwrap();
+
+    let mut out = Vec::new();
+    coverage.write_lcov(&sources, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let lines = out.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.first(), Some(&"SF:main"));
+    assert_eq!(lines.last(), Some(&"end_of_record"));
+    assert!(lines.contains(&"FN:1,sign"));
+    assert!(lines.contains(&"FNDA:1,sign"));
+    assert!(lines.contains(&"FNDA:0,zero"));
+    assert!(lines.contains(&"DA:3,1"));
+    assert!(lines.contains(&"DA:5,0"));
+
+    let file = &coverage.files(&sources)[0];
+    assert!(lines.contains(&format!("LF:{}", file.lines_found()).as_str()));
+    assert!(lines.contains(&format!("LH:{}", file.lines_hit()).as_str()));
+    assert!(file.lines_hit() < file.lines_found());
+}
@@ -0,0 +1,118 @@
+prelude!();
+
+use crate::runtime::debugger::{DebugEvent, Debugger, StepMode};
+use crate::tests::{sources, vm};
+
+const SOURCE: &str = r#"
+fn add(a, b) {
+    let c = a + b;
+    c
+}
+
+pub fn main() {
+    let x = 1;
+    let y = add(x, 2);
+    y * 2
+}
+"#;
+
+fn setup() -> (Vm, Debugger, crate::SourceId) {
+    let context = Context::with_default_modules().unwrap();
+    let mut sources = sources(SOURCE);
+    let vm = vm(&context, &mut sources, &mut Diagnostics::new()).unwrap();
+    let debugger = Debugger::new(vm.unit().clone(), &sources);
+    let source_id = sources.source_ids().next().unwrap();
+    (vm, debugger, source_id)
+}
+
+fn line(debugger: &Debugger, vm: &Vm) -> usize {
+    debugger.location_at(vm.ip()).expect("location").line
+}
+
+#[test]
+fn test_breakpoints() {
+    let (mut vm, mut debugger, source_id) = setup();
+
+    // Line 5 is empty, so the breakpoint is moved to the next line with code.
+    assert_eq!(debugger.set_breakpoint(source_id, 5), Some(6));
+    assert_eq!(debugger.set_breakpoint(source_id, 8), Some(8));
+    assert_eq!(debugger.set_breakpoint(source_id, 3), Some(3));
+    assert_eq!(debugger.set_breakpoint(source_id, 100), None);
+
+    let mut execution = vm.execute(["main"], ()).unwrap();
+
+    let event = debugger.resume(&mut execution, StepMode::Continue).unwrap();
+    assert_matches!(event, DebugEvent::Breakpoint { .. });
+    assert_eq!(line(&debugger, execution.vm()), 8);
+
+    let event = debugger.resume(&mut execution, StepMode::Continue).unwrap();
+    assert_matches!(event, DebugEvent::Breakpoint { .. });
+    assert_eq!(line(&debugger, execution.vm()), 3);
+
+    let frames = debugger.frames(execution.vm());
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[1].location.map(|l| l.line), Some(8));
+
+    let locals = debugger.locals(execution.vm(), &frames[0]);
+    let names = locals.iter().map(|l| &*l.name).collect::<Vec<_>>();
+    assert_eq!(names, ["a", "b", "c"]);
+    assert_eq!(from_value::<i64>(locals[2].value.clone()).unwrap(), 3);
+
+    let locals = debugger.locals(execution.vm(), &frames[1]);
+    let names = locals.iter().map(|l| &*l.name).collect::<Vec<_>>();
+    assert_eq!(names, ["x"]);
+
+    assert!(debugger.remove_breakpoint(source_id, 6));
+    assert!(!debugger.remove_breakpoint(source_id, 6));
+
+    let DebugEvent::Complete(value) = debugger.resume(&mut execution, StepMode::Continue).unwrap()
+    else {
+        panic!("expected completion");
+    };
+
+    assert_eq!(from_value::<i64>(value).unwrap(), 6);
+}
+
+#[test]
+fn test_stepping() {
+    let (mut vm, mut debugger, source_id) = setup();
+    debugger.set_breakpoint(source_id, 7);
+
+    let mut execution = vm.execute(["main"], ()).unwrap();
+
+    let event = debugger.resume(&mut execution, StepMode::Continue).unwrap();
+    assert_matches!(event, DebugEvent::Breakpoint { .. });
+    assert_eq!(line(&debugger, execution.vm()), 7);
+
+    let event = debugger.resume(&mut execution, StepMode::Over).unwrap();
+    assert_matches!(event, DebugEvent::Step { .. });
+    assert_eq!(line(&debugger, execution.vm()), 8);
+
+    let event = debugger.resume(&mut execution, StepMode::Into).unwrap();
+    assert_matches!(event, DebugEvent::Step { .. });
+    assert_eq!(line(&debugger, execution.vm()), 1);
+
+    let event = debugger.resume(&mut execution, StepMode::Over).unwrap();
+    assert_matches!(event, DebugEvent::Step { .. });
+    assert_eq!(line(&debugger, execution.vm()), 2);
+
+    let event = debugger.resume(&mut execution, StepMode::Out).unwrap();
+    assert_matches!(event, DebugEvent::Step { .. });
+    assert_eq!(line(&debugger, execution.vm()), 9);
+}
+
+#[test]
+fn test_pause() {
+    let (mut vm, mut debugger, _) = setup();
+    let mut execution = vm.execute(["main"], ()).unwrap();
+
+    debugger.pause_handle().pause();
+
+    // The instruction the execution is resumed at is always executed.
+    let event = debugger.resume(&mut execution, StepMode::Continue).unwrap();
+    assert_matches!(event, DebugEvent::Pause { .. });
+    assert_eq!(line(&debugger, execution.vm()), 8);
+
+    let event = debugger.resume(&mut execution, StepMode::Continue).unwrap();
+    assert_matches!(event, DebugEvent::Complete(..));
+}
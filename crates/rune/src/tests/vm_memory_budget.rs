@@ -0,0 +1,97 @@
+prelude!();
+
+use crate::runtime::{budget, VmError};
+use crate::tests::{sources, vm};
+
+/// Run `main` in the given source under a memory budget of `limit` bytes.
+fn run_with_memory(limit: usize, source: &str) -> Result<Value, VmError> {
+    let context = Context::with_default_modules().unwrap();
+    let mut vm = vm(&context, &mut sources(source), &mut Diagnostics::new()).unwrap();
+    let mut execution = vm.execute(["main"], ()).unwrap();
+    budget::with_memory(limit, || execution.complete().into_result()).call()
+}
+
+#[test]
+fn test_vec_growth() {
+    let error = run_with_memory(
+        1 << 16,
+        r#"
+        pub fn main() {
+            let values = [];
+
+            loop {
+                values.push(42);
+            }
+        }
+        "#,
+    )
+    .unwrap_err();
+
+    assert_matches!(
+        error.into_kind(),
+        VmErrorKind::MemoryLimitExceeded { limit: 65536 }
+    );
+}
+
+#[test]
+fn test_string_growth() {
+    let error = run_with_memory(
+        1 << 16,
+        r#"
+        pub fn main() {
+            let s = String::from_str("hello");
+
+            loop {
+                s.push_str("hello");
+            }
+        }
+        "#,
+    )
+    .unwrap_err();
+
+    assert_matches!(error.into_kind(), VmErrorKind::MemoryLimitExceeded { .. });
+}
+
+#[test]
+fn test_object_growth() {
+    let error = run_with_memory(
+        1 << 16,
+        r#"
+        pub fn main() {
+            let object = #{};
+            let n = 0;
+
+            loop {
+                object[`key${n}`] = n;
+                n += 1;
+            }
+        }
+        "#,
+    )
+    .unwrap_err();
+
+    assert_matches!(error.into_kind(), VmErrorKind::MemoryLimitExceeded { .. });
+}
+
+#[test]
+fn test_memory_is_released() {
+    let value = run_with_memory(
+        1 << 12,
+        r#"
+        pub fn main() {
+            let n = 0;
+
+            for i in 0..10000 {
+                let values = [i, i, i, i];
+                let s = `${i}`;
+                n += values.len() + s.len();
+            }
+
+            n
+        }
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(from_value::<i64>(value).unwrap(), 78890);
+}
@@ -0,0 +1,88 @@
+prelude!();
+
+use crate::runtime::Profiler;
+use crate::tests::{sources, vm};
+
+fn profile(interval: usize) -> Profiler {
+    let context = Context::with_default_modules().unwrap();
+
+    let mut sources = sources(
+        r#"
+        fn fib(n) {
+            if n < 2 { n } else { fib(n - 1) + fib(n - 2) }
+        }
+
+        pub fn main() {
+            fib(5)
+        }
+        "#,
+    );
+
+    let mut vm = vm(&context, &mut sources, &mut Diagnostics::new()).unwrap();
+    let mut profiler = Profiler::new(vm.unit().clone());
+    let mut execution = vm.execute(["main"], ()).unwrap();
+
+    profiler.set_interval(interval);
+
+    let value = profiler.complete(&mut execution).into_result().unwrap();
+    assert_eq!(from_value::<i64>(value).unwrap(), 5);
+    profiler
+}
+
+#[test]
+fn test_profile_functions() {
+    let profiler = profile(1);
+
+    let functions = profiler.functions();
+    let names = functions
+        .iter()
+        .map(|f| f.name.as_str())
+        .collect::<Vec<_>>();
+    assert!(names.contains(&"main"));
+    assert!(names.contains(&"fib"));
+
+    for function in &functions {
+        assert!(function.self_time <= function.total_time);
+    }
+
+    let main = functions.iter().find(|f| f.name == "main").unwrap();
+    let fib = functions.iter().find(|f| f.name == "fib").unwrap();
+
+    // Recursive calls are only counted once towards the total.
+    assert!(fib.total_time <= main.total_time);
+}
+
+#[test]
+fn test_profile_collapsed() {
+    let profiler = profile(1);
+
+    let mut out = Vec::new();
+    profiler.write_collapsed(&mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    let stacks = out
+        .lines()
+        .map(|line| line.rsplit_once(' ').unwrap().0)
+        .collect::<Vec<_>>();
+
+    assert!(stacks.contains(&"main"));
+    assert!(stacks.contains(&"main;fib"));
+    assert!(stacks.contains(&"main;fib;fib;fib;fib"));
+}
+
+#[test]
+fn test_profile_interval() {
+    let every = profile(1);
+    let sampled = profile(4);
+
+    let count = |profiler: &Profiler| {
+        profiler
+            .instructions()
+            .iter()
+            .map(|(_, profile)| profile.count)
+            .sum::<u64>()
+    };
+
+    let every = count(&every);
+    assert_eq!(count(&sampled), every / 4);
+}
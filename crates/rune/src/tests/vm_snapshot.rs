@@ -0,0 +1,179 @@
+prelude!();
+
+use std::sync::Arc;
+
+use crate::runtime::{GeneratorState, SnapshotError, VmExecution, VmSnapshot};
+use crate::tests::{sources, vm};
+
+/// Snapshot the execution and restore it after a roundtrip through bytes.
+fn roundtrip<T>(execution: &VmExecution<T>) -> VmExecution<Vm>
+where
+    T: AsRef<Vm> + AsMut<Vm>,
+{
+    let bytes = bincode::serialize(&execution.snapshot().unwrap()).unwrap();
+    let snapshot: VmSnapshot = bincode::deserialize(&bytes).unwrap();
+    let vm = execution.vm();
+    snapshot
+        .restore(vm.context().clone(), vm.unit().clone())
+        .unwrap()
+}
+
+#[test]
+fn test_resume_after_restore() {
+    let context = Context::with_default_modules().unwrap();
+
+    let mut vm = vm(
+        &context,
+        &mut sources(
+            r#"
+        struct Collector { values }
+
+        pub fn main() {
+            let values = [];
+            let collector = Collector { values };
+            values.push(yield 1);
+            let push = |n| values.push(n);
+            push(yield 2);
+            collector.values
+        }
+        "#,
+        ),
+        &mut Diagnostics::new(),
+    )
+    .unwrap();
+
+    let mut execution = vm.execute(["main"], ()).unwrap();
+    let state = execution.resume().into_result().unwrap();
+    assert_matches!(state, GeneratorState::Yielded(Value::Integer(1)));
+
+    let mut execution = roundtrip(&execution);
+    let state = execution.resume_with(Value::from(10i64)).into_result();
+    assert_matches!(state, Ok(GeneratorState::Yielded(Value::Integer(2))));
+
+    // Snapshot again, after the closure capturing `values` has been created.
+    let mut execution = roundtrip(&execution);
+
+    let state = execution.resume_with(Value::from(20i64)).into_result();
+
+    let Ok(GeneratorState::Complete(value)) = state else {
+        panic!("expected completion, got {state:?}");
+    };
+
+    // Aliasing between `values` and `collector.values` is preserved.
+    assert_eq!(from_value::<Vec<i64>>(value).unwrap(), [10, 20]);
+}
+
+#[test]
+fn test_nested_generator() {
+    let context = Context::with_default_modules().unwrap();
+
+    let mut vm = vm(
+        &context,
+        &mut sources(
+            r#"
+        fn numbers() {
+            yield 1;
+            yield 2;
+        }
+
+        pub fn main() {
+            let numbers = numbers();
+            yield numbers.next();
+            numbers.next()
+        }
+        "#,
+        ),
+        &mut Diagnostics::new(),
+    )
+    .unwrap();
+
+    let mut execution = vm.execute(["main"], ()).unwrap();
+    let state = execution.resume().into_result().unwrap();
+
+    let GeneratorState::Yielded(value) = state else {
+        panic!("expected yield, got {state:?}");
+    };
+
+    assert_eq!(from_value::<Option<i64>>(value).unwrap(), Some(1));
+
+    let mut execution = roundtrip(&execution);
+    let state = execution.resume().into_result().unwrap();
+
+    let GeneratorState::Complete(value) = state else {
+        panic!("expected completion, got {state:?}");
+    };
+
+    assert_eq!(from_value::<Option<i64>>(value).unwrap(), Some(2));
+}
+
+#[test]
+fn test_unsupported_values() {
+    let context = Context::with_default_modules().unwrap();
+
+    let mut vm = vm(
+        &context,
+        &mut sources(
+            r#"
+        async fn work() {
+            1
+        }
+
+        pub fn future() {
+            let future = work();
+            yield;
+            future
+        }
+
+        pub fn iterator() {
+            let iter = [1, 2, 3].iter();
+            yield;
+            iter
+        }
+        "#,
+        ),
+        &mut Diagnostics::new(),
+    )
+    .unwrap();
+
+    let mut execution = vm.execute(["future"], ()).unwrap();
+    execution.resume().into_result().unwrap();
+    let error = execution.snapshot().unwrap_err();
+    assert_matches!(error, SnapshotError::Unsupported { .. });
+    assert_eq!(error.to_string(), "Cannot snapshot value of type `Future`");
+
+    let mut execution = vm.execute(["iterator"], ()).unwrap();
+    execution.resume().into_result().unwrap();
+    let error = execution.snapshot().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Cannot snapshot value of type `Iterator`"
+    );
+}
+
+#[test]
+fn test_unit_mismatch() {
+    let context = Context::with_default_modules().unwrap();
+
+    let mut diagnostics = Diagnostics::new();
+
+    let other = vm(
+        &context,
+        &mut sources("pub fn main() { yield 1; yield 2; 3 }"),
+        &mut diagnostics,
+    )
+    .unwrap();
+
+    let mut vm = vm(
+        &context,
+        &mut sources("pub fn main() { yield 1; 2 }"),
+        &mut diagnostics,
+    )
+    .unwrap();
+
+    let mut execution = vm.execute(["main"], ()).unwrap();
+    execution.resume().into_result().unwrap();
+
+    let snapshot = execution.snapshot().unwrap();
+    let result = snapshot.restore(Arc::new(context.runtime()), other.unit().clone());
+    assert!(matches!(result, Err(SnapshotError::UnitMismatch)));
+}